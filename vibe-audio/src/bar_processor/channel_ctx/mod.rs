@@ -20,7 +20,7 @@ const DEFAULT_PADDING_SIZE: usize = 5;
 /// Contains every additional information for a channel to be processed.
 pub struct ChannelCtx {
     // The interpolation strategy for this channel
-    interpolator: Box<dyn Interpolater>,
+    interpolator: Box<dyn Interpolater + Send>,
     // Contains the index range for each supporting point within the fft output for each supporting point
     fft_out_ranges: Box<[Range<usize>]>,
 
@@ -79,13 +79,14 @@ impl ChannelCtx {
             supporting_points_fft_ranges,
         } = data;
 
-        let interpolator: Box<dyn Interpolater> = {
+        let interpolator: Box<dyn Interpolater + Send> = {
             let desc = InterpolatorDescriptor { supporting_points };
 
-            match config.interpolation {
+            match &config.interpolation {
                 InterpolationVariant::None => NothingInterpolation::boxed(desc),
                 InterpolationVariant::Linear => LinearInterpolation::boxed(desc),
                 InterpolationVariant::CubicSpline => CubicSplineInterpolation::boxed(desc),
+                InterpolationVariant::Custom(custom) => custom.create(desc),
             }
         };
 
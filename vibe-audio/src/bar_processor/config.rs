@@ -1,7 +1,9 @@
 use std::{num::NonZero, ops::Range};
 
+use crate::interpolation::CustomInterpolation;
+
 /// Decides which interpolation strategy for the bars.
-#[derive(Debug, Clone, Copy, Hash)]
+#[derive(Debug, Clone, Hash, PartialEq)]
 pub enum InterpolationVariant {
     /// No interpolation strategy should be used.
     ///
@@ -14,10 +16,13 @@ pub enum InterpolationVariant {
 
     /// Use the cubic spline interpolation (recommended since it's the smoothest).
     CubicSpline,
+
+    /// Use your own interpolation (see the [interpolation module](crate::interpolation)).
+    Custom(CustomInterpolation),
 }
 
 /// Set the distribution of the bars.
-#[derive(Debug, Clone, Copy, Hash, Default)]
+#[derive(Debug, Clone, Copy, Hash, Default, PartialEq)]
 pub enum BarDistribution {
     /// Tell the [`Barprocessor`] to distribute the bars so that the frequency spectrum
     /// looks like as if it would grow linear or in other words:
@@ -54,21 +59,21 @@ impl PaddingSide {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum PaddingSize {
     Auto,
     // unit: "Bars"
     Custom(NonZero<u16>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct PaddingConfig {
     pub side: PaddingSide,
     pub size: PaddingSize,
 }
 
 /// The config options for [crate::BarProcessor].
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct BarProcessorConfig {
     /// Set the amount of bars which should be created.
     pub amount_bars: NonZero<u16>,
@@ -103,3 +108,70 @@ impl Default for BarProcessorConfig {
         }
     }
 }
+
+impl BarProcessorConfig {
+    /// A preset for the bass and the kick drums (20Hz - 250Hz) with a few bars which react quickly.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::num::NonZero;
+    /// use vibe_audio::BarProcessorConfig;
+    ///
+    /// let config = BarProcessorConfig::bass_focused().with_amount_bars(NonZero::new(8).unwrap());
+    /// ```
+    pub fn bass_focused() -> Self {
+        Self {
+            amount_bars: NonZero::new(16).unwrap(),
+            freq_range: NonZero::new(20).unwrap()..NonZero::new(250).unwrap(),
+            interpolation: InterpolationVariant::CubicSpline,
+            sensitivity: 3.,
+            ..Default::default()
+        }
+    }
+
+    /// A preset for the range of the human voice and most melodies (200Hz - 4kHz).
+    pub fn vocal() -> Self {
+        Self {
+            amount_bars: NonZero::new(30).unwrap(),
+            freq_range: NonZero::new(200).unwrap()..NonZero::new(4_000).unwrap(),
+            interpolation: InterpolationVariant::CubicSpline,
+            sensitivity: 2.,
+            ..Default::default()
+        }
+    }
+
+    /// A preset for the whole audible range (20Hz - 20kHz) with many bars which move calmly.
+    pub fn full_range() -> Self {
+        Self {
+            amount_bars: NonZero::new(60).unwrap(),
+            freq_range: NonZero::new(20).unwrap()..NonZero::new(20_000).unwrap(),
+            interpolation: InterpolationVariant::Linear,
+            sensitivity: 1.5,
+            ..Default::default()
+        }
+    }
+
+    /// Sets [BarProcessorConfig::amount_bars].
+    pub fn with_amount_bars(mut self, amount_bars: NonZero<u16>) -> Self {
+        self.amount_bars = amount_bars;
+        self
+    }
+
+    /// Sets [BarProcessorConfig::freq_range].
+    pub fn with_freq_range(mut self, freq_range: Range<NonZero<u16>>) -> Self {
+        self.freq_range = freq_range;
+        self
+    }
+
+    /// Sets [BarProcessorConfig::interpolation].
+    pub fn with_interpolation(mut self, interpolation: InterpolationVariant) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
+    /// Sets [BarProcessorConfig::sensitivity].
+    pub fn with_sensitivity(mut self, sensitivity: f32) -> Self {
+        self.sensitivity = sensitivity;
+        self
+    }
+}
@@ -1,12 +1,14 @@
 mod channel_ctx;
 mod config;
+mod pool;
 
-use crate::{fetcher::Fetcher, SampleProcessor};
+use crate::AudioSource;
 use channel_ctx::ChannelCtx;
 use cpal::SampleRate;
 use std::num::NonZero;
 
 pub use config::*;
+pub use pool::{BarProcessorPool, SharedBarProcessor, SharedBars};
 
 // for clippy
 type Channels = Box<[ChannelCtx]>;
@@ -27,13 +29,16 @@ pub struct BarProcessor {
     config: BarProcessorConfig,
     sample_rate: SampleRate,
     sample_len: usize,
+
+    // the id and generation of the audio source of the last `process_bars` call
+    last_update: Option<(u64, u64)>,
 }
 
 impl BarProcessor {
     /// Creates a new instance.
     ///
     /// See the examples of this crate to see it's usage.
-    pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: BarProcessorConfig) -> Self {
+    pub fn new<A: AudioSource + ?Sized>(processor: &A, config: BarProcessorConfig) -> Self {
         let sample_rate = processor.sample_rate();
         let sample_len = processor.fft_size();
         let amount_channels = processor.amount_channels();
@@ -48,6 +53,8 @@ impl BarProcessor {
 
             sample_rate,
             sample_len,
+
+            last_update: None,
         }
     }
 
@@ -55,7 +62,16 @@ impl BarProcessor {
     ///
     /// If you access the returned value like this: `bar_processor.process_bars(&processor)[i][j]` then this would mean:
     /// You are accessing the `j`th bar value of the `i`th audio channel.
-    pub fn process_bars<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) -> &[Box<[f32]>] {
+    ///
+    /// The bar values are only computed once per [generation](AudioSource::generation) of `processor`,
+    /// further calls return the same values.
+    pub fn process_bars<A: AudioSource + ?Sized>(&mut self, processor: &A) -> &[Box<[f32]>] {
+        let update = Some((processor.id(), processor.generation()));
+        if self.last_update == update {
+            return &self.bar_values;
+        }
+        self.last_update = update;
+
         for ((channel_idx, channel), fft_ctx) in self
             .ctx
             .iter_mut()
@@ -69,6 +85,11 @@ impl BarProcessor {
         &self.bar_values
     }
 
+    /// Returns the bar values of the last call of [BarProcessor::process_bars].
+    pub(crate) fn bar_values(&self) -> &[Box<[f32]>] {
+        &self.bar_values
+    }
+
     /// Returns the current config of the bar processor.
     pub fn config(&self) -> &BarProcessorConfig {
         &self.config
@@ -115,6 +136,7 @@ impl BarProcessor {
 
         self.ctx = channels;
         self.bar_values = bar_values;
+        self.last_update = None;
     }
 
     /// Allocates the array for the final bar values and the respective channel context for each audio channel.
@@ -164,7 +186,10 @@ impl BarProcessor {
 
 #[cfg(test)]
 mod tests {
-    use crate::fetcher::DummyFetcher;
+    use crate::{
+        fetcher::{DummyFetcher, Fetcher, Signal},
+        SampleProcessor,
+    };
 
     use super::*;
 
@@ -206,6 +231,87 @@ mod tests {
         }
     }
 
+    #[test]
+    fn presets() {
+        let mut processor = SampleProcessor::new(DummyFetcher::with_signal(
+            2,
+            &Signal::Sines(vec![100., 1_000.]),
+        ));
+        processor.process_next_samples();
+
+        for config in [
+            BarProcessorConfig::bass_focused(),
+            BarProcessorConfig::vocal(),
+            BarProcessorConfig::full_range(),
+        ] {
+            let amount_bars = config.amount_bars.get() as usize;
+            let mut bar_processor = BarProcessor::new(&processor, config);
+
+            let bars = bar_processor.process_bars(&processor);
+            assert!(bars
+                .iter()
+                .all(|channel| channel.len() == amount_bars
+                    && channel.iter().all(|bar| bar.is_finite())));
+        }
+    }
+
+    #[test]
+    fn custom_interpolation() {
+        use crate::interpolation::{
+            CustomInterpolation, InterpolatorCreation, LinearInterpolation,
+        };
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static CREATED: AtomicUsize = AtomicUsize::new(0);
+
+        let mut processor =
+            SampleProcessor::new(DummyFetcher::with_signal(2, &Signal::Sines(vec![440.])));
+        processor.process_next_samples();
+
+        let custom = CustomInterpolation::from_fn(|desc| {
+            CREATED.fetch_add(1, Ordering::Relaxed);
+            LinearInterpolation::boxed(desc)
+        });
+        let config = BarProcessorConfig {
+            interpolation: InterpolationVariant::Custom(custom.clone()),
+            ..Default::default()
+        };
+        let linear = BarProcessorConfig {
+            interpolation: InterpolationVariant::Linear,
+            ..Default::default()
+        };
+
+        let mut custom_processor = BarProcessor::new(&processor, config.clone());
+        let mut linear_processor = BarProcessor::new(&processor, linear);
+
+        // one interpolator per channel
+        assert_eq!(CREATED.load(Ordering::Relaxed), 2);
+        assert_eq!(
+            custom_processor.process_bars(&processor),
+            linear_processor.process_bars(&processor)
+        );
+        assert_eq!(config.interpolation, InterpolationVariant::Custom(custom));
+    }
+
+    #[test]
+    fn cache_bars_of_same_generation() {
+        let fetcher = DummyFetcher::new(1);
+        let sample_buffer = fetcher.sample_buffer();
+        let mut processor = SampleProcessor::new(fetcher);
+        let mut bar_processor = BarProcessor::new(&processor, BarProcessorConfig::default());
+
+        processor.process_next_samples();
+        let silent = bar_processor.process_bars(&processor).to_vec();
+
+        // the new samples aren't processed yet
+        let sine: Vec<f32> = (0..1024).map(|idx| (idx as f32 * 0.3).sin()).collect();
+        sample_buffer.lock().unwrap().push_before(&sine);
+        assert_eq!(bar_processor.process_bars(&processor), silent.as_slice());
+
+        processor.process_next_samples();
+        assert_ne!(bar_processor.process_bars(&processor), silent.as_slice());
+    }
+
     /// If we already use the maximum amount of bars, then we don't have any bars left for the padding => should panic
     #[test]
     #[should_panic]
@@ -0,0 +1,232 @@
+use std::{
+    ops::Deref,
+    sync::{Arc, Mutex, MutexGuard, Weak},
+};
+
+use crate::{AudioSource, BarProcessor, BarProcessorConfig};
+
+struct Entry {
+    source_id: u64,
+    bar_processor: BarProcessor,
+}
+
+/// A pool which hands out [SharedBarProcessor]s.
+///
+/// Components which use the same [BarProcessorConfig] (and the same audio source)
+/// share the same [BarProcessor] so the bar values are computed only once per frame.
+/// Cloning the pool returns a handle to the same pool.
+///
+/// # Example
+/// ```
+/// use vibe_audio::{SampleProcessor, BarProcessorConfig, BarProcessorPool, fetcher::DummyFetcher};
+///
+/// let mut sample_processor = SampleProcessor::new(DummyFetcher::new(2));
+/// let pool = BarProcessorPool::new();
+///
+/// let mut bar_processor1 = pool.get(&sample_processor, BarProcessorConfig::default());
+/// let mut bar_processor2 = pool.get(&sample_processor, BarProcessorConfig::default());
+///
+/// // both share the same bar processor
+/// assert_eq!(pool.len(), 1);
+///
+/// sample_processor.process_next_samples();
+///
+/// // the bar values are computed here...
+/// let bars1 = bar_processor1.process_bars(&sample_processor).to_vec();
+/// // ... and simply reused here
+/// let bars2 = bar_processor2.process_bars(&sample_processor).to_vec();
+///
+/// assert_eq!(bars1, bars2);
+/// ```
+#[derive(Clone, Default)]
+pub struct BarProcessorPool {
+    entries: Arc<Mutex<Vec<Weak<Mutex<Entry>>>>>,
+}
+
+impl BarProcessorPool {
+    /// Creates a new, empty pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a bar processor with the given `config` for the given `processor`.
+    ///
+    /// If there's already a bar processor in use with the same config, it will be shared.
+    pub fn get<A: AudioSource + ?Sized>(
+        &self,
+        processor: &A,
+        config: BarProcessorConfig,
+    ) -> SharedBarProcessor {
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|entry| entry.strong_count() > 0);
+
+        for entry in entries.iter().filter_map(Weak::upgrade) {
+            let is_same = {
+                let entry = entry.lock().unwrap();
+                entry.source_id == processor.id() && *entry.bar_processor.config() == config
+            };
+
+            if is_same {
+                return SharedBarProcessor { entry };
+            }
+        }
+
+        let entry = Arc::new(Mutex::new(Entry {
+            source_id: processor.id(),
+            bar_processor: BarProcessor::new(processor, config),
+        }));
+        entries.push(Arc::downgrade(&entry));
+
+        SharedBarProcessor { entry }
+    }
+
+    /// Returns the amount of distinct bar processors which are currently in use.
+    pub fn len(&self) -> usize {
+        self.entries
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|entry| entry.strong_count() > 0)
+            .count()
+    }
+
+    /// Returns `true` if no bar processor of this pool is in use.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl std::fmt::Debug for BarProcessorPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BarProcessorPool")
+            .field("amount_bar_processors", &self.len())
+            .finish()
+    }
+}
+
+/// A [BarProcessor] which is shared with other users of the same [BarProcessorPool].
+pub struct SharedBarProcessor {
+    entry: Arc<Mutex<Entry>>,
+}
+
+impl SharedBarProcessor {
+    /// Returns the bar values for each channel.
+    ///
+    /// The bar values are only computed once per processed batch of samples of `processor`.
+    /// See [BarProcessor::process_bars] for more information.
+    pub fn process_bars<A: AudioSource + ?Sized>(&mut self, processor: &A) -> SharedBars<'_> {
+        let mut entry = self.entry.lock().unwrap();
+        entry.bar_processor.process_bars(processor);
+
+        SharedBars { entry }
+    }
+
+    /// Returns the config of the bar processor.
+    pub fn config(&self) -> BarProcessorConfig {
+        self.entry.lock().unwrap().bar_processor.config().clone()
+    }
+
+    /// Returns the amount of bars per channel which the bar processor generates (including the padded bars).
+    pub fn total_amount_bars(&self) -> usize {
+        self.entry.lock().unwrap().bar_processor.total_amount_bars()
+    }
+}
+
+/// The bar values of a [SharedBarProcessor].
+///
+/// The bar processor is locked as long as this value lives.
+pub struct SharedBars<'a> {
+    entry: MutexGuard<'a, Entry>,
+}
+
+impl Deref for SharedBars<'_> {
+    type Target = [Box<[f32]>];
+
+    fn deref(&self) -> &Self::Target {
+        self.entry.bar_processor.bar_values()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZero;
+
+    use super::*;
+    use crate::{fetcher::DummyFetcher, sample_processor::private::Sealed, SampleProcessor};
+
+    #[test]
+    fn share_identical_configs() {
+        let processor = SampleProcessor::new(DummyFetcher::new(1));
+        let pool = BarProcessorPool::new();
+
+        let _bar_processor1 = pool.get(&processor, BarProcessorConfig::default());
+        let _bar_processor2 = pool.get(&processor, BarProcessorConfig::default());
+
+        assert_eq!(pool.len(), 1);
+    }
+
+    #[test]
+    fn separate_different_configs() {
+        let processor = SampleProcessor::new(DummyFetcher::new(1));
+        let pool = BarProcessorPool::new();
+
+        let _bar_processor1 = pool.get(&processor, BarProcessorConfig::default());
+        let bar_processor2 = pool.get(
+            &processor,
+            BarProcessorConfig {
+                amount_bars: NonZero::new(10).unwrap(),
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(pool.len(), 2);
+        assert_eq!(bar_processor2.total_amount_bars(), 10);
+    }
+
+    #[test]
+    fn separate_different_sources() {
+        let processor1 = SampleProcessor::new(DummyFetcher::new(1));
+        let processor2 = SampleProcessor::new(DummyFetcher::new(1));
+        let pool = BarProcessorPool::new();
+
+        let _bar_processor1 = pool.get(&processor1, BarProcessorConfig::default());
+        let _bar_processor2 = pool.get(&processor2, BarProcessorConfig::default());
+
+        assert_eq!(pool.len(), 2);
+    }
+
+    #[test]
+    fn release_unused_bar_processors() {
+        let processor = SampleProcessor::new(DummyFetcher::new(1));
+        let pool = BarProcessorPool::new();
+
+        let bar_processor = pool.get(&processor, BarProcessorConfig::default());
+        assert_eq!(pool.len(), 1);
+
+        drop(bar_processor);
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn process_once_per_frame() {
+        let mut processor = SampleProcessor::new(DummyFetcher::new(1));
+        let pool = BarProcessorPool::new();
+
+        let mut bar_processor1 = pool.get(&processor, BarProcessorConfig::default());
+        let mut bar_processor2 = pool.get(&processor, BarProcessorConfig::default());
+
+        processor.process_next_samples();
+        bar_processor1.process_bars(&processor);
+        bar_processor2.process_bars(&processor);
+
+        assert_eq!(
+            bar_processor1
+                .entry
+                .lock()
+                .unwrap()
+                .bar_processor
+                .last_update,
+            Some((processor.id(), 1))
+        );
+    }
+}
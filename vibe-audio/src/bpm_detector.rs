@@ -1,4 +1,34 @@
-use crate::{fetcher::Fetcher, SampleProcessor};
+use std::ops::Range;
+
+use crate::AudioSource;
+
+/// How often the BPM is estimated (in seconds of audio).
+const UPDATE_INTERVAL_SECS: f32 = 15.0;
+
+/// The highest rate of new samples (per second) which the onset history can hold.
+/// Audio callbacks usually happen less often.
+const MAX_ONSETS_PER_SECOND: f32 = 240.0;
+
+/// How fast the adaptive threshold follows the onset strength (in seconds).
+const THRESHOLD_TIME_CONSTANT_SECS: f32 = 0.25;
+
+/// How strong the autocorrelation of the half lag has to be compared to the best lag to prefer it.
+const HARMONIC_RATIO: f32 = 0.9;
+
+/// A frequency band whose changes count as onsets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OnsetBand {
+    /// The frequencies of the band in Hz.
+    pub freq_range: Range<f32>,
+    /// How much the onsets of this band count compared to the other bands.
+    pub weight: f32,
+}
+
+impl OnsetBand {
+    pub fn new(freq_range: Range<f32>, weight: f32) -> Self {
+        Self { freq_range, weight }
+    }
+}
 
 /// Configuration for the BPM detector.
 #[derive(Debug, Clone)]
@@ -12,6 +42,12 @@ pub struct BpmDetectorConfig {
     /// Number of BPM estimates to keep for median calculation (default: 60)
     /// At 15-second intervals, 60 estimates = 15 minutes of history
     pub estimate_history_size: usize,
+    /// The frequency bands which are searched for onsets
+    /// (default: bass (20-200 Hz), mids (200-2000 Hz) and highs (2-8 kHz) with decreasing weights)
+    pub bands: Vec<OnsetBand>,
+    /// Only the part of the onset strength above this multiple of its recent average counts as onset,
+    /// so steady sounds don't hide the beats (default: 1.0, `0` disables it)
+    pub adaptive_threshold: f32,
 }
 
 impl Default for BpmDetectorConfig {
@@ -21,36 +57,54 @@ impl Default for BpmDetectorConfig {
             min_bpm: 60.0,
             max_bpm: 200.0,
             estimate_history_size: 60, // 60 estimates * 15 seconds = 15 minutes
+            bands: vec![
+                OnsetBand::new(20.0..200.0, 1.0),       // kick drums and bass
+                OnsetBand::new(200.0..2_000.0, 0.5),    // snares, vocals and chords
+                OnsetBand::new(2_000.0..8_000.0, 0.25), // hi-hats
+            ],
+            adaptive_threshold: 1.0,
         }
     }
 }
 
 /// Detects BPM from audio using spectral flux and autocorrelation.
 ///
-/// The detector analyzes bass frequencies (20-200 Hz) to find onsets,
-/// then uses autocorrelation to find the periodic tempo pattern.
+/// The detector sums up the weighted spectral flux of several frequency bands (see [BpmDetectorConfig::bands])
+/// to find onsets, so it also works for music without a prominent kick drum.
+/// It then uses autocorrelation to find the periodic tempo pattern.
 /// BPM is computed as the median of multiple estimates over time,
 /// providing stability against transient percussion.
+///
+/// Only calls with new samples (see [AudioSource::new_samples]) add an onset, so the result
+/// doesn't depend on the frame rate of the caller.
 pub struct BpmDetector {
     config: BpmDetectorConfig,
 
     // Onset detection state
-    prev_bass_energy: f32,
+    bands: Box<[BandState]>,
+    // the recent average of the onset strength
+    threshold: f32,
     onset_history: Box<[f32]>,
     onset_write_idx: usize,
 
     // BPM estimate history for median calculation
     bpm_estimates: Vec<f32>,
     current_bpm: f32,
+    // the (smoothed) amount of onsets per second of audio
     frames_per_second: f32,
 
-    // Update throttling - only compute BPM every N frames
-    frame_count: usize,
-    frames_between_updates: usize,
+    // Update throttling - only compute BPM every `UPDATE_INTERVAL_SECS`
+    secs_since_update: f32,
+    // the generation of the audio source of the last `process` call
+    last_generation: Option<u64>,
+}
 
-    // Bass frequency bin range in FFT output
-    bass_bin_start: usize,
-    bass_bin_end: usize,
+/// An [OnsetBand] with its range in the fft output.
+struct BandState {
+    bins: Range<usize>,
+    weight: f32,
+    // the (logarithmic) energy of the band of the last samples
+    prev_energy: f32,
 }
 
 impl BpmDetector {
@@ -58,73 +112,105 @@ impl BpmDetector {
     ///
     /// The detector needs the SampleProcessor to determine sample rate and FFT size
     /// for calculating frequency bin ranges and timing.
-    pub fn new<F: Fetcher>(processor: &SampleProcessor<F>, config: BpmDetectorConfig) -> Self {
+    pub fn new<A: AudioSource + ?Sized>(processor: &A, config: BpmDetectorConfig) -> Self {
         let sample_rate = processor.sample_rate() as f32;
         let fft_size = processor.fft_size();
         let frames_per_second = sample_rate / fft_size as f32;
 
         // Calculate onset buffer size (number of frames to store)
-        let history_frames = (config.history_seconds * frames_per_second) as usize;
-        let onset_history = vec![0.0f32; history_frames].into_boxed_slice();
+        let history_frames = (config.history_seconds * MAX_ONSETS_PER_SECOND) as usize;
+        let onset_history = vec![0.0f32; history_frames.max(1)].into_boxed_slice();
 
-        // Calculate bass frequency bin range (20-200 Hz)
+        // Calculate the frequency bin ranges of the bands
         let freq_resolution = sample_rate / fft_size as f32;
-        let bass_bin_start = (20.0f32 / freq_resolution).ceil() as usize;
-        let bass_bin_end = (200.0f32 / freq_resolution).ceil() as usize;
-
-        // Only update BPM every ~15 seconds
-        let frames_between_updates = (frames_per_second * 15.0) as usize;
+        let bands = config
+            .bands
+            .iter()
+            .map(|band| BandState {
+                bins: (band.freq_range.start / freq_resolution).ceil() as usize
+                    ..(band.freq_range.end / freq_resolution).ceil() as usize,
+                weight: band.weight,
+                prev_energy: 0.0,
+            })
+            .collect();
 
         Self {
             config,
-            prev_bass_energy: 0.0,
+            bands,
+            threshold: 0.0,
             onset_history,
             onset_write_idx: 0,
             bpm_estimates: Vec::with_capacity(60),
             current_bpm: 120.0, // Default starting BPM
             frames_per_second,
-            frame_count: 0,
-            frames_between_updates,
-            bass_bin_start,
-            bass_bin_end,
+            secs_since_update: 0.0,
+            last_generation: None,
         }
     }
 
     /// Process a new audio frame and return the current BPM estimate.
     ///
     /// This should be called once per frame after `SampleProcessor::process_next_samples()`.
-    pub fn process<F: Fetcher>(&mut self, processor: &SampleProcessor<F>) -> f32 {
+    /// Further calls with the same [generation](AudioSource::generation) of the samples return the cached estimate.
+    pub fn process<A: AudioSource + ?Sized>(&mut self, processor: &A) -> f32 {
+        let generation = Some(processor.generation());
+        if self.last_generation == generation {
+            return self.current_bpm;
+        }
+        self.last_generation = generation;
+
         let fft_out = processor.fft_out();
-        if fft_out.is_empty() {
+        if fft_out.is_empty() || processor.new_samples() == 0 {
             return self.current_bpm;
         }
 
+        let delta = processor.new_samples_duration().as_secs_f32();
+        self.frames_per_second =
+            (self.frames_per_second * 0.95 + 0.05 / delta).min(MAX_ONSETS_PER_SECOND);
+
         // Use first channel for BPM detection
         let fft_data = &fft_out[0].fft_out;
 
-        // Ensure bin range is valid
-        let bin_end = self.bass_bin_end.min(fft_data.len());
-        let bin_start = self.bass_bin_start.min(bin_end);
+        // 1. Weighted spectral flux of the bands (positive energy changes indicate onsets)
+        let mut flux = 0.0;
+        let mut total_weight = 0.0;
+        for band in self.bands.iter_mut() {
+            let bin_end = band.bins.end.min(fft_data.len());
+            let bin_start = band.bins.start.min(bin_end);
+            if bin_start >= bin_end {
+                continue;
+            }
 
-        if bin_start >= bin_end {
-            return self.current_bpm;
+            // logarithmic, so loud bands don't drown the quiet ones
+            let energy = fft_data[bin_start..bin_end]
+                .iter()
+                .map(|c| c.norm())
+                .sum::<f32>()
+                .ln_1p();
+
+            flux += band.weight * (energy - band.prev_energy).max(0.0);
+            total_weight += band.weight;
+            band.prev_energy = energy;
         }
 
-        // 1. Calculate bass energy from FFT output
-        let bass_energy: f32 = fft_data[bin_start..bin_end].iter().map(|c| c.norm()).sum();
+        if total_weight <= 0.0 {
+            return self.current_bpm;
+        }
+        let flux = flux / total_weight;
 
-        // 2. Spectral flux (positive energy changes indicate onsets)
-        let flux = (bass_energy - self.prev_bass_energy).max(0.0);
-        self.prev_bass_energy = bass_energy;
+        // 2. Adaptive threshold: only the flux above its recent average counts
+        let onset = (flux - self.config.adaptive_threshold * self.threshold).max(0.0);
+        let alpha = 1.0 - (-delta / THRESHOLD_TIME_CONSTANT_SECS).exp();
+        self.threshold += alpha * (flux - self.threshold);
 
         // 3. Store in onset history (circular buffer)
-        self.onset_history[self.onset_write_idx] = flux;
+        self.onset_history[self.onset_write_idx] = onset;
         self.onset_write_idx = (self.onset_write_idx + 1) % self.onset_history.len();
 
         // 4. Only compute BPM periodically (not every frame)
-        self.frame_count += 1;
-        if self.frame_count >= self.frames_between_updates {
-            self.frame_count = 0;
+        self.secs_since_update += delta;
+        if self.secs_since_update >= UPDATE_INTERVAL_SECS {
+            self.secs_since_update = 0.0;
 
             let detected_bpm = self.compute_bpm_from_autocorrelation();
 
@@ -162,7 +248,7 @@ impl BpmDetector {
         sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
 
         let mid = sorted.len() / 2;
-        if sorted.len() % 2 == 0 {
+        if sorted.len().is_multiple_of(2) {
             // Even number of elements: average the two middle values
             (sorted[mid - 1] + sorted[mid]) / 2.0
         } else {
@@ -179,7 +265,7 @@ impl BpmDetector {
         let max_lag = (60.0 / self.config.min_bpm * self.frames_per_second) as usize;
 
         // Ensure we don't exceed half the buffer (autocorrelation requirement)
-        let max_lag = max_lag.min(self.onset_history.len() / 2);
+        let max_lag = max_lag.min(self.history_len() / 2);
 
         if min_lag >= max_lag {
             return self.current_bpm;
@@ -197,6 +283,15 @@ impl BpmDetector {
             }
         }
 
+        // A periodic pattern correlates (nearly) as strong at the double of its lag,
+        // so prefer the faster tempo if it's about as strong
+        let half_lag = best_lag / 2;
+        if half_lag >= min_lag
+            && self.autocorrelation(half_lag) >= HARMONIC_RATIO * best_correlation
+        {
+            best_lag = half_lag;
+        }
+
         // Convert lag back to BPM
         if best_lag > 0 {
             60.0 * self.frames_per_second / best_lag as f32
@@ -205,20 +300,27 @@ impl BpmDetector {
         }
     }
 
+    /// The amount of the latest onsets which cover `history_seconds`.
+    fn history_len(&self) -> usize {
+        ((self.config.history_seconds * self.frames_per_second) as usize)
+            .clamp(1, self.onset_history.len())
+    }
+
     /// Compute autocorrelation at a given lag.
     fn autocorrelation(&self, lag: usize) -> f32 {
         let len = self.onset_history.len();
-        if lag >= len {
+        let history_len = self.history_len();
+        if lag >= history_len {
             return 0.0;
         }
 
         let mut sum = 0.0;
-        let count = len - lag;
+        let count = history_len - lag;
 
         // Since we're using a circular buffer, we need to handle wrap-around
         // For simplicity, we treat the buffer as linear starting from write position
         for i in 0..count {
-            let idx1 = (self.onset_write_idx + len - count + i) % len;
+            let idx1 = (self.onset_write_idx + len - history_len + i) % len;
             let idx2 = (idx1 + lag) % len;
             sum += self.onset_history[idx1] * self.onset_history[idx2];
         }
@@ -238,5 +340,64 @@ mod tests {
         assert_eq!(config.min_bpm, 60.0);
         assert_eq!(config.max_bpm, 200.0);
         assert_eq!(config.estimate_history_size, 60);
+        assert_eq!(config.bands.len(), 3);
+        assert_eq!(config.adaptive_threshold, 1.0);
+    }
+
+    const SAMPLE_RATE: u32 = 44_100;
+    const CHUNK: usize = 441;
+
+    /// A decaying sine with the given frequency on each beat of the given BPM.
+    fn beat(freq: f32, bpm: f32) -> impl Fn(usize) -> f32 {
+        move |idx: usize| {
+            let time = idx as f32 / SAMPLE_RATE as f32;
+            let since_beat = time % (60. / bpm);
+            (std::f32::consts::TAU * freq * time).sin() * (-since_beat * 30.).exp()
+        }
+    }
+
+    /// Feeds 16 seconds of the signal to a detector and calls `process` `calls_per_chunk` times per chunk.
+    fn detect(signal: impl Fn(usize) -> f32, calls_per_chunk: usize) -> f32 {
+        use crate::{fetcher::SampleFetcher, SampleProcessor};
+
+        let fetcher = SampleFetcher::new(SAMPLE_RATE, 1);
+        let pusher = fetcher.pusher();
+        let mut processor = SampleProcessor::new(fetcher);
+        let mut detector = BpmDetector::new(&processor, BpmDetectorConfig::default());
+
+        for chunk in 0..(SAMPLE_RATE as usize * 16 / CHUNK) {
+            let samples: Vec<f32> = (0..CHUNK).map(|idx| signal(chunk * CHUNK + idx)).collect();
+            pusher.push(&samples);
+
+            for _ in 0..calls_per_chunk {
+                processor.process_next_samples();
+                detector.process(&processor);
+            }
+        }
+
+        detector.bpm()
+    }
+
+    /// Calling `process` more often than new samples arrive mustn't change the BPM.
+    #[test]
+    fn independent_of_frame_rate() {
+        // a bass drum at 120 BPM
+        let bpm = detect(beat(60., 120.), 1);
+        assert!((bpm - 120.).abs() < 5., "{}", bpm);
+        assert_eq!(bpm, detect(beat(60., 120.), 3));
+    }
+
+    /// The beat is found without a kick drum.
+    #[test]
+    fn without_bass() {
+        // hi-hats at 150 BPM above a steady chord
+        let hi_hats = beat(5_000., 150.);
+        let signal = |idx: usize| {
+            let time = idx as f32 / SAMPLE_RATE as f32;
+            hi_hats(idx) * 0.5 + (std::f32::consts::TAU * 440. * time).sin() * 0.3
+        };
+
+        let bpm = detect(signal, 1);
+        assert!((bpm - 150.).abs() < 5., "{}", bpm);
     }
 }
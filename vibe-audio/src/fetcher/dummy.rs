@@ -2,8 +2,13 @@ use std::sync::{Arc, Mutex};
 
 use super::{Fetcher, SampleBuffer};
 
+const SAMPLE_RATE: u32 = 44_100;
+
 /// A dummy fetcher which does... nothing.
 /// Mainly used for docs and tests.
+///
+/// Use [DummyFetcher::with_signal] to get the same samples on every run,
+/// for example to compare the rendered frames of components against reference images.
 pub struct DummyFetcher {
     sample_buffer: Arc<Mutex<SampleBuffer>>,
 
@@ -14,10 +19,38 @@ impl DummyFetcher {
     /// Creates a new instance of this struct.
     pub fn new(amount_channels: u16) -> Self {
         Self {
-            sample_buffer: Arc::new(Mutex::new(SampleBuffer::new(44_100))),
+            sample_buffer: Arc::new(Mutex::new(SampleBuffer::new(SAMPLE_RATE))),
             amount_channels,
         }
     }
+
+    /// Creates a fetcher whose sample buffer is filled with the given signal on all channels.
+    ///
+    /// # Example
+    /// ```rust
+    /// use vibe_audio::{SampleProcessor, fetcher::{DummyFetcher, Signal}};
+    ///
+    /// let mut sample_processor = SampleProcessor::new(DummyFetcher::with_signal(2, &Signal::Sines(vec![440.])));
+    /// sample_processor.process_next_samples();
+    /// ```
+    pub fn with_signal(amount_channels: u16, signal: &Signal) -> Self {
+        let fetcher = Self::new(amount_channels);
+
+        {
+            let mut sample_buffer = fetcher.sample_buffer.lock().unwrap();
+            let amount_frames = sample_buffer.capacity() / amount_channels.max(1) as usize;
+
+            let samples: Vec<f32> = signal
+                .samples(SAMPLE_RATE, amount_frames)
+                .into_iter()
+                .flat_map(|sample| std::iter::repeat_n(sample, amount_channels as usize))
+                .collect();
+
+            sample_buffer.push_before(&samples);
+        }
+
+        fetcher
+    }
 }
 
 impl Fetcher for DummyFetcher {
@@ -29,3 +62,98 @@ impl Fetcher for DummyFetcher {
         self.amount_channels
     }
 }
+
+/// A deterministic audio signal for [DummyFetcher::with_signal].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Signal {
+    /// No audio at all.
+    Silence,
+    /// The sum of sine waves with the given frequencies (in Hz), normalized to an amplitude of `1`.
+    Sines(Vec<f32>),
+    /// White noise which is always the same for the same seed.
+    Noise { seed: u64 },
+}
+
+impl Signal {
+    /// Returns the first `amount` samples of the signal with the given sample rate.
+    pub fn samples(&self, sample_rate: u32, amount: usize) -> Vec<f32> {
+        match self {
+            Self::Silence => vec![0.; amount],
+            Self::Sines(freqs) => {
+                let amplitude = 1. / freqs.len().max(1) as f32;
+
+                (0..amount)
+                    .map(|idx| {
+                        let time = idx as f32 / sample_rate as f32;
+
+                        freqs
+                            .iter()
+                            .map(|freq| (std::f32::consts::TAU * freq * time).sin() * amplitude)
+                            .sum()
+                    })
+                    .collect()
+            }
+            Self::Noise { seed } => {
+                // xorshift, the state mustn't be 0
+                let mut state = seed.wrapping_add(0x9E37_79B9_7F4A_7C15).max(1);
+
+                (0..amount)
+                    .map(|_| {
+                        state ^= state << 13;
+                        state ^= state >> 7;
+                        state ^= state << 17;
+
+                        // map the upper 24 bits to [-1, 1]
+                        (state >> 40) as f32 / (1 << 23) as f32 - 1.
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals_are_deterministic() {
+        for signal in [
+            Signal::Silence,
+            Signal::Sines(vec![110., 440.]),
+            Signal::Noise { seed: 42 },
+        ] {
+            assert_eq!(signal.samples(48_000, 512), signal.samples(48_000, 512));
+        }
+
+        assert_ne!(
+            Signal::Noise { seed: 1 }.samples(48_000, 16),
+            Signal::Noise { seed: 2 }.samples(48_000, 16)
+        );
+    }
+
+    #[test]
+    fn signals_are_normalized() {
+        for signal in [
+            Signal::Sines(vec![50., 100., 1_000.]),
+            Signal::Noise { seed: 0 },
+        ] {
+            assert!(signal
+                .samples(44_100, 4_096)
+                .iter()
+                .all(|sample| (-1. ..=1.).contains(sample)));
+        }
+    }
+
+    #[test]
+    fn fills_all_channels() {
+        let fetcher = DummyFetcher::with_signal(2, &Signal::Sines(vec![440.]));
+        let sample_buffer = fetcher.sample_buffer();
+        let buffer = sample_buffer.lock().unwrap();
+
+        assert!(buffer.buffer().iter().any(|&sample| sample != 0.));
+        for [left, right] in buffer.buffer().as_chunks::<2>().0 {
+            assert_eq!(left, right);
+        }
+    }
+}
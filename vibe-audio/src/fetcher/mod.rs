@@ -1,12 +1,14 @@
 //! Each struct here can be used to fetch the audio data from various sources.
 //! Pick the one you need to fetch from.
 mod dummy;
+mod samples;
 mod system_audio;
 
 use cpal::SampleRate;
 use std::sync::{Arc, Mutex};
 
-pub use dummy::DummyFetcher;
+pub use dummy::{DummyFetcher, Signal};
+pub use samples::{SampleFetcher, SamplePusher};
 pub use system_audio::{
     Descriptor as SystemAudioFetcherDescriptor, SystemAudio as SystemAudioFetcher, SystemAudioError,
 };
@@ -20,16 +22,41 @@ pub trait Fetcher {
     fn channels(&self) -> u16;
 }
 
+/// The smallest capacity of a [SampleBuffer].
+pub const MIN_SAMPLE_BUFFER_CAPACITY: usize = 16;
+
 /// Holds the audio samples which gets filled by the fetcher
 #[derive(Debug, Clone)]
 pub struct SampleBuffer {
     buffer: Box<[f32]>,
     sample_rate: SampleRate,
+    // the amount of (interleaved) samples which got pushed so far
+    pushed: u64,
 }
 
 impl SampleBuffer {
-    /// Create a new instance for the given sample rate.
+    /// Create a new instance for the given sample rate with the [default capacity](SampleBuffer::default_capacity).
     pub fn new(sample_rate: SampleRate) -> Self {
+        Self::with_capacity(sample_rate, Self::default_capacity(sample_rate))
+    }
+
+    /// Create a new instance which holds the given amount of (interleaved) samples,
+    /// but at least [MIN_SAMPLE_BUFFER_CAPACITY].
+    ///
+    /// The capacity is also the size of the fft: Smaller buffers react faster to the audio,
+    /// larger buffers resolve the low frequencies better.
+    pub fn with_capacity(sample_rate: SampleRate, capacity: usize) -> Self {
+        let buffer = vec![0f32; capacity.max(MIN_SAMPLE_BUFFER_CAPACITY)].into_boxed_slice();
+
+        Self {
+            buffer,
+            sample_rate,
+            pushed: 0,
+        }
+    }
+
+    /// Returns the capacity which [SampleBuffer::new] uses for the given sample rate.
+    pub fn default_capacity(sample_rate: SampleRate) -> usize {
         // props to cava for this heuristic.
         let factor = if sample_rate < 8_125 {
             1
@@ -47,12 +74,7 @@ impl SampleBuffer {
             64
         };
 
-        let buffer = vec![0f32; factor * 128].into_boxed_slice();
-
-        Self {
-            buffer,
-            sample_rate,
-        }
+        factor * 128
     }
 
     /// Pushes the given data to the front of `buffer` and moves the current data to the right.
@@ -60,6 +82,7 @@ impl SampleBuffer {
     pub fn push_before(&mut self, data: &[f32]) {
         let data_len = data.len();
         let buffer_len = self.buffer.len();
+        self.pushed = self.pushed.wrapping_add(data_len as u64);
 
         // split point
         let split_point = buffer_len.min(data_len);
@@ -72,6 +95,11 @@ impl SampleBuffer {
         self.buffer[..split_point].copy_from_slice(&data[..split_point]);
     }
 
+    /// Sets all samples to silence. The amount of pushed samples stays the same.
+    pub fn clear(&mut self) {
+        self.buffer.fill(0.);
+    }
+
     pub fn sample_rate(&self) -> SampleRate {
         self.sample_rate
     }
@@ -83,6 +111,12 @@ impl SampleBuffer {
     pub fn buffer(&self) -> &[f32] {
         &self.buffer
     }
+
+    /// Returns the amount of (interleaved) samples which got pushed so far,
+    /// including the ones which didn't fit into the buffer.
+    pub fn pushed_samples(&self) -> u64 {
+        self.pushed
+    }
 }
 
 #[cfg(test)]
@@ -112,6 +146,38 @@ mod tests {
             assert!(sample_buffer.buffer[1..].iter().all(|&value| value == 0f32));
         }
 
+        #[test]
+        fn counts_pushed_samples() {
+            let mut sample_buffer = SampleBuffer::new(1);
+            sample_buffer.push_before(&[1f32; 129]);
+            sample_buffer.push_before(&[1f32; 3]);
+
+            assert_eq!(sample_buffer.pushed_samples(), 132);
+        }
+
+        #[test]
+        fn custom_capacity() {
+            assert_eq!(SampleBuffer::with_capacity(44_100, 4096).capacity(), 4096);
+            assert_eq!(
+                SampleBuffer::with_capacity(44_100, 0).capacity(),
+                MIN_SAMPLE_BUFFER_CAPACITY
+            );
+            assert_eq!(
+                SampleBuffer::new(44_100).capacity(),
+                SampleBuffer::default_capacity(44_100)
+            );
+        }
+
+        #[test]
+        fn clear() {
+            let mut sample_buffer = SampleBuffer::new(1);
+            sample_buffer.push_before(&[1f32; 3]);
+            sample_buffer.clear();
+
+            assert!(sample_buffer.buffer.iter().all(|&value| value == 0f32));
+            assert_eq!(sample_buffer.pushed_samples(), 3);
+        }
+
         #[test]
         fn no_values_pushed() {
             let mut sample_buffer = SampleBuffer::new(1);
@@ -0,0 +1,67 @@
+use std::sync::{Arc, Mutex};
+
+use cpal::SampleRate;
+
+use super::{Fetcher, SampleBuffer};
+
+/// A fetcher which gets its samples from the caller, for example from a decoded audio file.
+///
+/// Push the (interleaved) samples with [SampleFetcher::push] before processing them.
+pub struct SampleFetcher {
+    sample_buffer: Arc<Mutex<SampleBuffer>>,
+
+    amount_channels: u16,
+}
+
+impl SampleFetcher {
+    /// Creates a new instance for samples with the given sample rate and amount of channels.
+    pub fn new(sample_rate: SampleRate, amount_channels: u16) -> Self {
+        Self::with_buffer(SampleBuffer::new(sample_rate), amount_channels)
+    }
+
+    /// Like [SampleFetcher::new] but with the given capacity of the sample buffer
+    /// (see [SampleBuffer::with_capacity]).
+    pub fn with_capacity(sample_rate: SampleRate, amount_channels: u16, capacity: usize) -> Self {
+        Self::with_buffer(
+            SampleBuffer::with_capacity(sample_rate, capacity),
+            amount_channels,
+        )
+    }
+
+    fn with_buffer(sample_buffer: SampleBuffer, amount_channels: u16) -> Self {
+        Self {
+            sample_buffer: Arc::new(Mutex::new(sample_buffer)),
+            amount_channels,
+        }
+    }
+
+    /// Returns a handle to push samples once the fetcher is moved into a [crate::SampleProcessor].
+    pub fn pusher(&self) -> SamplePusher {
+        SamplePusher {
+            sample_buffer: self.sample_buffer.clone(),
+        }
+    }
+}
+
+impl Fetcher for SampleFetcher {
+    fn sample_buffer(&self) -> Arc<Mutex<SampleBuffer>> {
+        self.sample_buffer.clone()
+    }
+
+    fn channels(&self) -> u16 {
+        self.amount_channels
+    }
+}
+
+/// Pushes samples to a [SampleFetcher].
+#[derive(Clone)]
+pub struct SamplePusher {
+    sample_buffer: Arc<Mutex<SampleBuffer>>,
+}
+
+impl SamplePusher {
+    /// Adds the given interleaved samples as the latest ones.
+    pub fn push(&self, samples: &[f32]) {
+        self.sample_buffer.lock().unwrap().push_before(samples);
+    }
+}
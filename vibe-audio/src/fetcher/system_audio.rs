@@ -1,7 +1,10 @@
 use super::{Fetcher, SampleBuffer};
 use crate::DEFAULT_SAMPLE_RATE;
 use cpal::traits::{DeviceTrait, StreamTrait};
-use std::sync::{Arc, Mutex};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
 use tracing::debug;
 
 /// Errors which can occur while creating [crate::fetcher::SystemAudioFetcher].
@@ -27,6 +30,9 @@ pub struct Descriptor {
     pub sample_rate: cpal::SampleRate,
     pub sample_format: Option<cpal::SampleFormat>,
     pub amount_channels: Option<u16>,
+    /// The amount of (interleaved) samples of the sample buffer (see [SampleBuffer::with_capacity]).
+    /// `None` to pick it depending on the sample rate.
+    pub buffer_capacity: Option<usize>,
 }
 
 impl Default for Descriptor {
@@ -39,6 +45,7 @@ impl Default for Descriptor {
             sample_rate: DEFAULT_SAMPLE_RATE,
             sample_format: None,
             amount_channels: None,
+            buffer_capacity: None,
         }
     }
 }
@@ -47,7 +54,8 @@ impl Default for Descriptor {
 pub struct SystemAudio {
     sample_buffer: Arc<Mutex<SampleBuffer>>,
     channels: u16,
-    _stream: cpal::Stream,
+    stream: cpal::Stream,
+    paused: AtomicBool,
 }
 
 impl SystemAudio {
@@ -88,7 +96,10 @@ impl SystemAudio {
 
         debug!("Stream config: {:#?}", stream_config);
 
-        let sample_buffer = Arc::new(Mutex::new(SampleBuffer::new(sample_rate)));
+        let sample_buffer = Arc::new(Mutex::new(match desc.buffer_capacity {
+            Some(capacity) => SampleBuffer::with_capacity(sample_rate, capacity),
+            None => SampleBuffer::new(sample_rate),
+        }));
 
         let stream = {
             let stream = device.build_input_stream(
@@ -108,11 +119,48 @@ impl SystemAudio {
         };
 
         Ok(Self {
-            _stream: stream,
+            stream,
             channels,
             sample_buffer,
+            paused: AtomicBool::new(false),
         })
     }
+
+    /// Stops capturing audio until [SystemAudio::resume] is called.
+    ///
+    /// The sample buffer keeps its last samples in the meantime.
+    pub fn pause(&self) -> Result<(), cpal::PauseStreamError> {
+        if !self.paused.swap(true, Ordering::Relaxed) {
+            if let Err(err) = self.stream.pause() {
+                self.paused.store(false, Ordering::Relaxed);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Starts capturing audio again after [SystemAudio::pause].
+    ///
+    /// The samples from before the pause are dropped, so the processor starts with silence
+    /// instead of the stale audio.
+    pub fn resume(&self) -> Result<(), cpal::PlayStreamError> {
+        if self.paused.swap(false, Ordering::Relaxed) {
+            self.sample_buffer.lock().unwrap().clear();
+
+            if let Err(err) = self.stream.play() {
+                self.paused.store(true, Ordering::Relaxed);
+                return Err(err);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the capture is paused (see [SystemAudio::pause]).
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
 }
 
 impl Drop for SystemAudio {
@@ -120,7 +168,7 @@ impl Drop for SystemAudio {
     ///
     /// **Panics** if it couldn't close the stream correctly.
     fn drop(&mut self) {
-        self._stream.pause().expect("Stop stream");
+        self.stream.pause().expect("Stop stream");
     }
 }
 
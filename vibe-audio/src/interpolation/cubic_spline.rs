@@ -190,7 +190,6 @@ mod tests {
     fn no_supporting_points() {
         let mut interpolator = CubicSplineInterpolation::new(InterpolatorDescriptor {
             supporting_points: vec![].into(),
-            ..Default::default()
         });
         let mut buffer = vec![];
 
@@ -206,7 +205,6 @@ mod tests {
         let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
         let mut interpolator = CubicSplineInterpolation::new(InterpolatorDescriptor {
             supporting_points: supporting_points.clone().into(),
-            ..Default::default()
         });
 
         interpolator.interpolate(&mut buffer);
@@ -224,7 +222,6 @@ mod tests {
         let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
         let mut interpolator = CubicSplineInterpolation::new(InterpolatorDescriptor {
             supporting_points: supporting_points.clone().into(),
-            ..Default::default()
         });
 
         interpolator.interpolate(&mut buffer);
@@ -243,7 +240,6 @@ mod tests {
         let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
         let mut interpolator = CubicSplineInterpolation::new(InterpolatorDescriptor {
             supporting_points: supporting_points.clone().into(),
-            ..Default::default()
         });
 
         interpolator.interpolate(&mut buffer);
@@ -264,7 +260,6 @@ mod tests {
         let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
         let mut interpolator = CubicSplineInterpolation::new(InterpolatorDescriptor {
             supporting_points: supporting_points.clone().into(),
-            ..Default::default()
         });
 
         interpolator.interpolate(&mut buffer);
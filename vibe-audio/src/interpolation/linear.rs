@@ -57,7 +57,6 @@ mod tests {
     fn zero_supporting_points_and_zero_sections() {
         let mut interpolator = LinearInterpolation::new(InterpolatorDescriptor {
             supporting_points: vec![].into(),
-            ..Default::default()
         });
         let mut buffer = vec![];
 
@@ -71,7 +70,6 @@ mod tests {
 
         let mut interpolator = LinearInterpolation::new(InterpolatorDescriptor {
             supporting_points: supporting_points.clone().into(),
-            ..Default::default()
         });
         let mut buffer = [0f32];
 
@@ -90,7 +88,6 @@ mod tests {
         let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
         let mut interpolator = LinearInterpolation::new(InterpolatorDescriptor {
             supporting_points: supporting_points.clone().into(),
-            ..Default::default()
         });
 
         interpolator.interpolate(&mut buffer);
@@ -109,7 +106,6 @@ mod tests {
         let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
         let mut interpolator = LinearInterpolation::new(InterpolatorDescriptor {
             supporting_points: supporting_points.clone().into(),
-            ..Default::default()
         });
 
         interpolator.interpolate(&mut buffer);
@@ -128,7 +124,6 @@ mod tests {
         let mut buffer = vec![0f32; supporting_points.last().unwrap().x + 1];
         let mut interpolator = LinearInterpolation::new(InterpolatorDescriptor {
             supporting_points: supporting_points.clone().into(),
-            ..Default::default()
         });
 
         interpolator.interpolate(&mut buffer);
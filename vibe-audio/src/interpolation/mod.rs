@@ -1,11 +1,66 @@
 //! Everything related to the interpolation calculation.
+//!
+//! The [crate::BarProcessor] only computes the values of some bars (the supporting points),
+//! an interpolator fills the bars between them. Implement [Interpolater] and [InterpolatorCreation]
+//! to use your own interpolation with [InterpolationVariant::Custom](crate::InterpolationVariant::Custom).
+//!
+//! # Example
+//! ```rust
+//! use vibe_audio::{
+//!     interpolation::{
+//!         CustomInterpolation, InterpolationCtx, Interpolater, InterpolatorCreation,
+//!         InterpolatorDescriptor,
+//!     },
+//!     BarProcessorConfig, InterpolationVariant,
+//! };
+//!
+//! /// Fills the bars between two supporting points with the higher one.
+//! struct MaxInterpolation {
+//!     ctx: InterpolationCtx,
+//! }
+//!
+//! impl InterpolatorCreation for MaxInterpolation {
+//!     fn new(desc: InterpolatorDescriptor) -> Self {
+//!         Self { ctx: InterpolationCtx::new(desc) }
+//!     }
+//! }
+//!
+//! impl Interpolater for MaxInterpolation {
+//!     fn interpolate(&mut self, buffer: &mut [f32]) {
+//!         for point in self.ctx.supporting_points.iter() {
+//!             buffer[point.x] = point.y;
+//!         }
+//!
+//!         for section in self.ctx.sections.iter() {
+//!             let left = self.ctx.supporting_points[section.left_supporting_point_idx];
+//!             let right = self.ctx.supporting_points[section.left_supporting_point_idx + 1];
+//!
+//!             buffer[left.x + 1..right.x].fill(left.y.max(right.y));
+//!         }
+//!     }
+//!
+//!     fn get_ctx(&self) -> &InterpolationCtx {
+//!         &self.ctx
+//!     }
+//!
+//!     fn get_ctx_mut(&mut self) -> &mut InterpolationCtx {
+//!         &mut self.ctx
+//!     }
+//! }
+//!
+//! let config = BarProcessorConfig {
+//!     interpolation: InterpolationVariant::Custom(CustomInterpolation::new::<MaxInterpolation>()),
+//!     ..Default::default()
+//! };
+//! ```
 mod context;
 mod cubic_spline;
 mod linear;
 mod nothing;
 
-use context::InterpolationCtx;
-use std::ops::Range;
+use std::{fmt, hash::Hash, ops::Range, sync::Arc};
+
+pub use context::InterpolationCtx;
 
 pub use cubic_spline::CubicSplineInterpolation;
 pub use linear::LinearInterpolation;
@@ -13,8 +68,12 @@ pub use nothing::NothingInterpolation;
 
 /// Methods for the actual interpolating process.
 pub trait Interpolater {
+    /// Writes the values of the supporting points and the interpolated values between them into `buffer`.
+    ///
+    /// `buffer` has at least the length of the end of [Interpolater::covered_bar_range].
     fn interpolate(&mut self, buffer: &mut [f32]);
 
+    /// Returns the supporting points and the sections between them.
     fn get_ctx(&self) -> &InterpolationCtx;
 
     fn get_ctx_mut(&mut self) -> &mut InterpolationCtx;
@@ -44,6 +103,52 @@ pub trait InterpolatorCreation: Interpolater + Sized {
     }
 }
 
+type CreateInterpolator =
+    dyn Fn(InterpolatorDescriptor) -> Box<dyn Interpolater + Send> + Send + Sync;
+
+/// Creates the interpolators of [InterpolationVariant::Custom](crate::InterpolationVariant::Custom).
+///
+/// Two instances are only equal if one is a clone of the other.
+#[derive(Clone)]
+pub struct CustomInterpolation(Arc<CreateInterpolator>);
+
+impl CustomInterpolation {
+    /// Uses the given interpolator.
+    pub fn new<I: InterpolatorCreation + Send + 'static>() -> Self {
+        Self::from_fn(|desc| I::boxed(desc))
+    }
+
+    /// Uses the interpolators which are returned by `create`,
+    /// for example to pass parameters to them.
+    pub fn from_fn(
+        create: impl Fn(InterpolatorDescriptor) -> Box<dyn Interpolater + Send> + Send + Sync + 'static,
+    ) -> Self {
+        Self(Arc::new(create))
+    }
+
+    pub(crate) fn create(&self, desc: InterpolatorDescriptor) -> Box<dyn Interpolater + Send> {
+        (self.0)(desc)
+    }
+}
+
+impl fmt::Debug for CustomInterpolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("CustomInterpolation")
+    }
+}
+
+impl PartialEq for CustomInterpolation {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Hash for CustomInterpolation {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        (Arc::as_ptr(&self.0) as *const () as usize).hash(state);
+    }
+}
+
 // == Data structures ==
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -92,19 +92,19 @@
 //! ```
 mod bar_processor;
 mod bpm_detector;
-mod interpolation;
 mod sample_processor;
 
 pub mod fetcher;
+pub mod interpolation;
 pub mod util;
 
-pub use bpm_detector::{BpmDetector, BpmDetectorConfig};
+pub use bpm_detector::{BpmDetector, BpmDetectorConfig, OnsetBand};
 
 use cpal::SampleRate;
 
 pub use bar_processor::*;
 pub use cpal;
-pub use sample_processor::SampleProcessor;
+pub use sample_processor::{AudioSource, SampleProcessor};
 
 type Hz = u16;
 
@@ -1,6 +1,10 @@
 use std::{
     num::NonZero,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
 };
 
 use cpal::SampleRate;
@@ -8,6 +12,12 @@ use realfft::{num_complex::Complex32, RealFftPlanner};
 
 use crate::fetcher::{Fetcher, SampleBuffer};
 
+/// Used to give each [SampleProcessor] a unique id.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Samples with a lower amplitude are treated as silence.
+const SILENCE_THRESHOLD: f32 = 1e-4;
+
 /// Prepares the samples of the fetcher for the [crate::BarProcessor].
 pub struct SampleProcessor<F: Fetcher> {
     planner: RealFftPlanner<f32>,
@@ -20,8 +30,26 @@ pub struct SampleProcessor<F: Fetcher> {
     // aka fft input length
     fft_size: usize,
 
+    id: u64,
+    // the amount of processed batches of samples so far
+    generation: u64,
+    // the highest absolute amplitude of the last processed samples
+    peak: f32,
+    // how much the spectrum changed with the last processed samples
+    spectral_flux: f32,
+
+    // the value of `SampleBuffer::pushed_samples` of the last processed samples
+    pushed_samples: u64,
+    // the amount of new samples per channel of the last processed samples
+    new_samples: usize,
+    // the amount of samples per channel which got processed so far
+    total_samples: u64,
+    // when the last samples got processed
+    last_process: Option<Instant>,
+    delta_time: Duration,
+
     // Relevant for the system audio fetcher, otherwise it will get dropped and so the stream as well.
-    _fetcher: F,
+    fetcher: F,
 }
 
 impl<F: Fetcher> SampleProcessor<F> {
@@ -52,19 +80,55 @@ impl<F: Fetcher> SampleProcessor<F> {
 
             sample_buffer,
             fft_size,
-            _fetcher: fetcher,
+
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+            generation: 0,
+            peak: 0.,
+            spectral_flux: 0.,
+
+            pushed_samples: 0,
+            new_samples: 0,
+            total_samples: 0,
+            last_process: None,
+            delta_time: Duration::ZERO,
+
+            fetcher,
         }
     }
 
+    /// Returns the fetcher which the samples are fetched from.
+    pub fn fetcher(&self) -> &F {
+        &self.fetcher
+    }
+
     /// Tell the processor to take some samples of the fetcher and prepare them
     /// for the [crate::BarProcessor]s.
     pub fn process_next_samples(&mut self) {
         let amount_channels = self.channels.len();
+        self.generation = self.generation.wrapping_add(1);
+
+        let now = Instant::now();
+        self.delta_time = self
+            .last_process
+            .map(|last_process| now - last_process)
+            .unwrap_or_default();
+        self.last_process = Some(now);
 
         // fetch the latest data
         {
             let fft_input = self.sample_buffer.lock().unwrap();
 
+            let pushed_samples = fft_input.pushed_samples();
+            self.new_samples = (pushed_samples.wrapping_sub(self.pushed_samples)
+                / amount_channels as u64) as usize;
+            self.pushed_samples = pushed_samples;
+            self.total_samples = self.total_samples.wrapping_add(self.new_samples as u64);
+
+            self.peak = fft_input
+                .buffer()
+                .iter()
+                .fold(0f32, |peak, sample| peak.max(sample.abs()));
+
             for (sample_idx, samples) in
                 fft_input.buffer().chunks_exact(amount_channels).enumerate()
             {
@@ -76,6 +140,7 @@ impl<F: Fetcher> SampleProcessor<F> {
         }
 
         let fft = self.planner.plan_fft_forward(self.fft_size);
+        let mut spectral_flux = 0.;
         for channel in self.channels.iter_mut() {
             fft.process_with_scratch(
                 channel.fft_in.as_mut(),
@@ -83,33 +148,135 @@ impl<F: Fetcher> SampleProcessor<F> {
                 channel.scratch_buffer.as_mut(),
             )
             .unwrap();
+
+            spectral_flux += channel.update_magnitudes();
         }
+
+        self.spectral_flux = spectral_flux / amount_channels as f32;
+    }
+
+    /// Returns the highest absolute amplitude of the samples which got processed
+    /// by the last call of [SampleProcessor::process_next_samples].
+    pub fn peak(&self) -> f32 {
+        self.peak
+    }
+
+    /// Returns `true` if the samples of the last call of [SampleProcessor::process_next_samples]
+    /// are (nearly) silent.
+    pub fn is_silent(&self) -> bool {
+        self.peak < SILENCE_THRESHOLD
+    }
+
+    /// Returns how much the spectrum changed with the last call of [SampleProcessor::process_next_samples]
+    /// relative to the spectrum of the call before.
+    ///
+    /// `0` means that the spectrum didn't change at all, values around `1` and above mean
+    /// that the spectrum changed a lot (for example on a beat).
+    pub fn spectral_flux(&self) -> f32 {
+        self.spectral_flux
     }
 }
 
-impl<F: Fetcher> SampleProcessor<F> {
-    pub(crate) fn fft_size(&self) -> usize {
+/// A type-erased source of processed audio samples.
+///
+/// [crate::BarProcessor]s and the [crate::BpmDetector] can read from every [AudioSource]
+/// which allows to pass a `&dyn AudioSource` around if the type of the fetcher doesn't matter.
+///
+/// This trait is sealed and implemented by [SampleProcessor].
+pub trait AudioSource: private::Sealed {
+    /// Returns the amount of calls of [SampleProcessor::process_next_samples] so far.
+    ///
+    /// Everything which is computed from the processed samples only has to be recomputed
+    /// once this value changed, so users of the same audio source can cache their results
+    /// (like [crate::BarProcessor] and [crate::BpmDetector]).
+    fn generation(&self) -> u64;
+
+    /// Returns the amount of new samples (per channel) which the last call of
+    /// [SampleProcessor::process_next_samples] consumed.
+    ///
+    /// It's `0` if the fetcher didn't receive any samples in the meantime,
+    /// for example because the frame rate is higher than the rate of the audio callbacks.
+    fn new_samples(&self) -> usize;
+
+    /// Returns the duration of the new samples of the last call of [SampleProcessor::process_next_samples].
+    fn new_samples_duration(&self) -> Duration {
+        Duration::from_secs_f64(self.new_samples() as f64 / self.sample_rate() as f64)
+    }
+
+    /// Returns the wall-clock time between the last two calls of [SampleProcessor::process_next_samples]
+    /// (`0` after the first call).
+    fn delta_time(&self) -> Duration;
+
+    /// Returns the timestamp of the latest processed sample, relative to the first sample of the fetcher.
+    fn timestamp(&self) -> Duration;
+}
+
+impl<F: Fetcher> AudioSource for SampleProcessor<F> {
+    fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    fn new_samples(&self) -> usize {
+        self.new_samples
+    }
+
+    fn delta_time(&self) -> Duration {
+        self.delta_time
+    }
+
+    fn timestamp(&self) -> Duration {
+        Duration::from_secs_f64(
+            self.total_samples as f64 / private::Sealed::sample_rate(self) as f64,
+        )
+    }
+}
+
+pub(crate) mod private {
+    use super::{FftContext, NonZero, SampleRate};
+
+    pub trait Sealed {
+        fn fft_size(&self) -> usize;
+
+        fn fft_out(&self) -> &[FftContext];
+
+        fn sample_rate(&self) -> SampleRate;
+
+        fn amount_channels(&self) -> NonZero<u8>;
+
+        /// A unique id of the audio source.
+        fn id(&self) -> u64;
+    }
+}
+
+impl<F: Fetcher> private::Sealed for SampleProcessor<F> {
+    fn fft_size(&self) -> usize {
         self.fft_size
     }
 
-    pub(crate) fn fft_out(&self) -> &[FftContext] {
+    fn fft_out(&self) -> &[FftContext] {
         &self.channels
     }
 
-    pub(crate) fn sample_rate(&self) -> SampleRate {
+    fn sample_rate(&self) -> SampleRate {
         self.sample_buffer.lock().unwrap().sample_rate()
     }
 
-    pub(crate) fn amount_channels(&self) -> NonZero<u8> {
+    fn amount_channels(&self) -> NonZero<u8> {
         NonZero::new(self.channels.len() as u8).unwrap()
     }
+
+    fn id(&self) -> u64 {
+        self.id
+    }
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct FftContext {
+pub struct FftContext {
     fft_in: Box<[f32]>,
-    pub fft_out: Box<[Complex32]>,
+    pub(crate) fft_out: Box<[Complex32]>,
     scratch_buffer: Box<[Complex32]>,
+    // the magnitudes of the previous `fft_out`
+    magnitudes: Box<[f32]>,
 }
 
 impl FftContext {
@@ -117,11 +284,114 @@ impl FftContext {
         let fft_in = vec![0.; fft_size].into_boxed_slice();
         let fft_out = vec![Complex32::ZERO; fft_out_size].into_boxed_slice();
         let scratch_buffer = fft_out.clone();
+        let magnitudes = vec![0.; fft_out_size].into_boxed_slice();
 
         Self {
             fft_in,
             fft_out,
             scratch_buffer,
+            magnitudes,
         }
     }
+
+    /// Stores the magnitudes of `fft_out` and returns the spectral flux compared to the previous ones.
+    fn update_magnitudes(&mut self) -> f32 {
+        let mut diff = 0.;
+        let mut total = 0.;
+
+        for (prev, out) in self.magnitudes.iter_mut().zip(self.fft_out.iter()) {
+            let magnitude = out.norm();
+
+            diff += (magnitude - *prev).abs();
+            total += magnitude.max(*prev);
+
+            *prev = magnitude;
+        }
+
+        if total > 0. {
+            diff / total
+        } else {
+            0.
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetcher::DummyFetcher;
+
+    #[test]
+    fn silent_without_samples() {
+        let mut processor = SampleProcessor::new(DummyFetcher::new(2));
+        processor.process_next_samples();
+
+        assert!(processor.is_silent());
+    }
+
+    #[test]
+    fn not_silent_with_samples() {
+        let fetcher = DummyFetcher::new(2);
+        fetcher
+            .sample_buffer()
+            .lock()
+            .unwrap()
+            .push_before(&[0.5, -0.75, 0.25, 0.]);
+
+        let mut processor = SampleProcessor::new(fetcher);
+        processor.process_next_samples();
+
+        assert!(!processor.is_silent());
+        assert_eq!(processor.peak(), 0.75);
+    }
+
+    #[test]
+    fn spectral_flux() {
+        let fetcher = DummyFetcher::new(1);
+        let sample_buffer = fetcher.sample_buffer();
+        let capacity = sample_buffer.lock().unwrap().capacity();
+
+        let mut processor = SampleProcessor::new(fetcher);
+        processor.process_next_samples();
+        assert_eq!(processor.spectral_flux(), 0.);
+
+        let sine: Vec<f32> = (0..capacity).map(|idx| (idx as f32 * 0.3).sin()).collect();
+        sample_buffer.lock().unwrap().push_before(&sine);
+
+        // the spectrum changed completely
+        processor.process_next_samples();
+        assert!(processor.spectral_flux() > 0.9);
+
+        // nothing changed
+        processor.process_next_samples();
+        assert_eq!(processor.spectral_flux(), 0.);
+    }
+
+    #[test]
+    fn new_samples() {
+        let fetcher = DummyFetcher::new(2);
+        let sample_buffer = fetcher.sample_buffer();
+        let sample_rate = sample_buffer.lock().unwrap().sample_rate();
+
+        let mut processor = SampleProcessor::new(fetcher);
+        processor.process_next_samples();
+        assert_eq!(processor.new_samples(), 0);
+        assert_eq!(processor.delta_time(), Duration::ZERO);
+
+        // 441 samples per channel
+        sample_buffer.lock().unwrap().push_before(&[0.1; 882]);
+        processor.process_next_samples();
+        assert_eq!(processor.new_samples(), 441);
+        assert_eq!(
+            processor.new_samples_duration(),
+            Duration::from_secs_f64(441. / sample_rate as f64)
+        );
+
+        processor.process_next_samples();
+        assert_eq!(processor.new_samples(), 0);
+        assert_eq!(
+            processor.timestamp(),
+            Duration::from_secs_f64(441. / sample_rate as f64)
+        );
+    }
 }
@@ -1,7 +1,13 @@
+use std::{fmt, ops::RangeInclusive};
+
 use cpal::{
     traits::{DeviceTrait, HostTrait},
-    DeviceId,
+    DeviceId, SampleRate,
 };
+use regex_automata::meta::Regex;
+
+/// The prefix of device queries which are regular expressions (see [find_device]).
+pub const REGEX_PREFIX: &str = "regex:";
 
 type Devices = std::iter::Filter<cpal::Devices, for<'a> fn(&'a cpal::Device) -> bool>;
 
@@ -15,7 +21,8 @@ pub enum DeviceType {
 }
 
 /// Returns the given output/input device of the given id.
-/// You can retrieve a list of available ids by using the [`get_device_names`] function.
+/// You can retrieve a list of available ids by using the [`get_device_ids`] function.
+/// Use [`find_device`] to find a device by its name or its stable id.
 ///
 /// Returns `Err` if there's a problem retrieving an output/input device.
 /// Returns `Ok(None)` if retrieveing the output/input devices worked find but it couldn't find a device with the given name.
@@ -47,8 +54,263 @@ fn get_devices(device_type: DeviceType) -> Result<Devices, cpal::DevicesError> {
     }
 }
 
+/// Information about an audio device, see [list_devices].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceInfo {
+    /// The human readable name of the device.
+    pub name: String,
+    /// The id of the backend, it might change after a reboot. `None` if the backend doesn't have ids.
+    pub id: Option<DeviceId>,
+    /// An id which only depends on the host and the name of the device: `<host>:<name>`.
+    /// Devices with the same name get `#2`, `#3`, ... appended in the order of the backend.
+    pub stable_id: String,
+    /// `true` if it's the default device of the system.
+    pub is_default: bool,
+    /// The supported sample rates.
+    pub sample_rates: Vec<RangeInclusive<SampleRate>>,
+    /// The highest supported amount of channels.
+    pub max_channels: u16,
+}
+
+impl fmt::Display for DeviceInfo {
+    /// Formats the device as one line, for example:
+    /// `USB Microphone (stable id: "ALSA:USB Microphone", id: "alsa:hw:CARD=Mic,DEV=0", 44100-48000Hz, 2ch)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (stable id: \"{}\"", self.name, self.stable_id)?;
+
+        if let Some(id) = &self.id {
+            write!(f, ", id: \"{}\"", id)?;
+        }
+
+        for range in &self.sample_rates {
+            match range.start() == range.end() {
+                true => write!(f, ", {}Hz", range.start())?,
+                false => write!(f, ", {}-{}Hz", range.start(), range.end())?,
+            }
+        }
+
+        write!(f, ", {}ch)", self.max_channels)?;
+
+        if self.is_default {
+            f.write_str(" [default]")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The errors of [find_device].
+#[derive(thiserror::Error, Debug)]
+pub enum FindDeviceError {
+    #[error(transparent)]
+    Devices(#[from] cpal::DevicesError),
+
+    #[error("Invalid regular expression '{pattern}': {reason}")]
+    InvalidRegex { pattern: String, reason: String },
+}
+
+/// Returns information about all devices of the given type.
+pub fn list_devices(device_type: DeviceType) -> Result<Vec<DeviceInfo>, cpal::DevicesError> {
+    let host = cpal::default_host();
+    let default_id = get_default_device(device_type).and_then(|device| device.id().ok());
+
+    let mut infos: Vec<DeviceInfo> = Vec::new();
+    for device in get_devices(device_type)? {
+        let id = device.id().ok();
+        let name = device
+            .description()
+            .map(|description| description.name().to_string())
+            .or_else(|_| id.as_ref().map(|id| id.1.clone()).ok_or(()))
+            .unwrap_or_else(|_| "unknown".to_string());
+
+        let configs: Vec<cpal::SupportedStreamConfigRange> = match device_type {
+            DeviceType::Input => device
+                .supported_input_configs()
+                .map(Iterator::collect)
+                .unwrap_or_default(),
+            DeviceType::Output => device
+                .supported_output_configs()
+                .map(Iterator::collect)
+                .unwrap_or_default(),
+        };
+
+        let mut sample_rates: Vec<RangeInclusive<SampleRate>> = configs
+            .iter()
+            .map(|config| config.min_sample_rate()..=config.max_sample_rate())
+            .collect();
+        sample_rates.sort_by_key(|range| (*range.start(), *range.end()));
+        sample_rates.dedup();
+
+        let stable_id = stable_id(
+            host.id().name(),
+            &name,
+            infos.iter().filter(|info| info.name == name).count(),
+        );
+
+        infos.push(DeviceInfo {
+            is_default: id.is_some() && id == default_id,
+            stable_id,
+            name,
+            id,
+            sample_rates,
+            max_channels: configs
+                .iter()
+                .map(|config| config.channels())
+                .max()
+                .unwrap_or(0),
+        });
+    }
+
+    Ok(infos)
+}
+
+/// Returns the device which matches `query` the best:
+///
+/// 1. its id (see [DeviceInfo::id])
+/// 2. its stable id (see [DeviceInfo::stable_id])
+/// 3. its whole name (ignoring the case)
+/// 4. a part of its name (ignoring the case)
+///
+/// Queries which start with `regex:` (like `regex:USB.*Mic`) are regular expressions
+/// which have to match a part of the name or the stable id.
+///
+/// Returns `Ok(None)` if no device matches.
+pub fn find_device(
+    query: &str,
+    device_type: DeviceType,
+) -> Result<Option<cpal::Device>, FindDeviceError> {
+    let infos = list_devices(device_type)?;
+    let Some(idx) = best_match(query, &infos)? else {
+        return Ok(None);
+    };
+
+    // `list_devices` uses the same order
+    Ok(get_devices(device_type)?.nth(idx))
+}
+
+/// Returns the index of the device which matches `query` the best (see [find_device]).
+fn best_match(query: &str, infos: &[DeviceInfo]) -> Result<Option<usize>, FindDeviceError> {
+    if let Some(pattern) = query.strip_prefix(REGEX_PREFIX) {
+        let regex = Regex::new(pattern).map_err(|err| FindDeviceError::InvalidRegex {
+            pattern: pattern.to_string(),
+            reason: err.to_string(),
+        })?;
+
+        return Ok(infos.iter().position(|info| {
+            regex.is_match(info.name.as_str()) || regex.is_match(info.stable_id.as_str())
+        }));
+    }
+
+    let query_lowercase = query.to_lowercase();
+    let matchers: [&dyn Fn(&DeviceInfo) -> bool; 4] = [
+        &|info| info.id.as_ref().is_some_and(|id| id.to_string() == query),
+        &|info| info.stable_id == query,
+        &|info| info.name.to_lowercase() == query_lowercase,
+        &|info| info.name.to_lowercase().contains(&query_lowercase),
+    ];
+
+    Ok(matchers
+        .iter()
+        .find_map(|matches| infos.iter().position(matches)))
+}
+
+fn stable_id(host_name: &str, device_name: &str, amount_same_names: usize) -> String {
+    match amount_same_names {
+        0 => format!("{}:{}", host_name, device_name),
+        _ => format!("{}:{}#{}", host_name, device_name, amount_same_names + 1),
+    }
+}
+
 /// Returns a list of device ids which you can use for [`get_device`].
 /// Returns `Err` if there's a problem retrieving an output/input device.
 pub fn get_device_ids(device_type: DeviceType) -> Result<Vec<DeviceId>, cpal::DevicesError> {
     get_devices(device_type).map(|devices| devices.filter_map(|d| d.id().ok()).collect())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+
+    fn info(name: &str, id: &str, same_names: usize) -> DeviceInfo {
+        DeviceInfo {
+            name: name.to_string(),
+            id: Some(DeviceId::from_str(&format!("alsa:{}", id)).unwrap()),
+            stable_id: stable_id("ALSA", name, same_names),
+            is_default: false,
+            sample_rates: vec![44_100..=48_000, 96_000..=96_000],
+            max_channels: 2,
+        }
+    }
+
+    fn devices() -> Vec<DeviceInfo> {
+        vec![
+            info("Built-in Audio Analog Stereo", "hw:CARD=PCH,DEV=0", 0),
+            info("USB Microphone", "hw:CARD=Mic,DEV=0", 0),
+            info("USB Microphone", "hw:CARD=Mic_1,DEV=0", 1),
+            info("Monitor of Built-in Audio", "pulse", 0),
+        ]
+    }
+
+    #[test]
+    fn display() {
+        let mut device = info("USB Microphone", "hw:CARD=Mic,DEV=0", 0);
+        device.is_default = true;
+
+        assert_eq!(
+            device.to_string(),
+            "USB Microphone (stable id: \"ALSA:USB Microphone\", id: \"alsa:hw:CARD=Mic,DEV=0\", 44100-48000Hz, 96000Hz, 2ch) [default]"
+        );
+    }
+
+    #[test]
+    fn stable_ids() {
+        let devices = devices();
+
+        assert_eq!(devices[1].stable_id, "ALSA:USB Microphone");
+        assert_eq!(devices[2].stable_id, "ALSA:USB Microphone#2");
+    }
+
+    #[test]
+    fn match_ids() {
+        let devices = devices();
+
+        assert_eq!(
+            best_match("alsa:hw:CARD=Mic_1,DEV=0", &devices).unwrap(),
+            Some(2)
+        );
+        assert_eq!(
+            best_match("ALSA:USB Microphone#2", &devices).unwrap(),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn match_names() {
+        let devices = devices();
+
+        // the whole name is preferred over a part of another name
+        assert_eq!(
+            best_match("built-in audio analog stereo", &devices).unwrap(),
+            Some(0)
+        );
+        assert_eq!(best_match("monitor", &devices).unwrap(), Some(3));
+        assert_eq!(best_match("speakers", &devices).unwrap(), None);
+    }
+
+    #[test]
+    fn match_regex() {
+        let devices = devices();
+
+        assert_eq!(
+            best_match("regex:^Monitor of .*", &devices).unwrap(),
+            Some(3)
+        );
+        assert_eq!(best_match("regex:Mic.*#2$", &devices).unwrap(), Some(2));
+        assert!(matches!(
+            best_match("regex:(", &devices),
+            Err(FindDeviceError::InvalidRegex { .. })
+        ));
+    }
+}
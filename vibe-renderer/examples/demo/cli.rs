@@ -21,7 +21,10 @@ pub enum ComponentName {
     ChessyBoxVariant,
 
     TextureValueNoise,
+    TextureSimplexNoise,
+    TextureVoronoiNoise,
     TextureSdf,
+    TextureNormalMap,
 
     WallpaperPulseEdges,
 }
@@ -30,8 +33,9 @@ pub enum ComponentName {
 #[command(version, about)]
 pub struct Cli {
     /// Choose which component should be displayed in the demo.
+    /// Repeat it to compare multiple components side by side.
     #[arg(value_enum, long, short)]
-    pub component_name: Option<ComponentName>,
+    pub component_name: Vec<ComponentName>,
 
     /// Show a list of all available output devices which this demo should use.
     #[arg(long)]
@@ -1,7 +1,7 @@
 mod cli;
 mod texture_component;
 
-use std::{num::NonZero, str::FromStr, sync::Arc, time::Instant};
+use std::{collections::HashMap, num::NonZero, str::FromStr, sync::Arc, time::Instant};
 
 use anyhow::bail;
 use cgmath::Deg;
@@ -22,10 +22,12 @@ use vibe_renderer::{
         BarsFormat, BarsPlacement, Chessy, ChessyDescriptor, Circle, CircleDescriptor,
         CircleVariant, FragmentCanvas, FragmentCanvasDescriptor, Graph, GraphDescriptor,
         GraphFormat, GraphVariant, Radial, RadialDescriptor, RadialFormat, RadialVariant,
-        ShaderCode,
+        ShaderCode, Viewport, WithViewport,
     },
-    texture_generation::{SdfMask, SdfPattern, ValueNoise},
-    ComponentAudio, Renderer,
+    texture_generation::{
+        NormalMap, SdfMask, SdfPattern, SimplexNoise, ValueNoise, VoronoiDistance, VoronoiNoise,
+    },
+    Component, FrameUniforms, Renderer,
 };
 use winit::{
     application::ApplicationHandler,
@@ -50,14 +52,15 @@ struct State<'a> {
     window: Arc<Window>,
     time: Instant,
 
-    component: Box<dyn ComponentAudio<SystemAudioFetcher>>,
+    components: Vec<Box<dyn Component>>,
+    frame_uniforms: FrameUniforms,
 }
 
 impl<'a> State<'a> {
-    pub fn new<'b>(
+    pub fn new(
         window: Window,
-        processor: &'b SampleProcessor<SystemAudioFetcher>,
-        component_name: ComponentName,
+        processor: &SampleProcessor<SystemAudioFetcher>,
+        component_names: &[ComponentName],
     ) -> anyhow::Result<Self> {
         let window = Arc::new(window);
         let size = window.inner_size();
@@ -85,278 +88,27 @@ impl<'a> State<'a> {
 
         surface.configure(renderer.device(), &surface_config);
 
-        let component: Box<dyn ComponentAudio<SystemAudioFetcher>> = match component_name {
-            ComponentName::Aurodio => Ok(Box::new(Aurodio::new(&AurodioDescriptor {
-                renderer: &renderer,
-                sample_processor: &processor,
-                texture_format: surface_config.format,
-                layers: &[
-                    AurodioLayerDescriptor {
-                        freq_range: NonZero::new(50).unwrap()..NonZero::new(250).unwrap(),
-                        zoom_factor: 3.,
-                    },
-                    AurodioLayerDescriptor {
-                        freq_range: NonZero::new(500).unwrap()..NonZero::new(2_000).unwrap(),
-                        zoom_factor: 5.,
-                    },
-                    AurodioLayerDescriptor {
-                        freq_range: NonZero::new(4_000).unwrap()..NonZero::new(6_000).unwrap(),
-                        zoom_factor: 10.,
-                    },
-                ],
-                base_color: [0., 0.5, 0.5].into(),
-                movement_speed: 0.005,
-                sensitivity: 0.2,
-                seed: None,
-            })) as Box<dyn ComponentAudio<_>>),
-            ComponentName::BarsColorVariant => Bars::new(&BarsDescriptor {
-                renderer: &renderer,
-                sample_processor: &processor,
-                audio_conf: BarProcessorConfig {
-                    amount_bars: std::num::NonZero::new(60).unwrap(),
-                    sensitivity: 4.,
-                    ..Default::default()
-                },
-                texture_format: surface_config.format,
-                max_height: 0.5,
-                variant: BarVariant::Color([0., 0., 1., 1.].into()),
-                // placement: BarsPlacement::Custom {
-                //     bottom_left_corner: (0.5, 0.5),
-                //     width_factor: 0.5,
-                //     rotation: cgmath::Deg(45.),
-                // },
-                placement: BarsPlacement::Bottom,
-                format: BarsFormat::BassTreble,
-            })
-            .map(|bars| Box::new(bars) as Box<dyn ComponentAudio<_>>),
-            ComponentName::BarsPresenceGradientVariant => Bars::new(&BarsDescriptor {
-                renderer: &renderer,
-                sample_processor: &processor,
-                audio_conf: BarProcessorConfig {
-                    sensitivity: 4.,
-                    amount_bars: NonZero::new(30).unwrap(),
-                    ..Default::default()
-                },
-                texture_format: surface_config.format,
-                max_height: 0.25,
-                variant: BarVariant::PresenceGradient {
-                    high: TURQUOISE.into(),
-                    low: DARK_BLUE.into(),
-                },
-                placement: BarsPlacement::Custom {
-                    bottom_left_corner: (0., 0.5),
-                    width: NonZero::new(100).unwrap(),
-                    rotation: Deg(0.),
-                    height_mirrored: true,
-                },
-                format: BarsFormat::TrebleBassTreble,
+        let components = component_names
+            .iter()
+            .zip(grid(component_names.len()))
+            .map(|(&component_name, viewport)| {
+                let component =
+                    create_component(&renderer, processor, surface_config.format, component_name)?;
+
+                // a single component gets the whole window
+                Ok(match viewport {
+                    Some(viewport) => Box::new(WithViewport::new(
+                        &renderer,
+                        component,
+                        viewport,
+                        surface_config.format,
+                    )),
+                    None => component,
+                })
             })
-            .map(|bars| Box::new(bars) as Box<dyn ComponentAudio<_>>),
-            ComponentName::CircleCurvedVariant => Ok(Box::new(Circle::new(&CircleDescriptor {
-                renderer: &renderer,
-                sample_processor: processor,
-                audio_conf: vibe_audio::BarProcessorConfig {
-                    amount_bars: std::num::NonZero::new(30).unwrap(),
-                    ..Default::default()
-                },
-                texture_format: surface_config.format,
-                variant: CircleVariant::Graph {
-                    spike_sensitivity: 0.3,
-                    color: TURQUOISE.into(),
-                },
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
-                radius: 0.1,
-                rotation: cgmath::Deg(90.),
-                position: (0.5, 0.5),
-            })) as Box<dyn ComponentAudio<_>>),
-            ComponentName::FragmentCanvas => {
-                let fragment_source = ShaderCode {
-                    language: vibe_renderer::components::ShaderLanguage::Wgsl,
-                    source: vibe_renderer::components::ShaderSource::Code(
-                        "
-                    @fragment
-                    fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
-                        let uv = pos.xy / iResolution.xy - iMouse.xy;
-                        return vec4(abs(uv), .0, 1.);
-                    }
-                    "
-                        .into(),
-                    ),
-                };
-
-                FragmentCanvas::new(&FragmentCanvasDescriptor {
-                    sample_processor: &processor,
-                    audio_conf: vibe_audio::BarProcessorConfig::default(),
-                    renderer: &renderer,
-                    format: surface_config.format,
-
-                    img: None,
-                    fragment_code: fragment_source,
-                })
-                .map(|fc| Box::new(fc) as Box<dyn ComponentAudio<_>>)
-            }
-            ComponentName::GraphColorVariant => Ok(Box::new(Graph::new(&GraphDescriptor {
-                renderer: &renderer,
-                sample_processor: processor,
-                audio_conf: BarProcessorConfig::default(),
-                output_texture_format: surface_config.format,
-                variant: GraphVariant::Color(BLUE.into()),
-                max_height: 0.5,
-                format: GraphFormat::BassTreble,
-                // placement: vibe_renderer::components::GraphPlacement::Bottom,
-                placement: vibe_renderer::components::GraphPlacement::Custom {
-                    bottom_left_corner: [0.25, 0.2],
-                    rotation: Deg(-45.),
-                    amount_bars: NonZero::new(500).unwrap(),
-                },
-            })) as Box<dyn ComponentAudio<_>>),
-            ComponentName::GraphHorizontalGradientVariant => {
-                Ok(Box::new(Graph::new(&GraphDescriptor {
-                    renderer: &renderer,
-                    sample_processor: processor,
-                    audio_conf: BarProcessorConfig {
-                        sensitivity: 4.0,
-                        ..Default::default()
-                    },
-                    output_texture_format: surface_config.format,
-                    variant: GraphVariant::HorizontalGradient {
-                        left: RED.into(),
-                        right: BLUE.into(),
-                    },
-                    max_height: 0.5,
-                    format: GraphFormat::BassTreble,
-                    placement: vibe_renderer::components::GraphPlacement::Bottom,
-                })) as Box<dyn ComponentAudio<_>>)
-            }
-            ComponentName::GraphVerticalGradientVariant => {
-                Ok(Box::new(Graph::new(&GraphDescriptor {
-                    renderer: &renderer,
-                    sample_processor: processor,
-                    audio_conf: BarProcessorConfig {
-                        amount_bars: NonZero::new(256).unwrap(),
-                        sensitivity: 4.0,
-                        ..Default::default()
-                    },
-                    output_texture_format: surface_config.format,
-                    variant: GraphVariant::VerticalGradient {
-                        top: RED.into(),
-                        bottom: BLUE.into(),
-                    },
-                    max_height: 0.5,
-                    format: GraphFormat::BassTrebleBass,
-                    placement: vibe_renderer::components::GraphPlacement::Bottom,
-                    // placement: vibe_renderer::components::GraphPlacement::Custom {
-                    //     bottom_left_corner: [0.5, 0.2],
-                    //     rotation: Deg(-45.),
-                    // },
-                })) as Box<dyn ComponentAudio<_>>)
-            }
-            ComponentName::RadialColorVariant => Ok(Box::new(Radial::new(&RadialDescriptor {
-                renderer: &renderer,
-                processor,
-                audio_conf: vibe_audio::BarProcessorConfig {
-                    amount_bars: NonZero::new(60).unwrap(),
-                    sensitivity: 4.0,
-                    ..Default::default()
-                },
-                output_texture_format: surface_config.format,
-
-                variant: RadialVariant::Color(RED.into()),
-
-                init_rotation: cgmath::Deg(90.),
-                circle_radius: 0.2,
-                bar_height_sensitivity: 0.5,
-                bar_width: 0.015,
-                position: (0.5, 0.5),
-                format: RadialFormat::TrebleBass,
-            })) as Box<dyn ComponentAudio<_>>),
-
-            ComponentName::RadialHeightGradientVariant => {
-                Ok(Box::new(Radial::new(&RadialDescriptor {
-                    renderer: &renderer,
-                    processor,
-                    audio_conf: vibe_audio::BarProcessorConfig {
-                        amount_bars: NonZero::new(60).unwrap(),
-                        sensitivity: 4.0,
-                        ..Default::default()
-                    },
-                    output_texture_format: surface_config.format,
-
-                    variant: RadialVariant::HeightGradient {
-                        inner: RED.into(),
-                        outer: WHITE.into(),
-                    },
-
-                    init_rotation: cgmath::Deg(90.),
-                    circle_radius: 0.3,
-                    bar_height_sensitivity: 1.,
-                    bar_width: 0.02,
-                    position: (0.5, 0.5),
-                    format: RadialFormat::TrebleBass,
-                })) as Box<dyn ComponentAudio<_>>)
-            }
-            ComponentName::ChessyBoxVariant => Ok(Box::new(Chessy::new(&ChessyDescriptor {
-                renderer: &renderer,
-                sample_processor: processor,
-                audio_config: BarProcessorConfig {
-                    amount_bars: NonZero::new(10).unwrap(),
-                    ..Default::default()
-                },
-                texture_format: surface_config.format,
-                movement_speed: 0.1,
-                pattern: SdfPattern::Box,
-                zoom_factor: 4.,
-            })) as Box<dyn ComponentAudio<_>>),
-
-            ComponentName::TextureValueNoise => {
-                let texture = renderer.generate(&ValueNoise {
-                    texture_size: 256,
-                    octaves: 7,
-                    seed: None,
-                });
-
-                Ok(Box::new(TextureComponent::new(&TextureComponentDescriptor {
-                    device: renderer.device(),
-                    texture,
-                    format: surface_config.format,
-                })) as Box<dyn ComponentAudio<_>>)
-            }
-            ComponentName::TextureSdf => {
-                let texture = renderer.generate(&SdfMask {
-                    texture_size: 256,
-                    pattern: SdfPattern::Box,
-                });
-
-                Ok(Box::new(TextureComponent::new(&TextureComponentDescriptor {
-                    device: renderer.device(),
-                    texture,
-                    format: surface_config.format,
-                })) as Box<dyn ComponentAudio<_>>)
-            }
-            ComponentName::WallpaperPulseEdges => Ok(Box::new(
-                PulseEdges::new(&PulseEdgesDescriptor {
-                    renderer: &renderer,
-                    sample_processor: &processor,
-                    img: image::ImageReader::open("./assets/castle.jpg")
-                        .unwrap()
-                        .decode()
-                        .unwrap(),
-
-                    freq_range: NonZero::new(100).unwrap()..NonZero::new(250).unwrap(),
-                    audio_sensitivity: 8.,
-                    texture_format: surface_config.format,
-
-                    low_threshold_ratio: 0.4,
-                    high_threshold_ratio: 0.6,
-                    wallpaper_brightness: 0.5,
-                    edge_width: 0.3,
-                    pulse_brightness: 1.5,
-                    sigma: 10.,
-                    kernel_size: 49,
-                })
-                .unwrap(),
-            ) as Box<dyn ComponentAudio<_>>),
-        }?;
+        let frame_uniforms = FrameUniforms::new(&renderer);
 
         Ok(Self {
             time,
@@ -364,7 +116,8 @@ impl<'a> State<'a> {
             surface,
             window,
             surface_config,
-            component,
+            components,
+            frame_uniforms,
         })
     }
 
@@ -375,8 +128,9 @@ impl<'a> State<'a> {
             self.surface
                 .configure(self.renderer.device(), &self.surface_config);
 
-            self.component
-                .update_resolution(&self.renderer, [new_size.width, new_size.height]);
+            for component in self.components.iter_mut() {
+                component.update_resolution(&self.renderer, [new_size.width, new_size.height]);
+            }
         }
     }
 
@@ -384,17 +138,21 @@ impl<'a> State<'a> {
         &mut self,
         processor: &SampleProcessor<SystemAudioFetcher>,
     ) -> Result<(), wgpu::SurfaceError> {
-        self.component
-            .update_audio(self.renderer.queue(), processor);
-        self.component
-            .update_time(self.renderer.queue(), self.time.elapsed().as_secs_f32());
+        let curr_time = self.time.elapsed().as_secs_f32();
+        for component in self.components.iter_mut() {
+            component.update_audio(self.renderer.queue(), processor);
+            component.update_time(self.renderer.queue(), curr_time);
+        }
+        self.frame_uniforms.set_time(curr_time);
+
         let surface_texture = self.surface.get_current_texture()?;
 
         let view = surface_texture
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        self.renderer.render(&view, &[self.component.as_ref()]);
+        self.renderer
+            .render(&view, &self.frame_uniforms, &self.components);
 
         surface_texture.present();
         Ok(())
@@ -404,19 +162,373 @@ impl<'a> State<'a> {
         let rel_x = new_pos.x as f32 / self.surface_config.width as f32;
         let rel_y = new_pos.y as f32 / self.surface_config.height as f32;
 
-        self.component
-            .update_mouse_position(self.renderer.queue(), (rel_x, rel_y));
+        for component in self.components.iter_mut() {
+            component.update_mouse_position(self.renderer.queue(), (rel_x, rel_y));
+        }
+        self.frame_uniforms.set_mouse_position((rel_x, rel_y));
+    }
+}
+
+/// Creates the component with the hardcoded descriptor of the demo.
+fn create_component(
+    renderer: &Renderer,
+    processor: &SampleProcessor<SystemAudioFetcher>,
+    format: wgpu::TextureFormat,
+    component_name: ComponentName,
+) -> anyhow::Result<Box<dyn Component>> {
+    let component: Box<dyn Component> = match component_name {
+        ComponentName::Aurodio => Ok(Box::new(Aurodio::new(&AurodioDescriptor {
+            renderer,
+            sample_processor: processor,
+            texture_format: format,
+            layers: &[
+                AurodioLayerDescriptor {
+                    freq_range: NonZero::new(50).unwrap()..NonZero::new(250).unwrap(),
+                    zoom_factor: 3.,
+                },
+                AurodioLayerDescriptor {
+                    freq_range: NonZero::new(500).unwrap()..NonZero::new(2_000).unwrap(),
+                    zoom_factor: 5.,
+                },
+                AurodioLayerDescriptor {
+                    freq_range: NonZero::new(4_000).unwrap()..NonZero::new(6_000).unwrap(),
+                    zoom_factor: 10.,
+                },
+            ],
+            base_color: [0., 0.5, 0.5].into(),
+            movement_speed: 0.005,
+            sensitivity: 0.2,
+            seed: None,
+        })) as Box<dyn Component>),
+        ComponentName::BarsColorVariant => Bars::new(&BarsDescriptor {
+            renderer,
+            sample_processor: processor,
+            audio_conf: BarProcessorConfig {
+                amount_bars: std::num::NonZero::new(60).unwrap(),
+                sensitivity: 4.,
+                ..Default::default()
+            },
+            texture_format: format,
+            max_height: 0.5,
+            variant: BarVariant::Color([0., 0., 1., 1.].into()),
+            // placement: BarsPlacement::Custom {
+            //     bottom_left_corner: (0.5, 0.5),
+            //     width_factor: 0.5,
+            //     rotation: cgmath::Deg(45.),
+            // },
+            placement: BarsPlacement::Bottom,
+            format: BarsFormat::BassTreble,
+        })
+        .map(|bars| Box::new(bars) as Box<dyn Component>),
+        ComponentName::BarsPresenceGradientVariant => Bars::new(&BarsDescriptor {
+            renderer,
+            sample_processor: processor,
+            audio_conf: BarProcessorConfig {
+                sensitivity: 4.,
+                amount_bars: NonZero::new(30).unwrap(),
+                ..Default::default()
+            },
+            texture_format: format,
+            max_height: 0.25,
+            variant: BarVariant::PresenceGradient {
+                high: TURQUOISE.into(),
+                low: DARK_BLUE.into(),
+            },
+            placement: BarsPlacement::Custom {
+                bottom_left_corner: (0., 0.5),
+                width: NonZero::new(100).unwrap(),
+                rotation: Deg(0.),
+                height_mirrored: true,
+            },
+            format: BarsFormat::TrebleBassTreble,
+        })
+        .map(|bars| Box::new(bars) as Box<dyn Component>),
+        ComponentName::CircleCurvedVariant => Ok(Box::new(Circle::new(&CircleDescriptor {
+            renderer,
+            sample_processor: processor,
+            audio_conf: vibe_audio::BarProcessorConfig {
+                amount_bars: std::num::NonZero::new(30).unwrap(),
+                ..Default::default()
+            },
+            texture_format: format,
+            variant: CircleVariant::Graph {
+                spike_sensitivity: 0.3,
+                color: TURQUOISE.into(),
+            },
+
+            radius: 0.1,
+            rotation: cgmath::Deg(90.),
+            position: (0.5, 0.5),
+        })) as Box<dyn Component>),
+        ComponentName::FragmentCanvas => {
+            let fragment_source = ShaderCode {
+                language: vibe_renderer::components::ShaderLanguage::Wgsl,
+                source: vibe_renderer::components::ShaderSource::Code(
+                    "
+                @fragment
+                fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
+                    let uv = pos.xy / iResolution.xy - iMouse.xy;
+                    return vec4(abs(uv), .0, 1.);
+                }
+                "
+                    .into(),
+                ),
+            };
+
+            FragmentCanvas::new(&FragmentCanvasDescriptor {
+                sample_processor: processor,
+                audio_conf: vibe_audio::BarProcessorConfig::default(),
+                renderer,
+                format,
+
+                img: None,
+                fragment_code: fragment_source,
+                readback_slots: Vec::new(),
+                params: HashMap::new(),
+            })
+            .map(|fc| Box::new(fc) as Box<dyn Component>)
+        }
+        ComponentName::GraphColorVariant => Ok(Box::new(Graph::new(&GraphDescriptor {
+            renderer,
+            sample_processor: processor,
+            audio_conf: BarProcessorConfig::default(),
+            output_texture_format: format,
+            variant: GraphVariant::Color(BLUE.into()),
+            max_height: 0.5,
+            format: GraphFormat::BassTreble,
+            // placement: vibe_renderer::components::GraphPlacement::Bottom,
+            placement: vibe_renderer::components::GraphPlacement::Custom {
+                bottom_left_corner: [0.25, 0.2],
+                rotation: Deg(-45.),
+                amount_bars: NonZero::new(500).unwrap(),
+            },
+        })) as Box<dyn Component>),
+        ComponentName::GraphHorizontalGradientVariant => Ok(Box::new(Graph::new(&GraphDescriptor {
+            renderer,
+            sample_processor: processor,
+            audio_conf: BarProcessorConfig {
+                sensitivity: 4.0,
+                ..Default::default()
+            },
+            output_texture_format: format,
+            variant: GraphVariant::HorizontalGradient {
+                left: RED.into(),
+                right: BLUE.into(),
+            },
+            max_height: 0.5,
+            format: GraphFormat::BassTreble,
+            placement: vibe_renderer::components::GraphPlacement::Bottom,
+        })) as Box<dyn Component>),
+        ComponentName::GraphVerticalGradientVariant => {
+            Ok(Box::new(Graph::new(&GraphDescriptor {
+                renderer,
+                sample_processor: processor,
+                audio_conf: BarProcessorConfig {
+                    amount_bars: NonZero::new(256).unwrap(),
+                    sensitivity: 4.0,
+                    ..Default::default()
+                },
+                output_texture_format: format,
+                variant: GraphVariant::VerticalGradient {
+                    top: RED.into(),
+                    bottom: BLUE.into(),
+                },
+                max_height: 0.5,
+                format: GraphFormat::BassTrebleBass,
+                placement: vibe_renderer::components::GraphPlacement::Bottom,
+                // placement: vibe_renderer::components::GraphPlacement::Custom {
+                //     bottom_left_corner: [0.5, 0.2],
+                //     rotation: Deg(-45.),
+                // },
+            })) as Box<dyn Component>)
+        }
+        ComponentName::RadialColorVariant => Ok(Box::new(Radial::new(&RadialDescriptor {
+            renderer,
+            processor,
+            audio_conf: vibe_audio::BarProcessorConfig {
+                amount_bars: NonZero::new(60).unwrap(),
+                sensitivity: 4.0,
+                ..Default::default()
+            },
+            output_texture_format: format,
+
+            variant: RadialVariant::Color(RED.into()),
+
+            init_rotation: cgmath::Deg(90.),
+            circle_radius: 0.2,
+            bar_height_sensitivity: 0.5,
+            bar_width: 0.015,
+            position: (0.5, 0.5),
+            format: RadialFormat::TrebleBass,
+        })) as Box<dyn Component>),
+
+        ComponentName::RadialHeightGradientVariant => Ok(Box::new(Radial::new(&RadialDescriptor {
+            renderer,
+            processor,
+            audio_conf: vibe_audio::BarProcessorConfig {
+                amount_bars: NonZero::new(60).unwrap(),
+                sensitivity: 4.0,
+                ..Default::default()
+            },
+            output_texture_format: format,
+
+            variant: RadialVariant::HeightGradient {
+                inner: RED.into(),
+                outer: WHITE.into(),
+            },
+
+            init_rotation: cgmath::Deg(90.),
+            circle_radius: 0.3,
+            bar_height_sensitivity: 1.,
+            bar_width: 0.02,
+            position: (0.5, 0.5),
+            format: RadialFormat::TrebleBass,
+        })) as Box<dyn Component>),
+        ComponentName::ChessyBoxVariant => Chessy::new(&ChessyDescriptor {
+            renderer,
+            sample_processor: processor,
+            audio_config: BarProcessorConfig {
+                amount_bars: NonZero::new(10).unwrap(),
+                ..Default::default()
+            },
+            texture_format: format,
+            movement_speed: 0.1,
+            pattern: SdfPattern::Box,
+            zoom_factor: 4.,
+        })
+        .map(|chessy| Box::new(chessy) as Box<dyn Component>),
+
+        ComponentName::TextureValueNoise => {
+            let texture = renderer.generate(&ValueNoise {
+                texture_size: 256,
+                octaves: 7,
+                seed: None,
+            });
+
+            Ok(Box::new(TextureComponent::new(&TextureComponentDescriptor {
+                device: renderer.device(),
+                texture,
+                format,
+            })) as Box<dyn Component>)
+        }
+        ComponentName::TextureSimplexNoise => {
+            let texture = renderer.generate(&SimplexNoise {
+                texture_size: 256,
+                octaves: 7,
+                lacunarity: 2.,
+                gain: 0.5,
+                seed: None,
+            });
+
+            Ok(Box::new(TextureComponent::new(&TextureComponentDescriptor {
+                device: renderer.device(),
+                texture,
+                format,
+            })) as Box<dyn Component>)
+        }
+        ComponentName::TextureVoronoiNoise => {
+            let texture = renderer.generate(&VoronoiNoise {
+                texture_size: 256,
+                cells: 8,
+                distance: VoronoiDistance::F1,
+                seed: None,
+            });
+
+            Ok(Box::new(TextureComponent::new(&TextureComponentDescriptor {
+                device: renderer.device(),
+                texture,
+                format,
+            })) as Box<dyn Component>)
+        }
+        ComponentName::TextureSdf => {
+            let texture = renderer.generate(&SdfMask {
+                texture_size: 256,
+                pattern: SdfPattern::Box,
+            });
+
+            Ok(Box::new(TextureComponent::new(&TextureComponentDescriptor {
+                device: renderer.device(),
+                texture,
+                format,
+            })) as Box<dyn Component>)
+        }
+        ComponentName::TextureNormalMap => {
+            let img = image::ImageReader::open("./assets/castle.jpg")
+                .unwrap()
+                .decode()
+                .unwrap();
+
+            let texture = renderer.generate(&NormalMap {
+                src: &img,
+                strength: 2.,
+            });
+
+            Ok(Box::new(TextureComponent::new(&TextureComponentDescriptor {
+                device: renderer.device(),
+                texture,
+                format,
+            })) as Box<dyn Component>)
+        }
+        ComponentName::WallpaperPulseEdges => Ok(Box::new(
+            PulseEdges::new(&PulseEdgesDescriptor {
+                renderer,
+                sample_processor: processor,
+                img: image::ImageReader::open("./assets/castle.jpg")
+                    .unwrap()
+                    .decode()
+                    .unwrap(),
+
+                freq_range: NonZero::new(100).unwrap()..NonZero::new(250).unwrap(),
+                audio_sensitivity: 8.,
+                texture_format: format,
+
+                low_threshold_ratio: 0.4,
+                high_threshold_ratio: 0.6,
+                wallpaper_brightness: 0.5,
+                edge_width: 0.3,
+                pulse_brightness: 1.5,
+                sigma: 10.,
+                kernel_size: 49,
+            })
+            .unwrap(),
+        ) as Box<dyn Component>),
+    }?;
+
+    Ok(component)
+}
+
+/// Splits the window into a grid with a cell for each component.
+///
+/// Returns `None` for a single component.
+fn grid(amount: usize) -> Vec<Option<Viewport>> {
+    if amount == 1 {
+        return vec![None];
     }
+
+    let columns = (amount as f32).sqrt().ceil() as usize;
+    let rows = amount.div_ceil(columns);
+
+    (0..amount)
+        .map(|idx| {
+            Some(Viewport {
+                x: (idx % columns) as f32 / columns as f32,
+                y: (idx / columns) as f32 / rows as f32,
+                width: 1. / columns as f32,
+                height: 1. / rows as f32,
+            })
+        })
+        .collect()
 }
 
 struct App<'a> {
     sample_processor: SampleProcessor<SystemAudioFetcher>,
     state: Option<State<'a>>,
-    variant: ComponentName,
+    // rendered in a grid if there are multiple components
+    variants: Vec<ComponentName>,
 }
 
 impl<'a> App<'a> {
-    pub fn new(variant: ComponentName, device_id: Option<DeviceId>) -> anyhow::Result<Self> {
+    pub fn new(variants: Vec<ComponentName>, device_id: Option<DeviceId>) -> anyhow::Result<Self> {
         let sample_processor = {
             let device = match device_id {
                 Some(device_id) => {
@@ -430,7 +542,7 @@ impl<'a> App<'a> {
                                     "Please choose one from the list.\n",
                                 ],
                                 vibe_audio::util::get_device_ids(DeviceType::Output)?,
-                                device_id.to_string()
+                                device_id
                             )
                         }
                     }
@@ -462,7 +574,7 @@ impl<'a> App<'a> {
         Ok(Self {
             sample_processor,
             state: None,
-            variant,
+            variants,
         })
     }
 }
@@ -473,7 +585,7 @@ impl<'a> ApplicationHandler for App<'a> {
             .create_window(WindowAttributes::default().with_title("Vibe renderer - Demo"))
             .unwrap();
 
-        self.state = Some(State::new(window, &self.sample_processor, self.variant).unwrap());
+        self.state = Some(State::new(window, &self.sample_processor, &self.variants).unwrap());
     }
 
     fn window_event(
@@ -518,12 +630,12 @@ fn main() -> anyhow::Result<()> {
         return Ok(());
     }
 
-    if let Some(component) = cli.component_name {
+    if !cli.component_name.is_empty() {
         let event_loop = EventLoop::new()?;
 
         event_loop.set_control_flow(winit::event_loop::ControlFlow::Wait);
         let mut app = App::new(
-            component,
+            cli.component_name,
             cli.output_device_id
                 .map(|id| DeviceId::from_str(&id).unwrap()),
         )?;
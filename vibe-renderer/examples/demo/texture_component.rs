@@ -1,5 +1,4 @@
-use vibe_audio::fetcher::Fetcher;
-use vibe_renderer::{Component, ComponentAudio, Renderable};
+use vibe_renderer::{Component, Renderable};
 use wgpu::{include_wgsl, util::DeviceExt};
 
 #[repr(C)]
@@ -114,11 +113,9 @@ impl Renderable for TextureComponent {
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for TextureComponent {
-    fn update_audio(&mut self, _queue: &wgpu::Queue, _processor: &vibe_audio::SampleProcessor<F>) {}
-}
-
 impl Component for TextureComponent {
+    fn update_audio(&mut self, _queue: &wgpu::Queue, _processor: &dyn vibe_audio::AudioSource) {}
+
     fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
     fn update_resolution(&mut self, renderer: &vibe_renderer::Renderer, new_resolution: [u32; 2]) {
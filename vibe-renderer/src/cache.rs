@@ -1,6 +1,11 @@
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
-use tracing::info;
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::SystemTime,
+};
+use tracing::{info, warn};
 
 use crate::{texture_generation::TextureGenerator, Renderer};
 
@@ -8,6 +13,12 @@ const TEXTURE_INFO_FILE_NAME: &str = "info.toml";
 const TEXTURE_FILE_NAME: &str = "texture.raw";
 const TEXTURE_CHECKSUM_FILE_NAME: &str = "texture.hash";
 
+/// The default maximum size of the cache in bytes (1 GiB).
+pub const DEFAULT_MAX_SIZE: u64 = 1024 * 1024 * 1024;
+
+/// `u64::MAX` is used to mark the cache as unbounded.
+static MAX_SIZE: AtomicU64 = AtomicU64::new(DEFAULT_MAX_SIZE);
+
 /// Information about the cached texture.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct TextureInfo {
@@ -29,6 +40,27 @@ pub enum CacheError {
     SerializeTextureInfo { path: String, err: toml::ser::Error },
 }
 
+/// Some statistics about the cache.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The amount of cached textures.
+    pub amount_entries: usize,
+
+    /// The size of all cached textures in bytes.
+    pub size: u64,
+
+    /// The maximum size of the cache in bytes. `None` if the cache is unbounded.
+    pub max_size: Option<u64>,
+}
+
+/// A cached texture within the cache directory.
+#[derive(Debug)]
+struct CacheEntry {
+    dir_path: PathBuf,
+    size: u64,
+    last_used: SystemTime,
+}
+
 pub trait Cacheable {
     /// The subpath within the main cache directory of this cacheable texture.
     fn subpath(&self) -> PathBuf;
@@ -36,10 +68,55 @@ pub trait Cacheable {
     /// The checksum of the cached texture.
     fn checksum(&self) -> u64;
 
+    /// The version of the generator of the cached texture.
+    ///
+    /// Bump it whenever the generated texture changes (for example after improving its shader)
+    /// so that stale textures in the cache get regenerated.
+    fn version(&self) -> u32 {
+        0
+    }
+
     /// The format of the cached texture
     fn format(&self) -> wgpu::TextureFormat;
 }
 
+/// Sets the maximum size of the cache in bytes.
+/// If the cache exceeds this size, the least recently used textures will be removed
+/// after a new texture got stored.
+///
+/// `None` removes the limit.
+pub fn set_max_size(max_size: Option<u64>) {
+    MAX_SIZE.store(max_size.unwrap_or(u64::MAX), Ordering::Relaxed);
+}
+
+/// Returns the maximum size of the cache in bytes. `None` if the cache is unbounded.
+pub fn max_size() -> Option<u64> {
+    match MAX_SIZE.load(Ordering::Relaxed) {
+        u64::MAX => None,
+        max_size => Some(max_size),
+    }
+}
+
+/// Returns some statistics about the current state of the cache.
+pub fn stats() -> Result<CacheStats, CacheError> {
+    let entries = entries(&crate::get_cache_dir(""))?;
+
+    Ok(CacheStats {
+        amount_entries: entries.len(),
+        size: entries.iter().map(|entry| entry.size).sum(),
+        max_size: max_size(),
+    })
+}
+
+/// Removes all cached textures.
+pub fn clear_all() -> Result<(), CacheError> {
+    for entry in entries(&crate::get_cache_dir(""))? {
+        remove_entry(&entry)?;
+    }
+
+    Ok(())
+}
+
 pub fn load<C: Cacheable + TextureGenerator>(
     renderer: &Renderer,
     cacheable: &C,
@@ -47,6 +124,12 @@ pub fn load<C: Cacheable + TextureGenerator>(
     match load_texture(renderer, cacheable)? {
         Some(cached_texture) => {
             info!("Cache hit!");
+
+            let dir_path = crate::get_cache_dir(cacheable.subpath());
+            if let Err(err) = mark_as_used(&dir_path) {
+                warn!("{}", err);
+            }
+
             Ok(cached_texture)
         }
         None => {
@@ -54,6 +137,16 @@ pub fn load<C: Cacheable + TextureGenerator>(
 
             let texture = renderer.generate(cacheable);
             store_texture(renderer, cacheable, &texture)?;
+
+            if let Some(max_size) = max_size() {
+                let root = crate::get_cache_dir("");
+                let keep = crate::get_cache_dir(cacheable.subpath());
+
+                if let Err(err) = evict(&root, max_size, &keep) {
+                    warn!("Couldn't evict old textures from the cache: {}", err);
+                }
+            }
+
             Ok(texture)
         }
     }
@@ -100,7 +193,7 @@ fn load_texture<C: Cacheable>(
         bytes_buffer.copy_from_slice(&current_checksum_bytes);
 
         let current_checksum = u64::from_be_bytes(bytes_buffer);
-        current_checksum == cacheable.checksum()
+        current_checksum == versioned_checksum(cacheable)
     };
 
     if !cache_is_up_to_date {
@@ -267,12 +360,226 @@ fn store_texture<C: Cacheable>(
         err,
     })?;
 
-    std::fs::write(&texture_checksum_path, cacheable.checksum().to_be_bytes()).map_err(|err| {
-        CacheError::IO {
-            path: texture_checksum_path.to_string_lossy().to_string(),
-            err,
-        }
+    std::fs::write(
+        &texture_checksum_path,
+        versioned_checksum(cacheable).to_be_bytes(),
+    )
+    .map_err(|err| CacheError::IO {
+        path: texture_checksum_path.to_string_lossy().to_string(),
+        err,
     })?;
 
     Ok(())
 }
+
+/// Combines the checksum of the cacheable with the version of its generator.
+fn versioned_checksum<C: Cacheable>(cacheable: &C) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    cacheable.checksum().hash(&mut hasher);
+    cacheable.version().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Collects all cached textures within `root`.
+fn entries(root: &Path) -> Result<Vec<CacheEntry>, CacheError> {
+    let io_err = |path: &Path| {
+        let path = path.to_string_lossy().to_string();
+        move |err| CacheError::IO { path, err }
+    };
+
+    let mut entries = Vec::new();
+    for dir_entry in std::fs::read_dir(root).map_err(io_err(root))? {
+        let dir_path = dir_entry.map_err(io_err(root))?.path();
+
+        let texture_file_path = dir_path.join(TEXTURE_FILE_NAME);
+        if !texture_file_path.is_file() {
+            continue;
+        }
+
+        let last_used = std::fs::metadata(&texture_file_path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(io_err(&texture_file_path))?;
+
+        let mut size = 0;
+        for file in std::fs::read_dir(&dir_path).map_err(io_err(&dir_path))? {
+            let file = file.map_err(io_err(&dir_path))?;
+            size += file.metadata().map_err(io_err(&file.path()))?.len();
+        }
+
+        entries.push(CacheEntry {
+            dir_path,
+            size,
+            last_used,
+        });
+    }
+
+    Ok(entries)
+}
+
+/// Removes the least recently used textures within `root` until the cache fits into `max_size`.
+/// The entry in `keep` won't be removed.
+fn evict(root: &Path, max_size: u64, keep: &Path) -> Result<(), CacheError> {
+    let mut entries = entries(root)?;
+    let mut size: u64 = entries.iter().map(|entry| entry.size).sum();
+
+    entries.sort_by_key(|entry| entry.last_used);
+    for entry in entries.iter().filter(|entry| entry.dir_path != keep) {
+        if size <= max_size {
+            break;
+        }
+
+        info!(
+            "Removing '{}' from the cache.",
+            entry.dir_path.to_string_lossy()
+        );
+        remove_entry(entry)?;
+        size -= entry.size;
+    }
+
+    Ok(())
+}
+
+fn remove_entry(entry: &CacheEntry) -> Result<(), CacheError> {
+    std::fs::remove_dir_all(&entry.dir_path).map_err(|err| CacheError::IO {
+        path: entry.dir_path.to_string_lossy().to_string(),
+        err,
+    })
+}
+
+/// Updates the "last used" timestamp of the cached texture in `dir_path`.
+fn mark_as_used(dir_path: &Path) -> Result<(), CacheError> {
+    let texture_file_path = dir_path.join(TEXTURE_FILE_NAME);
+
+    std::fs::File::options()
+        .append(true)
+        .open(&texture_file_path)
+        .and_then(|file| file.set_modified(SystemTime::now()))
+        .map_err(|err| CacheError::IO {
+            path: texture_file_path.to_string_lossy().to_string(),
+            err,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn test_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("vibe-cache-test-{}", name));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        root
+    }
+
+    fn add_entry(root: &Path, name: &str, size: usize, age_secs: u64) -> PathBuf {
+        let dir_path = root.join(name);
+        std::fs::create_dir_all(&dir_path).unwrap();
+
+        let texture_file_path = dir_path.join(TEXTURE_FILE_NAME);
+        std::fs::write(&texture_file_path, vec![0u8; size]).unwrap();
+        std::fs::File::options()
+            .append(true)
+            .open(&texture_file_path)
+            .unwrap()
+            .set_modified(SystemTime::now() - Duration::from_secs(age_secs))
+            .unwrap();
+
+        dir_path
+    }
+
+    struct Dummy {
+        version: u32,
+    }
+
+    impl Cacheable for Dummy {
+        fn subpath(&self) -> PathBuf {
+            "dummy".into()
+        }
+
+        fn checksum(&self) -> u64 {
+            42
+        }
+
+        fn version(&self) -> u32 {
+            self.version
+        }
+
+        fn format(&self) -> wgpu::TextureFormat {
+            wgpu::TextureFormat::R8Unorm
+        }
+    }
+
+    #[test]
+    fn version_changes_checksum() {
+        assert_eq!(
+            versioned_checksum(&Dummy { version: 0 }),
+            versioned_checksum(&Dummy { version: 0 })
+        );
+        assert_ne!(
+            versioned_checksum(&Dummy { version: 0 }),
+            versioned_checksum(&Dummy { version: 1 })
+        );
+    }
+
+    #[test]
+    fn evict_least_recently_used() {
+        let root = test_root("evict_least_recently_used");
+        let oldest = add_entry(&root, "oldest", 100, 30);
+        let old = add_entry(&root, "old", 100, 20);
+        let new = add_entry(&root, "new", 100, 10);
+
+        evict(&root, 200, &new).unwrap();
+
+        assert!(!oldest.exists());
+        assert!(old.exists());
+        assert!(new.exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn keep_entry_even_if_it_exceeds_limit() {
+        let root = test_root("keep_entry_even_if_it_exceeds_limit");
+        let old = add_entry(&root, "old", 100, 20);
+        let new = add_entry(&root, "new", 300, 10);
+
+        evict(&root, 200, &new).unwrap();
+
+        assert!(!old.exists());
+        assert!(new.exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn mark_as_used_prevents_eviction() {
+        let root = test_root("mark_as_used_prevents_eviction");
+        let oldest = add_entry(&root, "oldest", 100, 30);
+        let old = add_entry(&root, "old", 100, 20);
+        let new = add_entry(&root, "new", 100, 10);
+
+        mark_as_used(&oldest).unwrap();
+        evict(&root, 200, &new).unwrap();
+
+        assert!(oldest.exists());
+        assert!(!old.exists());
+        assert!(new.exists());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn entries_ignore_unrelated_files() {
+        let root = test_root("entries_ignore_unrelated_files");
+        add_entry(&root, "texture", 100, 0);
+        std::fs::create_dir_all(root.join("something_else")).unwrap();
+        std::fs::write(root.join("file"), [0u8; 10]).unwrap();
+
+        let entries = entries(&root).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].size, 100);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
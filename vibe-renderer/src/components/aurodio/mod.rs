@@ -1,32 +1,28 @@
 mod descriptor;
 pub use descriptor::*;
 
-use super::{Component, Vec2f, Vec3f};
-use crate::{components::ComponentAudio, texture_generation::ValueNoise, Renderable};
+use super::{Component, Vec3f};
+use crate::{texture_generation::ValueNoise, Renderable};
 use std::num::NonZero;
-use vibe_audio::{fetcher::Fetcher, BarProcessor, BarProcessorConfig};
+use vibe_audio::{fetcher::Fetcher, AudioSource, BarProcessorConfig, SharedBarProcessor};
 use wgpu::{include_wgsl, util::DeviceExt};
 
 type BaseColor = Vec3f;
-type Time = f32;
-type Resolution = Vec2f;
 type MovementSpeed = f32;
 
+// `iTime` and `iResolution` are part of the frame uniforms (bind group 0)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, Default)]
 struct FragmentParams {
     base_color: BaseColor,
-    time: Time,
-    resolution: Resolution,
     movement_speed: MovementSpeed,
-    _padding: u32,
 }
 
 pub struct Aurodio {
-    bar_processors: Box<[BarProcessor]>,
+    bar_processors: Box<[SharedBarProcessor]>,
 
-    bind_group0: wgpu::BindGroup,
-    fragment_params_buffer: wgpu::Buffer,
+    bind_group1: wgpu::BindGroup,
+    _fragment_params_buffer: wgpu::Buffer,
     _zoom_factors_buffer: wgpu::Buffer,
     freqs_buffer: wgpu::Buffer,
 
@@ -43,7 +39,7 @@ impl Aurodio {
             let mut bar_processors = Vec::new();
 
             for layer in desc.layers.iter() {
-                bar_processors.push(BarProcessor::new(
+                bar_processors.push(desc.renderer.bar_processor_pool().get(
                     desc.sample_processor,
                     BarProcessorConfig {
                         amount_bars: NonZero::new(1).unwrap(),
@@ -60,16 +56,13 @@ impl Aurodio {
         let fragment_params_buffer = {
             let fragment_params = FragmentParams {
                 base_color: desc.base_color,
-                time: Time::default(),
-                resolution: Resolution::default(),
                 movement_speed: desc.movement_speed,
-                ..Default::default()
             };
 
             device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Aurodio: Fragment params buffer"),
                 contents: bytemuck::bytes_of(&fragment_params),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                usage: wgpu::BufferUsages::UNIFORM,
             })
         };
 
@@ -107,16 +100,80 @@ impl Aurodio {
             ..Default::default()
         });
 
+        let bind_group1_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Aurodio: Bind group 1 layout"),
+                entries: &[
+                    // fragment params
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // zoom factors
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // value noise texture
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    // sampler
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    // freqs
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
         let pipeline = {
             let vertex_module =
                 device.create_shader_module(include_wgsl!("../utils/full_screen_vertex.wgsl"));
 
             let fragment_module = device.create_shader_module(include_wgsl!("./shader.wgsl"));
 
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Aurodio: Pipeline layout"),
+                bind_group_layouts: &[desc.renderer.frame_uniforms_layout(), &bind_group1_layout],
+                ..Default::default()
+            });
+
             device.create_render_pipeline(&crate::util::simple_pipeline_descriptor(
                 crate::util::SimpleRenderPipelineDescriptor {
                     label: "Aurodio: Render pipeline",
-                    layout: None,
+                    layout: Some(&pipeline_layout),
                     vertex: wgpu::VertexState {
                         module: &vertex_module,
                         entry_point: None,
@@ -137,9 +194,9 @@ impl Aurodio {
             ))
         };
 
-        let bind_group0 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Aurodio: Bind group 0"),
-            layout: &pipeline.get_bind_group_layout(0),
+        let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Aurodio: Bind group 1"),
+            layout: &bind_group1_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -171,8 +228,8 @@ impl Aurodio {
         Self {
             bar_processors,
 
-            bind_group0,
-            fragment_params_buffer,
+            bind_group1,
+            _fragment_params_buffer: fragment_params_buffer,
             _zoom_factors_buffer: zoom_factors_buffer,
             freqs_buffer,
             bar_values_buffer,
@@ -184,14 +241,14 @@ impl Aurodio {
 
 impl Renderable for Aurodio {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
-        pass.set_bind_group(0, &self.bind_group0, &[]);
+        pass.set_bind_group(1, &self.bind_group1, &[]);
         pass.set_pipeline(&self.pipeline);
         pass.draw(0..4, 0..1);
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for Aurodio {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &vibe_audio::SampleProcessor<F>) {
+impl Component for Aurodio {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
         for (idx, bar_processor) in self.bar_processors.iter_mut().enumerate() {
             // we only have one bar
             self.bar_values_buffer[idx] = bar_processor.process_bars(processor)[0][0];
@@ -203,30 +260,12 @@ impl<F: Fetcher> ComponentAudio<F> for Aurodio {
             bytemuck::cast_slice(&self.bar_values_buffer),
         );
     }
-}
 
-impl Component for Aurodio {
-    fn update_time(&mut self, queue: &wgpu::Queue, new_time: f32) {
-        let offset = std::mem::size_of::<BaseColor>();
-
-        queue.write_buffer(
-            &self.fragment_params_buffer,
-            offset as wgpu::BufferAddress,
-            bytemuck::bytes_of(&new_time),
-        );
-    }
+    // `iTime` is part of the frame uniforms
+    fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
-    fn update_resolution(&mut self, renderer: &crate::Renderer, new_resolution: [u32; 2]) {
-        let queue = renderer.queue();
-
-        let offset = std::mem::size_of::<BaseColor>() + std::mem::size_of::<Time>();
-
-        queue.write_buffer(
-            &self.fragment_params_buffer,
-            offset as wgpu::BufferAddress,
-            bytemuck::cast_slice(&[new_resolution[0] as f32, new_resolution[1] as f32]),
-        );
-    }
+    // `iResolution` is part of the frame uniforms
+    fn update_resolution(&mut self, _renderer: &crate::Renderer, _new_resolution: [u32; 2]) {}
 
     fn update_mouse_position(&mut self, _queue: &wgpu::Queue, _new_pos: (f32, f32)) {}
 }
@@ -1,23 +1,28 @@
+// frame uniforms (see `FrameUniforms`)
+@group(0) @binding(0)
+var<uniform> iResolution: vec2f;
+
+@group(0) @binding(2)
+var<uniform> iTime: f32;
+
 struct FragmentParams {
     base_color: vec3f,
-    time: f32,
-    resolution: vec2f,
     movement_speed: f32,
 }
 
-@group(0) @binding(0)
+@group(1) @binding(0)
 var<uniform> fp: FragmentParams;
 
-@group(0) @binding(1)
+@group(1) @binding(1)
 var<storage, read> zoom_factors: array<f32>;
 
-@group(0) @binding(2)
+@group(1) @binding(2)
 var value_noise_texture: texture_2d<f32>;
 
-@group(0) @binding(3)
+@group(1) @binding(3)
 var sampler_nearest: sampler;
 
-@group(0) @binding(4)
+@group(1) @binding(4)
 var<storage, read> freqs: array<f32>;
 
 const CELL_DIAG: f32 = sqrt(2.);
@@ -46,7 +51,7 @@ fn cellular_noise(uv: vec2<f32>, layer_idx: u32, time: f32) -> f32 {
             let offset = vec2f(vec2i(x, y));
             let nid = id + offset;
 
-            let h = hash22(vec2f(nid)) * (f32(layer_idx) + 1.) * 100. + fp.time;
+            let h = hash22(vec2f(nid)) * (f32(layer_idx) + 1.) * 100. + iTime;
             var point = vec2f(cos(h.x), sin(h.y))*.4;
             point += vec2f(offset);
 
@@ -64,9 +69,9 @@ fn dust_layer(uv: vec2<f32>, color: vec3<f32>) -> vec4<f32> {
 
 @fragment
 fn main(@builtin(position) pos: vec4<f32>) -> @location(0) vec4<f32> {
-    let time = fp.time;
+    let time = iTime;
     var col: vec4<f32>;
-    var uv: vec2<f32> = (2. * pos.xy - fp.resolution.xy) / fp.resolution.y;
+    var uv: vec2<f32> = (2. * pos.xy - iResolution.xy) / iResolution.y;
 
     let phase = time * fp.movement_speed;
     uv += 10. * vec2f(cos(phase), sin(phase)) + 20.;
@@ -3,10 +3,10 @@ mod descriptor;
 pub use descriptor::*;
 
 use super::{Component, Pixels, Rgba, ShaderCodeError, Vec2f};
-use crate::{components::ComponentAudio, Renderable};
+use crate::{Renderable, Renderer};
 use cgmath::{Deg, Matrix2, Vector2};
 use std::num::NonZero;
-use vibe_audio::{fetcher::Fetcher, BarProcessor, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, AudioSource, SharedBarProcessor};
 use wgpu::{include_wgsl, util::DeviceExt};
 
 /// The x coords goes from -1 to 1.
@@ -87,16 +87,16 @@ impl FragmentEntrypoint {
 struct RenderCtx {
     freq_buffer: wgpu::Buffer,
 
-    bind_group1: wgpu::BindGroup,
+    bind_group2: wgpu::BindGroup,
     pipeline: wgpu::RenderPipeline,
 }
 
 pub struct Bars {
     amount_bars: NonZero<u16>,
-    bar_processor: BarProcessor,
+    bar_processor: SharedBarProcessor,
 
-    // `left` and `right` share the same bind group 0
-    bind_group0: wgpu::BindGroup,
+    // `left` and `right` share the same bind group 1
+    bind_group1: wgpu::BindGroup,
     vertex_params_buffer: wgpu::Buffer,
     _fragment_params_buffer: wgpu::Buffer,
 
@@ -112,7 +112,10 @@ pub struct Bars {
 impl Bars {
     pub fn new<F: Fetcher>(desc: &BarsDescriptor<F>) -> Result<Self, ShaderCodeError> {
         let device = desc.renderer.device();
-        let bar_processor = BarProcessor::new(desc.sample_processor, desc.audio_conf.clone());
+        let bar_processor = desc
+            .renderer
+            .bar_processor_pool()
+            .get(desc.sample_processor, desc.audio_conf.clone());
         let total_amount_bars = bar_processor.total_amount_bars();
 
         let (bottom_left_corner, angle, width) = match desc.placement {
@@ -233,16 +236,16 @@ impl Bars {
                 };
 
                 create_pipeline(
-                    device,
+                    desc.renderer,
                     desc.texture_format,
                     vertex_entrypoint,
                     fragment_entrypoint,
                 )
             };
 
-            let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Bars: Left bind group 1"),
-                layout: &pipeline.get_bind_group_layout(1),
+            let bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Bars: Left bind group 2"),
+                layout: &pipeline.get_bind_group_layout(2),
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
                     resource: freq_buffer.as_entire_binding(),
@@ -251,14 +254,14 @@ impl Bars {
 
             RenderCtx {
                 freq_buffer,
-                bind_group1,
+                bind_group2,
                 pipeline,
             }
         };
 
-        let bind_group0 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Bars: Bind group 0"),
-            layout: &left.pipeline.get_bind_group_layout(0),
+        let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Bars: Bind group 1"),
+            layout: &left.pipeline.get_bind_group_layout(1),
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -287,15 +290,15 @@ impl Bars {
                 });
 
                 let pipeline = create_pipeline(
-                    device,
+                    desc.renderer,
                     desc.texture_format,
                     vertex_entrypoint,
                     fragment_entrypoint,
                 );
 
-                let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: Some("Bars: Right bind group 1"),
-                    layout: &pipeline.get_bind_group_layout(1),
+                let bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Bars: Right bind group 2"),
+                    layout: &pipeline.get_bind_group_layout(2),
                     entries: &[wgpu::BindGroupEntry {
                         binding: 0,
                         resource: freq_buffer.as_entire_binding(),
@@ -304,7 +307,7 @@ impl Bars {
 
                 RenderCtx {
                     freq_buffer,
-                    bind_group1,
+                    bind_group2,
                     pipeline,
                 }
             })
@@ -314,7 +317,7 @@ impl Bars {
             amount_bars: NonZero::new(total_amount_bars as u16).unwrap(),
             bar_processor,
 
-            bind_group0,
+            bind_group1,
             vertex_params_buffer,
             _fragment_params_buffer: fragment_params_buffer,
 
@@ -331,24 +334,24 @@ impl Renderable for Bars {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
         let amount_bars = self.amount_bars.get() as u32;
 
-        pass.set_bind_group(0, &self.bind_group0, &[]);
+        pass.set_bind_group(1, &self.bind_group1, &[]);
 
         // left half
-        pass.set_bind_group(1, &self.left.bind_group1, &[]);
+        pass.set_bind_group(2, &self.left.bind_group2, &[]);
         pass.set_pipeline(&self.left.pipeline);
         pass.draw(0..4, 0..amount_bars);
 
         // right half (if it exists)
         if let Some(right) = &self.right {
-            pass.set_bind_group(1, &right.bind_group1, &[]);
+            pass.set_bind_group(2, &right.bind_group2, &[]);
             pass.set_pipeline(&right.pipeline);
             pass.draw(0..4, amount_bars..(2 * amount_bars));
         }
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for Bars {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>) {
+impl Component for Bars {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
         let bar_values = self.bar_processor.process_bars(processor);
 
         queue.write_buffer(
@@ -361,9 +364,7 @@ impl<F: Fetcher> ComponentAudio<F> for Bars {
             queue.write_buffer(&right.freq_buffer, 0, bytemuck::cast_slice(&bar_values[1]));
         }
     }
-}
 
-impl Component for Bars {
     fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
     fn update_resolution(&mut self, renderer: &crate::Renderer, new_resolution: [u32; 2]) {
@@ -407,18 +408,20 @@ impl Component for Bars {
 }
 
 fn create_pipeline(
-    device: &wgpu::Device,
+    renderer: &Renderer,
     texture_format: wgpu::TextureFormat,
     vertex_entrypoint: VertexEntrypoint,
     fragment_entrypoint: FragmentEntrypoint,
 ) -> wgpu::RenderPipeline {
+    let device = renderer.device();
     let module = device.create_shader_module(include_wgsl!("./shader.wgsl"));
 
     let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Bars: Pipeline layout"),
         bind_group_layouts: &[
+            renderer.frame_uniforms_layout(),
             &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Bars: Bind group 0 layout"),
+                label: Some("Bars: Bind group 1 layout"),
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
                         binding: 0,
@@ -443,7 +446,7 @@ fn create_pipeline(
                 ],
             }),
             &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Bars: Bind group 1 layout"),
+                label: Some("Bars: Bind group 2 layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
                     visibility: wgpu::ShaderStages::VERTEX,
@@ -16,14 +16,16 @@ struct FragmentParams {
 };
 
 
-@group(0) @binding(0)
+// bind group 0 contains the frame uniforms (see `FrameUniforms`)
+
+@group(1) @binding(0)
 var<uniform> vp: VertexParams;
 
-@group(0) @binding(1)
+@group(1) @binding(1)
 var<uniform> fp: FragmentParams;
 
 // In its own group, due to left (1st source) and right (2nd source) half of bars
-@group(1) @binding(0)
+@group(2) @binding(0)
 var<storage, read> freqs: array<f32>;
 
 const TRUE: u32 = 1;
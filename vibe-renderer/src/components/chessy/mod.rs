@@ -2,42 +2,37 @@ mod descriptor;
 
 pub use descriptor::*;
 
-use super::{Component, Vec2f};
+use super::{Component, ShaderCodeError};
 use crate::{
-    components::ComponentAudio,
     texture_generation::{SdfMask, SdfPattern},
     Renderable,
 };
-use vibe_audio::{fetcher::Fetcher, BarProcessor, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, AudioSource, SharedBarProcessor};
 use wgpu::{include_wgsl, util::DeviceExt};
 
 // this texture size seems good enough for a 1920x1080 screen.
 const DEFAULT_SDF_TEXTURE_SIZE: u32 = 512;
 
-type Resolution = Vec2f;
-type Time = f32;
 type MovementSpeed = f32;
 type ZoomFactor = f32;
 
+// `iTime` and `iResolution` are part of the frame uniforms (bind group 0)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, Default)]
 struct Data {
-    resolution: Resolution,
-    time: Time,
     movement_speed: MovementSpeed,
     zoom_factor: ZoomFactor,
-    _padding: f32,
 }
 
 pub struct Chessy {
-    bar_processor: BarProcessor,
+    bar_processor: SharedBarProcessor,
 
     data_buffer: wgpu::Buffer,
     freqs_buffer: wgpu::Buffer,
     grid_texture: wgpu::Texture,
     grid_sampler: wgpu::Sampler,
 
-    bind_group0: wgpu::BindGroup,
+    bind_group1: wgpu::BindGroup,
     pipeline: wgpu::RenderPipeline,
 
     // data to recreate the grid texture
@@ -45,24 +40,26 @@ pub struct Chessy {
 }
 
 impl Chessy {
-    pub fn new<F: Fetcher>(desc: &ChessyDescriptor<F>) -> Self {
+    pub fn new<F: Fetcher>(desc: &ChessyDescriptor<F>) -> Result<Self, ShaderCodeError> {
         let renderer = desc.renderer;
         let device = renderer.device();
-        let bar_processor = BarProcessor::new(desc.sample_processor, desc.audio_config.clone());
+        desc.pattern.validate(device)?;
+
+        let bar_processor = desc
+            .renderer
+            .bar_processor_pool()
+            .get(desc.sample_processor, desc.audio_config.clone());
         let total_amount_bars = bar_processor.total_amount_bars();
 
         let data = Data {
-            resolution: Resolution::default(),
-            time: 0f32,
             movement_speed: desc.movement_speed,
             zoom_factor: desc.zoom_factor,
-            ..Default::default()
         };
 
         let data_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Chessy: Data buffer"),
             contents: bytemuck::bytes_of(&data),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            usage: wgpu::BufferUsages::UNIFORM,
         });
 
         let freqs_buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -75,7 +72,7 @@ impl Chessy {
         // arbitrary size for the beginning
         let grid_texture = desc.renderer.generate(&SdfMask {
             texture_size: 50,
-            pattern: desc.pattern,
+            pattern: desc.pattern.clone(),
         });
 
         let grid_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
@@ -90,6 +87,53 @@ impl Chessy {
             ..Default::default()
         });
 
+        let bind_group1_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Chessy: Bind group 1 layout"),
+                entries: &[
+                    // data
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // grid texture
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    // grid sampler
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    // freqs
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
         let pipeline = {
             let vertex_module =
                 device.create_shader_module(include_wgsl!("../utils/full_screen_vertex.wgsl"));
@@ -97,10 +141,16 @@ impl Chessy {
             let fragment_module =
                 device.create_shader_module(include_wgsl!("./shaders/fragment_shader.wgsl"));
 
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Chessy: Pipeline layout"),
+                bind_group_layouts: &[renderer.frame_uniforms_layout(), &bind_group1_layout],
+                ..Default::default()
+            });
+
             device.create_render_pipeline(&crate::util::simple_pipeline_descriptor(
                 crate::util::SimpleRenderPipelineDescriptor {
                     label: "Chessy: Render pipeline",
-                    layout: None,
+                    layout: Some(&pipeline_layout),
                     vertex: wgpu::VertexState {
                         module: &vertex_module,
                         entry_point: None,
@@ -121,9 +171,9 @@ impl Chessy {
             ))
         };
 
-        let bind_group0 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Chessy: Bind group 0"),
-            layout: &pipeline.get_bind_group_layout(0),
+        let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Chessy: Bind group 1"),
+            layout: &bind_group1_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -146,7 +196,7 @@ impl Chessy {
             ],
         });
 
-        Self {
+        Ok(Self {
             bar_processor,
 
             data_buffer,
@@ -154,44 +204,34 @@ impl Chessy {
             grid_texture,
             grid_sampler,
 
-            bind_group0,
+            bind_group1,
             pipeline,
 
-            pattern: desc.pattern,
-        }
+            pattern: desc.pattern.clone(),
+        })
     }
 }
 
 impl Renderable for Chessy {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
-        pass.set_bind_group(0, &self.bind_group0, &[]);
+        pass.set_bind_group(1, &self.bind_group1, &[]);
 
         pass.set_pipeline(&self.pipeline);
         pass.draw(0..4, 0..1);
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for Chessy {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>) {
+impl Component for Chessy {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
         let bar_values = self.bar_processor.process_bars(processor);
 
         queue.write_buffer(&self.freqs_buffer, 0, bytemuck::cast_slice(&bar_values[0]));
     }
-}
 
-impl Component for Chessy {
-    fn update_time(&mut self, queue: &wgpu::Queue, new_time: f32) {
-        let resolution_size = 8;
-
-        queue.write_buffer(
-            &self.data_buffer,
-            resolution_size,
-            bytemuck::bytes_of(&new_time),
-        );
-    }
+    // `iTime` is part of the frame uniforms
+    fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
     fn update_resolution(&mut self, renderer: &crate::Renderer, new_resolution: [u32; 2]) {
-        let queue = renderer.queue();
         let device = renderer.device();
 
         {
@@ -208,12 +248,12 @@ impl Component for Chessy {
 
             self.grid_texture = renderer.generate(&SdfMask {
                 texture_size: new_size.ceil() as u32,
-                pattern: self.pattern,
+                pattern: self.pattern.clone(),
             });
 
-            self.bind_group0 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Chessy: Bind group 0"),
-                layout: &self.pipeline.get_bind_group_layout(0),
+            self.bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Chessy: Bind group 1"),
+                layout: &self.pipeline.get_bind_group_layout(1),
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
@@ -237,13 +277,6 @@ impl Component for Chessy {
                     },
                 ],
             });
-
-            // update `resolution` values
-            queue.write_buffer(
-                &self.data_buffer,
-                0,
-                bytemuck::cast_slice(&[new_resolution[0] as f32, new_resolution[1] as f32]),
-            );
         }
     }
 
@@ -1,17 +1,20 @@
+// frame uniforms (see `FrameUniforms`)
+@group(0) @binding(0)
+var<uniform> iResolution: vec2f;
+
 struct Data {
-    resolution: vec2f,
-    position_offset: vec2f,
     color: vec4f,
+    position_offset: vec2f,
     rotation: mat2x2f,
     radius: f32,
     spike_sensitivity: f32,
     freq_radiant_step: f32,
 }
 
-@group(0) @binding(0)
+@group(1) @binding(0)
 var<uniform> data: Data;
 
-@group(0) @binding(1)
+@group(1) @binding(1)
 var<storage, read> freqs: array<f32>;
 
 const PI: f32 = acos(-1.);
@@ -21,8 +24,8 @@ fn rotate(r: f32) -> mat2x2f {
 }
 
 fn get_uv(pos: vec2f) -> vec2f {
-    var uv = pos.xy / data.resolution.xy - data.position_offset;
-    uv.x *= data.resolution.x / data.resolution.y;
+    var uv = pos.xy / iResolution.xy - data.position_offset;
+    uv.x *= iResolution.x / iResolution.y;
     return data.rotation * uv;
 }
 
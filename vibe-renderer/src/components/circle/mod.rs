@@ -1,14 +1,13 @@
 mod descriptor;
 
 pub use descriptor::*;
-use vibe_audio::{fetcher::Fetcher, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, AudioSource, SharedBarProcessor};
 
 use super::{Component, Mat2x2, Rgba, Vec2f};
-use crate::{components::ComponentAudio, util::SimpleRenderPipelineDescriptor, Renderable};
+use crate::{util::SimpleRenderPipelineDescriptor, Renderable};
 use cgmath::Matrix2;
 use wgpu::{include_wgsl, util::DeviceExt};
 
-type Resolution = Vec2f;
 type PositionOffset = Vec2f;
 type Color = Rgba;
 type Rotation = Mat2x2;
@@ -16,26 +15,26 @@ type Radius = f32;
 type SpikeSensitivity = f32;
 type FreqRadiantStep = f32;
 
+// `iResolution` is part of the frame uniforms (bind group 0)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, Default)]
 struct Data {
-    resolution: Resolution,
-    position_offset: PositionOffset,
     color: Color,
+    position_offset: PositionOffset,
     rotation: Rotation,
     radius: Radius,
     spike_sensitivity: SpikeSensitivity,
     freq_radiant_step: FreqRadiantStep,
-    _padding: f32,
+    _padding: [f32; 3],
 }
 
 pub struct Circle {
-    bar_processor: vibe_audio::BarProcessor,
+    bar_processor: SharedBarProcessor,
 
-    data_buffer: wgpu::Buffer,
+    _data_buffer: wgpu::Buffer,
     freq_buffer: wgpu::Buffer,
 
-    bind_group0: wgpu::BindGroup,
+    bind_group1: wgpu::BindGroup,
 
     pipeline: wgpu::RenderPipeline,
 }
@@ -43,8 +42,10 @@ pub struct Circle {
 impl Circle {
     pub fn new<F: Fetcher>(desc: &CircleDescriptor<F>) -> Self {
         let device = desc.renderer.device();
-        let bar_processor =
-            vibe_audio::BarProcessor::new(desc.sample_processor, desc.audio_conf.clone());
+        let bar_processor = desc
+            .renderer
+            .bar_processor_pool()
+            .get(desc.sample_processor, desc.audio_conf.clone());
         let total_amount_bars = bar_processor.total_amount_bars();
 
         let data = {
@@ -66,7 +67,6 @@ impl Circle {
                 radius: desc.radius,
                 spike_sensitivity,
                 freq_radiant_step: std::f32::consts::PI / (total_amount_bars as f32 + 0.99),
-                resolution: Resolution::default(),
                 position_offset,
                 color,
                 rotation: Matrix2::from_angle(desc.rotation).into(),
@@ -77,7 +77,7 @@ impl Circle {
         let data_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Circle: `data` buffer"),
             contents: bytemuck::bytes_of(&data),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            usage: wgpu::BufferUsages::UNIFORM,
         });
 
         let freq_buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -87,6 +87,35 @@ impl Circle {
             mapped_at_creation: false,
         });
 
+        let bind_group1_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Circle: Bind group 1 layout"),
+                entries: &[
+                    // data
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // freqs
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
         let pipeline = {
             let fragment_module =
                 device.create_shader_module(include_wgsl!("./fragment_graph.wgsl"));
@@ -94,10 +123,16 @@ impl Circle {
             let vertex_module =
                 device.create_shader_module(include_wgsl!("../utils/full_screen_vertex.wgsl"));
 
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Circle: Pipeline layout"),
+                bind_group_layouts: &[desc.renderer.frame_uniforms_layout(), &bind_group1_layout],
+                ..Default::default()
+            });
+
             device.create_render_pipeline(&crate::util::simple_pipeline_descriptor(
                 SimpleRenderPipelineDescriptor {
                     label: "Circle: Render pipeline",
-                    layout: None,
+                    layout: Some(&pipeline_layout),
                     vertex: wgpu::VertexState {
                         module: &vertex_module,
                         entry_point: None,
@@ -118,9 +153,9 @@ impl Circle {
             ))
         };
 
-        let bind_group0 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Circle: Bind group 0"),
-            layout: &pipeline.get_bind_group_layout(0),
+        let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Circle: Bind group 1"),
+            layout: &bind_group1_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -137,9 +172,9 @@ impl Circle {
             bar_processor,
 
             freq_buffer,
-            data_buffer,
+            _data_buffer: data_buffer,
 
-            bind_group0,
+            bind_group1,
             pipeline,
         }
     }
@@ -147,33 +182,24 @@ impl Circle {
 
 impl Renderable for Circle {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
-        pass.set_bind_group(0, &self.bind_group0, &[]);
+        pass.set_bind_group(1, &self.bind_group1, &[]);
 
         pass.set_pipeline(&self.pipeline);
         pass.draw(0..3, 0..1);
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for Circle {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>) {
+impl Component for Circle {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
         let bar_values = self.bar_processor.process_bars(processor);
 
         queue.write_buffer(&self.freq_buffer, 0, bytemuck::cast_slice(&bar_values[0]));
     }
-}
 
-impl Component for Circle {
     fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
-    fn update_resolution(&mut self, renderer: &crate::Renderer, new_resolution: [u32; 2]) {
-        let queue = renderer.queue();
-
-        queue.write_buffer(
-            &self.data_buffer,
-            0,
-            bytemuck::cast_slice(&[new_resolution[0] as f32, new_resolution[1] as f32]),
-        );
-    }
+    // `iResolution` is part of the frame uniforms
+    fn update_resolution(&mut self, _renderer: &crate::Renderer, _new_resolution: [u32; 2]) {}
 
     fn update_mouse_position(&mut self, _queue: &wgpu::Queue, _new_pos: (f32, f32)) {}
 }
@@ -0,0 +1,9 @@
+
+// The outputs of the shader which are read back after each frame.
+// Only exists if the config of the component has `readback` slots with an `index`.
+//   - `iOutputs[index]`: the value of the slot with this index
+//
+// Usage (example): `if (all(lessThan(gl_FragCoord.xy, vec2(1.)))) { iOutputs[0] = vec4(score, 0., 0., 0.); }`
+layout(set = 1, binding = 8) buffer iOutputsBlock {
+    vec4 iOutputs[16];
+};
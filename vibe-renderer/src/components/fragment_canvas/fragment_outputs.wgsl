@@ -0,0 +1,8 @@
+
+// The outputs of the shader which are read back after each frame.
+// Only exists if the config of the component has `readback` slots with an `index`.
+//   - `iOutputs[index]`: the value of the slot with this index
+//
+// Usage (example): `if (all(pos.xy < vec2f(1.))) { iOutputs[0] = vec4f(score, 0., 0., 0.); }`
+@group(1) @binding(8)
+var<storage, read_write> iOutputs: array<vec4f, 16>;
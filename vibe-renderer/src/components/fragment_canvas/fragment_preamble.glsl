@@ -7,7 +7,7 @@ layout(set = 0, binding = 0) uniform vec2 iResolution;
 // You can imagine this to be the height-value for the bar-shader.
 //
 // Note: You can get the length of the array `freqs.length()`
-layout(set = 0, binding = 1) readonly buffer iAudio {
+layout(set = 1, binding = 1) readonly buffer iAudio {
     float[] freqs;
 };
 
@@ -25,7 +25,7 @@ layout(set = 0, binding = 3) uniform vec2 iMouse;
 // Contains the detected BPM (beats per minute) of the audio.
 // Typically in the range 60-200 for most music.
 // Use this to sync animations to the music tempo.
-layout(set = 0, binding = 4) uniform float iBPM;
+layout(set = 1, binding = 4) uniform float iBPM;
 
 // Color palette for shader customization.
 // Each color is a vec4 where xyz = RGB (0.0-1.0), w = 1.0.
@@ -38,13 +38,13 @@ layout(set = 0, binding = 5) uniform ColorPalette {
 } iColors;
 
 // The sampler for `iTexture`
-layout(set = 0, binding = 6) uniform sampler iSampler;
+layout(set = 1, binding = 6) uniform sampler iSampler;
 
 // The texture which contains the image you set.
 // Usage (example):
 //
 // `vec3 texel = texture(sampler2D(iTexture, iSampler), vec2(.0, .5)).rgb;`
-layout(set = 0, binding = 7) uniform texture2D iTexture;
+layout(set = 1, binding = 7) uniform texture2D iTexture;
 
 // Contains the last mouse click position and time.
 //   - xy: normalized click position (0-1), or (-1,-1) if cleared
@@ -55,6 +55,31 @@ layout(set = 0, binding = 8) uniform vec4 iMouseClick;
 // Contains the local wall-clock time as hours since midnight (0.0-24.0).
 layout(set = 0, binding = 9) uniform float iLocalTime;
 
+// Contains the last key event. Only set if the keyboard interactivity of the output is enabled.
+//   - x: xkb keysym of the key (for example `97.0` for `a`), 0.0 if no key was pressed yet
+//   - y: 1.0 if the key is pressed, 0.0 if it got released
+//   - z: time of the key event (seconds since start)
+//   - w: reserved (0.0)
+layout(set = 0, binding = 10) uniform vec4 iKeyboard;
+
+// Contains information about the track of the media player. Only set if `mpris_config` is enabled.
+//   - x: time of the last track change (seconds since start), -1.0 if the track didn't change yet
+//   - y: id of the track within [0, 1), stays the same while the track is playing
+//   - z: 1.0 if a track is playing, 0.0 otherwise
+//   - w: reserved (0.0)
+layout(set = 0, binding = 11) uniform vec4 iTrack;
+
+// The whole color palette of ~/.config/vibe/colors.toml (up to 16 colors, from the darkest to the brightest one).
+// `iColors` contains its first four colors.
+//   - `iPalette.colors[i]`: the i-th color (xyz = RGB, w = 1.0)
+//   - `iPalette.len`: the amount of colors, the remaining entries are black
+//
+// Usage (example): `vec3 c = iPalette.colors[int(t * float(iPalette.len - 1u))].rgb;`
+layout(set = 0, binding = 12) uniform Palette {
+    vec4 colors[16];
+    uint len;
+} iPalette;
+
 // The color for the fragment/pixel.
 // Needs to be set in your shader (like in shadertoy).
 layout(location = 0) out vec4 fragColor;
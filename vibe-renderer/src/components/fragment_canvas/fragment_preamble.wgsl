@@ -8,7 +8,7 @@ var<uniform> iResolution: vec2f;
 // You can imagine this to be the height-value for the bar-shader.
 //
 // Note: You can get the length of the array with the `arrayLength` function: https://webgpufundamentals.org/webgpu/lessons/webgpu-wgsl-function-reference.html#func-arrayLength
-@group(0) @binding(1)
+@group(1) @binding(1)
 var<storage, read> freqs: array<f32>;
 
 // Contains the time how long the shader has been running.
@@ -27,7 +27,7 @@ var<uniform> iMouse: vec2f;
 // Contains the detected BPM (beats per minute) of the audio.
 // Typically in the range 60-200 for most music.
 // Use this to sync animations to the music tempo.
-@group(0) @binding(4)
+@group(1) @binding(4)
 var<uniform> iBPM: f32;
 
 // Color palette for shader customization.
@@ -44,14 +44,14 @@ struct ColorPalette {
 var<uniform> iColors: ColorPalette;
 
 // The sampler for `iTexture`
-@group(0) @binding(6)
+@group(1) @binding(6)
 var iSampler: sampler;
 
 // The texture which contains the image you set.
 // Usage (example):
 //
 // `let col = textureSample(iTexture, iSampler, uv).rgb;`
-@group(0) @binding(7)
+@group(1) @binding(7)
 var iTexture: texture_2d<f32>;
 
 // Contains the last mouse click position and time.
@@ -64,3 +64,33 @@ var<uniform> iMouseClick: vec4f;
 // Contains the local wall-clock time as hours since midnight (0.0-24.0).
 @group(0) @binding(9)
 var<uniform> iLocalTime: f32;
+
+// Contains the last key event. Only set if the keyboard interactivity of the output is enabled.
+//   - x: xkb keysym of the key (for example `97.0` for `a`), 0.0 if no key was pressed yet
+//   - y: 1.0 if the key is pressed, 0.0 if it got released
+//   - z: time of the key event (seconds since start)
+//   - w: reserved (0.0)
+@group(0) @binding(10)
+var<uniform> iKeyboard: vec4f;
+
+// Contains information about the track of the media player. Only set if `mpris_config` is enabled.
+//   - x: time of the last track change (seconds since start), -1.0 if the track didn't change yet
+//   - y: id of the track within [0, 1), stays the same while the track is playing
+//   - z: 1.0 if a track is playing, 0.0 otherwise
+//   - w: reserved (0.0)
+@group(0) @binding(11)
+var<uniform> iTrack: vec4f;
+
+// The whole color palette of ~/.config/vibe/colors.toml (up to 16 colors, from the darkest to the brightest one).
+// `iColors` contains its first four colors.
+//   - `iPalette.colors[i]`: the i-th color (xyz = RGB, w = 1.0)
+//   - `iPalette.len`: the amount of colors, the remaining entries are black
+//
+// Usage (example): `let c = iPalette.colors[u32(t * f32(iPalette.len - 1u))].rgb;`
+struct Palette {
+    colors: array<vec4f, 16>,
+    len: u32,
+}
+
+@group(0) @binding(12)
+var<uniform> iPalette: Palette;
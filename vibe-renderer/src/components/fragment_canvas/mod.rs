@@ -1,17 +1,22 @@
 use super::{Component, ShaderCode, ShaderCodeError};
-use crate::{components::ComponentAudio, Renderable, Renderer};
-use chrono::Timelike;
+use crate::{
+    events::{Event, EventSink},
+    readback::{ReadbackSlot, ReadbackTarget, SlotLocation},
+    Renderable, Renderer,
+};
 use pollster::FutureExt;
-use std::borrow::Cow;
-use std::io::Write;
+use std::{borrow::Cow, collections::HashMap, sync::Arc};
 use vibe_audio::{
-    fetcher::Fetcher, BarProcessor, BarProcessorConfig, BpmDetector, BpmDetectorConfig,
-    SampleProcessor,
+    fetcher::Fetcher, AudioSource, BarProcessorConfig, BpmDetector, BpmDetectorConfig,
+    SampleProcessor, SharedBarProcessor,
 };
 use wgpu::include_wgsl;
 
 const ENTRYPOINT: &str = "main";
 
+// How many frames after a click the hit at pixel (0, 0) is read back.
+const CLICK_READBACK_FRAMES: u8 = 6;
+
 pub struct FragmentCanvasDescriptor<'a, F: Fetcher> {
     pub sample_processor: &'a SampleProcessor<F>,
     pub audio_conf: BarProcessorConfig,
@@ -21,6 +26,12 @@ pub struct FragmentCanvasDescriptor<'a, F: Fetcher> {
     // fragment shader relevant stuff
     pub fragment_code: ShaderCode,
     pub img: Option<image::DynamicImage>,
+    /// The outputs of the shader which are read back after each frame and sent as
+    /// [Event::Readback] to the event sink of the renderer.
+    pub readback_slots: Vec<ReadbackSlot>,
+    /// The members of the `iParams` uniform struct with their initial values.
+    /// They can be changed with [Component::update_params] later.
+    pub params: HashMap<String, f32>,
 }
 
 struct TextureCtx {
@@ -30,51 +41,50 @@ struct TextureCtx {
 }
 
 pub struct FragmentCanvas {
-    bar_processor: BarProcessor,
+    bar_processor: SharedBarProcessor,
     bpm_detector: BpmDetector,
+    // the last BPM which got sent to `event_sink`
+    last_bpm: f32,
+    event_sink: Option<Arc<dyn EventSink>>,
 
-    // GPU uniform buffers (bindings 0-9, see fragment_preamble.wgsl)
-    iresolution: wgpu::Buffer,
+    // GPU buffers of bind group 1 (see fragment_preamble.wgsl).
+    // `iTime`, `iResolution`, etc. are part of the frame uniforms (bind group 0).
     freqs: wgpu::Buffer,
-    itime: wgpu::Buffer,
-    imouse: wgpu::Buffer,
     ibpm: wgpu::Buffer,
-    icolors: wgpu::Buffer,
-    imouseclick: wgpu::Buffer,
-    ilocaltime: wgpu::Buffer,
     _itexture: Option<TextureCtx>,
+    // `None` if there aren't any params
+    iparams: Option<wgpu::Buffer>,
+    // the members of `iParams` in the order of the struct
+    params: Vec<(String, f32)>,
 
     // Click state (normalized [0,1] coordinates, see Component::update_mouse_click)
     last_click_pos: (f32, f32),
     last_click_time: f32,
     resolution: [u32; 2],
 
-    bind_group0: wgpu::BindGroup,
+    bind_group1: wgpu::BindGroup,
 
     pipeline: wgpu::RenderPipeline,
 
-    // GPU readback for pixel (0,0) — used by shaders that encode data there.
+    // the `readback_slots` of the descriptor, `None` if there aren't any
+    readback: Option<ReadbackTarget>,
+    // pixel (0,0) — used by shaders that encode the hit of a click there.
     // See post_render() for the full pipeline documentation.
-    readback_buffer: wgpu::Buffer,
+    click_readback: ReadbackTarget,
     readback_frames_remaining: u8, // countdown: try reading for N frames after click
-    surface_format: wgpu::TextureFormat, // needed to decode pixel byte order (BGRA vs RGBA)
 }
 
 impl FragmentCanvas {
     pub fn new<F: Fetcher>(desc: &FragmentCanvasDescriptor<F>) -> Result<Self, ShaderCodeError> {
         let device = desc.renderer.device();
         let queue = desc.renderer.queue();
-        let bar_processor = BarProcessor::new(desc.sample_processor, desc.audio_conf.clone());
+        let bar_processor = desc
+            .renderer
+            .bar_processor_pool()
+            .get(desc.sample_processor, desc.audio_conf.clone());
         let bpm_detector = BpmDetector::new(desc.sample_processor, BpmDetectorConfig::default());
         let total_amount_bars = bar_processor.total_amount_bars();
 
-        let iresolution = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Fragment canvas: `iResolution` buffer"),
-            size: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
         let freqs = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Fragment canvas: `freqs` buffer"),
             size: (std::mem::size_of::<f32>() * total_amount_bars) as wgpu::BufferAddress,
@@ -82,20 +92,6 @@ impl FragmentCanvas {
             mapped_at_creation: false,
         });
 
-        let itime = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Fragment canvas: `iTime` buffer"),
-            size: std::mem::size_of::<f32>() as wgpu::BufferAddress,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let imouse = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Fragment canvas: `iMouse` buffer"),
-            size: std::mem::size_of::<[f32; 2]>() as wgpu::BufferAddress,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
         let ibpm = device.create_buffer(&wgpu::BufferDescriptor {
             label: Some("Fragment canvas: `iBPM` buffer"),
             size: std::mem::size_of::<f32>() as wgpu::BufferAddress,
@@ -103,28 +99,6 @@ impl FragmentCanvas {
             mapped_at_creation: false,
         });
 
-        let icolors = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Fragment canvas: `iColors` buffer"),
-            // 4 colors as vec4f (vec4 for alignment, xyz = rgb, w = unused)
-            size: (std::mem::size_of::<[f32; 4]>() * 4) as wgpu::BufferAddress,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let imouseclick = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Fragment canvas: `iMouseClick` buffer"),
-            size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
-        let ilocaltime = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Fragment canvas: `iLocalTime` buffer"),
-            size: std::mem::size_of::<f32>() as wgpu::BufferAddress,
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
-
         let itexture = desc.img.as_ref().map(|img| {
             let sampler = device.create_sampler(&crate::util::DEFAULT_SAMPLER_DESCRIPTOR);
             let texture = crate::util::load_img_to_texture(device, queue, img);
@@ -137,18 +111,27 @@ impl FragmentCanvas {
             }
         });
 
-        let bind_group0_layout = {
+        let params = sorted_params(&desc.params)?;
+        let iparams = (!params.is_empty()).then(|| {
+            let size = (std::mem::size_of::<f32>() * params.len()).next_multiple_of(16);
+
+            device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("Fragment canvas: `iParams` buffer"),
+                size: size as wgpu::BufferAddress,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            })
+        });
+
+        let readback = (!desc.readback_slots.is_empty())
+            .then(|| ReadbackTarget::new(device, &desc.readback_slots, desc.format));
+        let outputs = readback
+            .as_ref()
+            .and_then(|readback| readback.outputs_buffer());
+
+        let bind_group1_layout = {
             let mut entries = vec![
-                wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
+                // freqs
                 wgpu::BindGroupLayoutEntry {
                     binding: 1,
                     visibility: wgpu::ShaderStages::FRAGMENT,
@@ -159,26 +142,6 @@ impl FragmentCanvas {
                     },
                     count: None,
                 },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 2,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                wgpu::BindGroupLayoutEntry {
-                    binding: 3,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
                 // iBPM
                 wgpu::BindGroupLayoutEntry {
                     binding: 4,
@@ -190,39 +153,6 @@ impl FragmentCanvas {
                     },
                     count: None,
                 },
-                // iColors
-                wgpu::BindGroupLayoutEntry {
-                    binding: 5,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // iMouseClick
-                wgpu::BindGroupLayoutEntry {
-                    binding: 8,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
-                // iLocalTime
-                wgpu::BindGroupLayoutEntry {
-                    binding: 9,
-                    visibility: wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
-                    },
-                    count: None,
-                },
             ];
 
             if let Some(_texture) = &itexture {
@@ -246,8 +176,36 @@ impl FragmentCanvas {
                 ]);
             }
 
+            if outputs.is_some() {
+                // iOutputs
+                entries.push(wgpu::BindGroupLayoutEntry {
+                    binding: 8,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                });
+            }
+
+            if iparams.is_some() {
+                // iParams
+                entries.push(wgpu::BindGroupLayoutEntry {
+                    binding: 9,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                });
+            }
+
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Fragment canvas: Bind group 0 layout"),
+                label: Some("Fragment canvas: Bind group 1 layout"),
                 entries: &entries,
             })
         };
@@ -262,12 +220,18 @@ impl FragmentCanvas {
                 let shader_source = match desc.fragment_code.language {
                     super::ShaderLanguage::Wgsl => {
                         const PREAMBLE: &str = include_str!("./fragment_preamble.wgsl");
-                        let full_code = format!("{}\n{}", PREAMBLE, &source);
+                        const OUTPUTS: &str = include_str!("./fragment_outputs.wgsl");
+                        let outputs = if outputs.is_some() { OUTPUTS } else { "" };
+                        let params = wgsl_params(&params);
+                        let full_code = format!("{}{}{}\n{}", PREAMBLE, outputs, params, source);
                         wgpu::ShaderSource::Wgsl(Cow::Owned(full_code))
                     }
                     super::ShaderLanguage::Glsl => {
                         const PREAMBLE: &str = include_str!("./fragment_preamble.glsl");
-                        let full_code = format!("{}\n{}", PREAMBLE, &source);
+                        const OUTPUTS: &str = include_str!("./fragment_outputs.glsl");
+                        let outputs = if outputs.is_some() { OUTPUTS } else { "" };
+                        let params = glsl_params(&params);
+                        let full_code = format!("{}{}{}\n{}", PREAMBLE, outputs, params, source);
                         wgpu::ShaderSource::Glsl {
                             shader: Cow::Owned(full_code),
                             stage: wgpu::naga::ShaderStage::Fragment,
@@ -291,7 +255,7 @@ impl FragmentCanvas {
 
             let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
                 label: Some("Fragment canvas: Pipeline layout"),
-                bind_group_layouts: &[&bind_group0_layout],
+                bind_group_layouts: &[desc.renderer.frame_uniforms_layout(), &bind_group1_layout],
                 ..Default::default()
             });
 
@@ -319,40 +283,16 @@ impl FragmentCanvas {
             ))
         };
 
-        let bind_group0 = {
+        let bind_group1 = {
             let mut entries = vec![
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: iresolution.as_entire_binding(),
-                },
                 wgpu::BindGroupEntry {
                     binding: 1,
                     resource: freqs.as_entire_binding(),
                 },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: itime.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 3,
-                    resource: imouse.as_entire_binding(),
-                },
                 wgpu::BindGroupEntry {
                     binding: 4,
                     resource: ibpm.as_entire_binding(),
                 },
-                wgpu::BindGroupEntry {
-                    binding: 5,
-                    resource: icolors.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 8,
-                    resource: imouseclick.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 9,
-                    resource: ilocaltime.as_entire_binding(),
-                },
             ];
 
             if let Some(texture) = &itexture {
@@ -368,229 +308,285 @@ impl FragmentCanvas {
                 ]);
             }
 
+            if let Some(outputs) = outputs {
+                entries.push(wgpu::BindGroupEntry {
+                    binding: 8,
+                    resource: outputs.as_entire_binding(),
+                });
+            }
+
+            if let Some(iparams) = &iparams {
+                entries.push(wgpu::BindGroupEntry {
+                    binding: 9,
+                    resource: iparams.as_entire_binding(),
+                });
+            }
+
             device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Fragment canvas: Bind group 0"),
-                layout: &bind_group0_layout,
+                label: Some("Fragment canvas: Bind group 1"),
+                layout: &bind_group1_layout,
                 entries: &entries,
             })
         };
 
-        let readback_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("Fragment canvas: readback staging buffer"),
-            size: 256, // wgpu requires bytes_per_row to be multiple of COPY_BYTES_PER_ROW_ALIGNMENT (256)
-            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+        if let Some(iparams) = &iparams {
+            let values: Vec<f32> = params.iter().map(|(_name, value)| *value).collect();
+            queue.write_buffer(iparams, 0, bytemuck::cast_slice(&values));
+        }
+
+        let click_readback = ReadbackTarget::new(
+            device,
+            &[ReadbackSlot::new("click-hit", SlotLocation::Texel([0, 0]))],
+            desc.format,
+        );
 
         Ok(Self {
             bar_processor,
             bpm_detector,
+            last_bpm: 0.,
+            event_sink: desc.renderer.event_sink(),
 
-            iresolution,
             freqs,
-            itime,
-            imouse,
             ibpm,
-            icolors,
-            imouseclick,
-            ilocaltime,
             _itexture: itexture,
+            iparams,
+            params,
 
             last_click_pos: (-1.0, -1.0),
             last_click_time: 0.0,
             resolution: [0, 0],
 
-            bind_group0,
+            bind_group1,
 
             pipeline,
 
-            readback_buffer,
+            readback,
+            click_readback,
             readback_frames_remaining: 0,
-            surface_format: desc.format,
         })
     }
 }
 
+/// Sorts the params by their name so the layout of `iParams` doesn't depend on the order of the config.
+fn sorted_params(params: &HashMap<String, f32>) -> Result<Vec<(String, f32)>, ShaderCodeError> {
+    let mut params: Vec<(String, f32)> = params
+        .iter()
+        .map(|(name, value)| (name.clone(), *value))
+        .collect();
+    params.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+    match params.iter().find(|(name, _)| !is_identifier(name)) {
+        Some((name, _)) => Err(ShaderCodeError::InvalidParamName(name.clone())),
+        None => Ok(params),
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// The declaration of `iParams` for the WGSL preamble (empty if there aren't any params).
+fn wgsl_params(params: &[(String, f32)]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let members: String = params
+        .iter()
+        .map(|(name, _)| format!("    {}: f32,\n", name))
+        .collect();
+
+    format!(
+        "\n// The `params` of the config.\nstruct Params {{\n{}}}\n\n@group(1) @binding(9)\nvar<uniform> iParams: Params;\n",
+        members
+    )
+}
+
+/// The declaration of `iParams` for the GLSL preamble (empty if there aren't any params).
+fn glsl_params(params: &[(String, f32)]) -> String {
+    if params.is_empty() {
+        return String::new();
+    }
+
+    let members: String = params
+        .iter()
+        .map(|(name, _)| format!("    float {};\n", name))
+        .collect();
+
+    format!(
+        "\n// The `params` of the config.\nlayout(set = 1, binding = 9) uniform Params {{\n{}}} iParams;\n",
+        members
+    )
+}
+
+impl FragmentCanvas {
+    fn send_event(&self, event: Event) {
+        if let Some(sink) = &self.event_sink {
+            sink.send(&event);
+        }
+    }
+}
+
 impl Renderable for FragmentCanvas {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
-        pass.set_bind_group(0, &self.bind_group0, &[]);
+        pass.set_bind_group(1, &self.bind_group1, &[]);
         pass.set_pipeline(&self.pipeline);
         pass.draw(0..4, 0..1);
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for FragmentCanvas {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>) {
-        let bar_values = self.bar_processor.process_bars(processor);
-        queue.write_buffer(&self.freqs, 0, bytemuck::cast_slice(&bar_values[0]));
+impl Component for FragmentCanvas {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
+        {
+            let bar_values = self.bar_processor.process_bars(processor);
+            queue.write_buffer(&self.freqs, 0, bytemuck::cast_slice(&bar_values[0]));
+        }
 
         // Update BPM
         let bpm = self.bpm_detector.process(processor);
         queue.write_buffer(&self.ibpm, 0, bytemuck::bytes_of(&bpm));
 
-        // Write BPM to file for external tools (waybar, etc.)
-        if let Ok(mut file) = std::fs::File::create("/tmp/vibe-bpm") {
-            let _ = writeln!(file, "{:.0}", bpm);
+        // for external tools (waybar, etc.)
+        if bpm.round() != self.last_bpm.round() {
+            self.last_bpm = bpm;
+            self.send_event(Event::Bpm(bpm));
         }
     }
-}
 
-impl Component for FragmentCanvas {
-    fn update_resolution(&mut self, renderer: &crate::Renderer, new_resolution: [u32; 2]) {
+    fn update_resolution(&mut self, _renderer: &crate::Renderer, new_resolution: [u32; 2]) {
         self.resolution = new_resolution;
-        let queue = renderer.queue();
-
-        queue.write_buffer(
-            &self.iresolution,
-            0,
-            bytemuck::cast_slice(&[new_resolution[0] as f32, new_resolution[1] as f32]),
-        );
     }
 
-    fn update_time(&mut self, queue: &wgpu::Queue, new_time: f32) {
-        queue.write_buffer(&self.itime, 0, bytemuck::bytes_of(&new_time));
+    // `iTime` and `iLocalTime` are part of the frame uniforms
+    fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
-        // Write current wall-clock time as hours since midnight
-        let now = chrono::Local::now();
-        let local_time =
-            now.hour() as f32 + now.minute() as f32 / 60.0 + now.second() as f32 / 3600.0;
-        queue.write_buffer(&self.ilocaltime, 0, bytemuck::bytes_of(&local_time));
-    }
+    // `iMouse` is part of the frame uniforms
+    fn update_mouse_position(&mut self, _queue: &wgpu::Queue, _new_pos: (f32, f32)) {}
 
-    fn update_mouse_position(&mut self, queue: &wgpu::Queue, new_pos: (f32, f32)) {
-        queue.write_buffer(
-            &self.imouse,
-            0,
-            bytemuck::cast_slice(&[new_pos.0, new_pos.1]),
-        );
+    /// Writes the values of the members of `iParams`. The set of members is fixed when
+    /// the canvas gets created, so other names are ignored.
+    fn update_params(&mut self, queue: &wgpu::Queue, params: &HashMap<String, f32>) {
+        let Some(iparams) = &self.iparams else {
+            return;
+        };
+
+        for (name, value) in self.params.iter_mut() {
+            if let Some(new_value) = params.get(name) {
+                *value = *new_value;
+            }
+        }
+
+        let values: Vec<f32> = self.params.iter().map(|(_name, value)| *value).collect();
+        queue.write_buffer(iparams, 0, bytemuck::cast_slice(&values));
     }
 
     /// Handle a mouse click.
     ///
     /// Click-to-interact pipeline overview:
     ///   1. Caller normalizes pixel coords to [0,1] and calls this method.
-    ///   2. We write the click to the GPU uniform `iMouseClick` (vec4f: x, y, time, 0).
-    ///   3. We write `/tmp/vibe-click` atomically with all click metadata.
+    ///   2. The caller writes the click to the frame uniform `iMouseClick` (vec4f: x, y, time, 0).
+    ///   3. We send an [Event::Click] with all click metadata to the event sink of the renderer.
     ///   4. We start GPU readback: for the next few frames, `post_render` copies pixel (0,0)
     ///      from the rendered texture. The shader encodes a clicked entity ID there.
-    ///   5. When readback finds a hit, we write `/tmp/vibe-click-species` with the species.
-    ///   6. The external Python daemon (pokemon-click-cry.py) watches both files.
-    fn update_mouse_click(&mut self, queue: &wgpu::Queue, pos: (f32, f32), time: f32) {
+    ///   5. When readback finds a hit, we send an [Event::ClickHit] with the species.
+    ///   6. External tools (like pokemon-click-cry.py) receive both events through the sink
+    ///      (for example the files of [FileSink](crate::events::FileSink)).
+    fn update_mouse_click(&mut self, _queue: &wgpu::Queue, pos: (f32, f32), time: f32) {
         self.last_click_pos = pos;
         self.last_click_time = time;
-        queue.write_buffer(
-            &self.imouseclick,
-            0,
-            bytemuck::cast_slice(&[pos.0, pos.1, time, 0.0]),
-        );
 
-        // Write click data atomically for external tools.
-        // File format: key=value pairs, one per line.
         if pos.0 >= 0.0 {
-            if let Ok(mut f) = std::fs::File::create("/tmp/vibe-click") {
-                let _ = write!(
-                    f,
-                    "x={}\ny={}\ntime={}\nwidth={}\nheight={}\n",
-                    pos.0, pos.1, time, self.resolution[0], self.resolution[1]
-                );
-            }
+            self.send_event(Event::Click {
+                x: pos.0,
+                y: pos.1,
+                time,
+                width: self.resolution[0],
+                height: self.resolution[1],
+            });
             // Start GPU readback: the shader may encode a hit species at pixel (0,0).
-            // We check for up to 6 frames to give the GPU time to render the click.
-            self.readback_frames_remaining = 6;
+            // We check for a few frames to give the GPU time to render the click.
+            self.readback_frames_remaining = CLICK_READBACK_FRAMES;
+            // the copy of an earlier frame might still be on its way
+            self.click_readback.discard();
         }
     }
 
-    /// GPU readback for shader-encoded click data.
+    /// GPU readback for shader-encoded data.
+    ///
+    /// The values of the `readback_slots` are sent as [Event::Readback] once they arrive.
     ///
     /// Some shaders (e.g., pokemon_grass.wgsl) encode hit-test results at pixel (0,0):
     ///   - The shader writes `red = (entity_id + 1) / 255.0` at pixel (0,0) when a click
     ///     hits an interactive entity (e.g., a Pokemon sprite).
     ///   - Red channel = 0 means no hit; red > 0 means hit with `entity_id = red - 1`.
     ///
-    /// This method copies pixel (0,0) to a staging buffer, maps it, and decodes the red
-    /// channel. On a hit, it writes the species to `/tmp/vibe-click-species` as a separate
-    /// file (no read-modify-write of the click file).
+    /// This pixel is only read back for a few frames after a click. On a hit, it sends the
+    /// species as an [Event::ClickHit].
     fn post_render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+        if let Some(values) = self
+            .readback
+            .as_ref()
+            .and_then(|readback| readback.read(device, queue, texture))
+        {
+            for (slot, value) in values {
+                self.send_event(Event::Readback {
+                    slot: slot.to_string(),
+                    value,
+                });
+            }
+        }
+
         if self.readback_frames_remaining == 0 {
             return;
         }
 
-        // Copy pixel (0,0) from rendered surface texture to staging buffer.
-        // The staging buffer is 256 bytes (wgpu requires COPY_BYTES_PER_ROW_ALIGNMENT).
-        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
-        encoder.copy_texture_to_buffer(
-            wgpu::TexelCopyTextureInfo {
-                texture,
-                mip_level: 0,
-                origin: wgpu::Origin3d::ZERO,
-                aspect: wgpu::TextureAspect::All,
-            },
-            wgpu::TexelCopyBufferInfo {
-                buffer: &self.readback_buffer,
-                layout: wgpu::TexelCopyBufferLayout {
-                    offset: 0,
-                    bytes_per_row: Some(256),
-                    rows_per_image: Some(1),
-                },
-            },
-            wgpu::Extent3d {
-                width: 1,
-                height: 1,
-                depth_or_array_layers: 1,
-            },
-        );
-        queue.submit(std::iter::once(encoder.finish()));
+        // The values arrive a frame (or more) after the copy, so this also counts the frames
+        // where nothing arrived yet.
+        self.readback_frames_remaining -= 1;
+        let hit = self
+            .click_readback
+            .read(device, queue, texture)
+            .and_then(|values| values.first().map(|(_slot, [red, ..])| *red))
+            .map(|red| (red * u8::MAX as f32).round() as u8)
+            .filter(|&red| red > 0);
+
+        if let Some(red) = hit {
+            // Hit! Decode entity_id and send it.
+            self.send_event(Event::ClickHit { id: red - 1 });
+            self.readback_frames_remaining = 0;
+        }
+    }
+}
 
-        // Map the buffer synchronously and read the pixel.
-        let buffer_slice = self.readback_buffer.slice(..4);
-        let (tx, rx) = std::sync::mpsc::channel();
-        buffer_slice.map_async(wgpu::MapMode::Read, move |result| {
-            let _ = tx.send(result);
-        });
-        let _ = device.poll(wgpu::PollType::Wait {
-            submission_index: None,
-            timeout: None,
-        });
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        if rx.recv().ok().and_then(|r| r.ok()).is_some() {
-            let data = buffer_slice.get_mapped_range();
-            let bytes: [u8; 4] = [data[0], data[1], data[2], data[3]];
-            drop(data);
-            self.readback_buffer.unmap();
-
-            // Decode red channel. Surface format determines byte order:
-            //   BGRA: bytes = [B, G, R, A] → red is bytes[2]
-            //   RGBA: bytes = [R, G, B, A] → red is bytes[0]
-            let red = match self.surface_format {
-                wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => bytes[2],
-                _ => bytes[0],
-            };
+    #[test]
+    fn params() {
+        let params =
+            sorted_params(&HashMap::from([("speed".into(), 2.), ("glow".into(), 0.4)])).unwrap();
 
-            if red > 0 {
-                // Hit! Decode entity_id and write to species file.
-                let species = (red - 1) as i32;
-                if let Ok(mut f) = std::fs::File::create("/tmp/vibe-click-species") {
-                    let _ = write!(f, "species={}\n", species);
-                }
-                self.readback_frames_remaining = 0;
-            } else {
-                self.readback_frames_remaining -= 1;
-            }
-        } else {
-            self.readback_buffer.unmap();
-            self.readback_frames_remaining -= 1;
-        }
+        assert_eq!(params, [("glow".into(), 0.4), ("speed".into(), 2.)]);
+        assert_eq!(
+            wgsl_params(&params),
+            "\n// The `params` of the config.\nstruct Params {\n    glow: f32,\n    speed: f32,\n}\n\n@group(1) @binding(9)\nvar<uniform> iParams: Params;\n"
+        );
+        assert_eq!(
+            glsl_params(&params),
+            "\n// The `params` of the config.\nlayout(set = 1, binding = 9) uniform Params {\n    float glow;\n    float speed;\n} iParams;\n"
+        );
+        assert_eq!(wgsl_params(&[]), "");
     }
 
-    fn update_colors(&mut self, queue: &wgpu::Queue, colors: &[[f32; 3]; 4]) {
-        // Convert to vec4 format for GPU alignment (xyz = rgb, w = 1.0)
-        let colors_vec4: [[f32; 4]; 4] = [
-            [colors[0][0], colors[0][1], colors[0][2], 1.0],
-            [colors[1][0], colors[1][1], colors[1][2], 1.0],
-            [colors[2][0], colors[2][1], colors[2][2], 1.0],
-            [colors[3][0], colors[3][1], colors[3][2], 1.0],
-        ];
-
-        queue.write_buffer(&self.icolors, 0, bytemuck::cast_slice(&colors_vec4));
+    #[test]
+    fn invalid_param_names() {
+        for name in ["1st", "with space", "", "glow-strength"] {
+            assert!(matches!(
+                sorted_params(&HashMap::from([(name.to_string(), 0.)])),
+                Err(ShaderCodeError::InvalidParamName(_))
+            ));
+        }
     }
 }
@@ -3,10 +3,10 @@ mod descriptor;
 pub use descriptor::*;
 
 use super::{Component, Rgba, Vec2f};
-use crate::{components::ComponentAudio, Renderable, Renderer};
+use crate::{Renderable, Renderer};
 use cgmath::{Deg, Matrix2, Vector2};
 use std::num::NonZero;
-use vibe_audio::{fetcher::Fetcher, BarProcessor, BarProcessorConfig, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, AudioSource, BarProcessor, BarProcessorConfig};
 use wgpu::{include_wgsl, util::DeviceExt};
 
 /// Each graph is put inside a box with 4 vertices.
@@ -50,7 +50,7 @@ impl FragmentEntrypoint {
 struct PipelineCtx {
     pipeline: wgpu::RenderPipeline,
 
-    bind_group1: wgpu::BindGroup,
+    bind_group2: wgpu::BindGroup,
     freqs_buffer: wgpu::Buffer,
 }
 
@@ -74,9 +74,11 @@ struct FragmentParams {
 }
 
 pub struct Graph {
+    // not shared with the bar processor pool since the amount of bars depends on the resolution
     bar_processor: vibe_audio::BarProcessor,
 
-    bind_group0: wgpu::BindGroup,
+    // `left` and `right` share the same bind group 1
+    bind_group1: wgpu::BindGroup,
     vertex_params_buffer: wgpu::Buffer,
     _fragment_params_buffer: wgpu::Buffer,
 
@@ -219,15 +221,15 @@ impl Graph {
             };
 
             let pipeline = create_pipeline(
-                device,
+                desc.renderer,
                 desc.output_texture_format,
                 vertex_entrypoint,
                 fragment_entrypoint,
             );
 
-            let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Graph: Left bind group 1"),
-                layout: &pipeline.get_bind_group_layout(1),
+            let bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Graph: Left bind group 2"),
+                layout: &pipeline.get_bind_group_layout(2),
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
                     resource: freqs_buffer.as_entire_binding(),
@@ -236,14 +238,14 @@ impl Graph {
 
             PipelineCtx {
                 pipeline,
-                bind_group1,
+                bind_group2,
                 freqs_buffer,
             }
         };
 
-        let bind_group0 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Graph: Bind group 0"),
-            layout: &left.pipeline.get_bind_group_layout(0),
+        let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Graph: Bind group 1"),
+            layout: &left.pipeline.get_bind_group_layout(1),
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -272,15 +274,15 @@ impl Graph {
                 });
 
                 let pipeline = create_pipeline(
-                    device,
+                    desc.renderer,
                     desc.output_texture_format,
                     vertex_entrypoint,
                     fragment_entrypoint,
                 );
 
-                let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: Some("Graph: Right bind group 1"),
-                    layout: &pipeline.get_bind_group_layout(1),
+                let bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Graph: Right bind group 2"),
+                    layout: &pipeline.get_bind_group_layout(2),
                     entries: &[wgpu::BindGroupEntry {
                         binding: 0,
                         resource: freqs_buffer.as_entire_binding(),
@@ -289,7 +291,7 @@ impl Graph {
 
                 PipelineCtx {
                     pipeline,
-                    bind_group1,
+                    bind_group2,
                     freqs_buffer,
                 }
             })
@@ -298,7 +300,7 @@ impl Graph {
         Self {
             bar_processor,
 
-            bind_group0,
+            bind_group1,
             vertex_params_buffer,
             _fragment_params_buffer: fragment_params_buffer,
 
@@ -313,22 +315,22 @@ impl Graph {
 
 impl Renderable for Graph {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
-        pass.set_bind_group(0, &self.bind_group0, &[]);
+        pass.set_bind_group(1, &self.bind_group1, &[]);
 
         pass.set_pipeline(&self.left.pipeline);
-        pass.set_bind_group(1, &self.left.bind_group1, &[]);
+        pass.set_bind_group(2, &self.left.bind_group2, &[]);
         pass.draw(0..AMOUNT_VERTICES, 0..1);
 
         if let Some(right) = &self.right {
             pass.set_pipeline(&right.pipeline);
-            pass.set_bind_group(1, &right.bind_group1, &[]);
+            pass.set_bind_group(2, &right.bind_group2, &[]);
             pass.draw(0..AMOUNT_VERTICES, 1..2);
         }
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for Graph {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>) {
+impl Component for Graph {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
         let bar_values = self.bar_processor.process_bars(processor);
 
         queue.write_buffer(
@@ -341,9 +343,7 @@ impl<F: Fetcher> ComponentAudio<F> for Graph {
             queue.write_buffer(&right.freqs_buffer, 0, bytemuck::cast_slice(&bar_values[1]));
         }
     }
-}
 
-impl Component for Graph {
     fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
     fn update_resolution(&mut self, renderer: &Renderer, new_resolution: [u32; 2]) {
@@ -394,9 +394,9 @@ impl Component for Graph {
                 ..buffer_desc
             });
 
-            let new_bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Graph: Left bind group 1"),
-                layout: &self.left.pipeline.get_bind_group_layout(1),
+            let new_bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Graph: Left bind group 2"),
+                layout: &self.left.pipeline.get_bind_group_layout(2),
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
                     resource: new_freqs_buffer.as_entire_binding(),
@@ -404,7 +404,7 @@ impl Component for Graph {
             });
 
             self.left.freqs_buffer = new_freqs_buffer;
-            self.left.bind_group1 = new_bind_group1;
+            self.left.bind_group2 = new_bind_group2;
         }
 
         // update right `freqs` buffer and bindings
@@ -414,9 +414,9 @@ impl Component for Graph {
                 ..buffer_desc
             });
 
-            let new_bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Graph: Right bind group 1"),
-                layout: &self.left.pipeline.get_bind_group_layout(1),
+            let new_bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Graph: Right bind group 2"),
+                layout: &self.left.pipeline.get_bind_group_layout(2),
                 entries: &[wgpu::BindGroupEntry {
                     binding: 0,
                     resource: new_freqs_buffer.as_entire_binding(),
@@ -424,7 +424,7 @@ impl Component for Graph {
             });
 
             right.freqs_buffer = new_freqs_buffer;
-            right.bind_group1 = new_bind_group1;
+            right.bind_group2 = new_bind_group2;
         }
     }
 
@@ -452,18 +452,20 @@ impl GraphAmountBars {
 }
 
 fn create_pipeline(
-    device: &wgpu::Device,
+    renderer: &Renderer,
     texture_format: wgpu::TextureFormat,
     vertex_entrypoint: VertexEntrypoint,
     fragment_entrypoint: FragmentEntrypoint,
 ) -> wgpu::RenderPipeline {
+    let device = renderer.device();
     let module = device.create_shader_module(include_wgsl!("./shader.wgsl"));
 
     let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Bars: Pipeline layout"),
         bind_group_layouts: &[
+            renderer.frame_uniforms_layout(),
             &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Bars: Bind group 0 layout"),
+                label: Some("Bars: Bind group 1 layout"),
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
                         binding: 0,
@@ -488,7 +490,7 @@ fn create_pipeline(
                 ],
             }),
             &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Bars: Bind group 1 layout"),
+                label: Some("Bars: Bind group 2 layout"),
                 entries: &[wgpu::BindGroupLayoutEntry {
                     binding: 0,
                     visibility: wgpu::ShaderStages::FRAGMENT,
@@ -16,14 +16,16 @@ struct FragmentParams {
     color2: vec4f,
 }
 
-@group(0) @binding(0)
+// bind group 0 contains the frame uniforms (see `FrameUniforms`)
+
+@group(1) @binding(0)
 var<uniform> vp: VertexParams;
 
-@group(0) @binding(1)
+@group(1) @binding(1)
 var<uniform> fp: FragmentParams;
 
 // used in fragment shader
-@group(1) @binding(0)
+@group(2) @binding(0)
 var<storage, read> freqs: array<f32>;
 
 struct Input {
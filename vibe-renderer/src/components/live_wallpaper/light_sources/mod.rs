@@ -3,16 +3,10 @@ mod descriptor;
 use std::num::NonZero;
 
 pub use descriptor::*;
-use vibe_audio::{fetcher::Fetcher, BarProcessor, BarProcessorConfig, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, AudioSource, BarProcessorConfig, SharedBarProcessor};
 use wgpu::{include_wgsl, util::DeviceExt};
 
-use crate::{components::ComponentAudio, Component, Renderable};
-
-#[repr(C)]
-#[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
-struct BindingGeneralData {
-    resolution: [f32; 2],
-}
+use crate::{Component, Renderable};
 
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
@@ -43,17 +37,17 @@ impl BindingLightData {
 }
 
 pub struct LightSources {
-    bar_processor: BarProcessor,
+    bar_processor: SharedBarProcessor,
 
     amount_light_sources: usize,
     uniform_pulse: bool,
 
     _wallpaper: wgpu::Texture,
     _sampler: wgpu::Sampler,
-    general_data_buffer: wgpu::Buffer,
     light_sources_buffer: wgpu::Buffer,
 
-    bind_group0: wgpu::BindGroup,
+    // `iResolution` is part of the frame uniforms (bind group 0)
+    bind_group1: wgpu::BindGroup,
     pipeline: wgpu::RenderPipeline,
 }
 
@@ -69,7 +63,7 @@ impl LightSources {
                 desc.sources.len() as u16
             };
 
-            BarProcessor::new(
+            desc.renderer.bar_processor_pool().get(
                 desc.processor,
                 BarProcessorConfig {
                     amount_bars: NonZero::new(amount_bars).unwrap(),
@@ -80,14 +74,6 @@ impl LightSources {
             )
         };
 
-        let general_data_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Light sources: General data buffer"),
-            contents: bytemuck::bytes_of(&BindingGeneralData {
-                resolution: [0f32; 2],
-            }),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
-
         let wallpaper = crate::util::load_img_to_texture(device, queue, &desc.wallpaper);
 
         let sampler = device.create_sampler(&crate::util::DEFAULT_SAMPLER_DESCRIPTOR);
@@ -104,6 +90,42 @@ impl LightSources {
             (datas.len(), buffer)
         };
 
+        let bind_group1_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Light sources: Bind group 1 layout"),
+                entries: &[
+                    // wallpaper
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    // sampler
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    // light sources
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: true },
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            });
+
         let pipeline = {
             let vertex_shader =
                 device.create_shader_module(include_wgsl!("../../utils/full_screen_vertex.wgsl"));
@@ -111,10 +133,16 @@ impl LightSources {
             let fragment_shader = device.create_shader_module(include_wgsl!("./shader.wgsl"));
             let entry_point = if desc.debug_sources { "debug" } else { "main" };
 
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Light sources: Pipeline layout"),
+                bind_group_layouts: &[desc.renderer.frame_uniforms_layout(), &bind_group1_layout],
+                ..Default::default()
+            });
+
             device.create_render_pipeline(&crate::util::simple_pipeline_descriptor(
                 crate::util::SimpleRenderPipelineDescriptor {
                     label: "Light sources: Render pipeline",
-                    layout: None,
+                    layout: Some(&pipeline_layout),
                     vertex: wgpu::VertexState {
                         module: &vertex_shader,
                         entry_point: None,
@@ -135,26 +163,22 @@ impl LightSources {
             ))
         };
 
-        let bind_group0 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Light sources: Bind group 0"),
-            layout: &pipeline.get_bind_group_layout(0),
+        let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Light sources: Bind group 1"),
+            layout: &bind_group1_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: general_data_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
                     resource: wgpu::BindingResource::TextureView(
                         &wallpaper.create_view(&wgpu::TextureViewDescriptor::default()),
                     ),
                 },
                 wgpu::BindGroupEntry {
-                    binding: 2,
+                    binding: 1,
                     resource: wgpu::BindingResource::Sampler(&sampler),
                 },
                 wgpu::BindGroupEntry {
-                    binding: 3,
+                    binding: 2,
                     resource: light_sources_buffer.as_entire_binding(),
                 },
             ],
@@ -165,13 +189,12 @@ impl LightSources {
 
             _wallpaper: wallpaper,
             _sampler: sampler,
-            general_data_buffer,
             light_sources_buffer,
 
             amount_light_sources,
             uniform_pulse: desc.uniform_pulse,
 
-            bind_group0,
+            bind_group1,
             pipeline,
         }
     }
@@ -179,14 +202,14 @@ impl LightSources {
 
 impl Renderable for LightSources {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
-        pass.set_bind_group(0, &self.bind_group0, &[]);
+        pass.set_bind_group(1, &self.bind_group1, &[]);
         pass.set_pipeline(&self.pipeline);
         pass.draw(0..4, 0..1);
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for LightSources {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>) {
+impl Component for LightSources {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
         let channels = self.bar_processor.process_bars(processor);
         let bars = &channels[0];
 
@@ -213,20 +236,11 @@ impl<F: Fetcher> ComponentAudio<F> for LightSources {
             }
         }
     }
-}
 
-impl Component for LightSources {
     fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
-    fn update_resolution(&mut self, renderer: &crate::Renderer, new_resolution: [u32; 2]) {
-        let queue = renderer.queue();
-
-        queue.write_buffer(
-            &self.general_data_buffer,
-            0,
-            bytemuck::cast_slice(&[new_resolution[0] as f32, new_resolution[1] as f32]),
-        );
-    }
+    // `iResolution` is part of the frame uniforms
+    fn update_resolution(&mut self, _renderer: &crate::Renderer, _new_resolution: [u32; 2]) {}
 
     fn update_mouse_position(&mut self, _queue: &wgpu::Queue, _new_pos: (f32, f32)) {}
 }
@@ -1,14 +1,11 @@
-struct GeneralData {
-    resolution: vec2f,
-}
-
+// frame uniforms (see `FrameUniforms`)
 @group(0) @binding(0)
-var<uniform> general_data: GeneralData;
+var<uniform> iResolution: vec2f;
 
-@group(0) @binding(1)
+@group(1) @binding(0)
 var wallpaper: texture_2d<f32>;
 
-@group(0) @binding(2)
+@group(1) @binding(1)
 var s: sampler;
 
 struct LightData {
@@ -17,12 +14,12 @@ struct LightData {
     radius: f32
 }
 
-@group(0) @binding(3)
+@group(1) @binding(2)
 var<storage, read> light_datas: array<LightData>;
 
 @fragment
 fn main(@builtin(position) pos: vec4f) -> @location(0) vec4f {
-    let uv = pos.xy / general_data.resolution.xy;
+    let uv = pos.xy / iResolution.xy;
     let texel = textureSample(wallpaper, s, uv);
 
     var result = vec4f(0.);
@@ -31,7 +28,7 @@ fn main(@builtin(position) pos: vec4f) -> @location(0) vec4f {
 
         var center = uv - light_data.center;
         // fix aspect ratio
-        center.x /= general_data.resolution.y / general_data.resolution.x;
+        center.x /= iResolution.y / iResolution.x;
 
         let flashbang_protection = min(light_data.freq * .075, .1);
         let x = length(center)*light_data.radius + .1 - max(flashbang_protection, 1e-3);
@@ -47,7 +44,7 @@ const GREEN: vec4f = vec4f(0., 1., 0., 1.);
 
 @fragment
 fn debug(@builtin(position) pos: vec4f) -> @location(0) vec4f {
-    let uv = pos.xy / general_data.resolution.xy;
+    let uv = pos.xy / iResolution.xy;
     let texel = textureSample(wallpaper, s, uv);
 
     var result = vec4f(0.);
@@ -56,7 +53,7 @@ fn debug(@builtin(position) pos: vec4f) -> @location(0) vec4f {
         let light_data = light_datas[i];
 
         var center = uv - light_data.center;
-        center.x /= general_data.resolution.y / general_data.resolution.x;
+        center.x /= iResolution.y / iResolution.x;
 
         // TODO: Circle is not correctly shown
         let x = length(center);
@@ -3,26 +3,22 @@ mod descriptor;
 pub use descriptor::*;
 
 use crate::{
-    components::ComponentAudio,
     texture_generation::{edge_distance_map::EdgeDistanceMap, GaussianBlur},
     Component, Renderable,
 };
 use std::num::NonZero;
-use vibe_audio::{fetcher::Fetcher, BarProcessor, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, AudioSource, SharedBarProcessor};
 use wgpu::include_wgsl;
 
+// `iResolution` is part of the frame uniforms (bind group 0)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
 struct DataBinding {
-    resolution: [f32; 2],
-    time: f32,
     freq: f32,
 
     wallpaper_brightness: f32,
     edge_width: f32,
     pulse_brightness: f32,
-
-    _padding: u32,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -35,7 +31,7 @@ pub enum PulseEdgesError {
 }
 
 pub struct PulseEdges {
-    bar_processor: BarProcessor,
+    bar_processor: SharedBarProcessor,
 
     data_binding_buffer: wgpu::Buffer,
 
@@ -51,7 +47,7 @@ impl PulseEdges {
             return Err(PulseEdgesError::EvenKernelSize(desc.kernel_size));
         }
 
-        let bar_processor = BarProcessor::new(
+        let bar_processor = desc.renderer.bar_processor_pool().get(
             desc.sample_processor,
             vibe_audio::BarProcessorConfig {
                 amount_bars: NonZero::new(1).unwrap(),
@@ -121,8 +117,6 @@ impl PulseEdges {
         };
 
         let data_binding = DataBinding {
-            resolution: [1f32; 2],
-            time: 0.,
             freq: 0.,
             wallpaper_brightness: desc.wallpaper_brightness.clamp(0., 1.),
             edge_width: {
@@ -130,7 +124,6 @@ impl PulseEdges {
                 1. / desc.edge_width.max(f32::EPSILON)
             },
             pulse_brightness: desc.pulse_brightness,
-            _padding: 0,
         };
 
         let data_binding_buffer = device.create_buffer(&wgpu::BufferDescriptor {
@@ -155,16 +148,65 @@ impl PulseEdges {
             border_color: None,
         });
 
+        let bind_group1_layout = {
+            let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+                binding,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            };
+
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("Encrust Wallpaper: Bind group 1 layout"),
+                entries: &[
+                    // wallpaper
+                    texture_entry(0),
+                    // edges
+                    texture_entry(1),
+                    // blurred wallpaper
+                    texture_entry(2),
+                    // sampler
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                        count: None,
+                    },
+                    // data
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 4,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
+            })
+        };
+
         let pipeline = {
             let vertex_shader =
                 device.create_shader_module(include_wgsl!("../../utils/full_screen_vertex.wgsl"));
 
             let fragment_shader = device.create_shader_module(include_wgsl!("./shader.wgsl"));
 
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Encrust Wallpaper: Pipeline layout"),
+                bind_group_layouts: &[renderer.frame_uniforms_layout(), &bind_group1_layout],
+                ..Default::default()
+            });
+
             device.create_render_pipeline(&crate::util::simple_pipeline_descriptor(
                 crate::util::SimpleRenderPipelineDescriptor {
                     label: "Encrust Wallpaper: Render pipeline",
-                    layout: None,
+                    layout: Some(&pipeline_layout),
                     vertex: wgpu::VertexState {
                         module: &vertex_shader,
                         entry_point: None,
@@ -186,8 +228,8 @@ impl PulseEdges {
         };
 
         let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Encrust Wallpaper: Bind group"),
-            layout: &pipeline.get_bind_group_layout(0),
+            label: Some("Encrust Wallpaper: Bind group 1"),
+            layout: &bind_group1_layout,
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -232,14 +274,14 @@ impl PulseEdges {
 
 impl Renderable for PulseEdges {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
-        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_bind_group(1, &self.bind_group, &[]);
         pass.set_pipeline(&self.pipeline);
         pass.draw(0..4, 0..1);
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for PulseEdges {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>) {
+impl Component for PulseEdges {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
         let bars = self.bar_processor.process_bars(processor);
 
         self.data_binding.freq = bars[0][0];
@@ -250,30 +292,12 @@ impl<F: Fetcher> ComponentAudio<F> for PulseEdges {
             bytemuck::bytes_of(&self.data_binding),
         );
     }
-}
-
-impl Component for PulseEdges {
-    fn update_time(&mut self, queue: &wgpu::Queue, new_time: f32) {
-        self.data_binding.time = new_time;
-
-        queue.write_buffer(
-            &self.data_binding_buffer,
-            0,
-            bytemuck::bytes_of(&self.data_binding),
-        );
-    }
-
-    fn update_resolution(&mut self, renderer: &crate::Renderer, new_resolution: [u32; 2]) {
-        let queue = renderer.queue();
 
-        self.data_binding.resolution = [new_resolution[0] as f32, new_resolution[1] as f32];
+    // `iTime` is part of the frame uniforms
+    fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
-        queue.write_buffer(
-            &self.data_binding_buffer,
-            0,
-            bytemuck::bytes_of(&self.data_binding),
-        );
-    }
+    // `iResolution` is part of the frame uniforms
+    fn update_resolution(&mut self, _renderer: &crate::Renderer, _new_resolution: [u32; 2]) {}
 
     fn update_mouse_position(&mut self, _queue: &wgpu::Queue, _new_pos: (f32, f32)) {}
 }
@@ -1,18 +1,20 @@
+// frame uniforms (see `FrameUniforms`)
 @group(0) @binding(0)
+var<uniform> iResolution: vec2f;
+
+@group(1) @binding(0)
 var wallpaper: texture_2d<f32>;
 
-@group(0) @binding(1)
+@group(1) @binding(1)
 var edges: texture_2d<f32>;
 
-@group(0) @binding(2)
+@group(1) @binding(2)
 var blurred_wallpaper: texture_2d<f32>;
 
-@group(0) @binding(3)
+@group(1) @binding(3)
 var sam: sampler;
 
 struct Data {
-    resolution: vec2f,
-    time: f32,
     freq: f32,
 
     wallpaper_brightness: f32,
@@ -20,7 +22,7 @@ struct Data {
     pulse_brightness: f32,
 }
 
-@group(0) @binding(4)
+@group(1) @binding(4)
 var<uniform> data: Data;
 
 @vertex
@@ -30,7 +32,7 @@ fn vs_main(@location(0) pos: vec2f) -> @builtin(position) vec4f {
 
 @fragment
 fn fs_main(@builtin(position) pos: vec4f) -> @location(0) vec4f {
-    let uv = pos.xy / data.resolution;
+    let uv = pos.xy / iResolution;
 
     let tex = textureSample(wallpaper, sam, uv);
     let dis = textureSample(edges, sam, uv).r;
@@ -7,8 +7,11 @@ mod chessy;
 mod circle;
 mod fragment_canvas;
 mod graph;
+mod palette;
 mod radial;
+mod render_scale;
 mod utils;
+mod viewport;
 
 pub use aurodio::{Aurodio, AurodioDescriptor, AurodioLayerDescriptor};
 pub use bars::{BarVariant, Bars, BarsDescriptor, BarsFormat, BarsPlacement};
@@ -16,13 +19,16 @@ pub use chessy::{Chessy, ChessyDescriptor};
 pub use circle::{Circle, CircleDescriptor, CircleVariant};
 pub use fragment_canvas::{FragmentCanvas, FragmentCanvasDescriptor};
 pub use graph::{Graph, GraphDescriptor, GraphFormat, GraphPlacement, GraphVariant};
+pub use palette::WithPalette;
 pub use radial::{Radial, RadialDescriptor, RadialFormat, RadialVariant};
+pub use render_scale::{WithRenderScale, MIN_RENDER_SCALE};
+pub use viewport::{Viewport, WithViewport};
 
 use crate::{Renderable, Renderer};
 use serde::{Deserialize, Serialize};
-use std::{num::NonZero, path::PathBuf};
+use std::{collections::HashMap, num::NonZero, path::PathBuf};
 use utils::wgsl_types::*;
-use vibe_audio::{fetcher::Fetcher, SampleProcessor};
+use vibe_audio::AudioSource;
 
 // rgba values are each directly set in the fragment shader
 pub type Rgba = Vec4f;
@@ -33,6 +39,9 @@ pub type Pixels<N> = NonZero<N>;
 /// It provides methods to update its internal state regarding the current
 /// audio and time for example.
 pub trait Component: Renderable {
+    /// Tells the component to update its bar values with the given audio `processor`.
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource);
+
     /// Tells the component the time.
     fn update_time(&mut self, queue: &wgpu::Queue, new_time: f32);
 
@@ -47,7 +56,8 @@ pub trait Component: Renderable {
     ///   - Callers (window.rs, output/mod.rs) normalize from pixel coords before calling.
     fn update_mouse_position(&mut self, queue: &wgpu::Queue, new_pos: (f32, f32));
 
-    fn update_colors(&mut self, _queue: &wgpu::Queue, _colors: &[[f32; 3]; 4]) {}
+    /// Tells the component the color palette (from the darkest to the brightest color, at most [crate::MAX_COLORS]).
+    fn update_colors(&mut self, _queue: &wgpu::Queue, _colors: &[[f32; 3]]) {}
 
     /// Notify the component of a mouse click at a normalized position.
     ///
@@ -56,6 +66,20 @@ pub trait Component: Renderable {
     /// `time`: Elapsed seconds since the renderer started (same timebase as `update_time`).
     fn update_mouse_click(&mut self, _queue: &wgpu::Queue, _pos: (f32, f32), _time: f32) {}
 
+    /// Notify the component that a key got pressed or released.
+    ///
+    /// `keysym`: The xkb keysym of the key (for example `0x61` for `a`).
+    /// `time`: Elapsed seconds since the renderer started (same timebase as `update_time`).
+    ///
+    /// Only called if the keyboard interactivity of the output is enabled.
+    fn update_key(&mut self, _queue: &wgpu::Queue, _keysym: u32, _pressed: bool, _time: f32) {}
+
+    /// Tells the component the named values of its config which can be tweaked without recreating it.
+    ///
+    /// Called after the component got created and whenever the values change.
+    /// Names which the component doesn't know should be ignored.
+    fn update_params(&mut self, _queue: &wgpu::Queue, _params: &HashMap<String, f32>) {}
+
     /// Called after the render pass completes with access to the rendered surface texture.
     ///
     /// This hook enables GPU pixel readback: components can copy pixels from the rendered
@@ -70,16 +94,23 @@ pub trait Component: Renderable {
     }
 }
 
-/// An extended version of `Component` which includes methods related to audio.
-pub trait ComponentAudio<F: Fetcher>: Component {
-    /// Tells the component to update its bar values with the given `processor`.
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>);
-}
-
 impl Renderable for Box<dyn Component> {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
         self.as_ref().render_with_renderpass(pass)
     }
+
+    fn palette_override(&self) -> Option<&crate::PaletteOverride> {
+        self.as_ref().palette_override()
+    }
+
+    fn pre_render(
+        &self,
+        renderer: &Renderer,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_uniforms: &crate::FrameUniforms,
+    ) {
+        self.as_ref().pre_render(renderer, encoder, frame_uniforms)
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -89,6 +120,9 @@ pub enum ShaderCodeError {
 
     #[error("Couldn't parse shader code: {0}")]
     ParseError(#[from] wgpu::Error),
+
+    #[error("`{0}` isn't a valid name for a param. Only letters, digits and `_` are allowed and it mustn't start with a digit.")]
+    InvalidParamName(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,91 @@
+//! Gives a component its own colors.
+use std::collections::HashMap;
+
+use vibe_audio::AudioSource;
+
+use super::Component;
+use crate::{PaletteOverride, Renderable, Renderer};
+
+/// Renders a component with its own color palette instead of the one of the output.
+///
+/// The component receives the colors through `iColors`/`iPalette` and [Component::update_colors],
+/// the colors which are passed to [WithPalette::update_colors] are ignored.
+pub struct WithPalette {
+    component: Box<dyn Component>,
+    colors: Vec<[f32; 3]>,
+    palette_override: PaletteOverride,
+}
+
+impl WithPalette {
+    /// Returns `None` if `colors` is empty.
+    pub fn new(
+        renderer: &Renderer,
+        component: Box<dyn Component>,
+        colors: Vec<[f32; 3]>,
+    ) -> Option<Self> {
+        let palette_override = PaletteOverride::new(renderer, &colors)?;
+
+        Some(Self {
+            component,
+            colors,
+            palette_override,
+        })
+    }
+}
+
+impl Renderable for WithPalette {
+    fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
+        self.component.render_with_renderpass(pass);
+    }
+
+    fn palette_override(&self) -> Option<&PaletteOverride> {
+        Some(&self.palette_override)
+    }
+
+    fn pre_render(
+        &self,
+        renderer: &Renderer,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_uniforms: &crate::FrameUniforms,
+    ) {
+        self.component.pre_render(renderer, encoder, frame_uniforms);
+    }
+}
+
+impl Component for WithPalette {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
+        self.component.update_audio(queue, processor);
+    }
+
+    fn update_time(&mut self, queue: &wgpu::Queue, new_time: f32) {
+        self.component.update_time(queue, new_time);
+    }
+
+    fn update_resolution(&mut self, renderer: &Renderer, new_resolution: [u32; 2]) {
+        self.component.update_resolution(renderer, new_resolution);
+    }
+
+    fn update_mouse_position(&mut self, queue: &wgpu::Queue, new_pos: (f32, f32)) {
+        self.component.update_mouse_position(queue, new_pos);
+    }
+
+    fn update_colors(&mut self, queue: &wgpu::Queue, _colors: &[[f32; 3]]) {
+        self.component.update_colors(queue, &self.colors);
+    }
+
+    fn update_mouse_click(&mut self, queue: &wgpu::Queue, pos: (f32, f32), time: f32) {
+        self.component.update_mouse_click(queue, pos, time);
+    }
+
+    fn update_key(&mut self, queue: &wgpu::Queue, keysym: u32, pressed: bool, time: f32) {
+        self.component.update_key(queue, keysym, pressed, time);
+    }
+
+    fn update_params(&mut self, queue: &wgpu::Queue, params: &HashMap<String, f32>) {
+        self.component.update_params(queue, params);
+    }
+
+    fn post_render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+        self.component.post_render(device, queue, texture);
+    }
+}
@@ -3,9 +3,9 @@ mod descriptor;
 pub use descriptor::*;
 
 use super::{Component, Rgba, Vec2f};
-use crate::{components::ComponentAudio, Renderable};
+use crate::{Renderable, Renderer};
 use cgmath::{Deg, Matrix2, Rad, Vector2};
-use vibe_audio::{fetcher::Fetcher, BarProcessor, SampleProcessor};
+use vibe_audio::{fetcher::Fetcher, AudioSource, SharedBarProcessor};
 use wgpu::{include_wgsl, util::DeviceExt};
 
 /// Entrypoints for the vertex shader
@@ -57,14 +57,14 @@ impl CirclePart {
 
 type PositionOffset = Vec2f;
 type CircleRadius = f32;
-type AspectRatio = f32;
 
+// the aspect ratio is computed with `iResolution` of the frame uniforms (bind group 0)
 #[repr(C)]
 #[derive(Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, Default)]
 struct VertexParams {
     position_offset: PositionOffset,
     circle_radius: CircleRadius,
-    aspect_ratio: AspectRatio,
+    _padding: f32,
 }
 
 #[repr(C)]
@@ -82,7 +82,7 @@ struct VertexFragmentParams {
 }
 
 struct PipelineCtx {
-    bind_group1: wgpu::BindGroup,
+    bind_group2: wgpu::BindGroup,
     freq_buffer: wgpu::Buffer,
     _rotations_buffer: wgpu::Buffer,
 
@@ -90,10 +90,11 @@ struct PipelineCtx {
 }
 
 pub struct Radial {
-    bar_processor: BarProcessor,
+    bar_processor: SharedBarProcessor,
 
-    bind_group0: wgpu::BindGroup,
-    vertex_params_buffer: wgpu::Buffer,
+    // `left` and `right` share the same bind group 1
+    bind_group1: wgpu::BindGroup,
+    _vertex_params_buffer: wgpu::Buffer,
     _fragment_params_buffer: wgpu::Buffer,
     _vertex_fragment_params_buffer: wgpu::Buffer,
 
@@ -106,7 +107,10 @@ pub struct Radial {
 impl Radial {
     pub fn new<F: Fetcher>(desc: &RadialDescriptor<F>) -> Self {
         let device = desc.renderer.device();
-        let bar_processor = BarProcessor::new(desc.processor, desc.audio_conf.clone());
+        let bar_processor = desc
+            .renderer
+            .bar_processor_pool()
+            .get(desc.processor, desc.audio_conf.clone());
         let total_amount_bars = bar_processor.total_amount_bars();
 
         let vertex_params_buffer = {
@@ -121,13 +125,13 @@ impl Radial {
             let vertex_params = VertexParams {
                 position_offset: position_offset.into(),
                 circle_radius: desc.circle_radius,
-                aspect_ratio: 0.,
+                ..Default::default()
             };
 
             device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
                 label: Some("Radial: Vertex params buffer"),
                 contents: bytemuck::bytes_of(&vertex_params),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                usage: wgpu::BufferUsages::UNIFORM,
             })
         };
 
@@ -205,15 +209,15 @@ impl Radial {
             };
 
             let pipeline = create_pipeline(
-                device,
+                desc.renderer,
                 desc.output_texture_format,
                 vertex_entry_point,
                 fragment_entrypoint,
             );
 
-            let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                label: Some("Radial: Left bind group 1"),
-                layout: &pipeline.get_bind_group_layout(1),
+            let bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("Radial: Left bind group 2"),
+                layout: &pipeline.get_bind_group_layout(2),
                 entries: &[
                     wgpu::BindGroupEntry {
                         binding: 0,
@@ -227,16 +231,16 @@ impl Radial {
             });
 
             PipelineCtx {
-                bind_group1,
+                bind_group2,
                 freq_buffer: freqs_buffer,
                 _rotations_buffer: rotations_buffer,
                 pipeline,
             }
         };
 
-        let bind_group0 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Radial: Bind group 0"),
-            layout: &left.pipeline.get_bind_group_layout(0),
+        let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Radial: Bind group 1"),
+            layout: &left.pipeline.get_bind_group_layout(1),
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
@@ -290,15 +294,15 @@ impl Radial {
                 };
 
                 let pipeline = create_pipeline(
-                    device,
+                    desc.renderer,
                     desc.output_texture_format,
                     vertex_entry_point,
                     fragment_entrypoint,
                 );
 
-                let bind_group1 = device.create_bind_group(&wgpu::BindGroupDescriptor {
-                    label: Some("Radial:Right bind group 1"),
-                    layout: &pipeline.get_bind_group_layout(1),
+                let bind_group2 = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Radial:Right bind group 2"),
+                    layout: &pipeline.get_bind_group_layout(2),
                     entries: &[
                         wgpu::BindGroupEntry {
                             binding: 0,
@@ -314,7 +318,7 @@ impl Radial {
                 PipelineCtx {
                     pipeline,
 
-                    bind_group1,
+                    bind_group2,
                     freq_buffer,
                     _rotations_buffer: rotations_buffer,
                 }
@@ -324,8 +328,8 @@ impl Radial {
         Self {
             bar_processor,
 
-            bind_group0,
-            vertex_params_buffer,
+            bind_group1,
+            _vertex_params_buffer: vertex_params_buffer,
             _fragment_params_buffer: fragment_params_buffer,
             _vertex_fragment_params_buffer: vertex_fragment_params_buffer,
 
@@ -339,24 +343,24 @@ impl Radial {
 
 impl Renderable for Radial {
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
-        pass.set_bind_group(0, &self.bind_group0, &[]);
+        pass.set_bind_group(1, &self.bind_group1, &[]);
 
         // render the left half of the circle
-        pass.set_bind_group(1, &self.left.bind_group1, &[]);
+        pass.set_bind_group(2, &self.left.bind_group2, &[]);
         pass.set_pipeline(&self.left.pipeline);
         pass.draw(0..4, 0..self.total_amount_bars as u32);
 
         // render the right half of the circle
         if let Some(right) = &self.right {
-            pass.set_bind_group(1, &right.bind_group1, &[]);
+            pass.set_bind_group(2, &right.bind_group2, &[]);
             pass.set_pipeline(&right.pipeline);
             pass.draw(0..4, 0..self.total_amount_bars as u32);
         }
     }
 }
 
-impl<F: Fetcher> ComponentAudio<F> for Radial {
-    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &SampleProcessor<F>) {
+impl Component for Radial {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
         let bar_values = self.bar_processor.process_bars(processor);
 
         queue.write_buffer(
@@ -369,27 +373,11 @@ impl<F: Fetcher> ComponentAudio<F> for Radial {
             queue.write_buffer(&right.freq_buffer, 0, bytemuck::cast_slice(&bar_values[1]));
         }
     }
-}
 
-impl Component for Radial {
     fn update_time(&mut self, _queue: &wgpu::Queue, _new_time: f32) {}
 
-    fn update_resolution(&mut self, renderer: &crate::Renderer, new_resolution: [u32; 2]) {
-        let queue = renderer.queue();
-
-        {
-            let aspect_ratio = new_resolution[0] as f32 / new_resolution[1] as f32;
-
-            let offset =
-                std::mem::size_of::<PositionOffset>() + std::mem::size_of::<CircleRadius>();
-
-            queue.write_buffer(
-                &self.vertex_params_buffer,
-                offset as wgpu::BufferAddress,
-                bytemuck::bytes_of(&aspect_ratio),
-            );
-        }
-    }
+    // the aspect ratio is computed with `iResolution` of the frame uniforms
+    fn update_resolution(&mut self, _renderer: &Renderer, _new_resolution: [u32; 2]) {}
 
     fn update_mouse_position(&mut self, _queue: &wgpu::Queue, _new_pos: (f32, f32)) {}
 }
@@ -437,18 +425,20 @@ fn compute_rotations(
 }
 
 fn create_pipeline(
-    device: &wgpu::Device,
+    renderer: &Renderer,
     texture_format: wgpu::TextureFormat,
     vertex_entrypoint: VertexEntrypoint,
     fragment_entrypoint: FragmentEntrypoint,
 ) -> wgpu::RenderPipeline {
+    let device = renderer.device();
     let module = device.create_shader_module(include_wgsl!("./shader.wgsl"));
 
     let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
         label: Some("Bars: Pipeline layout"),
         bind_group_layouts: &[
+            renderer.frame_uniforms_layout(),
             &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Bars: Bind group 0 layout"),
+                label: Some("Bars: Bind group 1 layout"),
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
                         binding: 0,
@@ -483,7 +473,7 @@ fn create_pipeline(
                 ],
             }),
             &device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                label: Some("Bars: Bind group 1 layout"),
+                label: Some("Bars: Bind group 2 layout"),
                 entries: &[
                     wgpu::BindGroupLayoutEntry {
                         binding: 0,
@@ -1,7 +1,10 @@
+// frame uniforms (see `FrameUniforms`)
+@group(0) @binding(0)
+var<uniform> iResolution: vec2f;
+
 struct VertexParams {
-    position_offset: vec2f,   
+    position_offset: vec2f,
     circle_radius: f32,
-    aspect_ratio: f32,
 }
 
 struct FragmentParams {
@@ -14,19 +17,19 @@ struct VertexFragmentParams {
     bar_height_sensitivity: f32,
 }
 
-@group(0) @binding(0)
+@group(1) @binding(0)
 var<uniform> vp: VertexParams;
 
-@group(0) @binding(1)
+@group(1) @binding(1)
 var<uniform> fp: FragmentParams;
 
-@group(0) @binding(2)
+@group(1) @binding(2)
 var<uniform> vfp: VertexFragmentParams;
 
-@group(1) @binding(0)
+@group(2) @binding(0)
 var<storage, read> freqs: array<f32>;
 
-@group(1) @binding(1)
+@group(2) @binding(1)
 var<storage, read> rotations: array<mat2x2f>;
 
 struct Input {
@@ -83,7 +86,7 @@ fn vertex_main(freq: f32, vertex_idx: u32, instance_idx: u32) -> Output {
 
     var final_pos: vec2f;
     final_pos = rotations[instance_idx] * rect_pos;
-    final_pos.x /= vp.aspect_ratio;
+    final_pos.x /= iResolution.x / iResolution.y;
     final_pos += vp.position_offset;
 
     var out: Output;
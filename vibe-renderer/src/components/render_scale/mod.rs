@@ -0,0 +1,367 @@
+//! Renders a component at a lower resolution and upscales it onto the output.
+use std::collections::HashMap;
+
+use vibe_audio::AudioSource;
+use wgpu::include_wgsl;
+
+use super::{Component, Viewport};
+use crate::{frame_uniforms::ResolutionOverride, FrameUniforms, Renderable, Renderer};
+
+/// The smallest supported factor of [WithRenderScale].
+pub const MIN_RENDER_SCALE: f32 = 0.1;
+
+/// Renders a component into a texture with a fraction of the resolution of the output and
+/// stretches the texture (with linear filtering) over the output.
+///
+/// Expensive components (like fragment shaders) become much cheaper on large outputs
+/// at the cost of sharpness. The component sees the smaller resolution in `iResolution`
+/// and [Component::update_resolution].
+///
+/// [WithViewport](super::WithViewport) uses it to render its component into a texture with the size of the viewport.
+pub struct WithRenderScale {
+    component: Box<dyn Component>,
+    scale: f32,
+    format: wgpu::TextureFormat,
+    // the rectangle of the output which the texture covers, `None` for the whole output
+    viewport: Option<Viewport>,
+    // the resolution of the whole output
+    resolution: [u32; 2],
+
+    uniforms: ResolutionOverride,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+    rect_buffer: wgpu::Buffer,
+
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl WithRenderScale {
+    /// Renders `component` with `scale` times the resolution of the output (clamped to
+    /// [MIN_RENDER_SCALE] and `1`). `format` has to be the format of the output.
+    pub fn new(
+        renderer: &Renderer,
+        component: Box<dyn Component>,
+        scale: f32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::with_viewport(renderer, component, scale, None, format)
+    }
+
+    /// Like [WithRenderScale::new] but the texture only covers `viewport` (and has `scale` times its size).
+    pub(crate) fn with_viewport(
+        renderer: &Renderer,
+        component: Box<dyn Component>,
+        scale: f32,
+        viewport: Option<Viewport>,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let device = renderer.device();
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Render scale: Bind group 1 layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline = {
+            let vertex_module =
+                device.create_shader_module(include_wgsl!("../utils/full_screen_vertex.wgsl"));
+            let fragment_module = device.create_shader_module(include_wgsl!("./render_scale.wgsl"));
+
+            let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: Some("Render scale: Pipeline layout"),
+                bind_group_layouts: &[renderer.frame_uniforms_layout(), &bind_group_layout],
+                ..Default::default()
+            });
+
+            device.create_render_pipeline(&crate::util::simple_pipeline_descriptor(
+                crate::util::SimpleRenderPipelineDescriptor {
+                    label: "Render scale: Render pipeline",
+                    layout: Some(&pipeline_layout),
+                    vertex: wgpu::VertexState {
+                        module: &vertex_module,
+                        entry_point: None,
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        buffers: &[],
+                    },
+                    fragment: wgpu::FragmentState {
+                        module: &fragment_module,
+                        entry_point: Some("main"),
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            // the component got alpha blended onto a transparent texture,
+                            // so its colors are already multiplied with its alpha
+                            blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::all(),
+                        })],
+                    },
+                },
+            ))
+        };
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Render scale: Sampler"),
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let rect_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Render scale: Rect buffer"),
+            size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let (texture, view, bind_group) =
+            Self::create_texture(device, &pipeline, &sampler, &rect_buffer, format, [1, 1]);
+
+        Self {
+            component,
+            scale: scale.clamp(MIN_RENDER_SCALE, 1.),
+            format,
+            viewport,
+            resolution: [0, 0],
+
+            uniforms: ResolutionOverride::new(renderer),
+            texture,
+            view,
+            sampler,
+            rect_buffer,
+
+            bind_group,
+            pipeline,
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        pipeline: &wgpu::RenderPipeline,
+        sampler: &wgpu::Sampler,
+        rect_buffer: &wgpu::Buffer,
+        format: wgpu::TextureFormat,
+        size: [u32; 2],
+    ) -> (wgpu::Texture, wgpu::TextureView, wgpu::BindGroup) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Render scale: Texture"),
+            size: wgpu::Extent3d {
+                width: size[0],
+                height: size[1],
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            // `COPY_SRC` for the readbacks of the component (see `post_render`)
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Render scale: Bind group 1"),
+            layout: &pipeline.get_bind_group_layout(1),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(sampler),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: rect_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        (texture, view, bind_group)
+    }
+
+    /// Returns the rectangle of the output which the texture covers (`[x, y, width, height]` in pixels).
+    fn rect(&self) -> [u32; 4] {
+        match self.viewport {
+            Some(viewport) => viewport.pixels(self.resolution),
+            None => [0, 0, self.resolution[0], self.resolution[1]],
+        }
+    }
+}
+
+/// Returns the resolution of the texture for the given size of its rectangle.
+fn scaled_resolution(resolution: [u32; 2], scale: f32) -> [u32; 2] {
+    resolution.map(|length| ((length as f32 * scale).round() as u32).max(1))
+}
+
+impl Renderable for WithRenderScale {
+    fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
+        let [width, height] = self.resolution;
+        if width == 0 || height == 0 {
+            return;
+        }
+
+        let [x, y, rect_width, rect_height] = self.rect();
+        pass.set_scissor_rect(x, y, rect_width, rect_height);
+
+        pass.set_bind_group(1, &self.bind_group, &[]);
+        pass.set_pipeline(&self.pipeline);
+        pass.draw(0..3, 0..1);
+
+        // the next renderables use the whole output again
+        pass.set_scissor_rect(0, 0, width, height);
+    }
+
+    fn pre_render(
+        &self,
+        renderer: &Renderer,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_uniforms: &FrameUniforms,
+    ) {
+        self.component.pre_render(renderer, encoder, frame_uniforms);
+
+        // the component gets the values of the frame with the resolution of the texture
+        let resolution = [self.texture.width(), self.texture.height()];
+        let bind_group = match self.component.palette_override() {
+            Some(palette_override) => {
+                palette_override.write(renderer.queue(), frame_uniforms, resolution);
+                palette_override.bind_group()
+            }
+            None => {
+                self.uniforms
+                    .write(renderer.queue(), frame_uniforms, resolution);
+                self.uniforms.bind_group()
+            }
+        };
+
+        let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Render scale: Render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &self.view,
+                resolve_target: None,
+                depth_slice: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                    store: wgpu::StoreOp::Store,
+                },
+            })],
+            ..Default::default()
+        });
+
+        pass.set_bind_group(crate::FRAME_UNIFORMS_GROUP, bind_group, &[]);
+        self.component.render_with_renderpass(&mut pass);
+    }
+}
+
+impl Component for WithRenderScale {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
+        self.component.update_audio(queue, processor);
+    }
+
+    fn update_time(&mut self, queue: &wgpu::Queue, new_time: f32) {
+        self.component.update_time(queue, new_time);
+    }
+
+    fn update_resolution(&mut self, renderer: &Renderer, new_resolution: [u32; 2]) {
+        self.resolution = new_resolution;
+
+        let rect = self.rect();
+        renderer.queue().write_buffer(
+            &self.rect_buffer,
+            0,
+            bytemuck::cast_slice(&rect.map(|value| value as f32)),
+        );
+
+        let resolution = scaled_resolution([rect[2], rect[3]], self.scale);
+        if [self.texture.width(), self.texture.height()] != resolution {
+            let (texture, view, bind_group) = Self::create_texture(
+                renderer.device(),
+                &self.pipeline,
+                &self.sampler,
+                &self.rect_buffer,
+                self.format,
+                resolution,
+            );
+
+            self.texture = texture;
+            self.view = view;
+            self.bind_group = bind_group;
+        }
+
+        self.component.update_resolution(renderer, resolution);
+    }
+
+    fn update_mouse_position(&mut self, queue: &wgpu::Queue, new_pos: (f32, f32)) {
+        self.component.update_mouse_position(queue, new_pos);
+    }
+
+    fn update_colors(&mut self, queue: &wgpu::Queue, colors: &[[f32; 3]]) {
+        self.component.update_colors(queue, colors);
+    }
+
+    fn update_mouse_click(&mut self, queue: &wgpu::Queue, pos: (f32, f32), time: f32) {
+        self.component.update_mouse_click(queue, pos, time);
+    }
+
+    fn update_key(&mut self, queue: &wgpu::Queue, keysym: u32, pressed: bool, time: f32) {
+        self.component.update_key(queue, keysym, pressed, time);
+    }
+
+    fn update_params(&mut self, queue: &wgpu::Queue, params: &HashMap<String, f32>) {
+        self.component.update_params(queue, params);
+    }
+
+    /// The component reads its pixels back from the texture which it got rendered into.
+    fn post_render(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        _texture: &wgpu::Texture,
+    ) {
+        self.component.post_render(device, queue, &self.texture);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolutions() {
+        assert_eq!(scaled_resolution([3840, 2160], 0.5), [1920, 1080]);
+        assert_eq!(scaled_resolution([1920, 1080], 1.), [1920, 1080]);
+        assert_eq!(scaled_resolution([5, 5], 0.1), [1, 1]);
+    }
+}
@@ -0,0 +1,14 @@
+@group(1) @binding(0)
+var content: texture_2d<f32>;
+
+@group(1) @binding(1)
+var content_sampler: sampler;
+
+// the rectangle of the output which the texture covers: `xy` is the top-left corner, `zw` the size (in pixels)
+@group(1) @binding(2)
+var<uniform> rect: vec4f;
+
+@fragment
+fn main(@builtin(position) pos: vec4f) -> @location(0) vec4f {
+    return textureSample(content, content_sampler, (pos.xy - rect.xy) / rect.zw);
+}
@@ -0,0 +1,211 @@
+//! Restricts a component to a rectangle of the output.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use vibe_audio::AudioSource;
+
+use super::{Component, WithRenderScale};
+use crate::{Renderable, Renderer};
+
+/// A rectangle within the output in normalized coordinates.
+///
+/// `(0, 0)` is the top-left corner and `(1, 1)` the bottom-right corner of the output.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Viewport {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Viewport {
+    /// Checks if the rectangle lies within the output and isn't empty.
+    pub fn validate(&self) -> Result<(), String> {
+        let is_normalized = |value: f32| (0. ..=1.).contains(&value);
+
+        if !is_normalized(self.x) || !is_normalized(self.y) {
+            return Err("`x` and `y` of the viewport have to be within 0 and 1.".to_string());
+        }
+
+        if !(self.width > 0. && self.height > 0.) {
+            return Err("`width` and `height` of the viewport have to be positive.".to_string());
+        }
+
+        if self.x + self.width > 1. || self.y + self.height > 1. {
+            return Err("The viewport has to lie within the output.".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Returns the rectangle in pixels (`[x, y, width, height]`) for the given resolution.
+    ///
+    /// The rectangle is at least one pixel big as long as the resolution isn't empty.
+    pub fn pixels(&self, resolution: [u32; 2]) -> [u32; 4] {
+        let [width, height] = resolution;
+        let to_pixels = |value: f32, size: u32| (value * size as f32).round() as u32;
+
+        let x = to_pixels(self.x, width).min(width.saturating_sub(1));
+        let y = to_pixels(self.y, height).min(height.saturating_sub(1));
+
+        [
+            x,
+            y,
+            to_pixels(self.width, width).clamp(1, (width - x).max(1)),
+            to_pixels(self.height, height).clamp(1, (height - y).max(1)),
+        ]
+    }
+
+    /// Maps a normalized position of the output into the viewport.
+    ///
+    /// Positions outside of the viewport lie outside of `[0, 1]`.
+    fn map_position(&self, pos: (f32, f32)) -> (f32, f32) {
+        (
+            (pos.0 - self.x) / self.width,
+            (pos.1 - self.y) / self.height,
+        )
+    }
+}
+
+/// Renders a component only within a [Viewport] of the output.
+///
+/// The component sees the viewport as its whole output: It gets rendered into a texture with the size of
+/// the viewport (which is then drawn into the viewport), so `iResolution`, `@builtin(position)` and
+/// the mouse positions are relative to the viewport.
+pub struct WithViewport {
+    component: WithRenderScale,
+    viewport: Viewport,
+}
+
+impl WithViewport {
+    /// `format` has to be the format of the output.
+    pub fn new(
+        renderer: &Renderer,
+        component: Box<dyn Component>,
+        viewport: Viewport,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self::with_render_scale(renderer, component, viewport, 1., format)
+    }
+
+    /// Like [WithViewport::new] but renders the component with `scale` times the resolution of the viewport
+    /// (see [WithRenderScale]).
+    pub fn with_render_scale(
+        renderer: &Renderer,
+        component: Box<dyn Component>,
+        viewport: Viewport,
+        scale: f32,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        Self {
+            component: WithRenderScale::with_viewport(
+                renderer,
+                component,
+                scale,
+                Some(viewport),
+                format,
+            ),
+            viewport,
+        }
+    }
+}
+
+impl Renderable for WithViewport {
+    fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
+        self.component.render_with_renderpass(pass);
+    }
+
+    fn pre_render(
+        &self,
+        renderer: &Renderer,
+        encoder: &mut wgpu::CommandEncoder,
+        frame_uniforms: &crate::FrameUniforms,
+    ) {
+        self.component.pre_render(renderer, encoder, frame_uniforms);
+    }
+}
+
+impl Component for WithViewport {
+    fn update_audio(&mut self, queue: &wgpu::Queue, processor: &dyn AudioSource) {
+        self.component.update_audio(queue, processor);
+    }
+
+    fn update_time(&mut self, queue: &wgpu::Queue, new_time: f32) {
+        self.component.update_time(queue, new_time);
+    }
+
+    fn update_resolution(&mut self, renderer: &Renderer, new_resolution: [u32; 2]) {
+        self.component.update_resolution(renderer, new_resolution);
+    }
+
+    fn update_mouse_position(&mut self, queue: &wgpu::Queue, new_pos: (f32, f32)) {
+        self.component
+            .update_mouse_position(queue, self.viewport.map_position(new_pos));
+    }
+
+    fn update_colors(&mut self, queue: &wgpu::Queue, colors: &[[f32; 3]]) {
+        self.component.update_colors(queue, colors);
+    }
+
+    fn update_mouse_click(&mut self, queue: &wgpu::Queue, pos: (f32, f32), time: f32) {
+        let pos = if pos == (-1., -1.) {
+            pos
+        } else {
+            self.viewport.map_position(pos)
+        };
+
+        self.component.update_mouse_click(queue, pos, time);
+    }
+
+    fn update_key(&mut self, queue: &wgpu::Queue, keysym: u32, pressed: bool, time: f32) {
+        self.component.update_key(queue, keysym, pressed, time);
+    }
+
+    fn update_params(&mut self, queue: &wgpu::Queue, params: &HashMap<String, f32>) {
+        self.component.update_params(queue, params);
+    }
+
+    fn post_render(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+        self.component.post_render(device, queue, texture);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOP_RIGHT: Viewport = Viewport {
+        x: 0.75,
+        y: 0.,
+        width: 0.25,
+        height: 0.5,
+    };
+
+    #[test]
+    fn pixels() {
+        assert_eq!(TOP_RIGHT.pixels([1920, 1080]), [1440, 0, 480, 540]);
+        assert_eq!(TOP_RIGHT.pixels([2, 1]), [2 - 1, 0, 1, 1]);
+    }
+
+    #[test]
+    fn validate() {
+        assert!(TOP_RIGHT.validate().is_ok());
+
+        let outside = Viewport {
+            width: 0.5,
+            ..TOP_RIGHT
+        };
+        assert!(outside.validate().is_err());
+
+        let empty = Viewport {
+            height: 0.,
+            ..TOP_RIGHT
+        };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn map_position() {
+        assert_eq!(TOP_RIGHT.map_position((0.875, 0.25)), (0.5, 0.5));
+        assert_eq!(TOP_RIGHT.map_position((0.5, 0.)), (-1., 0.));
+    }
+}
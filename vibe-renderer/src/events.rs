@@ -0,0 +1,276 @@
+//! Events of the components which other programs might want to react to
+//! (for example to show the BPM in a status bar or to play a sound after a click).
+//!
+//! The components don't know where the events should go to. Set an [EventSink] with
+//! [Renderer::set_event_sink](crate::Renderer::set_event_sink) to receive them.
+use std::{
+    fmt,
+    io::Write,
+    os::unix::net::UnixDatagram,
+    path::{Path, PathBuf},
+};
+
+use tracing::debug;
+
+/// An event of a component.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The detected beats per minute changed (rounded to whole beats).
+    Bpm(f32),
+    /// The output got clicked.
+    Click {
+        /// The normalized position of the click (`(0, 0)` is the top-left corner).
+        x: f32,
+        y: f32,
+        /// The time of the renderer when the click happened.
+        time: f32,
+        /// The resolution of the component.
+        width: u32,
+        height: u32,
+    },
+    /// The shader of a `FragmentCanvas` reported a hit of the last click at pixel `(0, 0)`.
+    ///
+    /// `id` is the id of the entity which got hit (the red channel of the pixel minus one).
+    ClickHit { id: u8 },
+    /// A shader wrote a new value into one of its [readback slots](crate::readback::ReadbackSlot).
+    Readback { slot: String, value: [f32; 4] },
+}
+
+impl fmt::Display for Event {
+    /// Formats the event as a single line of `key=value` pairs, starting with the kind of the event.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bpm(bpm) => write!(f, "bpm value={:.0}", bpm),
+            Self::Click {
+                x,
+                y,
+                time,
+                width,
+                height,
+            } => write!(
+                f,
+                "click x={} y={} time={} width={} height={}",
+                x, y, time, width, height
+            ),
+            Self::ClickHit { id } => write!(f, "click-hit id={}", id),
+            Self::Readback { slot, value } => write!(
+                f,
+                "readback slot={} value={},{},{},{}",
+                slot, value[0], value[1], value[2], value[3]
+            ),
+        }
+    }
+}
+
+/// Receives the events of the components.
+///
+/// It's implemented for closures, so a callback can be used directly:
+///
+/// ```rust
+/// use vibe_renderer::events::{Event, EventSink};
+///
+/// fn takes_sink(_sink: impl EventSink) {}
+///
+/// takes_sink(|event: &Event| println!("{}", event));
+/// ```
+pub trait EventSink: Send + Sync {
+    /// Called for each event. It's called from the render loop, so it should return quickly.
+    fn send(&self, event: &Event);
+}
+
+impl fmt::Debug for dyn EventSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("EventSink")
+    }
+}
+
+impl<F: Fn(&Event) + Send + Sync> EventSink for F {
+    fn send(&self, event: &Event) {
+        self(event)
+    }
+}
+
+/// Writes each event into its own file of a directory: `vibe-bpm`, `vibe-click`, `vibe-click-species`
+/// and `vibe-readback-<slot>`.
+///
+/// Each file is overwritten with the latest event of its kind.
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    dir: PathBuf,
+}
+
+impl FileSink {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn write(&self, file_name: &str, content: &str) {
+        let path = self.dir.join(file_name);
+        if let Err(err) = std::fs::write(&path, content) {
+            debug!(
+                "Couldn't write event to '{}': {}",
+                path.to_string_lossy(),
+                err
+            );
+        }
+    }
+}
+
+impl EventSink for FileSink {
+    fn send(&self, event: &Event) {
+        match event {
+            Event::Bpm(bpm) => self.write("vibe-bpm", &format!("{:.0}\n", bpm)),
+            Event::Click {
+                x,
+                y,
+                time,
+                width,
+                height,
+            } => self.write(
+                "vibe-click",
+                &format!(
+                    "x={}\ny={}\ntime={}\nwidth={}\nheight={}\n",
+                    x, y, time, width, height
+                ),
+            ),
+            Event::ClickHit { id } => {
+                self.write("vibe-click-species", &format!("species={}\n", id))
+            }
+            Event::Readback { slot, value } => {
+                // the names of the slots come from the configs, so they mustn't leave `dir`
+                let slot: String = slot
+                    .chars()
+                    .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+                    .collect();
+
+                self.write(
+                    &format!("vibe-readback-{}", slot),
+                    &format!(
+                        "value={},{},{},{}\n",
+                        value[0], value[1], value[2], value[3]
+                    ),
+                )
+            }
+        }
+    }
+}
+
+/// Sends each event as a datagram (one line, see the [Display](fmt::Display) implementation of [Event])
+/// to a unix socket.
+///
+/// Events are dropped while nobody is listening on the socket.
+#[derive(Debug)]
+pub struct UnixSocketSink {
+    path: PathBuf,
+    socket: UnixDatagram,
+}
+
+impl UnixSocketSink {
+    pub fn new(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            socket,
+        })
+    }
+}
+
+impl EventSink for UnixSocketSink {
+    fn send(&self, event: &Event) {
+        let mut line = Vec::new();
+        let _ = writeln!(line, "{}", event);
+
+        if let Err(err) = self.socket.send_to(&line, &self.path) {
+            debug!(
+                "Couldn't send event to '{}': {}",
+                self.path.to_string_lossy(),
+                err
+            );
+        }
+    }
+}
+
+/// Sends the events to all of the given sinks.
+impl EventSink for Vec<Box<dyn EventSink>> {
+    fn send(&self, event: &Event) {
+        for sink in self {
+            sink.send(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CLICK: Event = Event::Click {
+        x: 0.5,
+        y: 0.25,
+        time: 3.,
+        width: 1920,
+        height: 1080,
+    };
+
+    #[test]
+    fn lines() {
+        assert_eq!(Event::Bpm(120.4).to_string(), "bpm value=120");
+        assert_eq!(
+            CLICK.to_string(),
+            "click x=0.5 y=0.25 time=3 width=1920 height=1080"
+        );
+        assert_eq!(Event::ClickHit { id: 3 }.to_string(), "click-hit id=3");
+        assert_eq!(
+            Event::Readback {
+                slot: "score".into(),
+                value: [0.5, 1., 0., 0.]
+            }
+            .to_string(),
+            "readback slot=score value=0.5,1,0,0"
+        );
+    }
+
+    #[test]
+    fn file_sink() {
+        let dir = std::env::temp_dir().join(format!("vibe-events-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let sink = FileSink::new(&dir);
+        sink.send(&Event::Bpm(128.));
+        sink.send(&CLICK);
+        sink.send(&Event::ClickHit { id: 7 });
+        sink.send(&Event::Readback {
+            slot: "../score".into(),
+            value: [1., 2., 3., 4.],
+        });
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("vibe-bpm"), "128\n");
+        assert_eq!(
+            read("vibe-click"),
+            "x=0.5\ny=0.25\ntime=3\nwidth=1920\nheight=1080\n"
+        );
+        assert_eq!(read("vibe-click-species"), "species=7\n");
+        assert_eq!(read("vibe-readback----score"), "value=1,2,3,4\n");
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn socket_sink() {
+        let path = std::env::temp_dir().join(format!("vibe-events-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixDatagram::bind(&path).unwrap();
+
+        UnixSocketSink::new(&path)
+            .unwrap()
+            .send(&Event::ClickHit { id: 1 });
+
+        let mut buf = [0; 64];
+        let len = listener.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], b"click-hit id=1\n");
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
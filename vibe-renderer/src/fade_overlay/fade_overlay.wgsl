@@ -0,0 +1,14 @@
+// - x: opacity
+// - y: brightness
+@group(0) @binding(0)
+var<uniform> params: vec2f;
+
+@fragment
+fn main() -> @location(0) vec4f {
+    let opacity = params.x;
+    let brightness = params.y;
+
+    // the blend state multiplies the colors of the rendered frame with `(1 - opacity) * brightness`
+    // and its alpha with `1 - opacity`
+    return vec4f(vec3f(1. - (1. - opacity) * brightness), opacity);
+}
@@ -0,0 +1,133 @@
+//! Fades the already rendered frame out, for example to transition between two sets of components,
+//! and dims it.
+use wgpu::{include_wgsl, util::DeviceExt};
+
+use crate::{Renderable, Renderer};
+
+/// Fades everything which got rendered before it towards transparency and dims it towards black.
+///
+/// Render it as the last [Renderable] of [Renderer::render].
+pub struct FadeOverlay {
+    params_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+
+    opacity: f32,
+    brightness: f32,
+}
+
+impl FadeOverlay {
+    pub fn new(renderer: &Renderer, texture_format: wgpu::TextureFormat) -> Self {
+        let device = renderer.device();
+
+        let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Fade overlay: Params buffer"),
+            contents: bytemuck::cast_slice(&[0f32, 1.]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        });
+
+        let pipeline = {
+            let vertex_module = device
+                .create_shader_module(include_wgsl!("../components/utils/full_screen_vertex.wgsl"));
+
+            let fragment_module = device.create_shader_module(include_wgsl!("./fade_overlay.wgsl"));
+
+            // keep `1 - opacity` of the frame (premultiplied alpha)
+            let fade = wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Zero,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
+            };
+            // the shader returns `1 - (1 - opacity) * brightness` as the color so the colors are
+            // dimmed additionally
+            let fade_and_dim = wgpu::BlendComponent {
+                dst_factor: wgpu::BlendFactor::OneMinusSrc,
+                ..fade
+            };
+
+            device.create_render_pipeline(&crate::util::simple_pipeline_descriptor(
+                crate::util::SimpleRenderPipelineDescriptor {
+                    label: "Fade overlay: Render pipeline",
+                    layout: None,
+                    vertex: wgpu::VertexState {
+                        module: &vertex_module,
+                        entry_point: None,
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        buffers: &[],
+                    },
+                    fragment: wgpu::FragmentState {
+                        module: &fragment_module,
+                        entry_point: Some("main"),
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format: texture_format,
+                            blend: Some(wgpu::BlendState {
+                                color: fade_and_dim,
+                                alpha: fade,
+                            }),
+                            write_mask: wgpu::ColorWrites::all(),
+                        })],
+                    },
+                },
+            ))
+        };
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Fade overlay: Bind group 0"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params_buffer.as_entire_binding(),
+            }],
+        });
+
+        Self {
+            params_buffer,
+            bind_group,
+            pipeline,
+
+            opacity: 0.,
+            brightness: 1.,
+        }
+    }
+
+    /// `0` keeps the frame as it is, `1` makes it fully transparent.
+    pub fn set_opacity(&mut self, queue: &wgpu::Queue, opacity: f32) {
+        let opacity = opacity.clamp(0., 1.);
+        if opacity != self.opacity {
+            self.opacity = opacity;
+            self.write(queue);
+        }
+    }
+
+    /// `1` keeps the frame as it is, `0` makes it black.
+    pub fn set_brightness(&mut self, queue: &wgpu::Queue, brightness: f32) {
+        let brightness = brightness.clamp(0., 1.);
+        if brightness != self.brightness {
+            self.brightness = brightness;
+            self.write(queue);
+        }
+    }
+
+    fn write(&self, queue: &wgpu::Queue) {
+        queue.write_buffer(
+            &self.params_buffer,
+            0,
+            bytemuck::cast_slice(&[self.opacity, self.brightness]),
+        );
+    }
+
+    /// Returns `true` if the overlay doesn't change the frame and can be skipped.
+    pub fn is_invisible(&self) -> bool {
+        self.opacity <= 0. && self.brightness >= 1.
+    }
+}
+
+impl Renderable for FadeOverlay {
+    fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
+        pass.set_bind_group(0, &self.bind_group, &[]);
+
+        pass.set_pipeline(&self.pipeline);
+        pass.draw(0..3, 0..1);
+    }
+}
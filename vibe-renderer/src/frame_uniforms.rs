@@ -0,0 +1,346 @@
+use chrono::Timelike;
+
+use crate::Renderer;
+
+/// The bind group index of the frame uniforms.
+pub const FRAME_UNIFORMS_GROUP: u32 = 0;
+
+/// The maximum amount of colors of `iPalette`.
+pub const MAX_COLORS: usize = 16;
+
+// Each value gets its own binding (instead of one struct) so that shaders can keep
+// accessing them directly by their names (`iTime`, `iResolution`, ...).
+//
+// (binding, size in bytes)
+const RESOLUTION: (u32, usize) = (0, std::mem::size_of::<[f32; 2]>());
+const TIME: (u32, usize) = (2, std::mem::size_of::<f32>());
+const MOUSE: (u32, usize) = (3, std::mem::size_of::<[f32; 2]>());
+const COLORS: (u32, usize) = (5, std::mem::size_of::<[[f32; 4]; 4]>());
+const MOUSE_CLICK: (u32, usize) = (8, std::mem::size_of::<[f32; 4]>());
+const LOCAL_TIME: (u32, usize) = (9, std::mem::size_of::<f32>());
+const KEYBOARD: (u32, usize) = (10, std::mem::size_of::<[f32; 4]>());
+const TRACK: (u32, usize) = (11, std::mem::size_of::<[f32; 4]>());
+const PALETTE: (u32, usize) = (12, std::mem::size_of::<Palette>());
+
+const BINDINGS: [(u32, usize); 9] = [
+    RESOLUTION,
+    TIME,
+    MOUSE,
+    COLORS,
+    MOUSE_CLICK,
+    LOCAL_TIME,
+    KEYBOARD,
+    TRACK,
+    PALETTE,
+];
+
+/// The layout of `iPalette`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct Palette {
+    colors: [[f32; 4]; MAX_COLORS],
+    len: u32,
+    _padding: [u32; 3],
+}
+
+/// Values which are the same for every component which is rendered onto the same surface
+/// like `iTime`, `iResolution`, `iMouse`, `iColors`, etc.
+///
+/// They are stored in one buffer and bound to bind group [FRAME_UNIFORMS_GROUP] by [Renderer::render]
+/// so components don't need to manage (and update) their own buffers for them.
+/// The setters only update the values on the CPU side, they are uploaded to the gpu
+/// with a single write once [Renderer::render] is called.
+///
+/// Components can access them by adding [Renderer::frame_uniforms_layout] to their pipeline layout.
+pub struct FrameUniforms {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    // the distance between two values in the buffer
+    stride: usize,
+
+    time: f32,
+    mouse: [f32; 2],
+    colors: [[f32; 4]; 4],
+    palette: Palette,
+    mouse_click: [f32; 4],
+    keyboard: [f32; 4],
+    track: [f32; 4],
+}
+
+impl FrameUniforms {
+    pub fn new(renderer: &Renderer) -> Self {
+        let (buffer, bind_group, stride) = create_buffer(renderer, "Frame uniforms");
+
+        Self {
+            buffer,
+            bind_group,
+            stride,
+
+            time: 0.,
+            mouse: [0.; 2],
+            colors: [[0., 0., 0., 1.]; 4],
+            palette: Palette {
+                colors: [[0., 0., 0., 1.]; MAX_COLORS],
+                len: 0,
+                _padding: [0; 3],
+            },
+            mouse_click: [-1., -1., 0., 0.],
+            keyboard: [0.; 4],
+            track: [-1., 0., 0., 0.],
+        }
+    }
+
+    /// Sets the time in seconds (`iTime`).
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time;
+    }
+
+    /// Sets the normalized mouse position (`iMouse`).
+    ///
+    /// See [crate::Component::update_mouse_position] for the coordinate system.
+    pub fn set_mouse_position(&mut self, pos: (f32, f32)) {
+        self.mouse = [pos.0, pos.1];
+    }
+
+    /// Sets the normalized position and the time of the last mouse click (`iMouseClick`).
+    ///
+    /// See [crate::Component::update_mouse_click] for more information.
+    pub fn set_mouse_click(&mut self, pos: (f32, f32), time: f32) {
+        self.mouse_click = [pos.0, pos.1, time, 0.];
+    }
+
+    /// Sets the last key event (`iKeyboard`).
+    ///
+    /// See [crate::Component::update_key] for more information.
+    pub fn set_key(&mut self, keysym: u32, pressed: bool, time: f32) {
+        self.keyboard = [keysym as f32, if pressed { 1. } else { 0. }, time, 0.];
+    }
+
+    /// Sets the time of the last track change and the id of the playing track (`iTrack`).
+    ///
+    /// `id` is within `[0, 1)` and stays the same as long as the track doesn't change.
+    /// `None` if no track is playing.
+    pub fn set_track(&mut self, change_time: f32, id: Option<f32>) {
+        self.track = [
+            change_time,
+            id.unwrap_or_default(),
+            if id.is_some() { 1. } else { 0. },
+            0.,
+        ];
+    }
+
+    /// Sets the color palette (`iPalette`) and its first four colors (`iColors`).
+    ///
+    /// Only the first [MAX_COLORS] colors are used. `iColors` repeats the last color
+    /// if there are less than four colors.
+    pub fn set_colors(&mut self, colors: &[[f32; 3]]) {
+        if let Some((colors, palette)) = uniform_colors(colors, self.palette) {
+            self.colors = colors;
+            self.palette = palette;
+        }
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Uploads the current values to the gpu.
+    ///
+    /// [Renderer::render] calls this already. You only need it if you are using your own render pass.
+    pub fn write(&self, queue: &wgpu::Queue, resolution: [u32; 2]) {
+        self.write_to(queue, &self.buffer, resolution, &self.colors, &self.palette);
+    }
+
+    /// Uploads the current values with the given colors into `buffer`.
+    fn write_to(
+        &self,
+        queue: &wgpu::Queue,
+        buffer: &wgpu::Buffer,
+        resolution: [u32; 2],
+        colors: &[[f32; 4]; 4],
+        palette: &Palette,
+    ) {
+        let local_time = {
+            let now = chrono::Local::now();
+            now.hour() as f32 + now.minute() as f32 / 60.0 + now.second() as f32 / 3600.0
+        };
+
+        let resolution = [resolution[0] as f32, resolution[1] as f32];
+
+        let mut bytes = vec![0u8; self.stride * BINDINGS.len()];
+        let values: [&[u8]; 9] = [
+            bytemuck::cast_slice(&resolution),
+            bytemuck::bytes_of(&self.time),
+            bytemuck::cast_slice(&self.mouse),
+            bytemuck::cast_slice(colors),
+            bytemuck::cast_slice(&self.mouse_click),
+            bytemuck::bytes_of(&local_time),
+            bytemuck::cast_slice(&self.keyboard),
+            bytemuck::cast_slice(&self.track),
+            bytemuck::bytes_of(palette),
+        ];
+
+        for (idx, value) in values.iter().enumerate() {
+            let offset = idx * self.stride;
+            bytes[offset..offset + value.len()].copy_from_slice(value);
+        }
+
+        queue.write_buffer(buffer, 0, &bytes);
+    }
+}
+
+/// Replaces `iColors` and `iPalette` of the [FrameUniforms] for a single renderable
+/// (see [Renderable::palette_override](crate::Renderable::palette_override)).
+///
+/// It has its own copy of the frame uniforms which [Renderer::render] fills with
+/// the values of the frame and the colors of the override.
+pub struct PaletteOverride {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+
+    colors: [[f32; 4]; 4],
+    palette: Palette,
+}
+
+impl PaletteOverride {
+    /// Uses the given colors (from the darkest to the brightest one, at most [MAX_COLORS])
+    /// instead of the ones of the frame.
+    ///
+    /// Returns `None` if `colors` is empty.
+    pub fn new(renderer: &Renderer, colors: &[[f32; 3]]) -> Option<Self> {
+        let empty_palette = Palette {
+            colors: [[0., 0., 0., 1.]; MAX_COLORS],
+            len: 0,
+            _padding: [0; 3],
+        };
+        let (colors, palette) = uniform_colors(colors, empty_palette)?;
+        let (buffer, bind_group, _stride) = create_buffer(renderer, "Palette override");
+
+        Some(Self {
+            buffer,
+            bind_group,
+            colors,
+            palette,
+        })
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Uploads the values of `frame_uniforms` together with the colors of the override.
+    pub fn write(&self, queue: &wgpu::Queue, frame_uniforms: &FrameUniforms, resolution: [u32; 2]) {
+        frame_uniforms.write_to(queue, &self.buffer, resolution, &self.colors, &self.palette);
+    }
+}
+
+/// Replaces `iResolution` of the [FrameUniforms] for a renderable which renders into a texture
+/// of another size than the output (see [WithRenderScale](crate::components::WithRenderScale)).
+pub(crate) struct ResolutionOverride {
+    buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl ResolutionOverride {
+    pub fn new(renderer: &Renderer) -> Self {
+        let (buffer, bind_group, _stride) = create_buffer(renderer, "Resolution override");
+
+        Self { buffer, bind_group }
+    }
+
+    pub fn bind_group(&self) -> &wgpu::BindGroup {
+        &self.bind_group
+    }
+
+    /// Uploads the values of `frame_uniforms` with the given resolution.
+    pub fn write(&self, queue: &wgpu::Queue, frame_uniforms: &FrameUniforms, resolution: [u32; 2]) {
+        frame_uniforms.write_to(
+            queue,
+            &self.buffer,
+            resolution,
+            &frame_uniforms.colors,
+            &frame_uniforms.palette,
+        );
+    }
+}
+
+/// Creates the buffer of the frame uniforms together with its bind group and the distance between two values.
+fn create_buffer(renderer: &Renderer, label: &str) -> (wgpu::Buffer, wgpu::BindGroup, usize) {
+    let device = renderer.device();
+
+    let stride = {
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as usize;
+        let max_size = BINDINGS.iter().map(|(_, size)| *size).max().unwrap();
+
+        max_size.next_multiple_of(alignment)
+    };
+
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(&format!("{} buffer", label)),
+        size: (stride * BINDINGS.len()) as wgpu::BufferAddress,
+        usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let entries: Vec<wgpu::BindGroupEntry> = BINDINGS
+        .iter()
+        .enumerate()
+        .map(|(idx, &(binding, size))| wgpu::BindGroupEntry {
+            binding,
+            resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                buffer: &buffer,
+                offset: (idx * stride) as wgpu::BufferAddress,
+                size: wgpu::BufferSize::new(size as u64),
+            }),
+        })
+        .collect();
+
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some(&format!("{} bind group", label)),
+        layout: renderer.frame_uniforms_layout(),
+        entries: &entries,
+    });
+
+    (buffer, bind_group, stride)
+}
+
+/// Converts the colors into `iColors` and `iPalette`. The colors of `palette` after the new ones are kept.
+///
+/// Only the first [MAX_COLORS] colors are used. `iColors` repeats the last color
+/// if there are less than four colors. Returns `None` if `colors` is empty.
+fn uniform_colors(colors: &[[f32; 3]], mut palette: Palette) -> Option<([[f32; 4]; 4], Palette)> {
+    let colors = &colors[..colors.len().min(MAX_COLORS)];
+    let last = colors.last()?;
+
+    let rgba = |[r, g, b]: [f32; 3]| [r, g, b, 1.];
+    let first_four = std::array::from_fn(|idx| rgba(*colors.get(idx).unwrap_or(last)));
+
+    palette.len = colors.len() as u32;
+    for (slot, &color) in palette.colors.iter_mut().zip(colors) {
+        *slot = rgba(color);
+    }
+
+    Some((first_four, palette))
+}
+
+/// Creates the bind group layout of [FrameUniforms].
+pub(crate) fn create_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+    let entries: Vec<wgpu::BindGroupLayoutEntry> = BINDINGS
+        .iter()
+        .map(|&(binding, _)| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        })
+        .collect();
+
+    device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+        label: Some("Frame uniforms bind group layout"),
+        entries: &entries,
+    })
+}
@@ -1,19 +1,32 @@
 pub mod cache;
 pub mod components;
+pub mod events;
+pub mod readback;
 pub mod texture_generation;
 pub mod util;
 
-pub use components::{Component, ComponentAudio};
+mod fade_overlay;
+mod frame_uniforms;
+mod persistent_texture;
 
-use crate::texture_generation::TextureGenerator;
+pub use components::Component;
+pub use fade_overlay::FadeOverlay;
+pub use frame_uniforms::{FrameUniforms, PaletteOverride, FRAME_UNIFORMS_GROUP, MAX_COLORS};
+pub use persistent_texture::PersistentTexture;
+
+use crate::{events::EventSink, texture_generation::TextureGenerator};
 use pollster::FutureExt;
 use serde::{Deserialize, Serialize};
 use std::{
     ops::Deref,
     path::{Path, PathBuf},
-    sync::OnceLock,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, OnceLock,
+    },
 };
 use tracing::{error, info};
+use vibe_audio::BarProcessorPool;
 use xdg::BaseDirectories;
 
 static XDG: OnceLock<BaseDirectories> = OnceLock::new();
@@ -41,13 +54,41 @@ pub trait Renderable {
     /// impl Renderable for Triangle {
     ///     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
     ///          // // if you have any bind groups for example
-    ///          // pass.set_bind_group(0, &self.bind_group, &[]);
+    ///          // // (bind group 0 contains the frame uniforms and is set by the renderer)
+    ///          // pass.set_bind_group(1, &self.bind_group, &[]);
     ///          pass.set_pipeline(&self.pipeline);
     ///          pass.draw(0..4, 0..1);
     ///     }
     /// }
     /// ```
     fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass);
+
+    /// Returns the colors which the renderable uses instead of the ones of the [FrameUniforms].
+    fn palette_override(&self) -> Option<&PaletteOverride> {
+        None
+    }
+
+    /// Called by [Renderer::render_with] before the render pass of the output starts,
+    /// for example to render into an intermediate texture first
+    /// (see [WithRenderScale](crate::components::WithRenderScale)).
+    fn pre_render(
+        &self,
+        _renderer: &Renderer,
+        _encoder: &mut wgpu::CommandEncoder,
+        _frame_uniforms: &FrameUniforms,
+    ) {
+    }
+}
+
+/// What [Renderer::render_with] does with the previous content of the texture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadMode {
+    /// Clear the texture to transparent before rendering.
+    #[default]
+    Clear,
+    /// Render on top of the previous content, for example for effects which accumulate
+    /// over frames (see [PersistentTexture]).
+    Preserve,
 }
 
 /// The descriptor to configure and create a new renderer.
@@ -98,6 +139,13 @@ pub struct Renderer {
     adapter: wgpu::Adapter,
     device: wgpu::Device,
     queue: wgpu::Queue,
+
+    bar_processor_pool: BarProcessorPool,
+    frame_uniforms_layout: wgpu::BindGroupLayout,
+    // set by the device lost callback
+    device_lost: Arc<AtomicBool>,
+    // `None` if nobody is interested in the events of the components
+    event_sink: Option<Arc<dyn EventSink>>,
 }
 
 impl Renderer {
@@ -166,24 +214,77 @@ impl Renderer {
             .block_on()
             .unwrap();
 
+        let frame_uniforms_layout = frame_uniforms::create_layout(&device);
+
+        let device_lost = Arc::new(AtomicBool::new(false));
+        {
+            let device_lost = device_lost.clone();
+            device.set_device_lost_callback(move |reason, msg| {
+                // `Destroyed` is the reason if we drop the device ourself
+                if reason != wgpu::DeviceLostReason::Destroyed {
+                    error!("Lost the GPU device ({:?}): {}", reason, msg);
+                    device_lost.store(true, Ordering::Relaxed);
+                }
+            });
+        }
+
         Self {
             instance,
             adapter,
             device,
             queue,
+
+            bar_processor_pool: BarProcessorPool::new(),
+            frame_uniforms_layout,
+            device_lost,
+            event_sink: None,
         }
     }
 
     /// Start rendering multiple (or one) [`Renderable`]s onto `output_texture`.
+    ///
+    /// `frame_uniforms` (or the [palette override](Renderable::palette_override) of a renderable)
+    /// are uploaded and bound to [FRAME_UNIFORMS_GROUP] before each renderable is rendered.
+    /// The texture is cleared first, see [Renderer::render_with] to keep its content.
     pub fn render<'a, 'r, R: Deref<Target: Renderable> + 'r>(
         &self,
         output_texture: &'a wgpu::TextureView,
+        frame_uniforms: &FrameUniforms,
         renderables: impl IntoIterator<Item = &'r R>,
     ) {
+        self.render_with(output_texture, frame_uniforms, renderables, LoadMode::Clear);
+    }
+
+    /// Like [Renderer::render] but `load_mode` decides if the previous content of `output_texture` is kept.
+    pub fn render_with<'a, 'r, R: Deref<Target: Renderable> + 'r>(
+        &self,
+        output_texture: &'a wgpu::TextureView,
+        frame_uniforms: &FrameUniforms,
+        renderables: impl IntoIterator<Item = &'r R>,
+        load_mode: LoadMode,
+    ) {
+        let resolution = {
+            let size = output_texture.texture().size();
+            [size.width, size.height]
+        };
+        frame_uniforms.write(&self.queue, resolution);
+
+        let renderables: Vec<&'r R> = renderables.into_iter().collect();
+        for palette_override in renderables
+            .iter()
+            .filter_map(|renderable| renderable.palette_override())
+        {
+            palette_override.write(&self.queue, frame_uniforms, resolution);
+        }
+
         let mut encoder = self
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
 
+        for renderable in renderables.iter() {
+            renderable.pre_render(self, &mut encoder, frame_uniforms);
+        }
+
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -191,7 +292,10 @@ impl Renderer {
                     resolve_target: None,
                     depth_slice: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        load: match load_mode {
+                            LoadMode::Clear => wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                            LoadMode::Preserve => wgpu::LoadOp::Load,
+                        },
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -199,6 +303,13 @@ impl Renderer {
             });
 
             for renderable in renderables {
+                // the previous renderable might have replaced the bind group
+                let bind_group = match renderable.palette_override() {
+                    Some(palette_override) => palette_override.bind_group(),
+                    None => frame_uniforms.bind_group(),
+                };
+
+                render_pass.set_bind_group(FRAME_UNIFORMS_GROUP, bind_group, &[]);
                 renderable.render_with_renderpass(&mut render_pass);
             }
         }
@@ -239,6 +350,35 @@ impl Renderer {
     pub fn queue(&self) -> &wgpu::Queue {
         &self.queue
     }
+
+    /// Returns the bind group layout of [FrameUniforms].
+    pub fn frame_uniforms_layout(&self) -> &wgpu::BindGroupLayout {
+        &self.frame_uniforms_layout
+    }
+
+    /// Returns the [BarProcessorPool] which the components use to share their bar processors.
+    pub fn bar_processor_pool(&self) -> &BarProcessorPool {
+        &self.bar_processor_pool
+    }
+
+    /// Sets where the components send their [events](crate::events::Event) to.
+    ///
+    /// Only components which are created afterwards use the new sink.
+    pub fn set_event_sink(&mut self, sink: Option<Arc<dyn EventSink>>) {
+        self.event_sink = sink;
+    }
+
+    /// Returns the sink for the [events](crate::events::Event) of the components.
+    pub fn event_sink(&self) -> Option<Arc<dyn EventSink>> {
+        self.event_sink.clone()
+    }
+
+    /// Returns `true` if the GPU device got lost (for example after a GPU reset).
+    ///
+    /// Nothing can be rendered anymore in this case and a new renderer has to be created.
+    pub fn is_device_lost(&self) -> bool {
+        self.device_lost.load(Ordering::Relaxed)
+    }
 }
 
 impl Default for Renderer {
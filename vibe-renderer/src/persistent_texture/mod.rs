@@ -0,0 +1,161 @@
+//! A texture which keeps its content between frames, for example for trails or other
+//! effects which accumulate over time.
+use std::ops::Deref;
+
+use wgpu::include_wgsl;
+
+use crate::{FrameUniforms, LoadMode, Renderable, Renderer};
+
+/// An intermediate texture whose content is only cleared if you ask for it.
+///
+/// Render into it with [PersistentTexture::render] (each frame is drawn on top of the previous ones,
+/// add a [FadeOverlay](crate::FadeOverlay) first to let the old frames fade out) and render the
+/// texture itself as a [Renderable] onto the output.
+///
+/// The texture should have the same size as the output since it's drawn pixel by pixel.
+pub struct PersistentTexture {
+    format: wgpu::TextureFormat,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+
+    bind_group: wgpu::BindGroup,
+    pipeline: wgpu::RenderPipeline,
+}
+
+impl PersistentTexture {
+    /// Creates a new, transparent texture with the given format and size.
+    pub fn new(renderer: &Renderer, format: wgpu::TextureFormat, size: [u32; 2]) -> Self {
+        let device = renderer.device();
+
+        let pipeline = {
+            let vertex_module = device
+                .create_shader_module(include_wgsl!("../components/utils/full_screen_vertex.wgsl"));
+
+            let fragment_module =
+                device.create_shader_module(include_wgsl!("./persistent_texture.wgsl"));
+
+            device.create_render_pipeline(&crate::util::simple_pipeline_descriptor(
+                crate::util::SimpleRenderPipelineDescriptor {
+                    label: "Persistent texture: Render pipeline",
+                    layout: None,
+                    vertex: wgpu::VertexState {
+                        module: &vertex_module,
+                        entry_point: None,
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        buffers: &[],
+                    },
+                    fragment: wgpu::FragmentState {
+                        module: &fragment_module,
+                        entry_point: Some("main"),
+                        compilation_options: wgpu::PipelineCompilationOptions::default(),
+                        targets: &[Some(wgpu::ColorTargetState {
+                            format,
+                            // the content got alpha blended onto a transparent texture,
+                            // so its colors are already multiplied with its alpha
+                            blend: Some(wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING),
+                            write_mask: wgpu::ColorWrites::all(),
+                        })],
+                    },
+                },
+            ))
+        };
+
+        let (texture, view, bind_group) = Self::create_texture(device, &pipeline, format, size);
+
+        Self {
+            format,
+            texture,
+            view,
+
+            bind_group,
+            pipeline,
+        }
+    }
+
+    /// Recreates the texture if the size changed. Its content is lost in this case.
+    pub fn resize(&mut self, renderer: &Renderer, size: [u32; 2]) {
+        if [self.texture.width(), self.texture.height()] == size {
+            return;
+        }
+
+        let (texture, view, bind_group) =
+            Self::create_texture(renderer.device(), &self.pipeline, self.format, size);
+
+        self.texture = texture;
+        self.view = view;
+        self.bind_group = bind_group;
+    }
+
+    /// Renders the given renderables on top of the content of the previous frames.
+    pub fn render<'r, R: Deref<Target: Renderable> + 'r>(
+        &self,
+        renderer: &Renderer,
+        frame_uniforms: &FrameUniforms,
+        renderables: impl IntoIterator<Item = &'r R>,
+    ) {
+        renderer.render_with(&self.view, frame_uniforms, renderables, LoadMode::Preserve);
+    }
+
+    /// Makes the texture transparent again.
+    pub fn clear(&self, renderer: &Renderer, frame_uniforms: &FrameUniforms) {
+        renderer.render_with::<Box<dyn Renderable>>(
+            &self.view,
+            frame_uniforms,
+            [],
+            LoadMode::Clear,
+        );
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        pipeline: &wgpu::RenderPipeline,
+        format: wgpu::TextureFormat,
+        size: [u32; 2],
+    ) -> (wgpu::Texture, wgpu::TextureView, wgpu::BindGroup) {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Persistent texture"),
+            size: wgpu::Extent3d {
+                width: size[0].max(1),
+                height: size[1].max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Persistent texture: Bind group 0"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+
+        (texture, view, bind_group)
+    }
+}
+
+impl Renderable for PersistentTexture {
+    fn render_with_renderpass(&self, pass: &mut wgpu::RenderPass) {
+        pass.set_bind_group(0, &self.bind_group, &[]);
+
+        pass.set_pipeline(&self.pipeline);
+        pass.draw(0..3, 0..1);
+    }
+}
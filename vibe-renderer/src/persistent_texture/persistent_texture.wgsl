@@ -0,0 +1,8 @@
+@group(0) @binding(0)
+var content: texture_2d<f32>;
+
+@fragment
+fn main(@builtin(position) pos: vec4f) -> @location(0) vec4f {
+    let max_coord = vec2i(textureDimensions(content)) - 1;
+    return textureLoad(content, min(vec2i(pos.xy), max_coord), 0);
+}
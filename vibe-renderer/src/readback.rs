@@ -0,0 +1,327 @@
+//! Reads values back from the GPU which shaders write into named slots.
+//!
+//! A slot is either an entry of a small storage buffer (`iOutputs` for the `FragmentCanvas`)
+//! or a reserved texel of the rendered frame. [ReadbackTarget] copies all slots after a frame
+//! without waiting for the GPU, so the values arrive one or more frames later.
+//!
+//! The components forward the values as [Event::Readback](crate::events::Event::Readback)
+//! to the event sink of the [Renderer](crate::Renderer).
+use std::sync::{
+    atomic::{AtomicBool, AtomicU8, Ordering},
+    Arc,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+/// The amount of `vec4<f32>` entries of the storage buffer of [SlotLocation::Index].
+pub const MAX_BUFFER_SLOTS: u32 = 16;
+
+const SLOT_SIZE: u64 = std::mem::size_of::<[f32; 4]>() as u64;
+const OUTPUTS_SIZE: u64 = MAX_BUFFER_SLOTS as u64 * SLOT_SIZE;
+// each texel gets its own row in the staging buffer (see `wgpu::COPY_BYTES_PER_ROW_ALIGNMENT`)
+const TEXEL_STRIDE: u64 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT as u64;
+
+// states of the staging buffer
+const IDLE: u8 = 0;
+const PENDING: u8 = 1;
+const MAPPED: u8 = 2;
+const FAILED: u8 = 3;
+
+/// Where a shader writes the value of a slot to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SlotLocation {
+    /// The entry of the storage buffer with this index (has to be less than [MAX_BUFFER_SLOTS]).
+    Index(u32),
+    /// The texel `[x, y]` of the rendered frame (clamped to the size of the frame).
+    /// Only frames with 8 bit RGBA or BGRA formats are supported.
+    Texel([u32; 2]),
+}
+
+/// A named output of a shader.
+///
+/// In TOML: `{ name = "score", index = 0 }` or `{ name = "hit", texel = [0, 0] }`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReadbackSlot {
+    pub name: String,
+    #[serde(flatten)]
+    pub location: SlotLocation,
+}
+
+impl ReadbackSlot {
+    pub fn new(name: impl Into<String>, location: SlotLocation) -> Self {
+        Self {
+            name: name.into(),
+            location,
+        }
+    }
+}
+
+/// Copies the slots of a shader back from the GPU.
+pub struct ReadbackTarget {
+    slots: Vec<ReadbackSlot>,
+    format: wgpu::TextureFormat,
+
+    // only exists if a slot uses `SlotLocation::Index`
+    outputs: Option<wgpu::Buffer>,
+    staging: wgpu::Buffer,
+    state: Arc<AtomicU8>,
+    // the copy which is on its way is outdated
+    discard: AtomicBool,
+}
+
+impl ReadbackTarget {
+    /// Creates the buffers for the given slots of frames with the given format.
+    ///
+    /// Slots with an index of at least [MAX_BUFFER_SLOTS] are ignored.
+    pub fn new(device: &wgpu::Device, slots: &[ReadbackSlot], format: wgpu::TextureFormat) -> Self {
+        let slots: Vec<ReadbackSlot> = slots
+            .iter()
+            .filter(|slot| match slot.location {
+                SlotLocation::Index(idx) if idx >= MAX_BUFFER_SLOTS => {
+                    warn!(
+                        "Ignoring the readback slot '{}': Its index has to be less than {}.",
+                        slot.name, MAX_BUFFER_SLOTS
+                    );
+                    false
+                }
+                _ => true,
+            })
+            .cloned()
+            .collect();
+
+        let amount_texels = slots
+            .iter()
+            .filter(|slot| matches!(slot.location, SlotLocation::Texel(_)))
+            .count() as u64;
+
+        if amount_texels > 0 && decode_texel(format, [0; 4]).is_none() {
+            warn!(
+                "Texel readback slots aren't supported for frames with the format {:?}.",
+                format
+            );
+        }
+
+        let outputs = slots
+            .iter()
+            .any(|slot| matches!(slot.location, SlotLocation::Index(_)))
+            .then(|| {
+                device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Readback: outputs buffer"),
+                    size: OUTPUTS_SIZE,
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                    mapped_at_creation: false,
+                })
+            });
+
+        let staging = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Readback: staging buffer"),
+            size: OUTPUTS_SIZE + amount_texels.max(1) * TEXEL_STRIDE,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Self {
+            slots,
+            format,
+            outputs,
+            staging,
+            state: Arc::new(AtomicU8::new(IDLE)),
+            discard: AtomicBool::new(false),
+        }
+    }
+
+    pub fn slots(&self) -> &[ReadbackSlot] {
+        &self.slots
+    }
+
+    /// Returns the storage buffer (`array<vec4<f32>, MAX_BUFFER_SLOTS>`) which the shader writes
+    /// the [SlotLocation::Index] slots to. `None` if no slot uses it.
+    ///
+    /// The entries keep their value until the shader overwrites them.
+    pub fn outputs_buffer(&self) -> Option<&wgpu::Buffer> {
+        self.outputs.as_ref()
+    }
+
+    /// Drops the values which are still on their way, for example because the shader
+    /// got new inputs in the meantime.
+    pub fn discard(&self) {
+        self.discard.store(true, Ordering::Relaxed);
+    }
+
+    /// Starts to copy the slots of the frame in `texture` back from the GPU.
+    ///
+    /// Returns the values of an earlier frame if they arrived in the meantime. Slots which
+    /// can't be decoded are left out.
+    pub fn read(
+        &self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        texture: &wgpu::Texture,
+    ) -> Option<Vec<(&str, [f32; 4])>> {
+        // invokes the callback of `map_async` if the copy is done
+        let _ = device.poll(wgpu::PollType::Poll);
+
+        let values = match self.state.load(Ordering::Acquire) {
+            PENDING => return None,
+            MAPPED if self.discard.swap(false, Ordering::Relaxed) => {
+                self.staging.unmap();
+                None
+            }
+            MAPPED => {
+                let values = {
+                    let data = self.staging.slice(..).get_mapped_range();
+                    self.decode(&data)
+                };
+                self.staging.unmap();
+                Some(values)
+            }
+            _ => {
+                self.discard.store(false, Ordering::Relaxed);
+                None
+            }
+        };
+
+        self.copy(device, queue, texture);
+        values
+    }
+
+    fn copy(&self, device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Readback: copy encoder"),
+        });
+
+        if let Some(outputs) = &self.outputs {
+            encoder.copy_buffer_to_buffer(outputs, 0, &self.staging, 0, OUTPUTS_SIZE);
+        }
+
+        for (idx, [x, y]) in self.texels().enumerate() {
+            encoder.copy_texture_to_buffer(
+                wgpu::TexelCopyTextureInfo {
+                    texture,
+                    mip_level: 0,
+                    origin: wgpu::Origin3d {
+                        x: x.min(texture.width() - 1),
+                        y: y.min(texture.height() - 1),
+                        z: 0,
+                    },
+                    aspect: wgpu::TextureAspect::All,
+                },
+                wgpu::TexelCopyBufferInfo {
+                    buffer: &self.staging,
+                    layout: wgpu::TexelCopyBufferLayout {
+                        offset: OUTPUTS_SIZE + idx as u64 * TEXEL_STRIDE,
+                        bytes_per_row: None,
+                        rows_per_image: None,
+                    },
+                },
+                wgpu::Extent3d {
+                    width: 1,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        self.state.store(PENDING, Ordering::Release);
+        let state = self.state.clone();
+        self.staging
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                state.store(
+                    if result.is_ok() { MAPPED } else { FAILED },
+                    Ordering::Release,
+                );
+            });
+    }
+
+    fn texels(&self) -> impl Iterator<Item = [u32; 2]> + '_ {
+        self.slots.iter().filter_map(|slot| match slot.location {
+            SlotLocation::Texel(texel) => Some(texel),
+            SlotLocation::Index(_) => None,
+        })
+    }
+
+    /// Decodes the values of the slots from the content of the staging buffer.
+    fn decode(&self, data: &[u8]) -> Vec<(&str, [f32; 4])> {
+        let mut texel_idx = 0;
+
+        self.slots
+            .iter()
+            .filter_map(|slot| {
+                let value = match slot.location {
+                    SlotLocation::Index(idx) => {
+                        let offset = (idx as u64 * SLOT_SIZE) as usize;
+                        let bytes = &data[offset..offset + SLOT_SIZE as usize];
+                        Some(bytemuck::pod_read_unaligned::<[f32; 4]>(bytes))
+                    }
+                    SlotLocation::Texel(_) => {
+                        let offset = (OUTPUTS_SIZE + texel_idx * TEXEL_STRIDE) as usize;
+                        texel_idx += 1;
+                        let bytes: [u8; 4] = data[offset..offset + 4].try_into().unwrap();
+                        decode_texel(self.format, bytes)
+                    }
+                };
+
+                value.map(|value| (slot.name.as_str(), value))
+            })
+            .collect()
+    }
+}
+
+/// Converts the bytes of a texel into its normalized RGBA channels.
+///
+/// Returns `None` if the format isn't an 8 bit RGBA or BGRA format.
+pub fn decode_texel(format: wgpu::TextureFormat, bytes: [u8; 4]) -> Option<[f32; 4]> {
+    let [r, g, b, a] = match format {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => bytes,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => {
+            [bytes[2], bytes[1], bytes[0], bytes[3]]
+        }
+        _ => return None,
+    };
+
+    Some([r, g, b, a].map(|channel| channel as f32 / u8::MAX as f32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texels() {
+        assert_eq!(
+            decode_texel(wgpu::TextureFormat::Rgba8Unorm, [255, 0, 51, 255]),
+            Some([1., 0., 0.2, 1.])
+        );
+        assert_eq!(
+            decode_texel(wgpu::TextureFormat::Bgra8UnormSrgb, [255, 0, 51, 255]),
+            Some([0.2, 0., 1., 1.])
+        );
+        assert_eq!(decode_texel(wgpu::TextureFormat::Rgba16Float, [0; 4]), None);
+    }
+
+    #[test]
+    fn slots_from_toml() {
+        #[derive(Deserialize)]
+        struct Slots {
+            slots: Vec<ReadbackSlot>,
+        }
+
+        let slots: Slots = toml::from_str(
+            r#"slots = [{ name = "score", index = 3 }, { name = "hit", texel = [0, 1] }]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            slots.slots,
+            [
+                ReadbackSlot::new("score", SlotLocation::Index(3)),
+                ReadbackSlot::new("hit", SlotLocation::Texel([0, 1])),
+            ]
+        );
+    }
+}
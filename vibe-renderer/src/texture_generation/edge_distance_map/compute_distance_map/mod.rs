@@ -1,50 +1,72 @@
 use tracing::Span;
 use tracing_indicatif::span_ext::IndicatifSpanExt;
-use wgpu::include_wgsl;
+use wgpu::{include_wgsl, util::DeviceExt};
 
 use crate::texture_generation::edge_distance_map::EdgeDistanceMapStep;
 
+/// The format of the textures which store the coordinate of the closest edge-texel.
+const SEEDS_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg32Float;
+
 pub struct ComputeDistanceMapDescriptor<'a> {
     pub device: &'a wgpu::Device,
     pub src: wgpu::TextureView,
     pub dst: wgpu::TextureView,
-    pub iterations: u32,
 }
 
 pub struct ComputeDistanceMap {
     init_pipeline: wgpu::ComputePipeline,
     init_bind_group: wgpu::BindGroup,
 
-    update_pipeline: wgpu::ComputePipeline,
-    src_to_dst_bind_group: wgpu::BindGroup,
-    dst_to_src_bind_group: wgpu::BindGroup,
+    jump_pipeline: wgpu::ComputePipeline,
+    // one bind group per jump flooding pass
+    jump_bind_groups: Vec<wgpu::BindGroup>,
+
+    distance_pipeline: wgpu::ComputePipeline,
+    distance_bind_group: wgpu::BindGroup,
 
-    iterations: u32,
+    _seeds: [wgpu::Texture; 2],
+    _jump_buffers: Vec<wgpu::Buffer>,
 }
 
 impl ComputeDistanceMap {
     pub fn step(desc: ComputeDistanceMapDescriptor) -> Box<dyn EdgeDistanceMapStep> {
-        let ComputeDistanceMapDescriptor {
-            device,
-            src,
-            dst,
-            iterations,
-        } = desc;
+        let ComputeDistanceMapDescriptor { device, src, dst } = desc;
 
+        let size = dst.texture().size();
         let shader = device.create_shader_module(include_wgsl!("./shader.wgsl"));
 
-        // init fields
+        let seeds = {
+            let desc = wgpu::TextureDescriptor {
+                label: Some("Distance map: Seeds texture"),
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: SEEDS_FORMAT,
+                usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[],
+            };
+
+            [device.create_texture(&desc), device.create_texture(&desc)]
+        };
+
+        let seed_views = [
+            seeds[0].create_view(&wgpu::TextureViewDescriptor::default()),
+            seeds[1].create_view(&wgpu::TextureViewDescriptor::default()),
+        ];
+
+        // init seeds
         let init_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
             label: Some("Distance map: Init pipeline"),
             layout: None,
             module: &shader,
-            entry_point: Some("init_map"),
+            entry_point: Some("init_seeds"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
             cache: None,
         });
 
         let init_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Distance map: Bind group"),
+            label: Some("Distance map: Init bind group"),
             layout: &init_pipeline.get_bind_group_layout(0),
             entries: &[
                 wgpu::BindGroupEntry {
@@ -53,48 +75,83 @@ impl ComputeDistanceMap {
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::TextureView(&dst),
+                    resource: wgpu::BindingResource::TextureView(&seed_views[0]),
                 },
             ],
         });
 
-        // start updating the fields
-
-        let update_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-            label: Some("Distance map: Update dist"),
+        // jump flooding passes
+        let jump_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Distance map: Jump flood pipeline"),
             layout: None,
             module: &shader,
-            entry_point: Some("update_dist"),
+            entry_point: Some("jump_flood"),
             compilation_options: wgpu::PipelineCompilationOptions::default(),
             cache: None,
         });
 
-        let src_to_dst_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Distance map: Bind group (src => dst)"),
-            layout: &update_pipeline.get_bind_group_layout(0),
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&src),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(&dst),
-                },
-            ],
+        let step_sizes = jump_step_sizes(size.width.max(size.height));
+
+        let jump_buffers: Vec<wgpu::Buffer> = step_sizes
+            .iter()
+            .map(|&step_size| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Distance map: Jump data buffer"),
+                    contents: bytemuck::bytes_of(&(step_size as i32)),
+                    usage: wgpu::BufferUsages::UNIFORM,
+                })
+            })
+            .collect();
+
+        // pass `i` reads from `seeds[i % 2]` and writes into `seeds[(i + 1) % 2]`
+        let jump_bind_groups = jump_buffers
+            .iter()
+            .enumerate()
+            .map(|(idx, buffer)| {
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Distance map: Jump flood bind group"),
+                    layout: &jump_pipeline.get_bind_group_layout(0),
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: wgpu::BindingResource::TextureView(
+                                &seed_views[(idx + 1) % 2],
+                            ),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: wgpu::BindingResource::TextureView(&seed_views[idx % 2]),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 3,
+                            resource: buffer.as_entire_binding(),
+                        },
+                    ],
+                })
+            })
+            .collect();
+
+        // convert the seeds to the distances
+        let distance_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: Some("Distance map: Write distances pipeline"),
+            layout: None,
+            module: &shader,
+            entry_point: Some("write_distances"),
+            compilation_options: wgpu::PipelineCompilationOptions::default(),
+            cache: None,
         });
 
-        let dst_to_src_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            label: Some("Distance map: Bind group (dst => src)"),
-            layout: &update_pipeline.get_bind_group_layout(0),
+        let distance_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Distance map: Write distances bind group"),
+            layout: &distance_pipeline.get_bind_group_layout(0),
             entries: &[
                 wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&dst),
+                    binding: 2,
+                    resource: wgpu::BindingResource::TextureView(&seed_views[step_sizes.len() % 2]),
                 },
                 wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wgpu::BindingResource::TextureView(&src),
+                    binding: 4,
+                    resource: wgpu::BindingResource::TextureView(&dst),
                 },
             ],
         });
@@ -103,12 +160,14 @@ impl ComputeDistanceMap {
             init_pipeline,
             init_bind_group,
 
-            update_pipeline,
+            jump_pipeline,
+            jump_bind_groups,
 
-            src_to_dst_bind_group,
-            dst_to_src_bind_group,
+            distance_pipeline,
+            distance_bind_group,
 
-            iterations,
+            _seeds: seeds,
+            _jump_buffers: jump_buffers,
         })
     }
 }
@@ -116,52 +175,74 @@ impl ComputeDistanceMap {
 impl EdgeDistanceMapStep for ComputeDistanceMap {
     fn compute(&self, device: &wgpu::Device, queue: &wgpu::Queue, x: u32, y: u32) {
         let span = Span::current();
-        // init fields first
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+
         {
-            let mut encoder =
-                device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
 
-            {
-                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&self.init_pipeline);
+            pass.set_bind_group(0, &self.init_bind_group, &[]);
+            pass.dispatch_workgroups(x, y, 1);
+            span.pb_inc(1);
 
-                pass.set_pipeline(&self.init_pipeline);
-                pass.set_bind_group(0, &self.init_bind_group, &[]);
+            pass.set_pipeline(&self.jump_pipeline);
+            for bind_group in self.jump_bind_groups.iter() {
+                pass.set_bind_group(0, bind_group, &[]);
                 pass.dispatch_workgroups(x, y, 1);
                 span.pb_inc(1);
             }
 
-            queue.submit(std::iter::once(encoder.finish()));
+            pass.set_pipeline(&self.distance_pipeline);
+            pass.set_bind_group(0, &self.distance_bind_group, &[]);
+            pass.dispatch_workgroups(x, y, 1);
+            span.pb_inc(1);
         }
 
-        // now update the distances for each step
-        {
-            for _ in 0..self.iterations.div_ceil(2) {
-                let mut encoder =
-                    device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        queue.submit(std::iter::once(encoder.finish()));
+    }
+
+    fn amount_steps(&self) -> u32 {
+        1 + self.jump_bind_groups.len() as u32 + 1
+    }
+}
 
-                {
-                    let mut pass =
-                        encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+/// Returns the step sizes of each jump flooding pass for a texture whose longest side is `max_side_length`.
+///
+/// An additional pass with a step size of `1` is appended (also known as "JFA+1") to fix
+/// most of the errors which the jump flooding algorithm produces.
+fn jump_step_sizes(max_side_length: u32) -> Vec<u32> {
+    let mut step_sizes = Vec::new();
+
+    let mut step_size = max_side_length.next_power_of_two() / 2;
+    while step_size > 0 {
+        step_sizes.push(step_size);
+        step_size /= 2;
+    }
 
-                    pass.set_pipeline(&self.update_pipeline);
+    step_sizes.push(1);
+    step_sizes
+}
 
-                    // dst => src (= 1 iteration)
-                    pass.set_bind_group(0, &self.dst_to_src_bind_group, &[]);
-                    pass.dispatch_workgroups(x, y, 1);
-                    span.pb_inc(1);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-                    // src => dst (= 1 iteration)
-                    pass.set_bind_group(0, &self.src_to_dst_bind_group, &[]);
-                    pass.dispatch_workgroups(x, y, 1);
-                    span.pb_inc(1);
-                }
+    #[test]
+    fn step_sizes_of_power_of_two() {
+        assert_eq!(jump_step_sizes(8), vec![4, 2, 1, 1]);
+    }
 
-                queue.submit(std::iter::once(encoder.finish()));
-            }
-        }
+    #[test]
+    fn step_sizes_of_non_power_of_two() {
+        assert_eq!(
+            jump_step_sizes(3840),
+            vec![2048, 1024, 512, 256, 128, 64, 32, 16, 8, 4, 2, 1, 1]
+        );
     }
 
-    fn amount_steps(&self) -> u32 {
-        1 + self.iterations.div_ceil(2) * 2
+    #[test]
+    fn step_sizes_of_single_texel() {
+        assert_eq!(jump_step_sizes(1), vec![1]);
     }
 }
@@ -1,69 +1,98 @@
+// Computes the distance map with the jump flooding algorithm:
+// https://en.wikipedia.org/wiki/Jump_flooding_algorithm
+//
+// Each texel of the `seeds` textures stores the coordinate of the closest edge-texel
+// which has been found so far or `NO_SEED` if none has been found yet.
+
 @group(0) @binding(0)
 var src: texture_storage_2d<r32float, read>;
 
 @group(0) @binding(1)
+var seeds_out: texture_storage_2d<rg32float, write>;
+
+@group(0) @binding(2)
+var seeds_in: texture_2d<f32>;
+
+struct JumpData {
+    step_size: i32,
+}
+
+@group(0) @binding(3)
+var<uniform> jump: JumpData;
+
+@group(0) @binding(4)
 var dst: texture_storage_2d<r32float, write>;
 
 const INF: f32 = 9999.;
+const NO_SEED: vec2f = vec2f(-1.);
 
 @compute
 @workgroup_size(16, 16, 1)
-fn init_map(@builtin(global_invocation_id) gid: vec3u) {
+fn init_seeds(@builtin(global_invocation_id) gid: vec3u) {
     let value = textureLoad(src, gid.xy).r;
 
     if (is_edge(value)) {
-        textureStore(dst, gid.xy, vec4f(0.));
+        textureStore(seeds_out, gid.xy, vec4f(vec2f(gid.xy), 0., 0.));
     } else {
-        textureStore(dst, gid.xy, vec4f(INF, 0., 0., 0.));
+        textureStore(seeds_out, gid.xy, vec4f(NO_SEED, 0., 0.));
     }
 }
 
 @compute
 @workgroup_size(16, 16, 1)
-fn update_dist(@builtin(global_invocation_id) gid: vec3u) {
+fn jump_flood(@builtin(global_invocation_id) gid: vec3u) {
+    let size = vec2i(textureDimensions(seeds_in));
     let igid = vec2i(gid.xy);
+    let pos = vec2f(gid.xy);
 
-    var min_dist = textureLoad(src, gid.xy).r;
+    var best_seed = NO_SEED;
+    var best_dist = INF;
     for (var x: i32 = -1; x < 2; x++) {
         for (var y: i32 = -1; y < 2; y++) {
-            let offset = vec2i(x, y);
-            let coord = igid + offset;
+            let coord = igid + vec2i(x, y) * jump.step_size;
 
-            if (is_valid_coord(coord)) {
-                let neighbour_distance = textureLoad(src, coord).r;
+            if (is_valid_coord(coord, size)) {
+                let seed = textureLoad(seeds_in, coord, 0).xy;
 
-                // we skip "invalid" distances
-                if (neighbour_distance < INF) {
-                    let new_dist = neighbour_distance + length(vec2f(offset));
+                if (is_seed(seed)) {
+                    let dist = distance(seed, pos);
 
-                    min_dist = min(min_dist, new_dist);
+                    if (dist < best_dist) {
+                        best_dist = dist;
+                        best_seed = seed;
+                    }
                 }
             }
         }
     }
 
-    textureStore(dst, gid.xy, vec4f(min_dist, 0., 0., 0.));
+    textureStore(seeds_out, gid.xy, vec4f(best_seed, 0., 0.));
 }
 
 @compute
 @workgroup_size(16, 16, 1)
-fn normalize_distances(@builtin(global_invocation_id) gid: vec3u) {
-    let size = vec2f(textureDimensions(dst));
-    let max_dist = max(size.x, size.y);
+fn write_distances(@builtin(global_invocation_id) gid: vec3u) {
+    let seed = textureLoad(seeds_in, gid.xy, 0).xy;
 
-    let value = textureLoad(src, gid.xy).r;
-    textureStore(dst, gid.xy, vec4f(value / max_dist, 0., 0., 0.));
-}
+    var dist = INF;
+    if (is_seed(seed)) {
+        dist = distance(seed, vec2f(gid.xy));
+    }
 
-fn is_valid_coord(coord: vec2i) -> bool {
-    let size = vec2i(textureDimensions(dst));
+    textureStore(dst, gid.xy, vec4f(dist, 0., 0., 0.));
+}
 
+fn is_valid_coord(coord: vec2i, size: vec2i) -> bool {
     let x_is_valid = 0 <= coord.x && coord.x < size.x;
     let y_is_valid = 0 <= coord.y && coord.y < size.y;
 
     return x_is_valid && y_is_valid;
 }
 
+fn is_seed(seed: vec2f) -> bool {
+    return seed.x >= 0.;
+}
+
 fn is_edge(value: f32) -> bool {
     return value > 0.9;
 }
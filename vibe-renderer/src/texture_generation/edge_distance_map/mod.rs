@@ -75,6 +75,11 @@ impl<'a> Cacheable for EdgeDistanceMap<'a> {
         hasher.finish()
     }
 
+    fn version(&self) -> u32 {
+        // 1: Distance map is computed with jump flooding
+        1
+    }
+
     fn format(&self) -> wgpu::TextureFormat {
         TEXTURE_FORMAT
     }
@@ -165,7 +170,6 @@ impl<'a> TextureGenerator for EdgeDistanceMap<'a> {
                 device,
                 src: tv2.clone(),
                 dst: tv1.clone(),
-                iterations: max_side_length,
             }),
         ];
 
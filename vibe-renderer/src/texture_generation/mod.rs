@@ -2,12 +2,18 @@
 //! used by [crate::Component]s.
 pub mod edge_distance_map;
 mod gaussian_blur;
+mod normal_map;
 mod sdf_mask;
+mod simplex_noise;
 mod value_noise;
+mod voronoi_noise;
 
 pub use gaussian_blur::GaussianBlur;
+pub use normal_map::NormalMap;
 pub use sdf_mask::{SdfMask, SdfPattern};
+pub use simplex_noise::SimplexNoise;
 pub use value_noise::ValueNoise;
+pub use voronoi_noise::{VoronoiDistance, VoronoiNoise};
 
 /// Provides a method for structs which are there to generate (helper-)textures which can then be used
 /// by [crate::Component]s.
@@ -0,0 +1,130 @@
+use wgpu::{include_wgsl, util::DeviceExt};
+
+use crate::texture_generation::TextureGenerator;
+
+const WORKGROUP_SIZE: u32 = 16;
+
+/// Derives a normal map from the luminance of the given image (by using the sobel operator).
+///
+/// The normals are stored in the `rgb` channels of the returned texture (remapped from `[-1, 1]` to `[0, 1]`).
+/// `z` points out of the image.
+pub struct NormalMap<'a> {
+    pub src: &'a image::DynamicImage,
+
+    /// Decides how "bumpy" the surface should look.
+    /// The higher the value, the stronger the normals tilt at edges.
+    pub strength: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DataBinding {
+    strength: f32,
+}
+
+impl<'a> TextureGenerator for NormalMap<'a> {
+    fn generate(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
+        let img_texture = crate::util::load_img_to_texture(device, queue, self.src);
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Normal map: Texture"),
+            size: img_texture.size(),
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        let data_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Normal map: Data buffer"),
+            contents: bytemuck::bytes_of(&DataBinding {
+                strength: self.strength,
+            }),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let pipeline = {
+            let shader = device.create_shader_module(include_wgsl!("./shader.wgsl"));
+
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Normal map: Pipeline"),
+                layout: None,
+                module: &shader,
+                entry_point: None,
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+        };
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Normal map: Bind group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(
+                        &img_texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(
+                        &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: data_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Normal map: Command encoder"),
+        });
+
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Normal map: Compute pass"),
+                timestamp_writes: None,
+            });
+
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.set_pipeline(&pipeline);
+            pass.dispatch_workgroups(
+                texture.width().div_ceil(WORKGROUP_SIZE),
+                texture.height().div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        texture
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Renderer;
+
+    use super::*;
+
+    #[test]
+    fn general() {
+        let renderer = Renderer::default();
+        let img = image::DynamicImage::new_rgba8(50, 30);
+
+        let texture = renderer.generate(&NormalMap {
+            src: &img,
+            strength: 2.,
+        });
+
+        assert_eq!(texture.width(), 50);
+        assert_eq!(texture.height(), 30);
+    }
+}
@@ -0,0 +1,48 @@
+@group(0) @binding(0)
+var src: texture_2d<f32>;
+
+@group(0) @binding(1)
+var dst: texture_storage_2d<rgba8unorm, write>;
+
+struct Data {
+    strength: f32,
+}
+
+@group(0) @binding(2)
+var<uniform> data: Data;
+
+fn luminance(coord: vec2i) -> f32 {
+    let size = vec2i(textureDimensions(src));
+    let pixel = textureLoad(src, clamp(coord, vec2i(0), size - 1), 0);
+
+    return 0.3 * pixel.r + 0.59 * pixel.g + 0.11 * pixel.b;
+}
+
+@compute
+@workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) gid: vec3u) {
+    let size = textureDimensions(src);
+    if (gid.x >= size.x || gid.y >= size.y) {
+        return;
+    }
+
+    let c = vec2i(gid.xy);
+
+    let tl = luminance(c + vec2i(-1, -1));
+    let t = luminance(c + vec2i(0, -1));
+    let tr = luminance(c + vec2i(1, -1));
+    let l = luminance(c + vec2i(-1, 0));
+    let r = luminance(c + vec2i(1, 0));
+    let bl = luminance(c + vec2i(-1, 1));
+    let b = luminance(c + vec2i(0, 1));
+    let br = luminance(c + vec2i(1, 1));
+
+    // sobel operator
+    let dx = (tr + 2. * r + br) - (tl + 2. * l + bl);
+    let dy = (bl + 2. * b + br) - (tl + 2. * t + tr);
+
+    let normal = normalize(vec3f(-dx * data.strength, -dy * data.strength, 1.));
+
+    // remap [-1, 1] to [0, 1]
+    textureStore(dst, gid.xy, vec4f(.5 * normal + .5, 1.));
+}
@@ -1,25 +1,65 @@
+use pollster::FutureExt;
 use serde::{Deserialize, Serialize};
-use wgpu::{include_wgsl, util::DeviceExt};
+use std::borrow::Cow;
+use wgpu::util::DeviceExt;
 
-use crate::texture_generation::TextureGenerator;
+use crate::{components::ShaderCodeError, texture_generation::TextureGenerator};
 
 const WORKGROUP_SIZE: u32 = 16;
 
+/// Used for the built-in patterns so that the shader always contains an `sdf` function.
+const DEFAULT_SDF_FUNCTION: &str = "fn sdf(uv: vec2f) -> f32 { return 0.; }";
+
 pub struct SdfMask {
     pub pattern: SdfPattern,
     pub texture_size: u32,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum SdfPattern {
-    Box = 0,
-    Circle = 1,
-    Heart = 2,
+    Box,
+    Circle,
+    Heart,
+
+    /// WGSL code which implements the distance function of your own pattern.
+    ///
+    /// The code must contain a function with the following signature:
+    /// ```wgsl
+    /// fn sdf(uv: vec2f) -> f32
+    /// ```
+    /// where `uv` is within `[-1, 1] x [-1, 1]` and the returned value is the signed
+    /// distance to the border of your shape (negative = inside).
+    Custom(String),
 }
 
 impl SdfPattern {
     pub fn id(&self) -> u32 {
-        *self as u32
+        match self {
+            Self::Box => 0,
+            Self::Circle => 1,
+            Self::Heart => 2,
+            Self::Custom(_) => 3,
+        }
+    }
+
+    /// Checks if the shader code of the pattern is valid.
+    ///
+    /// Only [SdfPattern::Custom] can fail here.
+    pub fn validate(&self, device: &wgpu::Device) -> Result<(), ShaderCodeError> {
+        let err_scope = device.push_error_scope(wgpu::ErrorFilter::Validation);
+        create_shader_module(device, self);
+
+        match err_scope.pop().block_on() {
+            Some(err) => Err(ShaderCodeError::ParseError(err)),
+            None => Ok(()),
+        }
+    }
+
+    fn sdf_function(&self) -> &str {
+        match self {
+            Self::Custom(code) => code,
+            _ => DEFAULT_SDF_FUNCTION,
+        }
     }
 }
 
@@ -57,7 +97,7 @@ impl TextureGenerator for SdfMask {
         });
 
         let pipeline = {
-            let shader = device.create_shader_module(include_wgsl!("./shader.wgsl"));
+            let shader = create_shader_module(device, &self.pattern);
 
             device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
                 label: Some("Sdfmask: Compute pipeline"),
@@ -111,6 +151,19 @@ impl TextureGenerator for SdfMask {
     }
 }
 
+fn create_shader_module(device: &wgpu::Device, pattern: &SdfPattern) -> wgpu::ShaderModule {
+    let code = format!(
+        "{}\n{}",
+        include_str!("./shader.wgsl"),
+        pattern.sdf_function()
+    );
+
+    device.create_shader_module(wgpu::ShaderModuleDescriptor {
+        label: Some("Sdfmask: Shader"),
+        source: wgpu::ShaderSource::Wgsl(Cow::Owned(code)),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::Renderer;
@@ -140,4 +193,19 @@ mod tests {
     fn generate_heart() {
         generate(SdfPattern::Heart);
     }
+
+    #[test]
+    fn generate_custom() {
+        generate(SdfPattern::Custom(
+            "fn sdf(uv: vec2f) -> f32 { return length(uv) - .2; }".to_string(),
+        ));
+    }
+
+    #[test]
+    fn invalid_custom_code() {
+        let renderer = Renderer::default();
+        let pattern = SdfPattern::Custom("fn sdf(uv: vec2f) -> f32 { return uv; }".to_string());
+
+        assert!(pattern.validate(renderer.device()).is_err());
+    }
 }
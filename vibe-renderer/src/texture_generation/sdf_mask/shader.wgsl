@@ -12,6 +12,7 @@ var<uniform> data: Data;
 const BOX: u32 = 0;
 const CIRCLE: u32 = 1;
 const HEART: u32 = 2;
+const CUSTOM: u32 = 3;
 
 // helper functions
 fn dot2(v: vec2f) -> f32 {
@@ -66,6 +67,9 @@ fn main(@builtin(global_invocation_id) gid: vec3u) {
         case HEART: {
             d = sdfHeart(uv);
         }
+        case CUSTOM: {
+            d = sdf(uv);
+        }
     }
 
     let glow = min(1., .1 / max(1e-5, d) - .15);
@@ -0,0 +1,91 @@
+@group(0) @binding(0)
+var output: texture_storage_2d<r16unorm, write>;
+
+struct Data {
+    octaves: u32,
+    lacunarity: f32,
+    gain: f32,
+    seed: f32,
+    canvas_size: f32,
+}
+
+@group(0) @binding(1)
+var<uniform> data: Data;
+
+fn mod289_2(x: vec2f) -> vec2f {
+    return x - floor(x * (1. / 289.)) * 289.;
+}
+
+fn mod289_3(x: vec3f) -> vec3f {
+    return x - floor(x * (1. / 289.)) * 289.;
+}
+
+fn permute(x: vec3f) -> vec3f {
+    return mod289_3(((x * 34.) + 1.) * x);
+}
+
+// https://github.com/ashima/webgl-noise/blob/master/src/noise2D.glsl
+// Returns a value within the range [-1, 1].
+fn simplex_noise(v: vec2f) -> f32 {
+    const C = vec4f(
+        0.211324865405187,  // (3.0 - sqrt(3.0)) / 6.0
+        0.366025403784439,  // 0.5 * (sqrt(3.0) - 1.0)
+        -0.577350269189626, // -1.0 + 2.0 * C.x
+        0.024390243902439   // 1.0 / 41.0
+    );
+
+    // first corner
+    var i = floor(v + dot(v, C.yy));
+    let x0 = v - i + dot(i, C.xx);
+
+    // other corners
+    var i1 = vec2f(0., 1.);
+    if (x0.x > x0.y) {
+        i1 = vec2f(1., 0.);
+    }
+    var x12 = x0.xyxy + C.xxzz;
+    x12 = vec4f(x12.xy - i1, x12.zw);
+
+    // permutations
+    i = mod289_2(i);
+    let p = permute(permute(i.y + vec3f(0., i1.y, 1.)) + i.x + vec3f(0., i1.x, 1.));
+
+    var m = max(vec3f(.5) - vec3f(dot(x0, x0), dot(x12.xy, x12.xy), dot(x12.zw, x12.zw)), vec3f(0.));
+    m = m * m;
+    m = m * m;
+
+    // gradients
+    let x = 2. * fract(p * C.www) - 1.;
+    let h = abs(x) - .5;
+    let ox = floor(x + .5);
+    let a0 = x - ox;
+
+    // normalise gradients implicitly by scaling m
+    m *= 1.79284291400159 - 0.85373472095314 * (a0 * a0 + h * h);
+
+    let g = vec3f(a0.x * x0.x + h.x * x0.y, a0.yz * x12.xz + h.yz * x12.yw);
+    return 130. * dot(m, g);
+}
+
+@compute
+@workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) gid: vec3u) {
+    let uv = vec2f(gid.xy) / data.canvas_size + data.seed;
+
+    var presence: f32 = 0.;
+    var max_presence: f32 = 0.;
+
+    var freq = 1.;
+    var amp = .5;
+    for (var i: u32 = 0; i < data.octaves; i++) {
+        presence += amp * simplex_noise(freq * uv);
+        max_presence += amp;
+
+        freq *= data.lacunarity;
+        amp *= data.gain;
+    }
+
+    // remap [-1, 1] to [0, 1]
+    let value = .5 * presence / max(max_presence, 1e-5) + .5;
+    textureStore(output, gid.xy, vec4f(value, 0., 0., 1.));
+}
@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use wgpu::{include_wgsl, util::DeviceExt};
+
+use crate::texture_generation::TextureGenerator;
+
+const WORKGROUP_SIZE: u32 = 16;
+
+/// Generates cellular (voronoi) noise.
+///
+/// The generated texture is tileable.
+pub struct VoronoiNoise {
+    pub texture_size: u32,
+
+    /// The amount of cells per side.
+    pub cells: u32,
+    pub distance: VoronoiDistance,
+    pub seed: Option<u64>,
+}
+
+/// Decides which distance should be stored in each texel.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum VoronoiDistance {
+    /// The distance to the closest feature point.
+    F1,
+
+    /// The distance to the second closest feature point.
+    F2,
+
+    /// The difference between [VoronoiDistance::F2] and [VoronoiDistance::F1].
+    /// Highlights the borders of the cells.
+    F2MinusF1,
+}
+
+impl VoronoiDistance {
+    pub fn id(&self) -> u32 {
+        match self {
+            Self::F1 => 0,
+            Self::F2 => 1,
+            Self::F2MinusF1 => 2,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+struct DataBinding {
+    cells: u32,
+    distance: u32,
+    seed: f32,
+    canvas_size: f32,
+}
+
+impl TextureGenerator for VoronoiNoise {
+    fn generate(&self, device: &wgpu::Device, queue: &wgpu::Queue) -> wgpu::Texture {
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Voronoi noise: Texture"),
+            size: wgpu::Extent3d {
+                width: self.texture_size,
+                height: self.texture_size,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::R16Unorm,
+            usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let data_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Voronoi noise: Data buffer"),
+            contents: bytemuck::bytes_of(&DataBinding {
+                cells: self.cells.max(1),
+                distance: self.distance.id(),
+                canvas_size: self.texture_size as f32,
+                // range: [15, 35]
+                seed: self
+                    .seed
+                    .map(|seed| fastrand::Rng::with_seed(seed).f32())
+                    .unwrap_or(fastrand::f32())
+                    * 20.
+                    + 15.,
+            }),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+
+        let pipeline = {
+            let shader = device.create_shader_module(include_wgsl!("./shader.wgsl"));
+
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some("Voronoi noise: Pipeline"),
+                layout: None,
+                module: &shader,
+                entry_point: None,
+                compilation_options: wgpu::PipelineCompilationOptions::default(),
+                cache: None,
+            })
+        };
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Voronoi noise: Bind group"),
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(
+                        &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    ),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: data_buffer.as_entire_binding(),
+                },
+            ],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: Some("Voronoi noise: Command encoder"),
+        });
+
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+                label: Some("Voronoi noise: Compute pass"),
+                timestamp_writes: None,
+            });
+
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.set_pipeline(&pipeline);
+            pass.dispatch_workgroups(
+                self.texture_size.div_ceil(WORKGROUP_SIZE),
+                self.texture_size.div_ceil(WORKGROUP_SIZE),
+                1,
+            );
+        }
+
+        queue.submit(std::iter::once(encoder.finish()));
+
+        texture
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Renderer;
+
+    use super::*;
+
+    fn generate(distance: VoronoiDistance) {
+        let renderer = Renderer::default();
+
+        renderer.generate(&VoronoiNoise {
+            texture_size: 64,
+            cells: 8,
+            distance,
+            seed: Some(42),
+        });
+    }
+
+    #[test]
+    fn generate_f1() {
+        generate(VoronoiDistance::F1);
+    }
+
+    #[test]
+    fn generate_f2() {
+        generate(VoronoiDistance::F2);
+    }
+
+    #[test]
+    fn generate_f2_minus_f1() {
+        generate(VoronoiDistance::F2MinusF1);
+    }
+}
@@ -0,0 +1,68 @@
+@group(0) @binding(0)
+var output: texture_storage_2d<r16unorm, write>;
+
+struct Data {
+    cells: u32,
+    distance: u32,
+    seed: f32,
+    canvas_size: f32,
+}
+
+@group(0) @binding(1)
+var<uniform> data: Data;
+
+const F1: u32 = 0;
+const F2: u32 = 1;
+const F2_MINUS_F1: u32 = 2;
+
+// https://www.shadertoy.com/view/4djSRW
+fn hash22(p: vec2f) -> vec2f {
+    var p3 = fract(vec3f(p.xyx) * vec3f(.1031, .1030, .0973));
+    p3 += dot(p3, p3.yzx + 33.33 + data.seed);
+    return fract((p3.xx + p3.yz) * p3.zy);
+}
+
+@compute
+@workgroup_size(16, 16, 1)
+fn main(@builtin(global_invocation_id) gid: vec3u) {
+    let cells = f32(data.cells);
+    let uv = vec2f(gid.xy) / data.canvas_size * cells;
+
+    let id = floor(uv);
+    let gv = fract(uv);
+
+    // distance to the closest (f1) and second closest (f2) feature point
+    var f1 = 8.;
+    var f2 = 8.;
+    for (var y: i32 = -1; y <= 1; y++) {
+        for (var x: i32 = -1; x <= 1; x++) {
+            let offset = vec2f(f32(x), f32(y));
+            // wrap the cell ids so that the texture is tileable
+            let cell_id = (id + offset + cells) % cells;
+            let point = offset + hash22(cell_id);
+
+            let dist = length(point - gv);
+            if (dist < f1) {
+                f2 = f1;
+                f1 = dist;
+            } else if (dist < f2) {
+                f2 = dist;
+            }
+        }
+    }
+
+    var value = 0.;
+    switch data.distance {
+        case F1, default: {
+            value = f1;
+        }
+        case F2: {
+            value = f2;
+        }
+        case F2_MINUS_F1: {
+            value = f2 - f1;
+        }
+    }
+
+    textureStore(output, gid.xy, vec4f(clamp(value, 0., 1.), 0., 0., 1.));
+}
@@ -17,7 +17,8 @@ fn test() {
         movement_speed: 0.1,
         pattern: SdfPattern::Heart,
         zoom_factor: 2.,
-    });
+    })
+    .unwrap();
 
     tester.evaluate(&mut chessy, include_bytes!("./reference.png"), "chessy");
 }
@@ -1,4 +1,4 @@
-use std::io::Cursor;
+use std::{collections::HashMap, io::Cursor};
 
 use image::{DynamicImage, ImageReader};
 use vibe_audio::{fetcher::DummyFetcher, BarProcessorConfig, SampleProcessor};
@@ -32,6 +32,8 @@ fn wgsl_passes_without_img() {
                 include_str!("./frag_without_img.wgsl").into(),
             ),
         },
+        readback_slots: Vec::new(),
+        params: HashMap::new(),
     })
     .unwrap_or_else(|msg| panic!("{}", msg));
 
@@ -59,6 +61,8 @@ fn wgsl_passes_with_img() {
                 include_str!("./frag_with_img.wgsl").into(),
             ),
         },
+        readback_slots: Vec::new(),
+        params: HashMap::new(),
     })
     .unwrap_or_else(|msg| panic!("{}", msg));
 
@@ -86,6 +90,8 @@ fn glsl_passes_without_img() {
                 include_str!("./frag_without_img.glsl").into(),
             ),
         },
+        readback_slots: Vec::new(),
+        params: HashMap::new(),
     })
     .unwrap_or_else(|msg| panic!("{}", msg));
 
@@ -114,6 +120,8 @@ fn glsl_passes_with_img() {
                 include_str!("./frag_with_img.glsl").into(),
             ),
         },
+        readback_slots: Vec::new(),
+        params: HashMap::new(),
     })
     .unwrap_or_else(|msg| panic!("{}", msg));
 
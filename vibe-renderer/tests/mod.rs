@@ -1,8 +1,11 @@
 use colored::Colorize;
-use image::{buffer::ConvertBuffer, ImageReader, RgbaImage};
+use image::{buffer::ConvertBuffer, ImageReader, RgbImage, RgbaImage};
 use std::{io::Cursor, path::Path};
-use vibe_audio::SampleProcessor;
-use vibe_renderer::{ComponentAudio, Renderer, RendererDescriptor};
+use vibe_audio::{
+    fetcher::{DummyFetcher, Fetcher, Signal},
+    SampleProcessor,
+};
+use vibe_renderer::{Component, FrameUniforms, Renderer, RendererDescriptor, FRAME_UNIFORMS_GROUP};
 
 mod aurodio;
 
@@ -25,6 +28,8 @@ mod chessy;
 mod live_wallpaper_light_sources;
 mod live_wallpaper_pulse_edges;
 
+mod snapshots;
+
 mod fetcher;
 
 use fetcher::TestFetcher;
@@ -33,18 +38,25 @@ const PIXEL_SIZE: u32 = std::mem::size_of::<u32>() as u32;
 /// The environment variable which needs to be set to create and save the diff images of the tests.
 const DIFF_ENV: &str = "VIBE_TEST_SAVE_DIFF";
 const DIFF_PATH_PREFIX: &str = "/tmp/vibe_test_diffs";
+/// The environment variable which needs to be set to (re)create the reference images of [Tester::assert_snapshot].
+const UPDATE_SNAPSHOTS_ENV: &str = "VIBE_TEST_UPDATE_SNAPSHOTS";
+const SNAPSHOT_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots");
+/// The default of [Tester::max_mean_error].
+const MAX_MEAN_ERROR: f32 = 0.004;
 
 // some colors
 const BLUE: [f32; 4] = [0., 0., 1., 1.];
 const RED: [f32; 4] = [1., 0., 0., 1.];
 const WHITE: [f32; 4] = [1f32; 4];
 
-pub struct Tester<'a> {
+pub struct Tester<'a, F: Fetcher = TestFetcher> {
     pub output_width: u32,
     pub output_height: u32,
+    /// The mean FLIP error which a rendered image may have compared to its reference image.
+    pub max_mean_error: f32,
 
     pub renderer: Renderer,
-    pub sample_processor: SampleProcessor<TestFetcher>,
+    pub sample_processor: SampleProcessor<F>,
 
     output_texture_desc: wgpu::TextureDescriptor<'a>,
     output_texture: wgpu::Texture,
@@ -53,12 +65,25 @@ pub struct Tester<'a> {
 
 impl<'a> Tester<'a> {
     pub fn new(width: u32, height: u32) -> Self {
+        Self::with_fetcher(width, height, TestFetcher::new())
+    }
+}
+
+impl<'a> Tester<'a, DummyFetcher> {
+    /// Creates a tester whose audio is the given (deterministic) signal.
+    pub fn with_signal(width: u32, height: u32, signal: &Signal) -> Self {
+        Self::with_fetcher(width, height, DummyFetcher::with_signal(2, signal))
+    }
+}
+
+impl<'a, F: Fetcher> Tester<'a, F> {
+    pub fn with_fetcher(width: u32, height: u32, fetcher: F) -> Self {
         let renderer = Renderer::new(&RendererDescriptor {
             fallback_to_software_rendering: true,
             ..Default::default()
         });
         let sample_processor = {
-            let mut sample_processor = SampleProcessor::new(TestFetcher::new());
+            let mut sample_processor = SampleProcessor::new(fetcher);
             sample_processor.process_next_samples();
             sample_processor
         };
@@ -97,6 +122,7 @@ impl<'a> Tester<'a> {
             sample_processor,
             output_width,
             output_height,
+            max_mean_error: MAX_MEAN_ERROR,
             output_texture,
             output_texture_desc,
             output_buffer,
@@ -108,11 +134,21 @@ impl<'a> Tester<'a> {
     }
 
     /// Renders the given component and returns the rendered image
-    pub fn render<C: ComponentAudio<TestFetcher>>(&self, component: &mut C) -> RgbaImage {
+    pub fn render<C: Component + ?Sized>(&self, component: &mut C) -> RgbaImage {
         component.update_resolution(&self.renderer, [self.output_width, self.output_height]);
         component.update_audio(self.renderer.queue(), &self.sample_processor);
         component.update_time(self.renderer.queue(), 100.);
 
+        let frame_uniforms = {
+            let mut frame_uniforms = FrameUniforms::new(&self.renderer);
+            frame_uniforms.set_time(100.);
+            frame_uniforms.write(
+                self.renderer.queue(),
+                [self.output_width, self.output_height],
+            );
+            frame_uniforms
+        };
+
         let view = self
             .output_texture
             .create_view(&wgpu::TextureViewDescriptor::default());
@@ -137,6 +173,7 @@ impl<'a> Tester<'a> {
                 ..Default::default()
             });
 
+            render_pass.set_bind_group(FRAME_UNIFORMS_GROUP, frame_uniforms.bind_group(), &[]);
             component.render_with_renderpass(&mut render_pass);
         }
 
@@ -186,17 +223,7 @@ impl<'a> Tester<'a> {
         rgba_image
     }
 
-    pub fn evaluate<C: ComponentAudio<TestFetcher>>(
-        &self,
-        component: &mut C,
-        reference: &'static [u8],
-        id: &str,
-    ) {
-        let test_img: image::RgbImage = self.render(component).convert();
-
-        let test_flip_img =
-            nv_flip::FlipImageRgb8::with_data(test_img.width(), test_img.height(), &test_img);
-
+    pub fn evaluate<C: Component>(&self, component: &mut C, reference: &'static [u8], id: &str) {
         let ref_img = ImageReader::new(Cursor::new(reference))
             .with_guessed_format()
             .unwrap()
@@ -204,6 +231,45 @@ impl<'a> Tester<'a> {
             .unwrap()
             .into_rgb8();
 
+        self.compare(self.render(component).convert(), ref_img, id);
+    }
+
+    /// Compares the rendered image of the component with the reference image `tests/snapshots/<id>.png`.
+    ///
+    /// The reference image is only (re)created if the `VIBE_TEST_UPDATE_SNAPSHOTS` variable is set,
+    /// a missing reference image fails the test otherwise.
+    pub fn assert_snapshot<C: Component + ?Sized>(&self, component: &mut C, id: &str) {
+        let path = Path::new(SNAPSHOT_DIR).join(format!("{}.png", id));
+        let img = self.render(component);
+
+        if std::env::var_os(UPDATE_SNAPSHOTS_ENV).is_some() {
+            img.save(&path).unwrap();
+            println!("Saved reference image {}", path.to_string_lossy().blue());
+            return;
+        }
+
+        assert!(
+            path.exists(),
+            "The reference image {} is missing. Create it with `{}=1 cargo test`.",
+            path.to_string_lossy(),
+            UPDATE_SNAPSHOTS_ENV
+        );
+
+        let ref_img = image::open(&path).unwrap().into_rgb8();
+        self.compare(img.convert(), ref_img, id);
+    }
+
+    /// Panics if the images differ too much and saves the images together with their diff in that case.
+    fn compare(&self, test_img: RgbImage, ref_img: RgbImage, id: &str) {
+        assert_eq!(
+            test_img.dimensions(),
+            ref_img.dimensions(),
+            "The rendered image of '{}' has another size than its reference image.",
+            id
+        );
+
+        let test_flip_img =
+            nv_flip::FlipImageRgb8::with_data(test_img.width(), test_img.height(), &test_img);
         let ref_flip_img =
             nv_flip::FlipImageRgb8::with_data(ref_img.width(), ref_img.height(), &ref_img);
 
@@ -213,8 +279,11 @@ impl<'a> Tester<'a> {
             nv_flip::DEFAULT_PIXELS_PER_DEGREE,
         );
 
+        let pool = nv_flip::FlipPool::from_image(&error_map);
+        let failed = pool.mean() >= self.max_mean_error;
+
         // save diff
-        if std::env::var(DIFF_ENV).ok().is_some() {
+        if failed || std::env::var(DIFF_ENV).ok().is_some() {
             let visualized = error_map.apply_color_lut(&nv_flip::magma_lut());
 
             let diff_img = image::RgbImage::from_raw(
@@ -227,28 +296,27 @@ impl<'a> Tester<'a> {
             let prefix = format!("{}/{}", DIFF_PATH_PREFIX, id);
             std::fs::create_dir_all(&prefix).unwrap();
 
-            test_img.save(&format!("{}/rendered.png", prefix)).unwrap();
-            ref_img.save(&format!("{}/reference.png", prefix)).unwrap();
-            diff_img.save(&format!("{}/diff.png", prefix)).unwrap();
+            test_img.save(format!("{}/rendered.png", prefix)).unwrap();
+            ref_img.save(format!("{}/reference.png", prefix)).unwrap();
+            diff_img.save(format!("{}/diff.png", prefix)).unwrap();
 
             println!("Saved diff to {}*", prefix.blue());
         }
 
-        let pool = nv_flip::FlipPool::from_image(&error_map);
-
         assert!(
-            pool.mean() < 0.004,
-            "Got mean of {}. Set the `{}` variable to see the diffs in `{}`.",
+            !failed,
+            "Got mean of {} for '{}'. See the diffs in `{}` (set the `{}` variable to save them for passing tests as well).",
             pool.mean(),
-            DIFF_ENV.yellow(),
-            DIFF_PATH_PREFIX.yellow()
+            id,
+            DIFF_PATH_PREFIX.yellow(),
+            DIFF_ENV.yellow()
         );
     }
 
     /// A little helper function to create the reference file of a component.
     pub fn create_reference_img<C, P>(&self, component: &mut C, dest: P)
     where
-        C: ComponentAudio<TestFetcher>,
+        C: Component,
         P: AsRef<Path>,
     {
         let img = self.render(component);
@@ -0,0 +1,218 @@
+//! Renders every component with each test signal and compares them against the reference images
+//! of this directory (see [Tester::assert_snapshot]).
+use crate::{Tester, BLUE, RED, WHITE};
+use image::ImageReader;
+use std::{collections::HashMap, num::NonZero};
+use vibe_audio::{
+    fetcher::{DummyFetcher, Signal},
+    BarProcessorConfig,
+};
+use vibe_renderer::{
+    components::{
+        live_wallpaper::{
+            light_sources::{LightSourceData, LightSources, LightSourcesDescriptor},
+            pulse_edges::{PulseEdges, PulseEdgesDescriptor},
+        },
+        Aurodio, AurodioDescriptor, AurodioLayerDescriptor, BarVariant, Bars, BarsDescriptor,
+        BarsFormat, BarsPlacement, Chessy, ChessyDescriptor, Circle, CircleDescriptor,
+        CircleVariant, FragmentCanvas, FragmentCanvasDescriptor, Graph, GraphDescriptor,
+        GraphFormat, GraphPlacement, GraphVariant, Radial, RadialDescriptor, RadialFormat,
+        RadialVariant, ShaderCode, ShaderLanguage, ShaderSource,
+    },
+    texture_generation::SdfPattern,
+    Component,
+};
+
+const SEED: u64 = 69;
+
+fn signals() -> [(&'static str, Signal); 3] {
+    [
+        ("silence", Signal::Silence),
+        ("sines", Signal::Sines(vec![60., 440., 3_000.])),
+        ("noise", Signal::Noise { seed: SEED }),
+    ]
+}
+
+/// Creates each component with a fixed descriptor.
+fn components(tester: &Tester<DummyFetcher>) -> Vec<(&'static str, Box<dyn Component>)> {
+    let renderer = &tester.renderer;
+    let sample_processor = &tester.sample_processor;
+    let format = tester.output_texture_format();
+
+    let wallpaper = || {
+        ImageReader::open("../assets/castle.jpg")
+            .unwrap()
+            .decode()
+            .unwrap()
+    };
+
+    vec![
+        (
+            "aurodio",
+            Box::new(Aurodio::new(&AurodioDescriptor {
+                renderer,
+                sample_processor,
+                texture_format: format,
+                base_color: [0., 0., 1.].into(),
+                movement_speed: 0.2,
+                layers: &[AurodioLayerDescriptor {
+                    freq_range: NonZero::new(50).unwrap()..NonZero::new(2_000).unwrap(),
+                    zoom_factor: 5.,
+                }],
+                sensitivity: 0.2,
+                seed: Some(SEED),
+            })),
+        ),
+        (
+            "bars",
+            Box::new(
+                Bars::new(&BarsDescriptor {
+                    renderer,
+                    sample_processor,
+                    audio_conf: BarProcessorConfig::default(),
+                    texture_format: format,
+                    max_height: 1.,
+                    variant: BarVariant::Color(RED.into()),
+                    placement: BarsPlacement::Bottom,
+                    format: BarsFormat::BassTreble,
+                })
+                .unwrap(),
+            ),
+        ),
+        (
+            "chessy",
+            Box::new(
+                Chessy::new(&ChessyDescriptor {
+                    renderer,
+                    sample_processor,
+                    audio_config: BarProcessorConfig::default(),
+                    texture_format: format,
+                    movement_speed: 0.1,
+                    pattern: SdfPattern::Heart,
+                    zoom_factor: 2.,
+                })
+                .unwrap(),
+            ),
+        ),
+        (
+            "circle",
+            Box::new(Circle::new(&CircleDescriptor {
+                renderer,
+                sample_processor,
+                audio_conf: BarProcessorConfig::default(),
+                texture_format: format,
+                variant: CircleVariant::Graph {
+                    spike_sensitivity: 0.1,
+                    color: WHITE.into(),
+                },
+                radius: 0.1,
+                rotation: cgmath::Deg(90.),
+                position: (0.5, 0.5),
+            })),
+        ),
+        (
+            "fragment-canvas",
+            Box::new(
+                FragmentCanvas::new(&FragmentCanvasDescriptor {
+                    sample_processor,
+                    audio_conf: BarProcessorConfig::default(),
+                    renderer,
+                    format,
+
+                    img: None,
+                    fragment_code: ShaderCode {
+                        language: ShaderLanguage::Wgsl,
+                        source: ShaderSource::Code(
+                            include_str!("../fragment_canvas/frag_without_img.wgsl").into(),
+                        ),
+                    },
+                    readback_slots: Vec::new(),
+                    params: HashMap::new(),
+                })
+                .unwrap(),
+            ),
+        ),
+        (
+            "graph",
+            Box::new(Graph::new(&GraphDescriptor {
+                renderer,
+                sample_processor,
+                audio_conf: BarProcessorConfig::default(),
+                output_texture_format: format,
+                max_height: 1.,
+                variant: GraphVariant::Color(BLUE.into()),
+                placement: GraphPlacement::Bottom,
+                format: GraphFormat::BassTreble,
+            })),
+        ),
+        (
+            "light-sources",
+            Box::new(LightSources::new(&LightSourcesDescriptor {
+                renderer,
+                format,
+                processor: sample_processor,
+                wallpaper: wallpaper(),
+                freq_range: NonZero::new(50).unwrap()..NonZero::new(500).unwrap(),
+                sensitivity: 4.0,
+                sources: &[LightSourceData {
+                    center: [0.5; 2],
+                    radius: 0.2,
+                }],
+                uniform_pulse: false,
+                debug_sources: false,
+            })),
+        ),
+        (
+            "pulse-edges",
+            Box::new(
+                PulseEdges::new(&PulseEdgesDescriptor {
+                    renderer,
+                    sample_processor,
+                    texture_format: format,
+                    img: wallpaper(),
+                    freq_range: NonZero::new(50).unwrap()..NonZero::new(500).unwrap(),
+                    audio_sensitivity: 4.0,
+                    high_threshold_ratio: 0.7,
+                    low_threshold_ratio: 0.3,
+                    wallpaper_brightness: 0.2,
+                    edge_width: 4.0,
+                    pulse_brightness: 4.0,
+                    sigma: 1.,
+                    kernel_size: 9,
+                })
+                .unwrap(),
+            ),
+        ),
+        (
+            "radial",
+            Box::new(Radial::new(&RadialDescriptor {
+                renderer,
+                processor: sample_processor,
+                audio_conf: BarProcessorConfig::default(),
+                output_texture_format: format,
+                variant: RadialVariant::Color(RED.into()),
+                init_rotation: cgmath::Deg(90.0),
+                circle_radius: 0.2,
+                bar_height_sensitivity: 1.0,
+                bar_width: 0.01,
+                position: (0.5, 0.5),
+                format: RadialFormat::BassTreble,
+            })),
+        ),
+    ]
+}
+
+#[test]
+#[ignore = "the reference images aren't committed yet, create them with `VIBE_TEST_UPDATE_SNAPSHOTS=1 cargo test -- --ignored`"]
+fn all_components() {
+    for (signal_name, signal) in signals() {
+        let tester = Tester::with_signal(256, 256, &signal);
+
+        for (component_name, mut component) in components(&tester) {
+            tester.assert_snapshot(
+                component.as_mut(),
+                &format!("{}-{}", component_name, signal_name),
+            );
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! The audio which is visualized by the outputs.
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use tracing::warn;
+use vibe_audio::{fetcher::SystemAudioFetcher, BpmDetector, BpmDetectorConfig, SampleProcessor};
+
+use crate::{config::AudioConfig, stats::Usage};
+
+/// A silence which lasts at least this long counts as the gap between two tracks.
+const TRACK_GAP: Duration = Duration::from_secs(1);
+
+/// A sample processor together with the silence state of its audio.
+pub struct AudioSource {
+    pub processor: SampleProcessor<SystemAudioFetcher>,
+
+    // the point of time since the audio is silent
+    silent_since: Option<Instant>,
+    // the amount of silences which ended after at least `TRACK_GAP`
+    track_changes: u64,
+    bpm_detector: BpmDetector,
+    // how much time is spent on processing the samples
+    usage: Usage,
+}
+
+impl AudioSource {
+    fn new(processor: SampleProcessor<SystemAudioFetcher>) -> Self {
+        let bpm_detector = BpmDetector::new(&processor, BpmDetectorConfig::default());
+
+        Self {
+            processor,
+            silent_since: None,
+            track_changes: 0,
+            bpm_detector,
+            usage: Usage::default(),
+        }
+    }
+
+    /// Processes the next samples and returns `true` if the silence ended.
+    fn process_next_samples(&mut self) -> bool {
+        let start = Instant::now();
+        self.processor.process_next_samples();
+        self.bpm_detector.process(&self.processor);
+        self.usage.add(start.elapsed());
+
+        if !self.processor.is_silent() {
+            let Some(silent_since) = self.silent_since.take() else {
+                return false;
+            };
+
+            if silent_since.elapsed() >= TRACK_GAP {
+                self.track_changes += 1;
+            }
+
+            true
+        } else {
+            if self.silent_since.is_none() {
+                self.silent_since = Some(Instant::now());
+            }
+
+            false
+        }
+    }
+
+    pub fn silent_since(&self) -> Option<Instant> {
+        self.silent_since
+    }
+
+    /// Returns how often the audio returned after a gap between two tracks.
+    pub fn track_changes(&self) -> u64 {
+        self.track_changes
+    }
+
+    /// Returns the estimated beats per minute of the audio.
+    pub fn bpm(&self) -> f32 {
+        self.bpm_detector.bpm()
+    }
+
+    /// Returns the fraction of the time which is spent on processing the samples.
+    pub fn cpu_usage(&self) -> f32 {
+        self.usage.usage()
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        let fetcher = self.processor.fetcher();
+        let result = match paused {
+            true => fetcher.pause().map_err(|err| err.to_string()),
+            false => fetcher.resume().map_err(|err| err.to_string()),
+        };
+
+        if let Err(err) = result {
+            warn!("Couldn't pause/resume the audio capture: {}", err);
+        }
+
+        // the silence before the pause doesn't count for the silence after it
+        self.silent_since = None;
+    }
+}
+
+/// The audio sources of all outputs, keyed by the `audio_target` of the output config.
+///
+/// `None` is the device of the main config which is always available.
+pub struct AudioSources {
+    config: AudioConfig,
+    sources: HashMap<Option<String>, AudioSource>,
+    paused: bool,
+}
+
+impl AudioSources {
+    pub fn new(config: AudioConfig) -> anyhow::Result<Self> {
+        let default_source = AudioSource::new(config.sample_processor(None)?);
+
+        Ok(Self {
+            config,
+            sources: HashMap::from([(None, default_source)]),
+            paused: false,
+        })
+    }
+
+    /// Starts listening to the given target unless another output does already.
+    pub fn add(&mut self, target: &Option<String>) -> anyhow::Result<()> {
+        if !self.sources.contains_key(target) {
+            let processor = self.config.sample_processor(target.as_deref())?;
+            let mut source = AudioSource::new(processor);
+            if self.paused {
+                source.set_paused(true);
+            }
+
+            self.sources.insert(target.clone(), source);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the source of the given target. It has to be added before.
+    pub fn get(&self, target: &Option<String>) -> &AudioSource {
+        &self.sources[target]
+    }
+
+    /// Returns all sources together with their target.
+    pub fn iter(&self) -> impl Iterator<Item = (&Option<String>, &AudioSource)> {
+        self.sources.iter()
+    }
+
+    /// Stops listening to the targets which aren't used anymore.
+    pub fn retain<'a>(&mut self, used_targets: impl Iterator<Item = &'a Option<String>>) {
+        let used_targets: Vec<&Option<String>> = used_targets.collect();
+
+        self.sources
+            .retain(|target, _source| target.is_none() || used_targets.contains(&target));
+    }
+
+    /// Stops (or restarts) capturing the audio of all sources, for example while nothing is rendered.
+    pub fn set_paused(&mut self, paused: bool) {
+        if paused == self.paused {
+            return;
+        }
+
+        self.paused = paused;
+        for source in self.sources.values_mut() {
+            source.set_paused(paused);
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Processes the next samples of all sources and returns `true` if the silence of one of them ended.
+    pub fn process_next_samples(&mut self) -> bool {
+        let mut silence_ended = false;
+        for source in self.sources.values_mut() {
+            silence_ended |= source.process_next_samples();
+        }
+
+        silence_ended
+    }
+
+    /// Returns the highest spectral flux of all sources.
+    pub fn spectral_flux(&self) -> f32 {
+        self.sources
+            .values()
+            .map(|source| source.processor.spectral_flux())
+            .fold(0., f32::max)
+    }
+}
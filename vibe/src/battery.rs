@@ -0,0 +1,199 @@
+//! Lowers the quality while the laptop runs on battery.
+//!
+//! The power status is read from `/sys/class/power_supply` (which is also used by `upower`).
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::output::config::component;
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+/// How often the power status is checked.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BatteryConfig {
+    /// Only save power once the charge of the battery drops to this percentage.
+    /// Power is saved as soon as the laptop runs on battery if not set.
+    pub threshold_percent: Option<u8>,
+    /// The maximum frame rate while saving power.
+    pub max_fps: Option<f32>,
+    /// Use the integrated GPU while saving power. `vibe` restarts its session to switch the GPU.
+    pub low_power_adapter: bool,
+    /// Replaces the components of all outputs while saving power.
+    pub fallback_component: Option<component::Config>,
+}
+
+impl Default for BatteryConfig {
+    fn default() -> Self {
+        Self {
+            threshold_percent: None,
+            max_fps: Some(30.),
+            low_power_adapter: false,
+            fallback_component: None,
+        }
+    }
+}
+
+impl BatteryConfig {
+    /// Returns `true` if power should be saved with the given power status.
+    pub fn should_save_power(&self, status: &PowerStatus) -> bool {
+        if !status.on_battery {
+            return false;
+        }
+
+        match (self.threshold_percent, status.capacity) {
+            (Some(threshold), Some(capacity)) => capacity <= threshold,
+            _ => true,
+        }
+    }
+
+    /// Returns the minimal duration between two frames while saving power.
+    pub fn min_frame_interval(&self) -> Duration {
+        self.max_fps
+            .filter(|&fps| fps > 0.)
+            .map(|fps| Duration::from_secs_f32(1. / fps))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PowerStatus {
+    /// `true` if the system has a battery and no power adapter is plugged in.
+    pub on_battery: bool,
+    /// The charge of the batteries in percent.
+    pub capacity: Option<u8>,
+}
+
+impl PowerStatus {
+    /// Reads the current power status of the system.
+    pub fn read() -> Self {
+        Self::read_from(Path::new(POWER_SUPPLY_DIR))
+    }
+
+    fn read_from(dir: &Path) -> Self {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            // no power supply information, for example inside a VM
+            return Self::default();
+        };
+
+        let mut has_battery = false;
+        let mut plugged_in = false;
+        let mut capacities = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let read = |name: &str| {
+                std::fs::read_to_string(path.join(name))
+                    .map(|value| value.trim().to_string())
+                    .ok()
+            };
+
+            match read("type").as_deref() {
+                Some("Battery") => {
+                    // ignore the batteries of connected devices (like mice)
+                    if read("scope").as_deref() == Some("Device") {
+                        continue;
+                    }
+
+                    has_battery = true;
+                    capacities
+                        .extend(read("capacity").and_then(|capacity| capacity.parse::<u32>().ok()));
+                }
+                Some("Mains" | "USB") => plugged_in |= read("online").as_deref() == Some("1"),
+                _ => {}
+            }
+        }
+
+        let capacity = (!capacities.is_empty())
+            .then(|| (capacities.iter().sum::<u32>() / capacities.len() as u32).min(100) as u8);
+
+        Self {
+            on_battery: has_battery && !plugged_in,
+            capacity,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn power_supply_dir(name: &str, supplies: &[&[(&str, &str)]]) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("vibe-power-supply-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        for (idx, files) in supplies.iter().enumerate() {
+            let supply_dir = dir.join(idx.to_string());
+            std::fs::create_dir_all(&supply_dir).unwrap();
+
+            for (file, content) in files.iter() {
+                std::fs::write(supply_dir.join(file), format!("{}\n", content)).unwrap();
+            }
+        }
+
+        dir
+    }
+
+    #[test]
+    fn laptop_on_battery() {
+        let dir = power_supply_dir(
+            "battery",
+            &[
+                &[("type", "Mains"), ("online", "0")],
+                &[("type", "Battery"), ("capacity", "42")],
+                &[("type", "Battery"), ("scope", "Device"), ("capacity", "5")],
+            ],
+        );
+
+        assert_eq!(
+            PowerStatus::read_from(&dir),
+            PowerStatus {
+                on_battery: true,
+                capacity: Some(42)
+            }
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn laptop_plugged_in() {
+        let dir = power_supply_dir(
+            "plugged-in",
+            &[
+                &[("type", "Mains"), ("online", "1")],
+                &[("type", "Battery"), ("capacity", "80")],
+            ],
+        );
+
+        assert!(!PowerStatus::read_from(&dir).on_battery);
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn desktop_without_power_supplies() {
+        let dir = Path::new("/this/path/does/not/exist");
+        assert_eq!(PowerStatus::read_from(dir), PowerStatus::default());
+    }
+
+    #[test]
+    fn threshold() {
+        let config = BatteryConfig {
+            threshold_percent: Some(20),
+            ..Default::default()
+        };
+        let on_battery = |capacity| PowerStatus {
+            on_battery: true,
+            capacity,
+        };
+
+        assert!(!config.should_save_power(&on_battery(Some(50))));
+        assert!(config.should_save_power(&on_battery(Some(20))));
+        assert!(config.should_save_power(&on_battery(None)));
+        assert!(!config.should_save_power(&PowerStatus::default()));
+        assert!(BatteryConfig::default().should_save_power(&on_battery(Some(99))));
+    }
+}
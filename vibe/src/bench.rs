@@ -0,0 +1,246 @@
+//! Renders the components of an output config offscreen and measures how expensive each of them is.
+use std::{
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context};
+use tracing::info;
+use vibe_audio::{
+    fetcher::{SampleFetcher, Signal},
+    SampleProcessor, DEFAULT_SAMPLE_RATE,
+};
+use vibe_renderer::{FrameUniforms, Renderer, RendererDescriptor};
+
+use crate::{
+    colors::ColorManager,
+    record::{self, AMOUNT_CHANNELS},
+    snapshot,
+    types::size::Size,
+};
+
+/// The audio is fed to the components like at this frame rate. It's also the frame budget of the report.
+const FPS: u32 = 60;
+
+/// The first frames aren't measured since they include the lazy setup of the GPU driver.
+const WARMUP_FRAMES: u32 = 10;
+
+/// The seed of the noise which is used if no audio file is given.
+const NOISE_SEED: u64 = 42;
+
+/// How the components are benchmarked.
+#[derive(Debug, Clone)]
+pub struct BenchOptions {
+    pub frames: u32,
+    pub size: Size,
+    pub audio: Option<PathBuf>,
+}
+
+/// The measured times of a single component.
+#[derive(Debug, Default)]
+struct Times {
+    // updating the time, the audio and the colors on the CPU
+    prepare: Vec<Duration>,
+    // submitting the render pass until the GPU finished it
+    render: Vec<Duration>,
+}
+
+/// Renders each component of `target` (an output name or the path to an output config file)
+/// on its own and prints the average and maximum times of each of them.
+pub fn run(target: String, options: BenchOptions) -> anyhow::Result<()> {
+    if options.frames == 0 {
+        bail!("`--frames` has to be greater than 0.");
+    }
+
+    let config = crate::config::load()?;
+    config.apply_global_config();
+    let output_config = snapshot::load_output_config(&target)?;
+
+    let amount_frames = (WARMUP_FRAMES + options.frames) as usize;
+    let samples = match &options.audio {
+        Some(path) => record::decode_audio(
+            path,
+            Duration::from_secs_f32(amount_frames as f32 / FPS as f32),
+        )?,
+        None => Signal::Noise { seed: NOISE_SEED }.samples(
+            DEFAULT_SAMPLE_RATE,
+            record::frame_samples(amount_frames, FPS).start,
+        ),
+    };
+
+    let renderer = Renderer::new(&RendererDescriptor::from(&config.graphics_config));
+    let fetcher = SampleFetcher::new(DEFAULT_SAMPLE_RATE, AMOUNT_CHANNELS);
+    let pusher = fetcher.pusher();
+    let mut processor = SampleProcessor::new(fetcher);
+
+    let size = options.size;
+    let mut components = crate::output::create_components(
+        &renderer,
+        &processor,
+        &output_config.components,
+        snapshot::TEXTURE_FORMAT,
+    )
+    .context("Couldn't create the components")?;
+    for component in components.iter_mut() {
+        component.update_resolution(&renderer, [size.width, size.height]);
+    }
+
+    let texture = snapshot::create_texture(&renderer, size);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let colors = ColorManager::new().colors();
+    let mut frame_uniforms = FrameUniforms::new(&renderer);
+    frame_uniforms.set_colors(&colors);
+
+    let mut times: Vec<Times> = components.iter().map(|_| Times::default()).collect();
+
+    info!(
+        "Rendering {} frames of '{}' with {}...",
+        options.frames,
+        target,
+        match &options.audio {
+            Some(path) => format!("'{}'", path.to_string_lossy()),
+            None => "white noise".to_string(),
+        }
+    );
+    for frame in 0..amount_frames {
+        let range = record::frame_samples(frame, FPS);
+        let start = range.start.min(samples.len());
+        let end = range.end.min(samples.len());
+        pusher.push(&samples[start..end]);
+        processor.process_next_samples();
+
+        let time = frame as f32 / FPS as f32;
+        frame_uniforms.set_time(time);
+
+        for (component, times) in components.iter_mut().zip(times.iter_mut()) {
+            let prepare_start = Instant::now();
+            component.update_time(renderer.queue(), time);
+            component.update_audio(renderer.queue(), &processor);
+            component.update_colors(renderer.queue(), &colors);
+            let prepare = prepare_start.elapsed();
+
+            let render_start = Instant::now();
+            renderer.render(&view, &frame_uniforms, std::slice::from_ref(component));
+            component.post_render(renderer.device(), renderer.queue(), &texture);
+            renderer
+                .device()
+                .poll(wgpu::PollType::Wait {
+                    submission_index: None,
+                    timeout: None,
+                })
+                .context("Lost the GPU device")?;
+            let render = render_start.elapsed();
+
+            if frame >= WARMUP_FRAMES as usize {
+                times.prepare.push(prepare);
+                times.render.push(render);
+            }
+        }
+    }
+
+    let names: Vec<String> = output_config
+        .components
+        .iter()
+        .enumerate()
+        .map(|(idx, config)| format!("[{}] {}", idx, config.name()))
+        .collect();
+
+    println!("{}", report(&names, &times, size));
+    Ok(())
+}
+
+/// Formats the times of the components as a table.
+fn report(names: &[String], times: &[Times], size: Size) -> String {
+    let budget = Duration::from_secs(1) / FPS;
+
+    let mut table = format!(
+        "{:<28} {:>10} {:>10} {:>10} {:>10} {:>8}",
+        "component", "cpu avg", "cpu max", "gpu avg", "gpu max", "budget"
+    );
+
+    let mut total = Duration::ZERO;
+    for (name, times) in names.iter().zip(times) {
+        let avg = average(&times.prepare) + average(&times.render);
+        total += avg;
+
+        table.push_str(&format!(
+            "\n{:<28} {:>10} {:>10} {:>10} {:>10} {:>7.1}%",
+            name,
+            millis(average(&times.prepare)),
+            millis(max(&times.prepare)),
+            millis(average(&times.render)),
+            millis(max(&times.render)),
+            share(avg, budget)
+        ));
+    }
+
+    let components = match names.len() {
+        1 => "The component takes".to_string(),
+        amount => format!("The {} components take", amount),
+    };
+    table.push_str(&format!(
+        "\n\n{} {} per frame at {}x{}, which is {:.1}% of a frame at {} FPS ({}).",
+        components,
+        millis(total),
+        size.width,
+        size.height,
+        share(total, budget),
+        FPS,
+        millis(budget)
+    ));
+
+    table
+}
+
+fn average(durations: &[Duration]) -> Duration {
+    match durations.len() {
+        0 => Duration::ZERO,
+        len => durations.iter().sum::<Duration>() / len as u32,
+    }
+}
+
+fn max(durations: &[Duration]) -> Duration {
+    durations.iter().copied().max().unwrap_or_default()
+}
+
+fn share(duration: Duration, budget: Duration) -> f32 {
+    duration.as_secs_f32() / budget.as_secs_f32() * 100.
+}
+
+/// Like [crate::stats::millis] but precise enough for cheap components.
+fn millis(duration: Duration) -> String {
+    format!("{:.2}ms", duration.as_secs_f32() * 1000.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table() {
+        let times = Times {
+            prepare: vec![Duration::from_micros(100), Duration::from_micros(300)],
+            render: vec![Duration::from_millis(4), Duration::from_millis(2)],
+        };
+
+        let report = report(
+            &["[0] Bars".to_string()],
+            &[times],
+            Size {
+                width: 1920,
+                height: 1080,
+            },
+        );
+
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(
+            lines[1],
+            "[0] Bars                         0.20ms     0.30ms     3.00ms     4.00ms    19.2%"
+        );
+        assert_eq!(
+            lines[3],
+            "The component takes 3.20ms per frame at 1920x1080, which is 19.2% of a frame at 60 FPS (16.67ms)."
+        );
+    }
+}
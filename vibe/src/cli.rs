@@ -1,12 +1,197 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+use crate::{types::size::Size, window::WindowPosition};
 
 #[derive(Parser, Debug)]
-#[command(version, about)]
+#[command(version, about, args_conflicts_with_subcommands = true)]
 pub struct Args {
     /// Show a list of input devices which you can choose from and set it in your config.
     #[arg(long)]
     pub show_input_devices: bool,
 
+    /// Show the size of the texture cache.
+    #[arg(long)]
+    pub show_cache_stats: bool,
+
+    /// Remove all cached textures.
+    #[arg(long)]
+    pub clear_cache: bool,
+
+    /// Notify systemd once the daemon is ready and keep its watchdog alive.
+    /// Use this together with `Type=notify` (and optionally `WatchdogSec=`) in a service file.
+    #[arg(long)]
+    pub systemd: bool,
+
+    /// Print the frame times of the outputs and the load of the audio processing every few seconds.
+    /// `vibe ctl stats` prints them once.
+    #[arg(long)]
+    pub stats: bool,
+
+    /// Render the config of the output in a regular window of the given size (like `800x200`)
+    /// instead of a wallpaper, for example for compositors without layer-shell.
+    #[arg(long, value_name = "WIDTHxHEIGHT", requires = "output_name")]
+    pub windowed: Option<Size>,
+
+    /// Where the window of `--windowed` is placed on the screen (ignored on wayland).
+    #[arg(long, value_enum, requires = "windowed")]
+    pub position: Option<WindowPosition>,
+
+    /// Hide the title bar and the borders of the window of `--windowed`.
+    #[arg(long, requires = "windowed")]
+    pub undecorated: bool,
+
+    /// Keep the window of `--windowed` below all other windows.
+    #[arg(long, requires = "windowed")]
+    pub on_bottom: bool,
+
     /// The output name to start hot reloading the config of the given output.
     pub output_name: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Render the config of an output in a window and reload it on changes.
+    ///
+    /// The output doesn't have to be connected, so you can work on the config of another monitor.
+    Preview {
+        /// The name of the output (for example `DP-1`) or the path to an output config file.
+        target: String,
+    },
+
+    /// Render a single frame of the config of an output into an image (like `snapshot.png`).
+    ///
+    /// No compositor is needed, so it also works in scripts to create screenshots of configs.
+    Snapshot {
+        /// The name of the output (for example `DP-1`) or the path to an output config file.
+        target: String,
+
+        /// How long to render before the frame is taken (like `5s` or `500ms`).
+        #[arg(long, default_value = "0s", value_parser = crate::snapshot::parse_duration)]
+        after: std::time::Duration,
+
+        /// The size of the image.
+        #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "1920x1080")]
+        size: Size,
+
+        /// The image file to write.
+        file: std::path::PathBuf,
+    },
+
+    /// Replay an audio file through the config of an output and encode the frames into a video
+    /// (like `clip.mp4`).
+    ///
+    /// No compositor is needed but `ffmpeg` has to be installed.
+    Record {
+        /// The name of the output (for example `DP-1`) or the path to an output config file.
+        target: String,
+
+        /// How long the clip is (like `10s`).
+        #[arg(long, value_parser = crate::snapshot::parse_duration)]
+        duration: std::time::Duration,
+
+        /// The audio file which is replayed (any format which `ffmpeg` can decode).
+        #[arg(long)]
+        audio: std::path::PathBuf,
+
+        /// The size of the video.
+        #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "1920x1080")]
+        size: Size,
+
+        /// The frame rate of the video.
+        #[arg(long, default_value_t = 60)]
+        fps: u32,
+
+        /// The video file to write.
+        file: std::path::PathBuf,
+    },
+
+    /// Render each component of the config of an output offscreen and print how long it takes
+    /// on the CPU and on the GPU.
+    ///
+    /// No compositor is needed. Without `--audio`, white noise is used as audio.
+    Bench {
+        /// The name of the output (for example `DP-1`) or the path to an output config file.
+        target: String,
+
+        /// How many frames are measured.
+        #[arg(long, default_value_t = 600)]
+        frames: u32,
+
+        /// The resolution which the components render at.
+        #[arg(long, value_name = "WIDTHxHEIGHT", default_value = "1920x1080")]
+        size: Size,
+
+        /// An audio file which is replayed instead of the white noise
+        /// (any format which `ffmpeg` can decode).
+        #[arg(long)]
+        audio: Option<std::path::PathBuf>,
+    },
+
+    /// Control the running daemon.
+    Ctl {
+        #[command(subcommand)]
+        command: crate::ipc::Command,
+    },
+
+    /// Print information about the GPUs, the compositor, the audio devices and the configs.
+    ///
+    /// Please add its output to bug reports.
+    Doctor,
+
+    /// Import shaders of other websites as presets.
+    Import {
+        #[command(subcommand)]
+        command: ImportCommand,
+    },
+
+    /// Manage the config files.
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ConfigCommand {
+    /// Check all config files and compile their shaders without starting the daemon.
+    Validate,
+
+    /// Create a commented starter config for an output.
+    ///
+    /// Lists the detected outputs if no output is given.
+    New {
+        /// The name of the output (for example `DP-1`).
+        output: Option<String>,
+
+        /// The component which the config starts with.
+        #[arg(long, value_enum, default_value_t = crate::new_config::Template::Bars)]
+        template: crate::new_config::Template,
+
+        /// Overwrite the config of the output if it exists already.
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ImportCommand {
+    /// Download a single-pass shader of shadertoy.com and save it as the preset `shadertoy-<id>`.
+    ///
+    /// The shader has to be published with the "Public + API" visibility.
+    Shadertoy {
+        /// The id of the shader (like `XsXXDn`) or the URL of its page.
+        id: String,
+
+        /// The key of your shadertoy app (see <https://www.shadertoy.com/myapps>).
+        /// Uses the environment variable `SHADERTOY_API_KEY` if not set.
+        #[arg(long)]
+        key: Option<String>,
+
+        /// Overwrite the preset if it exists already.
+        #[arg(long)]
+        force: bool,
+    },
 }
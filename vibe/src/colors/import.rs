@@ -0,0 +1,237 @@
+//! Reads the colors which got generated by other theming tools.
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{json, Palette, MAX_COLORS};
+
+/// Takes the colors from the output of another program instead of `colors.toml`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorImport {
+    pub format: ImportFormat,
+    /// The file which contains the colors.
+    /// Defaults to `~/.cache/wal/colors.json` for pywal, required for the other formats.
+    pub path: Option<PathBuf>,
+    /// The names of the colors inside the file (from the darkest to the brightest one).
+    /// See [ImportFormat] for the defaults.
+    pub keys: Option<Vec<String>>,
+    /// The scheme of matugen which should be used (`dark` if not set).
+    pub mode: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ImportFormat {
+    /// The `colors.json` of [pywal](https://github.com/dylanaraps/pywal).
+    ///
+    /// Keys are looked up in `special` and `colors`.
+    /// Default keys: `background`, `color4`, `color2`, `foreground`.
+    Pywal,
+    /// The output of `matugen <source> --json hex` from [matugen](https://github.com/InioX/matugen).
+    ///
+    /// Keys are the names of the color roles.
+    /// Default keys: `surface`, `primary_container`, `secondary`, `primary`.
+    Matugen,
+    /// Extract the colors of an image, like the wallpaper.
+    ///
+    /// `keys` and `mode` are ignored.
+    Wallpaper,
+}
+
+const PYWAL_PATH: &str = "~/.cache/wal/colors.json";
+const DEFAULT_MATUGEN_MODE: &str = "dark";
+
+impl ImportFormat {
+    fn default_keys(&self) -> [&'static str; 4] {
+        match self {
+            Self::Pywal => ["background", "color4", "color2", "foreground"],
+            Self::Matugen => ["surface", "primary_container", "secondary", "primary"],
+            Self::Wallpaper => unreachable!("Images don't contain named colors"),
+        }
+    }
+}
+
+impl ColorImport {
+    /// Returns the path of the file which contains the colors.
+    pub fn path(&self) -> Option<PathBuf> {
+        match (&self.path, self.format) {
+            (Some(path), _format) => Some(crate::expand_home(path)),
+            (None, ImportFormat::Pywal) => Some(crate::expand_home(Path::new(PYWAL_PATH))),
+            (None, ImportFormat::Matugen | ImportFormat::Wallpaper) => None,
+        }
+    }
+
+    /// Reads the colors from the file.
+    pub fn load(&self) -> Result<Palette, String> {
+        let path = self.path().ok_or_else(|| {
+            format!(
+                "`path` is required to import the colors of the format `{:?}`.",
+                self.format
+            )
+        })?;
+
+        if self.format == ImportFormat::Wallpaper {
+            return super::wallpaper::palette(&path);
+        }
+
+        let content = std::fs::read_to_string(&path)
+            .map_err(|err| format!("Couldn't read '{}': {}", path.to_string_lossy(), err))?;
+
+        self.parse(&content)
+            .map_err(|err| format!("'{}': {}", path.to_string_lossy(), err))
+    }
+
+    fn parse(&self, content: &str) -> Result<Palette, String> {
+        let value = json::parse(content)?;
+        let mode = self.mode.as_deref().unwrap_or(DEFAULT_MATUGEN_MODE);
+
+        let keys: Vec<&str> = match &self.keys {
+            Some(keys) => keys.iter().map(String::as_str).collect(),
+            None => self.format.default_keys().to_vec(),
+        };
+        if keys.is_empty() || keys.len() > MAX_COLORS {
+            return Err(format!(
+                "`keys` has to contain between 1 and {} colors.",
+                MAX_COLORS
+            ));
+        }
+
+        let mut palette = Vec::with_capacity(keys.len());
+        for key in keys {
+            let hex = match self.format {
+                ImportFormat::Pywal => ["special", "colors"]
+                    .iter()
+                    .find_map(|group| value.get(group)?.get(key)),
+                // older versions of matugen group the colors by the mode, newer ones by the color
+                ImportFormat::Matugen => value.get("colors").and_then(|colors| {
+                    colors
+                        .get(mode)
+                        .and_then(|colors| colors.get(key))
+                        .or_else(|| colors.get(key)?.get(mode))
+                }),
+                ImportFormat::Wallpaper => unreachable!("Images are loaded by `load`"),
+            }
+            .and_then(json::Value::as_str)
+            .ok_or_else(|| format!("Couldn't find the color '{}'", key))?;
+
+            palette
+                .push(parse_hex(hex).ok_or_else(|| format!("Invalid color '{}': {}", key, hex))?);
+        }
+
+        Ok(palette)
+    }
+}
+
+/// Parses colors like `#1d1f21`.
+fn parse_hex(hex: &str) -> Option<[f32; 3]> {
+    let hex = hex.strip_prefix('#').unwrap_or(hex);
+    if hex.len() != 6 {
+        return None;
+    }
+
+    let mut color = [0.; 3];
+    for (idx, channel) in color.iter_mut().enumerate() {
+        let value = u8::from_str_radix(hex.get(idx * 2..idx * 2 + 2)?, 16).ok()?;
+        *channel = value as f32 / 255.;
+    }
+
+    Some(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PYWAL: &str = r##"{
+    "wallpaper": "/home/user/wallpaper.png",
+    "alpha": "100",
+    "special": { "background": "#000000", "foreground": "#ffffff", "cursor": "#ffffff" },
+    "colors": { "color0": "#000000", "color2": "#00ff00", "color4": "#0000ff" }
+}"##;
+
+    fn import(format: ImportFormat) -> ColorImport {
+        ColorImport {
+            format,
+            path: None,
+            keys: None,
+            mode: None,
+        }
+    }
+
+    #[test]
+    fn import_pywal() {
+        assert_eq!(
+            import(ImportFormat::Pywal).parse(PYWAL),
+            Ok(vec![[0., 0., 0.], [0., 0., 1.], [0., 1., 0.], [1., 1., 1.]])
+        );
+
+        let more_colors = ColorImport {
+            keys: Some(
+                ["color0", "color2", "color4", "cursor", "foreground"]
+                    .map(str::to_string)
+                    .to_vec(),
+            ),
+            ..import(ImportFormat::Pywal)
+        };
+        assert_eq!(more_colors.parse(PYWAL).map(|palette| palette.len()), Ok(5));
+
+        let custom_keys = ColorImport {
+            keys: Some(
+                ["color0", "color0", "color0", "missing"]
+                    .map(str::to_string)
+                    .to_vec(),
+            ),
+            ..import(ImportFormat::Pywal)
+        };
+        assert!(custom_keys.parse(PYWAL).is_err());
+    }
+
+    #[test]
+    fn import_matugen() {
+        let grouped_by_mode = r##"{ "colors": {
+            "dark": { "surface": "#000000", "primary_container": "#ff0000", "secondary": "#00ff00", "primary": "#0000ff" },
+            "light": { "surface": "#ffffff", "primary_container": "#ffffff", "secondary": "#ffffff", "primary": "#ffffff" }
+        } }"##;
+        let grouped_by_color = r##"{ "colors": {
+            "surface": { "dark": "#000000", "light": "#ffffff" },
+            "primary_container": { "dark": "#ff0000", "light": "#ffffff" },
+            "secondary": { "dark": "#00ff00", "light": "#ffffff" },
+            "primary": { "dark": "#0000ff", "light": "#ffffff" }
+        } }"##;
+
+        let expected = vec![[0., 0., 0.], [1., 0., 0.], [0., 1., 0.], [0., 0., 1.]];
+        assert_eq!(
+            import(ImportFormat::Matugen).parse(grouped_by_mode),
+            Ok(expected.clone())
+        );
+        assert_eq!(
+            import(ImportFormat::Matugen).parse(grouped_by_color),
+            Ok(expected)
+        );
+
+        let light = ColorImport {
+            mode: Some("light".to_string()),
+            ..import(ImportFormat::Matugen)
+        };
+        assert_eq!(light.parse(grouped_by_color), Ok(vec![[1.; 3]; 4]));
+    }
+
+    #[test]
+    fn matugen_requires_path() {
+        assert_eq!(import(ImportFormat::Matugen).path(), None);
+        assert!(import(ImportFormat::Matugen).load().is_err());
+    }
+
+    #[test]
+    fn wallpaper_requires_path() {
+        assert_eq!(import(ImportFormat::Wallpaper).path(), None);
+        assert!(import(ImportFormat::Wallpaper).load().is_err());
+    }
+
+    #[test]
+    fn parse_hex_colors() {
+        assert_eq!(parse_hex("#ff0000"), Some([1., 0., 0.]));
+        assert_eq!(parse_hex("00ff00"), Some([0., 1., 0.]));
+        assert_eq!(parse_hex("#fff"), None);
+        assert_eq!(parse_hex("#gg0000"), None);
+    }
+}
@@ -0,0 +1,370 @@
+//! Color palette configuration for shaders.
+//!
+//! Reads colors from ~/.config/vibe/colors.toml (or the file of pywal/matugen, see [ColorImport])
+//! and provides them to shaders.
+//! The files are checked for modifications on each frame (via mtime) for live updates.
+mod import;
+pub mod json;
+mod wallpaper;
+
+pub use import::ColorImport;
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use tracing::warn;
+
+/// The colors from the darkest to the brightest one (at most [MAX_COLORS]).
+pub type Palette = Vec<[f32; 3]>;
+
+pub use vibe_renderer::MAX_COLORS;
+
+/// The size of the image which is used to compute the colors of an image.
+const PALETTE_SAMPLE_SIZE: u32 = 32;
+
+/// Default color palette (muted blue/purple theme)
+const DEFAULT_COLORS: [[f32; 3]; 4] = [
+    [0.08, 0.10, 0.18], // dark blue-gray
+    [0.15, 0.20, 0.35], // muted blue
+    [0.25, 0.35, 0.50], // slate blue
+    [0.30, 0.25, 0.45], // muted purple
+];
+
+const DEFAULT_TRANSITION_SECS: f32 = 1.;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorConfig {
+    /// First color (typically darkest/background)
+    pub color1: [f32; 3],
+    /// Second color
+    pub color2: [f32; 3],
+    /// Third color
+    pub color3: [f32; 3],
+    /// Fourth color (typically brightest/accent)
+    pub color4: [f32; 3],
+    /// All colors of the palette (from the darkest to the brightest one).
+    /// Replaces `color1` to `color4` if set.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub colors: Vec<[f32; 3]>,
+    /// How long (in seconds) the old colors blend into the new ones once they changed.
+    /// `0` switches the colors instantly.
+    pub transition_secs: f32,
+    /// Use the colors of another program instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import: Option<ColorImport>,
+}
+
+impl Default for ColorConfig {
+    fn default() -> Self {
+        Self {
+            color1: DEFAULT_COLORS[0],
+            color2: DEFAULT_COLORS[1],
+            color3: DEFAULT_COLORS[2],
+            color4: DEFAULT_COLORS[3],
+            colors: Vec::new(),
+            transition_secs: DEFAULT_TRANSITION_SECS,
+            import: None,
+        }
+    }
+}
+
+impl ColorConfig {
+    /// Returns `colors` or `color1` to `color4` if `colors` is empty.
+    pub fn as_palette(&self) -> Palette {
+        if self.colors.is_empty() {
+            vec![self.color1, self.color2, self.color3, self.color4]
+        } else {
+            self.colors.iter().copied().take(MAX_COLORS).collect()
+        }
+    }
+
+    /// Returns the imported colors or the ones of the config if nothing is imported.
+    pub fn palette(&self) -> Result<Palette, String> {
+        if self.colors.len() > MAX_COLORS {
+            return Err(format!(
+                "`colors` contains {} colors but at most {} are supported.",
+                self.colors.len(),
+                MAX_COLORS
+            ));
+        }
+
+        match &self.import {
+            Some(import) => import.load(),
+            None => Ok(self.as_palette()),
+        }
+    }
+}
+
+/// Parses `colors.toml` and imports its colors. Returns `None` if the file doesn't exist.
+///
+/// Unlike [ColorManager], this doesn't fall back to the default colors if the file is invalid.
+pub fn load() -> Option<(PathBuf, Result<ColorConfig, String>)> {
+    let path = ColorManager::config_path();
+    let content = std::fs::read_to_string(&path).ok()?;
+
+    let config = toml::from_str::<ColorConfig>(&content)
+        .map_err(|err| err.to_string())
+        .and_then(|config| config.palette().map(|_palette| config));
+
+    Some((path, config))
+}
+
+/// Manages color configuration with file watching via mtime checks.
+pub struct ColorManager {
+    colors: Palette,
+    path: PathBuf,
+    // the file of `import` inside the config
+    import_path: Option<PathBuf>,
+    // the modification times of `path` and `import_path`
+    last_mtimes: [Option<SystemTime>; 2],
+    // replaces the colors of the config file, for example with the colors of the album art
+    override_colors: Option<Palette>,
+    // the colors of the active entry of the schedule, `override_colors` has a higher priority
+    scheduled_colors: Option<Palette>,
+    transition_duration: Duration,
+    transition: Option<Transition>,
+}
+
+/// A running blend from the previous colors to the current ones.
+struct Transition {
+    from: Palette,
+    start: Instant,
+}
+
+impl ColorManager {
+    pub fn new() -> Self {
+        let mut manager = Self {
+            colors: DEFAULT_COLORS.to_vec(),
+            path: Self::config_path(),
+            import_path: None,
+            last_mtimes: [None; 2],
+            override_colors: None,
+            scheduled_colors: None,
+            transition_duration: Duration::ZERO,
+            transition: None,
+        };
+
+        manager.reload();
+        // the colors don't change at the start
+        manager.transition = None;
+        manager
+    }
+
+    fn config_path() -> PathBuf {
+        crate::get_xdg().place_config_file("colors.toml").unwrap()
+    }
+
+    fn reload(&mut self) {
+        let previous = self.colors();
+
+        let config: ColorConfig = std::fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|content| toml::from_str(&content).ok())
+            .unwrap_or_default();
+
+        self.colors = config.palette().unwrap_or_else(|err| {
+            warn!(
+                "Couldn't import the colors: {} Using the colors of colors.toml.",
+                err
+            );
+            config.as_palette()
+        });
+        self.import_path = config.import.as_ref().and_then(|import| import.path());
+        self.last_mtimes = self.current_mtimes();
+        self.transition_duration = Duration::try_from_secs_f32(config.transition_secs)
+            .unwrap_or_else(|_| {
+                warn!(
+                    "Invalid `transition_secs` in colors.toml: {}",
+                    config.transition_secs
+                );
+                Duration::ZERO
+            });
+
+        self.start_transition(previous);
+    }
+
+    fn start_transition(&mut self, from: Palette) {
+        if &from != self.target_colors() && !self.transition_duration.is_zero() {
+            self.transition = Some(Transition {
+                from,
+                start: Instant::now(),
+            });
+        }
+    }
+
+    fn current_mtimes(&self) -> [Option<SystemTime>; 2] {
+        let mtime = |path: &Path| std::fs::metadata(path).ok().and_then(|m| m.modified().ok());
+
+        [
+            mtime(&self.path),
+            self.import_path.as_deref().and_then(mtime),
+        ]
+    }
+
+    /// Check if the config file (or the imported file) has been modified and reload if necessary.
+    /// Returns true if colors were updated.
+    pub fn check_and_reload(&mut self) -> bool {
+        if self
+            .transition
+            .as_ref()
+            .is_some_and(|transition| transition.start.elapsed() >= self.transition_duration)
+        {
+            self.transition = None;
+        }
+
+        if self.current_mtimes() != self.last_mtimes {
+            self.reload();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Uses the given colors instead of the ones of the config file until `None` is set.
+    pub fn set_override(&mut self, colors: Option<Palette>) {
+        let previous = self.colors();
+        self.override_colors = colors;
+        self.start_transition(previous);
+    }
+
+    /// Uses the given colors instead of the ones of the config file until `None` is set.
+    ///
+    /// Unlike [ColorManager::set_override], the colors are only used if no override is set.
+    pub fn set_scheduled(&mut self, colors: Option<Palette>) {
+        if colors != self.scheduled_colors {
+            let previous = self.colors();
+            self.scheduled_colors = colors;
+            self.start_transition(previous);
+        }
+    }
+
+    /// Get the current color configuration as an array.
+    /// The colors are blended while a transition is running.
+    pub fn colors(&self) -> Palette {
+        let target = self.target_colors();
+
+        match &self.transition {
+            Some(transition) => {
+                let progress = transition.start.elapsed().as_secs_f32()
+                    / self.transition_duration.as_secs_f32();
+                blend(&transition.from, target, progress)
+            }
+            None => target.clone(),
+        }
+    }
+
+    // the colors once the transition is done
+    fn target_colors(&self) -> &Palette {
+        self.override_colors
+            .as_ref()
+            .or(self.scheduled_colors.as_ref())
+            .unwrap_or(&self.colors)
+    }
+}
+
+/// Blends `from` into `to` with an eased `progress` (within `[0, 1]`).
+///
+/// The result has as many colors as `to`. Missing colors of `from` are blended from its last color.
+fn blend(from: &[[f32; 3]], to: &[[f32; 3]], progress: f32) -> Palette {
+    let progress = progress.clamp(0., 1.);
+    // smoothstep
+    let t = progress * progress * (3. - 2. * progress);
+
+    to.iter()
+        .enumerate()
+        .map(|(idx, to)| {
+            let from = from.get(idx).or(from.last()).unwrap_or(to);
+            std::array::from_fn(|ch| from[ch] + (to[ch] - from[ch]) * t)
+        })
+        .collect()
+}
+
+/// Computes four colors of the image: The pixels are sorted by their brightness and each color is
+/// the average of a quarter of them.
+pub fn palette_from_image(img: &DynamicImage) -> Palette {
+    // `thumbnail` would scale small images up
+    let img = if img.width() > PALETTE_SAMPLE_SIZE || img.height() > PALETTE_SAMPLE_SIZE {
+        img.thumbnail(PALETTE_SAMPLE_SIZE, PALETTE_SAMPLE_SIZE)
+            .to_rgb32f()
+    } else {
+        img.to_rgb32f()
+    };
+
+    let mut pixels: Vec<[f32; 3]> = img.pixels().map(|pixel| pixel.0).collect();
+    if pixels.is_empty() {
+        return DEFAULT_COLORS.to_vec();
+    }
+
+    let luminance = |[r, g, b]: &[f32; 3]| 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    pixels.sort_by(|a, b| luminance(a).total_cmp(&luminance(b)));
+
+    let mut palette = vec![[0.; 3]; 4];
+    for (idx, color) in palette.iter_mut().enumerate() {
+        // every quarter contains at least one pixel
+        let start = (idx * pixels.len() / 4).min(pixels.len() - 1);
+        let end = ((idx + 1) * pixels.len() / 4).max(start + 1);
+        let quarter = &pixels[start..end];
+
+        for pixel in quarter {
+            for channel in 0..3 {
+                color[channel] += pixel[channel] / quarter.len() as f32;
+            }
+        }
+    }
+
+    palette
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn palette_is_sorted_by_brightness() {
+        let img = RgbImage::from_fn(4, 4, |x, _y| match x {
+            0 => Rgb([255, 255, 255]),
+            1 => Rgb([0, 0, 0]),
+            2 => Rgb([255, 0, 0]),
+            _ => Rgb([0, 0, 255]),
+        });
+
+        let palette = palette_from_image(&DynamicImage::ImageRgb8(img));
+
+        for (color, expected) in
+            palette
+                .iter()
+                .zip([[0., 0., 0.], [0., 0., 1.], [1., 0., 0.], [1., 1., 1.]])
+        {
+            for channel in 0..3 {
+                assert!((color[channel] - expected[channel]).abs() < 1e-3);
+            }
+        }
+    }
+
+    #[test]
+    fn blend_palettes() {
+        let black = [[0.; 3]; 4];
+        let white = [[1.; 3]; 4];
+
+        assert_eq!(blend(&black, &white, 0.), black);
+        assert_eq!(blend(&black, &white, 0.5), [[0.5; 3]; 4]);
+        assert_eq!(blend(&black, &white, 1.), white);
+        assert_eq!(blend(&black, &white, 7.), white);
+
+        // the new palette has more colors
+        let red = [[1., 0., 0.]];
+        assert_eq!(blend(&red, &white, 0.), [[1., 0., 0.]; 4]);
+        assert_eq!(blend(&white, &red, 0.), [[1.; 3]]);
+    }
+
+    #[test]
+    fn palette_of_tiny_image() {
+        let img = RgbImage::from_pixel(1, 1, Rgb([255, 0, 0]));
+
+        let palette = palette_from_image(&DynamicImage::ImageRgb8(img));
+        assert!(palette.iter().all(|color| color[0] > 0.99));
+    }
+}
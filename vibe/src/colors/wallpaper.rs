@@ -0,0 +1,176 @@
+//! Extracts the color palette of a wallpaper.
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use image::DynamicImage;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use super::Palette;
+
+/// The directory inside the cache directory which contains the palettes of the wallpapers.
+const CACHE_DIR_NAME: &str = "palettes";
+
+/// Bump it whenever [extract] changes so that stale palettes in the cache are extracted again.
+const EXTRACTION_VERSION: u32 = 0;
+
+/// The size of the image which is used for the extraction.
+const SAMPLE_SIZE: u32 = 64;
+
+const MAX_ITERATIONS: usize = 16;
+
+/// A palette inside the cache.
+#[derive(Debug, Serialize, Deserialize)]
+struct CachedPalette {
+    colors: Palette,
+}
+
+/// Returns the palette of the given wallpaper.
+///
+/// The palette is cached as long as the wallpaper doesn't change.
+pub fn palette(path: &Path) -> Result<Palette, String> {
+    let metadata = std::fs::metadata(path)
+        .map_err(|err| format!("Couldn't read '{}': {}", path.to_string_lossy(), err))?;
+
+    let cache_path = cache_path(path, metadata.modified().ok(), metadata.len());
+    let cached = cache_path
+        .as_ref()
+        .and_then(|cache_path| std::fs::read_to_string(cache_path).ok())
+        .and_then(|content| toml::from_str::<CachedPalette>(&content).ok());
+
+    if let Some(cached) = cached {
+        debug!("Using the cached palette of '{}'", path.to_string_lossy());
+        return Ok(cached.colors);
+    }
+
+    let img = image::open(path).map_err(|err| {
+        format!(
+            "Couldn't load wallpaper '{}': {}",
+            path.to_string_lossy(),
+            err
+        )
+    })?;
+    let colors = extract(&img);
+
+    if let Some(cache_path) = cache_path {
+        let content = toml::to_string(&CachedPalette {
+            colors: colors.clone(),
+        })
+        .unwrap();
+        if let Err(err) = std::fs::write(&cache_path, content) {
+            warn!(
+                "Couldn't cache the palette in '{}': {}",
+                cache_path.to_string_lossy(),
+                err
+            );
+        }
+    }
+
+    Ok(colors)
+}
+
+fn cache_path(path: &Path, modified: Option<SystemTime>, len: u64) -> Option<PathBuf> {
+    let mut hasher = DefaultHasher::new();
+    (path, modified, len, EXTRACTION_VERSION).hash(&mut hasher);
+
+    crate::get_xdg()
+        .place_cache_file(format!("{}/{:016x}.toml", CACHE_DIR_NAME, hasher.finish()))
+        .ok()
+}
+
+/// Clusters the pixels of the image into four colors (k-means) and sorts them by their brightness.
+pub fn extract(img: &DynamicImage) -> Palette {
+    let pixels: Vec<[f32; 3]> = img
+        .thumbnail(SAMPLE_SIZE, SAMPLE_SIZE)
+        .to_rgb32f()
+        .pixels()
+        .map(|pixel| pixel.0)
+        .collect();
+
+    // the brightness quarters are a good (and deterministic) start
+    let mut centers = super::palette_from_image(img);
+
+    for _ in 0..MAX_ITERATIONS {
+        let mut sums = [[0f32; 3]; 4];
+        let mut amounts = [0usize; 4];
+
+        for pixel in pixels.iter() {
+            let nearest = (0..centers.len())
+                .min_by(|&a, &b| {
+                    distance(pixel, &centers[a]).total_cmp(&distance(pixel, &centers[b]))
+                })
+                .unwrap();
+
+            for channel in 0..3 {
+                sums[nearest][channel] += pixel[channel];
+            }
+            amounts[nearest] += 1;
+        }
+
+        let mut new_centers = centers.clone();
+        for (idx, center) in new_centers.iter_mut().enumerate() {
+            // keep the center of an empty cluster
+            if amounts[idx] > 0 {
+                *center = sums[idx].map(|sum| sum / amounts[idx] as f32);
+            }
+        }
+
+        if new_centers == centers {
+            break;
+        }
+        centers = new_centers;
+    }
+
+    centers.sort_by(|a, b| luminance(a).total_cmp(&luminance(b)));
+    centers
+}
+
+fn distance(a: &[f32; 3], b: &[f32; 3]) -> f32 {
+    (0..3)
+        .map(|channel| (a[channel] - b[channel]).powi(2))
+        .sum()
+}
+
+fn luminance([r, g, b]: &[f32; 3]) -> f32 {
+    0.2126 * r + 0.7152 * g + 0.0722 * b
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgb, RgbImage};
+
+    #[test]
+    fn clusters_the_main_colors() {
+        // a dark blue wallpaper with an orange sun
+        let img = RgbImage::from_fn(100, 100, |x, y| {
+            let in_sun = (x as i32 - 70).pow(2) + (y as i32 - 30).pow(2) < 15 * 15;
+            match (in_sun, y < 50) {
+                (true, _) => Rgb([255, 160, 0]),
+                (false, true) => Rgb([20, 30, 90]),
+                (false, false) => Rgb([5, 5, 20]),
+            }
+        });
+
+        let palette = extract(&DynamicImage::ImageRgb8(img));
+
+        for pair in palette.windows(2) {
+            assert!(luminance(&pair[0]) <= luminance(&pair[1]));
+        }
+
+        // the brightest color is the sun
+        let sun = palette[3];
+        assert!(sun[0] > 0.9 && sun[1] > 0.5 && sun[2] < 0.1);
+    }
+
+    #[test]
+    fn single_color_wallpaper() {
+        let img = RgbImage::from_pixel(10, 10, Rgb([0, 255, 0]));
+
+        let palette = extract(&DynamicImage::ImageRgb8(img));
+        assert!(palette.iter().all(|color| color[1] > 0.99));
+    }
+}
@@ -1,17 +1,25 @@
-use anyhow::anyhow;
+use anyhow::Context;
+use regex_automata::meta::Regex;
 use serde::{Deserialize, Serialize};
-use std::{io, str::FromStr};
+use std::{io, path::PathBuf, sync::Arc};
+use tracing::warn;
 use vibe_audio::{
-    cpal::DeviceId,
     fetcher::{SystemAudioFetcher, SystemAudioFetcherDescriptor},
     util::DeviceType,
     SampleProcessor,
 };
-use vibe_renderer::RendererDescriptor;
+use vibe_renderer::{
+    events::{EventSink, FileSink, UnixSocketSink},
+    RendererDescriptor,
+};
 
-use crate::output::config::component;
+use crate::{
+    battery::BatteryConfig, migration::Version, mpris::MprisConfig, output::config::component,
+    pause_rules::PauseRulesConfig, schedule::ScheduleConfig, video_export::VideoExportConfig,
+};
 
 const STEREO_AUDIO: u16 = 2;
+const MIB: u64 = 1024 * 1024;
 
 #[derive(thiserror::Error, Debug)]
 pub enum ConfigError {
@@ -20,6 +28,15 @@ pub enum ConfigError {
 
     #[error("The config file format is invalid: {0}")]
     Serde(#[from] toml::de::Error),
+
+    #[error("Invalid config version: {0}")]
+    InvalidVersion(String),
+
+    #[error(
+        "The config file has the version {0} but this version of `vibe` only supports up to version {max}. Please update `vibe`.",
+        max = crate::migration::CURRENT_VERSION
+    )]
+    UnsupportedVersion(u32),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -52,63 +69,216 @@ impl From<&GraphicsConfig> for RendererDescriptor {
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct AudioConfig {
+    /// The id, the stable id or (a part of) the name of the audio device, see [vibe_audio::util::find_device].
     pub output_device_id: Option<String>,
+    /// The amount of samples which are analyzed at once (the size of the fft).
+    /// Smaller values react faster, larger ones resolve the bass better. Picked from the sample rate if `None`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sample_buffer_capacity: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    /// The maximum size of the texture cache in MiB. `None` for no limit.
+    pub max_size_mib: Option<u64>,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            max_size_mib: Some(vibe_renderer::cache::DEFAULT_MAX_SIZE / MIB),
+        }
+    }
+}
+
+/// Where the components send their events (the detected BPM and clicks) to.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventsConfig {
+    /// Write the events into `vibe-bpm`, `vibe-click` and `vibe-click-species` of this directory.
+    pub dir: Option<PathBuf>,
+    /// Send each event as a line to this unix (datagram) socket.
+    pub socket: Option<PathBuf>,
+}
+
+impl EventsConfig {
+    /// Returns the sink of the configured destinations or `None` if none is set.
+    pub fn event_sink(&self) -> anyhow::Result<Option<Arc<dyn EventSink>>> {
+        let mut sinks: Vec<Box<dyn EventSink>> = Vec::new();
+
+        if let Some(dir) = &self.dir {
+            sinks.push(Box::new(FileSink::new(dir)));
+        }
+
+        if let Some(socket) = &self.socket {
+            let sink = UnixSocketSink::new(socket).context("Couldn't create the event socket")?;
+            sinks.push(Box::new(sink));
+        }
+
+        Ok((!sinks.is_empty()).then(|| Arc::new(sinks) as Arc<dyn EventSink>))
+    }
+}
+
+/// Lowers the frame rate while the audio barely changes. The full frame rate is used on transients.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FramePacingConfig {
+    /// The frame rate which is used if the audio doesn't change.
+    pub min_fps: f32,
+}
+
+impl Default for FramePacingConfig {
+    fn default() -> Self {
+        Self { min_fps: 15. }
+    }
+}
+
+/// Decides which outputs get a wallpaper.
+///
+/// A pattern matches an output if it's the name of the output (like `DP-1`)
+/// or a part of its description (like `Dell Inc. DELL U2720Q`).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutputFilter {
+    /// Only show a wallpaper on the matching outputs. All outputs are used if it's empty.
+    pub only: Vec<String>,
+    /// Never show a wallpaper on the matching outputs.
+    pub skip: Vec<String>,
+}
+
+impl OutputFilter {
+    /// Returns `true` if the output with the given name and description should get a wallpaper.
+    pub fn allows(&self, name: &str, description: Option<&str>) -> bool {
+        let matches = |pattern: &String| {
+            pattern == name
+                || description.is_some_and(|description| description.contains(pattern.as_str()))
+        };
+
+        (self.only.is_empty() || self.only.iter().any(matches)) && !self.skip.iter().any(matches)
+    }
+}
+
+/// The components of new outputs whose name or description matches `pattern`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DefaultOutputConfig {
+    /// A regular expression which has to match the whole name (like `DP-.*`)
+    /// or the whole description of the output.
+    pub pattern: String,
+    pub components: Vec<component::Config>,
+}
+
+impl DefaultOutputConfig {
+    /// Returns an error if `pattern` isn't a valid regular expression.
+    pub fn matches(&self, name: &str, description: Option<&str>) -> Result<bool, String> {
+        let regex = Regex::new(&format!("^(?:{})$", self.pattern))
+            .map_err(|err| format!("Invalid pattern '{}': {}", self.pattern, err))?;
+
+        Ok(regex.is_match(name)
+            || description.is_some_and(|description| regex.is_match(description)))
+    }
+
+    /// Returns the first config which matches the output.
+    pub fn find<'a>(
+        configs: &'a [Self],
+        name: &str,
+        description: Option<&str>,
+    ) -> Option<&'a Self> {
+        configs.iter().find(|config| {
+            config
+                .matches(name, description)
+                .inspect_err(|err| warn!("default_output_configs: {}", err))
+                .unwrap_or(false)
+        })
+    }
 }
 
 #[derive(Default, Clone, Debug, Serialize, Deserialize)]
 pub struct Config {
+    #[serde(default)]
+    pub version: Version,
     pub graphics_config: GraphicsConfig,
     pub audio_config: Option<AudioConfig>,
+    pub cache_config: Option<CacheConfig>,
+    pub frame_pacing_config: Option<FramePacingConfig>,
     pub default_component: Option<component::Config>,
+    /// Used instead of `default_component` for new outputs which match one of the patterns.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub default_output_configs: Vec<DefaultOutputConfig>,
+    /// React to the track of the media player.
+    pub mpris_config: Option<MprisConfig>,
+    /// Change the brightness and the colors depending on the time of the day.
+    pub schedule_config: Option<ScheduleConfig>,
+    /// Lower the quality while the laptop runs on battery.
+    pub battery_config: Option<BatteryConfig>,
+    /// Pause rendering while some programs are running.
+    pub pause_rules: Option<PauseRulesConfig>,
+    /// Only use some of the outputs.
+    pub output_filter: Option<OutputFilter>,
+    /// Stop rendering outputs which are covered by a focused fullscreen or maximized window (default: `true`).
+    pub pause_when_covered: Option<bool>,
+    /// Show a desktop notification if a config or a shader can't be loaded (default: `true`).
+    pub notify_errors: Option<bool>,
+    /// A shell command which prints the path of the current wallpaper (for `wallpaper_path = "auto"`).
+    pub wallpaper_command: Option<String>,
+    /// Publish the frames of the outputs as PipeWire video streams.
+    pub video_export: Option<VideoExportConfig>,
+    /// Send the events of the components (like the BPM and clicks) to other programs.
+    pub events: Option<EventsConfig>,
 }
 
 impl Config {
+    /// Applies the settings which are shared by all outputs:
+    /// The cache config to the texture cache of the renderer and the command to find the current wallpaper.
+    pub fn apply_global_config(&self) {
+        let max_size_mib = self.cache_config.clone().unwrap_or_default().max_size_mib;
+        vibe_renderer::cache::set_max_size(max_size_mib.map(|mib| mib * MIB));
+
+        crate::current_wallpaper::set_command(self.wallpaper_command.clone());
+    }
+
+    pub fn notify_errors(&self) -> bool {
+        self.notify_errors.unwrap_or(true)
+    }
+
     pub fn save(&self) -> io::Result<()> {
         std::fs::write(crate::get_config_path(), toml::to_string(self).unwrap())
     }
+}
 
-    pub fn sample_processor(&self) -> anyhow::Result<SampleProcessor<SystemAudioFetcher>> {
-        let device = match self
-            .audio_config
-            .clone()
-            .unwrap_or_default()
-            .output_device_id
-        {
-            Some(output_device_id) => {
-                let device_id = DeviceId::from_str(&output_device_id).map_err(|err| {
-                    anyhow!(
-                        "Couldn't parse the device id from your config file (in '{}'):\n{}",
-                        crate::get_config_path().to_string_lossy(),
-                        err
-                    )
-                })?;
-
-                match vibe_audio::util::get_device(device_id, DeviceType::Input)? {
-                    Some(device) => device,
-                    None => {
-                        anyhow::bail!(
+impl AudioConfig {
+    /// Creates a sample processor which listens to the device which matches `device_id`
+    /// (see [vibe_audio::util::find_device]).
+    ///
+    /// Falls back to `output_device_id` (or the default device of the system) if `device_id` is `None`.
+    pub fn sample_processor(
+        &self,
+        device_id: Option<&str>,
+    ) -> anyhow::Result<SampleProcessor<SystemAudioFetcher>> {
+        let device = match device_id.or(self.output_device_id.as_deref()) {
+            Some(query) => match vibe_audio::util::find_device(query, DeviceType::Input)? {
+                Some(device) => device,
+                None => {
+                    anyhow::bail!(
                         concat![
-                            "Available output devices:\n\n{:#?}\n",
-                            "\nThere's no output device with the id \"{}\" as you've set in \"{}\"\n",
-                            "Please choose one from the list and add it to your config."
+                            "Available input devices:\n\n{}\n",
+                            "\nThere's no input device which matches \"{}\" as you've set in \"{}\"\n",
+                            "Please choose one from the list (its name, a part of its name or its stable id) and add it to your config."
                         ],
-                        vibe_audio::util::get_device_ids(DeviceType::Input)?,
-                        &output_device_id,
+                        device_list()?,
+                        query,
                         crate::get_config_path().to_string_lossy()
                     );
-                    }
                 }
-            }
+            },
             None => match vibe_audio::util::get_default_device(DeviceType::Input) {
                 Some(device) => device,
                 None => {
                     anyhow::bail!(
                         concat![
-                            "Available output devices:\n\n{:#?}\n",
-                            "\nCouldn't find the default output device on your system.\n",
+                            "Available input devices:\n\n{}\n",
+                            "\nCouldn't find the default input device on your system.\n",
                             "Please choose one from the list and add it to your config in \"{}\"."
                         ],
-                        vibe_audio::util::get_device_ids(DeviceType::Input)?,
+                        device_list()?,
                         crate::get_config_path().to_string_lossy()
                     );
                 }
@@ -118,6 +288,7 @@ impl Config {
         let system_audio_fetcher = SystemAudioFetcher::new(&SystemAudioFetcherDescriptor {
             device,
             amount_channels: Some(STEREO_AUDIO),
+            buffer_capacity: self.sample_buffer_capacity,
             ..Default::default()
         })?;
 
@@ -125,7 +296,69 @@ impl Config {
     }
 }
 
+/// Lists the input devices, one per line.
+pub fn device_list() -> anyhow::Result<String> {
+    Ok(vibe_audio::util::list_devices(DeviceType::Input)?
+        .iter()
+        .map(|device| format!("  {}", device))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
 pub fn load() -> Result<Config, ConfigError> {
-    let content = std::fs::read_to_string(crate::get_config_path())?;
-    toml::from_str(&content).map_err(|err| err.into())
+    crate::migration::load(&crate::get_config_path())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: Option<&str> = Some("Dell Inc. DELL U2720Q 8LXMZ13 (DP-1)");
+
+    fn filter(only: &[&str], skip: &[&str]) -> OutputFilter {
+        OutputFilter {
+            only: only.iter().map(|s| s.to_string()).collect(),
+            skip: skip.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn empty_filter_allows_everything() {
+        assert!(OutputFilter::default().allows("DP-1", DESCRIPTION));
+        assert!(OutputFilter::default().allows("HDMI-A-1", None));
+    }
+
+    #[test]
+    fn match_by_name_or_description() {
+        assert!(filter(&["DP-1"], &[]).allows("DP-1", None));
+        assert!(filter(&["DELL U2720Q"], &[]).allows("DP-2", DESCRIPTION));
+        assert!(!filter(&["DP"], &[]).allows("DP-2", None));
+        assert!(!filter(&["eDP-1"], &[]).allows("DP-1", Some("Unknown")));
+    }
+
+    #[test]
+    fn default_output_config_patterns() {
+        let config = |pattern: &str| DefaultOutputConfig {
+            pattern: pattern.to_string(),
+            components: Vec::new(),
+        };
+        let configs = [config("HDMI-.*"), config("DP-.*"), config(".*DELL.*")];
+
+        let found = |name, description| {
+            DefaultOutputConfig::find(&configs, name, description).map(|c| c.pattern.as_str())
+        };
+        assert_eq!(found("DP-1", None), Some("DP-.*"));
+        assert_eq!(found("eDP-1", None), None);
+        assert_eq!(found("eDP-1", DESCRIPTION), Some(".*DELL.*"));
+
+        assert!(config("DP-(").matches("DP-1", None).is_err());
+    }
+
+    #[test]
+    fn skip_wins() {
+        let filter = filter(&["DP-1", "HDMI-A-1"], &["Dell"]);
+
+        assert!(!filter.allows("DP-1", DESCRIPTION));
+        assert!(filter.allows("HDMI-A-1", Some("LG")));
+    }
 }
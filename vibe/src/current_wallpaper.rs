@@ -0,0 +1,198 @@
+//! Finds the wallpaper which is currently shown by the wallpaper daemon.
+//!
+//! Components with `wallpaper_path = "auto"` use it instead of a fixed path.
+//! The wallpaper is looked up with `wallpaper_command` of the config of `vibe` (if set)
+//! or from the state of `swww`, `hyprpaper` or `swaybg`.
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::Mutex,
+    time::Duration,
+};
+
+use tracing::debug;
+
+/// The value of `wallpaper_path` to use the current wallpaper.
+pub const AUTO: &str = "auto";
+
+/// How often the daemon checks if the wallpaper changed.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+const PROC_DIR: &str = "/proc";
+
+static COMMAND: Mutex<Option<String>> = Mutex::new(None);
+
+/// Sets the shell command which prints the path of the current wallpaper.
+pub fn set_command(command: Option<String>) {
+    *COMMAND.lock().unwrap() = command;
+}
+
+/// Returns `true` if the path is the placeholder for the current wallpaper.
+pub fn is_auto(path: &Path) -> bool {
+    path == Path::new(AUTO)
+}
+
+/// Replaces `auto` with the path of the current wallpaper.
+///
+/// Returns `None` if the current wallpaper couldn't be found.
+pub fn resolve(path: &Path) -> Option<PathBuf> {
+    if is_auto(path) {
+        find()
+    } else {
+        Some(path.to_path_buf())
+    }
+}
+
+/// Returns the path of the current wallpaper.
+pub fn find() -> Option<PathBuf> {
+    let command = COMMAND.lock().unwrap().clone();
+    if let Some(command) = command {
+        return run("sh", &["-c", &command]).and_then(|output| parse_command(&output));
+    }
+
+    run("swww", &["query"])
+        .and_then(|output| parse_swww(&output))
+        .or_else(|| {
+            run("hyprctl", &["hyprpaper", "listactive"]).and_then(|output| parse_hyprpaper(&output))
+        })
+        .or_else(swaybg)
+}
+
+/// Runs the program and returns its output if it succeeded.
+fn run(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .inspect_err(|err| debug!("Couldn't run `{}`: {}", program, err))
+        .ok()?;
+
+    if !output.status.success() {
+        debug!("`{}` exited with {}", program, output.status);
+        return None;
+    }
+
+    String::from_utf8(output.stdout).ok()
+}
+
+/// The first non-empty line of the output of `wallpaper_command`.
+fn parse_command(output: &str) -> Option<PathBuf> {
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Parses the output of `swww query`, which has a line like
+/// `DP-1: 1920x1080, scale: 1, currently displaying: image: /path/to/wallpaper.png`
+/// for each output.
+fn parse_swww(output: &str) -> Option<PathBuf> {
+    output.lines().find_map(|line| {
+        line.split_once("image: ")
+            .map(|(_, path)| PathBuf::from(path.trim()))
+    })
+}
+
+/// Parses the output of `hyprctl hyprpaper listactive`, which has a line like
+/// `DP-1 = /path/to/wallpaper.png` for each output.
+fn parse_hyprpaper(output: &str) -> Option<PathBuf> {
+    output.lines().find_map(|line| {
+        line.split_once(" = ")
+            .map(|(_, path)| path.trim())
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+/// Looks for the image in the arguments of a running `swaybg` process.
+fn swaybg() -> Option<PathBuf> {
+    let entries = std::fs::read_dir(PROC_DIR).ok()?;
+
+    entries.flatten().find_map(|entry| {
+        let comm = std::fs::read_to_string(entry.path().join("comm")).ok()?;
+        if comm.trim_end() != "swaybg" {
+            return None;
+        }
+
+        let cmdline = std::fs::read(entry.path().join("cmdline")).ok()?;
+        let args: Vec<String> = cmdline
+            .split(|&byte| byte == 0)
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+
+        parse_swaybg_args(&args)
+    })
+}
+
+/// Returns the image of the arguments of `swaybg` (`-i <path>` or `--image <path>`).
+fn parse_swaybg_args(args: &[String]) -> Option<PathBuf> {
+    args.iter().enumerate().find_map(|(idx, arg)| {
+        if let Some(path) = arg.strip_prefix("--image=") {
+            return Some(PathBuf::from(path));
+        }
+
+        (arg == "-i" || arg == "--image")
+            .then(|| args.get(idx + 1).map(PathBuf::from))
+            .flatten()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn swww() {
+        let output = "eDP-1: 2560x1600, scale: 1.5, currently displaying: color: 000000\n\
+                      DP-1: 1920x1080, scale: 1, currently displaying: image: /home/user/walls/forest.png\n";
+
+        assert_eq!(
+            parse_swww(output),
+            Some(PathBuf::from("/home/user/walls/forest.png"))
+        );
+        assert_eq!(parse_swww("DP-1: 1920x1080, scale: 1"), None);
+    }
+
+    #[test]
+    fn hyprpaper() {
+        assert_eq!(
+            parse_hyprpaper("DP-1 = /home/user/walls/forest.png\n"),
+            Some(PathBuf::from("/home/user/walls/forest.png"))
+        );
+        assert_eq!(parse_hyprpaper("no wallpapers active\n"), None);
+    }
+
+    #[test]
+    fn swaybg_args() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            parse_swaybg_args(&args(&[
+                "swaybg",
+                "-o",
+                "*",
+                "-i",
+                "/walls/a.png",
+                "-m",
+                "fill"
+            ])),
+            Some(PathBuf::from("/walls/a.png"))
+        );
+        assert_eq!(
+            parse_swaybg_args(&args(&["swaybg", "--image=/walls/b.jpg"])),
+            Some(PathBuf::from("/walls/b.jpg"))
+        );
+        assert_eq!(parse_swaybg_args(&args(&["swaybg", "-c", "#000000"])), None);
+    }
+
+    #[test]
+    fn command() {
+        assert_eq!(
+            parse_command("\n/walls/a.png\n"),
+            Some(PathBuf::from("/walls/a.png"))
+        );
+        assert_eq!(parse_command(""), None);
+    }
+}
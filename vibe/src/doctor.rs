@@ -0,0 +1,227 @@
+//! Collects information about the system for bug reports.
+use std::ptr::NonNull;
+
+use raw_window_handle::{
+    RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
+};
+use smithay_client_toolkit::{
+    delegate_output, delegate_registry,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+};
+use vibe_renderer::{Renderer, RendererDescriptor};
+use wayland_client::{
+    delegate_noop,
+    globals::{registry_queue_init, GlobalList},
+    protocol::{wl_compositor::WlCompositor, wl_output::WlOutput, wl_surface::WlSurface},
+    Connection, Proxy, QueueHandle,
+};
+
+/// The protocols which `vibe` uses (the first one is required).
+const PROTOCOLS: [&str; 4] = [
+    "zwlr_layer_shell_v1",
+    "ext_idle_notifier_v1",
+    "zwlr_foreign_toplevel_manager_v1",
+    "wl_seat",
+];
+
+/// Prints information about the GPUs, the compositor, the audio devices and the configs.
+///
+/// Returns an error if at least one config file is invalid.
+pub fn run() -> anyhow::Result<()> {
+    println!("vibe {}\n", env!("CARGO_PKG_VERSION"));
+
+    let config = crate::config::load().unwrap_or_default();
+    let renderer = Renderer::new(&RendererDescriptor::from(&config.graphics_config));
+
+    print_adapters(&renderer);
+
+    println!("\n# Compositor");
+    if let Err(err) = print_compositor(&renderer) {
+        println!("  Couldn't connect to the compositor: {}", err);
+    }
+
+    println!("\n# Audio");
+    print_audio_devices(config.audio_config.as_ref());
+
+    println!("\n# Configs");
+    crate::validate::run()
+}
+
+fn print_adapters(renderer: &Renderer) {
+    println!("# GPUs");
+
+    let chosen = renderer.adapter().get_info();
+    let adapters = pollster::block_on(
+        renderer
+            .instance()
+            .enumerate_adapters(wgpu::Backends::all()),
+    );
+    for adapter in adapters {
+        let info = adapter.get_info();
+        let marker = if info == chosen { "*" } else { " " };
+
+        println!(
+            "{} {} ({:?}, {:?})\n    driver: {} {}",
+            marker, info.name, info.backend, info.device_type, info.driver, info.driver_info
+        );
+    }
+    println!("  (* is used for rendering)");
+}
+
+/// Collects the outputs of the compositor.
+struct Doctor {
+    registry_state: RegistryState,
+    output_state: OutputState,
+}
+
+fn print_compositor(renderer: &Renderer) -> anyhow::Result<()> {
+    let conn = Connection::connect_to_env()?;
+    let (globals, mut event_queue) = registry_queue_init(&conn)?;
+    let qh = event_queue.handle();
+
+    let mut doctor = Doctor {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+    };
+
+    // the first roundtrip binds the outputs, the second one receives their information
+    event_queue.roundtrip(&mut doctor)?;
+    event_queue.roundtrip(&mut doctor)?;
+
+    println!("  protocols:");
+    for protocol in PROTOCOLS {
+        let supported = globals
+            .contents()
+            .with_list(|list| list.iter().any(|global| global.interface == protocol));
+        println!("    {}: {}", protocol, if supported { "yes" } else { "no" });
+    }
+
+    println!("  outputs:");
+    let mut infos: Vec<_> = doctor
+        .output_state
+        .outputs()
+        .filter_map(|output| doctor.output_state.info(&output))
+        .collect();
+    infos.sort_by(|a, b| a.name.cmp(&b.name));
+
+    for info in infos {
+        let mode = info.modes.iter().find(|mode| mode.current);
+        println!(
+            "    {}: {} ({}x{} @ {:.2}Hz, scale {}, {:?})",
+            info.name.as_deref().unwrap_or("unknown"),
+            info.description.as_deref().unwrap_or(""),
+            mode.map(|mode| mode.dimensions.0).unwrap_or_default(),
+            mode.map(|mode| mode.dimensions.1).unwrap_or_default(),
+            mode.map(|mode| mode.refresh_rate as f32 / 1000.)
+                .unwrap_or_default(),
+            info.scale_factor,
+            info.transform
+        );
+    }
+
+    // the capabilities don't depend on the output, so a single surface is enough
+    let wl_surface = create_surface(&globals, &qh)?;
+    event_queue.roundtrip(&mut doctor)?;
+
+    let surface = unsafe {
+        renderer
+            .instance()
+            .create_surface_unsafe(wgpu::SurfaceTargetUnsafe::RawHandle {
+                raw_display_handle: RawDisplayHandle::Wayland(WaylandDisplayHandle::new(
+                    NonNull::new(conn.backend().display_ptr() as *mut _).unwrap(),
+                )),
+                raw_window_handle: RawWindowHandle::Wayland(WaylandWindowHandle::new(
+                    NonNull::new(wl_surface.id().as_ptr() as *mut _).unwrap(),
+                )),
+            })?
+    };
+
+    let capabilities = surface.get_capabilities(renderer.adapter());
+    println!("  surface formats: {:?}", capabilities.formats);
+    println!("  alpha modes: {:?}", capabilities.alpha_modes);
+    println!("  present modes: {:?}", capabilities.present_modes);
+
+    drop(surface);
+    wl_surface.destroy();
+    Ok(())
+}
+
+fn create_surface(globals: &GlobalList, qh: &QueueHandle<Doctor>) -> anyhow::Result<WlSurface> {
+    let compositor: WlCompositor = globals.bind(qh, 1..=4, ())?;
+    Ok(compositor.create_surface(qh, ()))
+}
+
+fn print_audio_devices(audio_config: Option<&crate::config::AudioConfig>) {
+    let configured = audio_config.and_then(|config| config.output_device_id.as_deref());
+    println!("  configured device: {}", configured.unwrap_or("default"));
+
+    println!("  host: {:?}", vibe_audio::cpal::default_host().id());
+
+    let devices = match vibe_audio::util::list_devices(vibe_audio::util::DeviceType::Input) {
+        Ok(devices) => devices,
+        Err(err) => {
+            println!("  Couldn't list the input devices: {}", err);
+            return;
+        }
+    };
+
+    if let Some(query) = configured {
+        match vibe_audio::util::find_device(query, vibe_audio::util::DeviceType::Input) {
+            Ok(Some(_)) => {}
+            Ok(None) => println!("  No input device matches \"{}\"!", query),
+            Err(err) => println!("  Couldn't find the configured device: {}", err),
+        }
+    }
+
+    for device in devices {
+        let marker = if device.is_default { "*" } else { " " };
+        let sample_rates = device
+            .sample_rates
+            .iter()
+            .map(|range| format!("{}-{}Hz", range.start(), range.end()))
+            .collect::<Vec<String>>()
+            .join(", ");
+
+        println!(
+            "  {} {}\n      stable id: {}\n      id: {}\n      {} {}ch",
+            marker,
+            device.name,
+            device.stable_id,
+            device
+                .id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "unknown".to_string()),
+            sample_rates,
+            device.max_channels
+        );
+    }
+    println!("  (* is the default input device)");
+}
+
+delegate_noop!(Doctor: WlCompositor);
+delegate_noop!(Doctor: ignore WlSurface);
+
+delegate_output!(Doctor);
+impl OutputHandler for Doctor {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {}
+
+    fn update_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {}
+
+    fn output_destroyed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {
+    }
+}
+
+delegate_registry!(Doctor);
+impl ProvidesRegistryState for Doctor {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
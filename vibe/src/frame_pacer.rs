@@ -0,0 +1,94 @@
+//! Lowers the frame rate while the audio barely changes.
+use std::time::{Duration, Instant};
+
+use crate::config::FramePacingConfig;
+
+/// The spectral flux from which on the outputs are rendered with the full frame rate.
+const ACTIVE_FLUX: f32 = 0.25;
+
+/// How fast the activity decays after a transient.
+/// After this duration the activity dropped to ~37% of its previous value.
+const ACTIVITY_DECAY: Duration = Duration::from_millis(500);
+
+/// Decides how long to wait between two frames depending on the audio activity.
+pub struct FramePacer {
+    max_interval: Duration,
+
+    // the smoothed spectral flux
+    activity: f32,
+    last_update: Instant,
+}
+
+impl FramePacer {
+    pub fn new(config: &FramePacingConfig) -> Self {
+        Self {
+            max_interval: Duration::from_secs_f64(1. / config.min_fps.max(1.) as f64),
+
+            activity: 0.,
+            last_update: Instant::now(),
+        }
+    }
+
+    /// Updates the activity with the spectral flux of the latest samples.
+    ///
+    /// See [vibe_audio::SampleProcessor::spectral_flux].
+    pub fn update(&mut self, spectral_flux: f32) {
+        let now = Instant::now();
+        self.update_with_delta(spectral_flux, now - self.last_update);
+        self.last_update = now;
+    }
+
+    fn update_with_delta(&mut self, spectral_flux: f32, delta: Duration) {
+        // ramp up instantly on transients but calm down slowly
+        let decay = (-delta.as_secs_f32() / ACTIVITY_DECAY.as_secs_f32()).exp();
+        self.activity = spectral_flux.max(self.activity * decay);
+    }
+
+    /// Returns the minimal duration between two frames.
+    ///
+    /// `Duration::ZERO` means that frames should be rendered as fast as the compositor allows.
+    pub fn frame_interval(&self) -> Duration {
+        let calmness = 1. - (self.activity / ACTIVE_FLUX).clamp(0., 1.);
+        self.max_interval.mul_f64(calmness as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pacer() -> FramePacer {
+        FramePacer::new(&FramePacingConfig { min_fps: 10. })
+    }
+
+    #[test]
+    fn calm_audio_uses_min_fps() {
+        let pacer = pacer();
+        assert_eq!(pacer.frame_interval(), Duration::from_millis(100));
+    }
+
+    #[test]
+    fn transients_use_full_frame_rate() {
+        let mut pacer = pacer();
+        pacer.update_with_delta(ACTIVE_FLUX, Duration::from_millis(16));
+
+        assert_eq!(pacer.frame_interval(), Duration::ZERO);
+    }
+
+    #[test]
+    fn activity_decays() {
+        let mut pacer = pacer();
+        pacer.update_with_delta(1., Duration::from_millis(16));
+
+        let mut prev_interval = pacer.frame_interval();
+        for _ in 0..10 {
+            pacer.update_with_delta(0., Duration::from_millis(500));
+
+            let interval = pacer.frame_interval();
+            assert!(interval >= prev_interval);
+            prev_interval = interval;
+        }
+
+        assert!(prev_interval > Duration::from_millis(95));
+    }
+}
@@ -0,0 +1,168 @@
+//! Detects if the user is idle with the `ext-idle-notify` protocol.
+//!
+//! There's no need to render the wallpaper while nobody is looking at it,
+//! for example while the screen is locked.
+use std::{collections::HashMap, time::Duration};
+
+use smithay_client_toolkit::reexports::protocols::ext::idle_notify::v1::client::{
+    ext_idle_notification_v1::{self, ExtIdleNotificationV1},
+    ext_idle_notifier_v1::{self, ExtIdleNotifierV1},
+};
+use tracing::{debug, info};
+use wayland_client::{
+    globals::GlobalList,
+    protocol::{wl_output::WlOutput, wl_seat::WlSeat},
+    Connection, Dispatch, QueueHandle,
+};
+
+pub trait IdleHandler {
+    fn idle_state(&mut self) -> &mut IdleState;
+
+    /// Called each time the user got idle or active again (from the view of the given output).
+    fn idle_changed(&mut self, output: &WlOutput, qh: &QueueHandle<Self>)
+    where
+        Self: Sized;
+}
+
+#[derive(Debug)]
+struct Notification {
+    handle: ExtIdleNotificationV1,
+    timeout: Duration,
+    idle: bool,
+}
+
+/// Keeps track of the idle notifications of the outputs.
+///
+/// Each output has its own notification since each output can have its own timeout.
+#[derive(Debug)]
+pub struct IdleState {
+    // `None` if the compositor doesn't support the protocol
+    notifier: Option<ExtIdleNotifierV1>,
+    notifications: HashMap<WlOutput, Notification>,
+}
+
+impl IdleState {
+    pub fn new<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<ExtIdleNotifierV1, ()> + 'static,
+    {
+        let notifier = globals.bind(qh, 1..=1, ()).ok();
+        if notifier.is_none() {
+            info!(concat![
+                "Your compositor doesn't support the ext_idle_notify protocol. ",
+                "`idle_config` of the outputs is ignored."
+            ]);
+        }
+
+        Self {
+            notifier,
+            notifications: HashMap::new(),
+        }
+    }
+
+    /// Notifies [IdleHandler::idle_changed] once the user didn't do anything for `timeout`
+    /// and once the user is active again.
+    ///
+    /// `None` stops watching the output.
+    pub fn watch<D>(
+        &mut self,
+        output: &WlOutput,
+        seat: Option<&WlSeat>,
+        timeout: Option<Duration>,
+        qh: &QueueHandle<D>,
+    ) where
+        D: Dispatch<ExtIdleNotificationV1, WlOutput> + 'static,
+    {
+        let same_timeout = self
+            .notifications
+            .get(output)
+            .is_some_and(|notification| Some(notification.timeout) == timeout);
+        if same_timeout {
+            return;
+        }
+
+        self.unwatch(output);
+
+        let (Some(notifier), Some(seat), Some(timeout)) = (&self.notifier, seat, timeout) else {
+            return;
+        };
+
+        let timeout_ms = timeout.as_millis().min(u32::MAX as u128) as u32;
+        let handle = notifier.get_idle_notification(timeout_ms, seat, qh, output.clone());
+        self.notifications.insert(
+            output.clone(),
+            Notification {
+                handle,
+                timeout,
+                idle: false,
+            },
+        );
+    }
+
+    pub fn unwatch(&mut self, output: &WlOutput) {
+        if let Some(notification) = self.notifications.remove(output) {
+            notification.handle.destroy();
+        }
+    }
+
+    /// Returns `true` if the user is idle for longer than the timeout of the output.
+    pub fn is_idle(&self, output: &WlOutput) -> bool {
+        self.notifications
+            .get(output)
+            .is_some_and(|notification| notification.idle)
+    }
+}
+
+impl Drop for IdleState {
+    fn drop(&mut self) {
+        for (_output, notification) in self.notifications.drain() {
+            notification.handle.destroy();
+        }
+
+        if let Some(notifier) = self.notifier.take() {
+            notifier.destroy();
+        }
+    }
+}
+
+impl<D> Dispatch<ExtIdleNotifierV1, (), D> for IdleState
+where
+    D: Dispatch<ExtIdleNotifierV1, ()>,
+{
+    fn event(
+        _state: &mut D,
+        _notifier: &ExtIdleNotifierV1,
+        _event: ext_idle_notifier_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<D>,
+    ) {
+        // the notifier doesn't have any events
+    }
+}
+
+impl<D> Dispatch<ExtIdleNotificationV1, WlOutput, D> for IdleState
+where
+    D: Dispatch<ExtIdleNotificationV1, WlOutput> + IdleHandler + 'static,
+{
+    fn event(
+        state: &mut D,
+        _handle: &ExtIdleNotificationV1,
+        event: ext_idle_notification_v1::Event,
+        output: &WlOutput,
+        _conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        let idle = match event {
+            ext_idle_notification_v1::Event::Idled => true,
+            ext_idle_notification_v1::Event::Resumed => false,
+            _ => return,
+        };
+
+        if let Some(notification) = state.idle_state().notifications.get_mut(output) {
+            debug!("Idle: {}", idle);
+            notification.idle = idle;
+            state.idle_changed(output, qh);
+        }
+    }
+}
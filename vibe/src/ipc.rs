@@ -0,0 +1,381 @@
+//! Control socket of the daemon.
+//!
+//! Each connection sends exactly one [Command] as a single line and receives the reply
+//! of the daemon afterwards:
+//!
+//! ```text
+//! -> set-sensitivity 2.5
+//! <- ok
+//! ```
+//!
+//! The first line of the reply is either `ok` or `error`, the remaining lines contain the message.
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    str::FromStr,
+    sync::{mpsc, Arc, Mutex},
+    time::Duration,
+};
+
+use anyhow::Context;
+use clap::Subcommand;
+use smithay_client_toolkit::reexports::calloop::channel::Sender;
+use tracing::{debug, warn};
+
+const SOCKET_NAME: &str = "vibe.sock";
+
+// marks the output of `switch-preset` on the socket since the preset name may contain spaces
+const OUTPUT_FLAG: &str = "--output";
+
+const RESTARTING: &str = "The daemon is restarting, please try again.";
+
+/// How long a client waits for the reply of the daemon.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How long the server waits for the command of a client. The clients are served one after another,
+/// so a client which doesn't send its command mustn't block the others.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The commands which the daemon accepts through its socket.
+#[derive(Subcommand, Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Stop rendering (and processing audio) until `resume` is sent.
+    Pause,
+
+    /// Continue rendering after `pause`.
+    Resume,
+
+    /// Reload the config files of all outputs and remove the outputs which got disabled.
+    ///
+    /// `config.toml` is only reloaded by `SIGHUP`. This also drops the changes of `set-sensitivity` and `switch-preset`.
+    Reload,
+
+    /// Set the audio sensitivity of all components until the next `reload`.
+    SetSensitivity { value: f32 },
+
+    /// Show the components of the given preset until the next `reload`.
+    ///
+    /// This stops the playlist of the output as well.
+    SwitchPreset {
+        /// The name of the preset file (without `.toml`) inside the preset directory.
+        name: String,
+
+        /// Only switch the preset of the given output instead of all outputs.
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Show the next preset (in alphabetical order) until the next `reload`.
+    ///
+    /// The next preset is chosen by the current preset of the (first) output.
+    NextPreset {
+        /// Only switch the preset of the given output instead of all outputs.
+        #[arg(long)]
+        output: Option<String>,
+    },
+
+    /// Raise the audio sensitivity of each component (by a factor of its current one) until the next `reload`.
+    SensitivityUp,
+
+    /// Lower the audio sensitivity of each component (by a factor of its current one) until the next `reload`.
+    SensitivityDown,
+
+    /// Show the state of the daemon.
+    Status,
+
+    /// Show the frame times of the outputs and the load of the audio processing.
+    Stats,
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pause => write!(f, "pause"),
+            Self::Resume => write!(f, "resume"),
+            Self::Reload => write!(f, "reload"),
+            Self::SetSensitivity { value } => write!(f, "set-sensitivity {}", value),
+            Self::SwitchPreset { name, output } => {
+                write!(f, "switch-preset")?;
+                if let Some(output) = output {
+                    write!(f, " {} {}", OUTPUT_FLAG, output)?;
+                }
+                write!(f, " {}", name)
+            }
+            Self::NextPreset { output } => {
+                write!(f, "next-preset")?;
+                if let Some(output) = output {
+                    write!(f, " {}", output)?;
+                }
+                Ok(())
+            }
+            Self::SensitivityUp => write!(f, "sensitivity-up"),
+            Self::SensitivityDown => write!(f, "sensitivity-down"),
+            Self::Status => write!(f, "status"),
+            Self::Stats => write!(f, "stats"),
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // everything after the name of the command is its argument, so preset names may contain spaces
+        let s = s.trim();
+        let (name, arg) = s.split_once(char::is_whitespace).unwrap_or((s, ""));
+        let arg = arg.trim();
+
+        if name.is_empty() {
+            return Err("Empty command".to_string());
+        }
+
+        let command = match name {
+            "pause" => Self::Pause,
+            "resume" => Self::Resume,
+            "reload" => Self::Reload,
+            "set-sensitivity" => {
+                if arg.is_empty() {
+                    return Err("Missing sensitivity value".to_string());
+                }
+
+                let value = arg
+                    .parse()
+                    .map_err(|_| format!("Invalid sensitivity value: '{}'", arg))?;
+
+                return Ok(Self::SetSensitivity { value });
+            }
+            "switch-preset" => {
+                // output names don't contain spaces
+                let flag = arg
+                    .strip_prefix(OUTPUT_FLAG)
+                    .filter(|rest| rest.starts_with(char::is_whitespace));
+                let (output, name) = match flag {
+                    Some(rest) => {
+                        let (output, name) = rest
+                            .trim_start()
+                            .split_once(char::is_whitespace)
+                            .ok_or("Missing preset name")?;
+                        (Some(output.to_string()), name.trim())
+                    }
+                    None => (None, arg),
+                };
+
+                if name.is_empty() {
+                    return Err("Missing preset name".to_string());
+                }
+
+                return Ok(Self::SwitchPreset {
+                    name: name.to_string(),
+                    output,
+                });
+            }
+            "next-preset" => {
+                return Ok(Self::NextPreset {
+                    output: (!arg.is_empty()).then(|| arg.to_string()),
+                })
+            }
+            "sensitivity-up" => Self::SensitivityUp,
+            "sensitivity-down" => Self::SensitivityDown,
+            "status" => Self::Status,
+            "stats" => Self::Stats,
+            other => return Err(format!("Unknown command: '{}'", other)),
+        };
+
+        match arg {
+            "" => Ok(command),
+            arg => Err(format!("Unexpected argument: '{}'", arg)),
+        }
+    }
+}
+
+/// The answer of the daemon to a [Command]. The error contains the reason why the command failed.
+pub type Reply = Result<String, String>;
+
+/// A [Command] which got sent to the daemon together with the channel to send the [Reply] back.
+pub struct Request {
+    pub command: Command,
+    pub reply: mpsc::Sender<Reply>,
+}
+
+/// Listens on the socket for commands and forwards them to the event loop of the daemon.
+///
+/// The socket file is removed again once the server is dropped.
+pub struct Server {
+    path: PathBuf,
+    // `None` while the daemon is (re)starting
+    sender: Arc<Mutex<Option<Sender<Request>>>>,
+}
+
+impl Server {
+    pub fn bind() -> anyhow::Result<Self> {
+        let path = crate::get_xdg()
+            .place_runtime_file(SOCKET_NAME)
+            .context("Couldn't find the runtime directory for the socket")?;
+
+        if UnixStream::connect(&path).is_ok() {
+            anyhow::bail!(
+                "'{}' is already used by another instance.",
+                path.to_string_lossy()
+            );
+        }
+
+        // remove the socket file of a previous instance which didn't exit properly
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)
+            .with_context(|| format!("Couldn't bind to '{}'", path.to_string_lossy()))?;
+
+        let sender: Arc<Mutex<Option<Sender<Request>>>> = Arc::new(Mutex::new(None));
+        let thread_sender = sender.clone();
+        std::thread::Builder::new()
+            .name("IPC server".to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    let sender = thread_sender.lock().unwrap().clone();
+                    let result = stream.and_then(|stream| handle_client(stream, sender));
+                    if let Err(err) = result {
+                        warn!("IPC client failed: {}", err);
+                    }
+                }
+            })?;
+
+        debug!("Listening on '{}'", path.to_string_lossy());
+        Ok(Self { path, sender })
+    }
+
+    /// Forwards the commands to the event loop of the given sender from now on.
+    pub fn set_sender(&self, sender: Sender<Request>) {
+        *self.sender.lock().unwrap() = Some(sender);
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+fn handle_client(mut stream: UnixStream, sender: Option<Sender<Request>>) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let reply = match (Command::from_str(&line), sender) {
+        (Ok(command), Some(sender)) => {
+            let (reply_tx, reply_rx) = mpsc::channel();
+
+            match sender.send(Request {
+                command,
+                reply: reply_tx,
+            }) {
+                Ok(()) => reply_rx
+                    .recv_timeout(REPLY_TIMEOUT)
+                    .unwrap_or_else(|_| Err("The daemon didn't reply in time".to_string())),
+                Err(_) => Err(RESTARTING.to_string()),
+            }
+        }
+        (Ok(_command), None) => Err(RESTARTING.to_string()),
+        (Err(err), _sender) => Err(err),
+    };
+
+    let response = match reply {
+        Ok(msg) => format!("ok\n{}", msg),
+        Err(msg) => format!("error\n{}", msg),
+    };
+
+    stream.write_all(response.as_bytes())
+}
+
+/// Sends the command to the running daemon and returns its reply.
+pub fn send(command: &Command) -> anyhow::Result<String> {
+    let path = crate::get_xdg()
+        .get_runtime_file(SOCKET_NAME)
+        .context("Couldn't find the runtime directory of the socket")?;
+
+    let mut stream = UnixStream::connect(&path).with_context(|| {
+        format!(
+            "Couldn't connect to '{}'. Is `vibe` running?",
+            path.to_string_lossy()
+        )
+    })?;
+    stream.set_read_timeout(Some(REPLY_TIMEOUT * 2))?;
+
+    writeln!(stream, "{}", command)?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    let (status, msg) = response.split_once('\n').unwrap_or((&response, ""));
+    match status {
+        "ok" => Ok(msg.to_string()),
+        "error" => anyhow::bail!("{}", msg),
+        _ => anyhow::bail!("Invalid reply of daemon: '{}'", response),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_roundtrip() {
+        let commands = [
+            Command::Pause,
+            Command::Resume,
+            Command::Reload,
+            Command::SetSensitivity { value: 2.5 },
+            Command::SwitchPreset {
+                name: "calm".to_string(),
+                output: None,
+            },
+            Command::SwitchPreset {
+                name: "calm".to_string(),
+                output: Some("DP-1".to_string()),
+            },
+            Command::SwitchPreset {
+                name: "late night".to_string(),
+                output: None,
+            },
+            Command::SwitchPreset {
+                name: "late night".to_string(),
+                output: Some("DP-1".to_string()),
+            },
+            Command::NextPreset { output: None },
+            Command::NextPreset {
+                output: Some("DP-1".to_string()),
+            },
+            Command::SensitivityUp,
+            Command::SensitivityDown,
+            Command::Status,
+            Command::Stats,
+        ];
+
+        for command in commands {
+            assert_eq!(Command::from_str(&command.to_string()), Ok(command));
+        }
+    }
+
+    #[test]
+    fn reject_invalid_commands() {
+        assert!(Command::from_str("").is_err());
+        assert!(Command::from_str("jump").is_err());
+        assert!(Command::from_str("set-sensitivity").is_err());
+        assert!(Command::from_str("set-sensitivity loud").is_err());
+        assert!(Command::from_str("pause now").is_err());
+        assert!(Command::from_str("switch-preset").is_err());
+        assert!(Command::from_str("switch-preset --output DP-1").is_err());
+    }
+
+    #[test]
+    fn silent_client_times_out() {
+        let (server, _client) = UnixStream::pair().unwrap();
+        assert!(handle_client(server, None).is_err());
+    }
+}
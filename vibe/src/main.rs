@@ -1,16 +1,55 @@
+mod audio_source;
+mod battery;
+mod bench;
 mod cli;
 mod colors;
 mod config;
+mod current_wallpaper;
+mod doctor;
+mod frame_pacer;
+mod idle;
+mod ipc;
+mod migration;
+mod mpris;
+mod new_config;
+mod notification;
 mod output;
+mod pause_rules;
+mod playlist;
+mod record;
+mod schedule;
+mod shadertoy;
+mod signals;
+mod snapshot;
 mod state;
+mod stats;
+mod systemd;
+mod toplevel;
 mod types;
+mod validate;
+mod video_export;
 mod window;
 
-use std::{path::PathBuf, sync::OnceLock};
+use std::{
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::OnceLock,
+    time::{Duration, Instant},
+};
 
+use anyhow::Context;
 use clap::Parser;
+use smithay_client_toolkit::reexports::{
+    calloop::{
+        self,
+        generic::Generic,
+        timer::{TimeoutAction, Timer},
+        EventLoop, Interest, Mode, PostAction,
+    },
+    calloop_wayland_source::WaylandSource,
+};
 use state::State;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 use tracing_indicatif::IndicatifLayer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 use wayland_client::{globals::registry_queue_init, Connection};
@@ -18,7 +57,22 @@ use xdg::BaseDirectories;
 
 const APP_NAME: &str = env!("CARGO_PKG_NAME");
 const OUTPUT_CONFIG_DIR_NAME: &str = "output_configs";
+const PRESET_DIR_NAME: &str = "presets";
 const CONFIG_FILE_NAME: &str = "config.toml";
+const ALL_OUTPUTS_CONFIG_FILE_NAME: &str = "all-outputs.toml";
+
+/// How often `--stats` prints the statistics.
+const STATS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How often the audio is checked while no output is rendering.
+const AUDIO_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to wait before reconnecting after the compositor or the GPU went away.
+const RESTART_DELAY: Duration = Duration::from_secs(1);
+/// How often the daemon is restarted in a row before giving up.
+const MAX_RESTART_ATTEMPTS: u32 = 10;
+/// A session which ran at least this long counts as successful and resets the restart attempts.
+const MIN_SESSION_DURATION: Duration = Duration::from_secs(30);
 
 static XDG: OnceLock<BaseDirectories> = OnceLock::new();
 
@@ -26,23 +80,119 @@ fn main() -> anyhow::Result<()> {
     init_logging();
 
     let args = cli::Args::parse();
+    match args.command {
+        Some(cli::Command::Ctl { command }) => {
+            let reply = ipc::send(&command)?;
+            if !reply.is_empty() {
+                println!("{}", reply);
+            }
+            return Ok(());
+        }
+        Some(cli::Command::Preview { target }) => {
+            return run_window(target, window::WindowOptions::preview())
+        }
+        Some(cli::Command::Snapshot {
+            target,
+            after,
+            size,
+            file,
+        }) => {
+            if !snapshot::is_supported_file(&file) {
+                anyhow::bail!(
+                    "Can't save an image as '{}'. Use an extension like `.png`.",
+                    file.to_string_lossy()
+                );
+            }
+
+            return snapshot::run(target, after, size, file);
+        }
+        Some(cli::Command::Record {
+            target,
+            duration,
+            audio,
+            size,
+            fps,
+            file,
+        }) => {
+            let options = record::RecordOptions {
+                duration,
+                audio,
+                size,
+                fps,
+            };
+
+            return record::run(target, options, file);
+        }
+        Some(cli::Command::Bench {
+            target,
+            frames,
+            size,
+            audio,
+        }) => {
+            let options = bench::BenchOptions {
+                frames,
+                size,
+                audio,
+            };
+
+            return bench::run(target, options);
+        }
+        Some(cli::Command::Doctor) => return doctor::run(),
+        Some(cli::Command::Import { command }) => {
+            return match command {
+                cli::ImportCommand::Shadertoy { id, key, force } => shadertoy::run(id, key, force),
+            };
+        }
+        Some(cli::Command::Config { command }) => {
+            return match command {
+                cli::ConfigCommand::Validate => validate::run(),
+                cli::ConfigCommand::New {
+                    output,
+                    template,
+                    force,
+                } => new_config::run(output, template, force),
+            };
+        }
+        None => {}
+    }
+
     if args.show_input_devices {
-        let device_ids = vibe_audio::util::get_device_ids(vibe_audio::util::DeviceType::Input)?
-            .iter()
-            .map(|id| id.to_string())
-            .collect::<Vec<String>>();
+        info!("Available input devices:\n\n{}\n", config::device_list()?);
+        return Ok(());
+    }
+
+    if args.show_cache_stats {
+        if let Ok(config) = config::load() {
+            config.apply_global_config();
+        }
 
-        info!("Available input devices:\n\n{:#?}\n", device_ids);
+        let stats = vibe_renderer::cache::stats()?;
+        info!("Texture cache:\n\n{:#?}\n", stats);
         return Ok(());
     }
 
-    let result = if let Some(output_name) = args.output_name {
-        window::run(output_name)
-    } else {
-        run_daemon()
-    };
+    if args.clear_cache {
+        vibe_renderer::cache::clear_all()?;
+        info!("Cleared texture cache.");
+        return Ok(());
+    }
+
+    if let Some(output_name) = args.output_name {
+        let options = match args.windowed {
+            Some(size) => window::WindowOptions {
+                size: Some(size),
+                position: args.position,
+                decorations: !args.undecorated,
+                on_bottom: args.on_bottom,
+                hot_reload: false,
+            },
+            None => window::WindowOptions::preview(),
+        };
+
+        return run_window(output_name, options);
+    }
 
-    if let Err(err) = result {
+    if let Err(err) = run_daemon(args.systemd, args.stats) {
         error!("{:?}", err);
         anyhow::bail!("Fatal error");
     }
@@ -50,20 +200,220 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-fn run_daemon() -> anyhow::Result<()> {
-    let (mut state, mut event_loop) = {
-        let conn = Connection::connect_to_env()?;
-        let (globals, event_loop) = registry_queue_init(&conn)?;
-        let qh = event_loop.handle();
-        let state = State::new(&globals, &qh)?;
+fn run_window(target: String, options: window::WindowOptions) -> anyhow::Result<()> {
+    if let Err(err) = window::run(target, options) {
+        error!("{:?}", err);
+        anyhow::bail!("Fatal error");
+    }
+
+    Ok(())
+}
+
+fn run_daemon(systemd: bool, stats: bool) -> anyhow::Result<()> {
+    let notifier = if systemd {
+        match systemd::Notifier::from_env()? {
+            Some(notifier) => Some(Rc::new(notifier)),
+            None => {
+                warn!("`--systemd` is set but `vibe` isn't started as a systemd service with `Type=notify`.");
+                None
+            }
+        }
+    } else {
+        None
+    };
+
+    let signals = match signals::Signals::install() {
+        Ok(signals) => Some(signals),
+        Err(err) => {
+            warn!("Couldn't install the signal handlers: {}", err);
+            None
+        }
+    };
+
+    let server = match ipc::Server::bind() {
+        Ok(server) => Some(server),
+        Err(err) => {
+            warn!("Couldn't start IPC server: {:?}", err);
+            None
+        }
+    };
+
+    // the shaders should continue where they stopped after a restart
+    let start_time = Instant::now();
+    let mut failed_attempts = 0;
+    loop {
+        let session_start = Instant::now();
+
+        match run_session(
+            start_time,
+            server.as_ref(),
+            signals.as_ref(),
+            notifier.as_ref(),
+            stats,
+        ) {
+            Ok(SessionEnd::Exit) => break,
+            Ok(SessionEnd::Restart) => continue,
+            Err(err) => {
+                if session_start.elapsed() >= MIN_SESSION_DURATION {
+                    failed_attempts = 0;
+                }
+
+                failed_attempts += 1;
+                if failed_attempts > MAX_RESTART_ATTEMPTS {
+                    return Err(err.context("Giving up to restart"));
+                }
+
+                warn!(
+                    "{:?}\nRestarting in {:?} (attempt {}/{})...",
+                    err, RESTART_DELAY, failed_attempts, MAX_RESTART_ATTEMPTS
+                );
+                std::thread::sleep(RESTART_DELAY);
+            }
+        }
+    }
+
+    if let Some(notifier) = notifier {
+        if let Err(err) = notifier.stopping() {
+            warn!("Couldn't notify systemd: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+/// Why a session ended without an error.
+enum SessionEnd {
+    Exit,
+    /// The session has to be recreated, for example to switch the GPU.
+    Restart,
+}
+
+/// Connects to the compositor and renders until the daemon should exit.
+///
+/// Returns an error if the connection to the compositor or the GPU got lost.
+fn run_session(
+    start_time: Instant,
+    server: Option<&ipc::Server>,
+    signals: Option<&signals::Signals>,
+    notifier: Option<&Rc<systemd::Notifier>>,
+    stats: bool,
+) -> anyhow::Result<SessionEnd> {
+    let mut event_loop: EventLoop<State> = EventLoop::try_new()?;
+    let conn = Connection::connect_to_env()?;
+
+    let (mut state, qh) = {
+        let conn = conn.clone();
+        let (globals, event_queue) = registry_queue_init(&conn)?;
+        let qh = event_queue.handle();
+        let state = State::new(&globals, &qh, event_loop.handle(), start_time)?;
 
-        (state, event_loop)
+        WaylandSource::new(conn, event_queue)
+            .insert(event_loop.handle())
+            .map_err(|err| err.error)?;
+
+        (state, qh)
     };
 
+    {
+        let qh = qh.clone();
+        event_loop
+            .handle()
+            .insert_source(
+                Timer::from_duration(AUDIO_POLL_INTERVAL),
+                move |_deadline, _, state: &mut State| {
+                    state.poll_audio(&qh);
+                    TimeoutAction::ToDuration(AUDIO_POLL_INTERVAL)
+                },
+            )
+            .map_err(|err| err.error)?;
+    }
+
+    if stats {
+        event_loop
+            .handle()
+            .insert_source(
+                Timer::from_duration(STATS_INTERVAL),
+                |_deadline, _, state: &mut State| {
+                    println!("{}\n", state.stats());
+                    TimeoutAction::ToDuration(STATS_INTERVAL)
+                },
+            )
+            .map_err(|err| err.error)?;
+    }
+
+    if let Some(signals) = signals {
+        let qh = qh.clone();
+        event_loop
+            .handle()
+            .insert_source(
+                Generic::new(signals.try_clone()?, Interest::READ, Mode::Level),
+                move |_readiness, signals, state: &mut State| {
+                    for signal in signals.read()? {
+                        state.handle_signal(signal, &qh);
+                    }
+                    Ok(PostAction::Continue)
+                },
+            )
+            .map_err(|err| err.error)?;
+    }
+
+    if let Some(server) = server {
+        let (sender, channel) = calloop::channel::channel();
+        server.set_sender(sender);
+
+        event_loop
+            .handle()
+            .insert_source(channel, move |event, _, state: &mut State| {
+                if let calloop::channel::Event::Msg(request) = event {
+                    let reply = state.handle_command(request.command, &qh);
+                    let _ = request.reply.send(reply);
+                }
+            })
+            .map_err(|err| err.error)?;
+    }
+
+    if let Some(notifier) = notifier {
+        init_systemd(&event_loop, notifier)?;
+    }
+
     while state.run {
-        event_loop.blocking_dispatch(&mut state)?;
+        event_loop
+            .dispatch(None, &mut state)
+            .context("Lost the connection to the compositor")?;
+
+        if state.is_device_lost() {
+            anyhow::bail!("Lost the GPU device");
+        }
+
+        if state.restart {
+            return Ok(SessionEnd::Restart);
+        }
+    }
+
+    // send the destruction of the layer surfaces before the connection gets closed
+    let _ = conn.flush();
+    Ok(SessionEnd::Exit)
+}
+
+/// Tells systemd that we are ready and keeps its watchdog alive as long as the event loop is running.
+fn init_systemd(
+    event_loop: &EventLoop<State>,
+    notifier: &Rc<systemd::Notifier>,
+) -> anyhow::Result<()> {
+    if let Some(interval) = systemd::watchdog_interval() {
+        let notifier = notifier.clone();
+        event_loop
+            .handle()
+            .insert_source(Timer::immediate(), move |_deadline, _, _state| {
+                if let Err(err) = notifier.watchdog() {
+                    warn!("Couldn't notify the watchdog of systemd: {}", err);
+                }
+                TimeoutAction::ToDuration(interval)
+            })
+            .map_err(|err| err.error)?;
     }
 
+    notifier.ready()?;
     Ok(())
 }
 
@@ -97,7 +447,28 @@ pub fn get_output_config_dir() -> PathBuf {
         .unwrap()
 }
 
+/// Returns the path to the directory where the presets lie.
+/// Each preset has the form `<preset-name>.toml`.
+pub fn get_preset_dir() -> PathBuf {
+    get_xdg().create_config_directory(PRESET_DIR_NAME).unwrap()
+}
+
 /// Returns the path to the config file of `vibe`.
 pub fn get_config_path() -> PathBuf {
     get_xdg().place_config_file(CONFIG_FILE_NAME).unwrap()
 }
+
+/// Returns the path to the output config which is used by all outputs without their own config file.
+pub fn get_all_outputs_config_path() -> PathBuf {
+    get_xdg()
+        .place_config_file(ALL_OUTPUTS_CONFIG_FILE_NAME)
+        .unwrap()
+}
+
+/// Replaces a leading `~` of the path with the home directory.
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
@@ -0,0 +1,310 @@
+//! Upgrades config files which were written for older versions of `vibe`.
+//!
+//! Each config file stores the `version` of its format. Files without a version are treated as version `0`.
+//! If the format of a config changes, add a migration to [MIGRATIONS] which upgrades the files
+//! of the previous version, so existing configs of users keep working.
+use std::path::{Path, PathBuf};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use toml::{Table, Value};
+use tracing::{info, warn};
+
+use crate::config::ConfigError;
+
+const VERSION_KEY: &str = "version";
+
+/// Upgrades a config file from version `i` to version `i + 1` where `i` is the index of the migration.
+type Migration = fn(&mut Table);
+
+const MIGRATIONS: &[Migration] = &[move_texture_path];
+
+/// The version of the config files which this version of `vibe` writes.
+pub const CURRENT_VERSION: u32 = MIGRATIONS.len() as u32;
+
+/// The version of the format of a config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Version(pub u32);
+
+impl Default for Version {
+    fn default() -> Self {
+        Self(CURRENT_VERSION)
+    }
+}
+
+/// An upgrade of a config to the current version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Upgrade {
+    old_version: u32,
+    // `false` if only the version changed
+    changed: bool,
+}
+
+/// Parses the content of a config file and upgrades it to the current version in memory.
+pub fn parse<T: DeserializeOwned>(content: &str) -> Result<T, ConfigError> {
+    let mut table: Table = toml::from_str(content)?;
+    migrate(&mut table)?;
+
+    Ok(Value::Table(table).try_into()?)
+}
+
+/// Reads the config file and upgrades it to the current version.
+///
+/// Upgraded files are written back. If a migration changed the content, the original file
+/// is kept as `<file>.v<version>.back` (the comments are lost in this case). Otherwise only
+/// the `version` is added to the file.
+pub fn load<T: DeserializeOwned>(path: &Path) -> Result<T, ConfigError> {
+    let content = std::fs::read_to_string(path)?;
+    let mut table: Table = toml::from_str(&content)?;
+
+    if let Some(upgrade) = migrate(&mut table)? {
+        let result = if upgrade.changed {
+            let backup_path = backup_path(path, upgrade.old_version);
+            info!(
+                "Upgraded '{}' from version {} to {}. The old file is saved as '{}'.",
+                path.to_string_lossy(),
+                upgrade.old_version,
+                CURRENT_VERSION,
+                backup_path.to_string_lossy()
+            );
+
+            std::fs::copy(path, &backup_path)
+                .and_then(|_| std::fs::write(path, toml::to_string(&table).unwrap()))
+        } else {
+            info!(
+                "Set the version of '{}' to {}.",
+                path.to_string_lossy(),
+                CURRENT_VERSION
+            );
+
+            std::fs::write(path, with_version(&content, upgrade.old_version))
+        };
+
+        if let Err(err) = result {
+            warn!(
+                "Couldn't save the upgraded config file '{}': {}",
+                path.to_string_lossy(),
+                err
+            );
+        }
+    }
+
+    Ok(Value::Table(table).try_into()?)
+}
+
+/// Upgrades the config to the current version.
+///
+/// Returns `None` if the config already has the current version.
+fn migrate(table: &mut Table) -> Result<Option<Upgrade>, ConfigError> {
+    let version = match table.get(VERSION_KEY) {
+        None => 0,
+        Some(Value::Integer(version)) => {
+            u32::try_from(*version).map_err(|_| ConfigError::InvalidVersion(version.to_string()))?
+        }
+        Some(other) => return Err(ConfigError::InvalidVersion(other.to_string())),
+    };
+
+    if version > CURRENT_VERSION {
+        return Err(ConfigError::UnsupportedVersion(version));
+    }
+
+    if version == CURRENT_VERSION {
+        return Ok(None);
+    }
+
+    let original = table.clone();
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(table);
+    }
+    let changed = *table != original;
+
+    table.insert(
+        VERSION_KEY.to_string(),
+        Value::Integer(CURRENT_VERSION.into()),
+    );
+
+    Ok(Some(Upgrade {
+        old_version: version,
+        changed,
+    }))
+}
+
+/// Sets the `version` of the content of an unchanged config file without touching the rest of it.
+fn with_version(content: &str, old_version: u32) -> String {
+    // the key has to come before the first table, so it's placed at the top of files without a version
+    match old_version_line(content, old_version) {
+        Some(line) => {
+            let (key, value) = line.split_once('=').unwrap_or_default();
+            let comment = value
+                .trim_start()
+                .strip_prefix(&old_version.to_string())
+                .unwrap_or_default();
+
+            content.replacen(line, &format!("{}= {}{}", key, CURRENT_VERSION, comment), 1)
+        }
+        None => format!("{} = {}\n{}", VERSION_KEY, CURRENT_VERSION, content),
+    }
+}
+
+/// Returns the line of the top-level `version` key of the content.
+fn old_version_line(content: &str, old_version: u32) -> Option<&str> {
+    content
+        .lines()
+        .take_while(|line| !line.trim_start().starts_with('['))
+        .find(|line| {
+            line.split_once('=').is_some_and(|(key, value)| {
+                key.trim() == VERSION_KEY
+                    && value.split('#').next().unwrap_or_default().trim() == old_version.to_string()
+            })
+        })
+}
+
+fn backup_path(path: &Path, version: u32) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_os_string();
+    file_name.push(format!(".v{}.back", version));
+
+    path.with_file_name(file_name)
+}
+
+/// Returns the tables of all component configs inside the config file.
+///
+/// Output configs and presets contain `components`, the main config a `default_component`
+/// and the `components` of its `default_output_configs`.
+fn component_tables(table: &mut Table) -> Vec<&mut Table> {
+    let mut tables = Vec::new();
+
+    for (key, value) in table.iter_mut() {
+        match (key.as_str(), value) {
+            ("components", Value::Array(components)) => {
+                tables.extend(components.iter_mut().filter_map(Value::as_table_mut));
+            }
+            ("default_component", Value::Table(component)) => tables.push(component),
+            ("default_output_configs", Value::Array(configs)) => {
+                for config in configs.iter_mut().filter_map(Value::as_table_mut) {
+                    tables.extend(component_tables(config));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    tables
+}
+
+/// 0 -> 1: `FragmentCanvas.texture_path` moved to `FragmentCanvas.texture.path`.
+fn move_texture_path(table: &mut Table) {
+    for component in component_tables(table) {
+        let Some(Value::Table(canvas)) = component.get_mut("FragmentCanvas") else {
+            continue;
+        };
+
+        if let Some(path) = canvas.remove("texture_path") {
+            canvas.insert(
+                "texture".to_string(),
+                Value::Table(Table::from_iter([("path".to_string(), path)])),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OLD_FRAGMENT_CANVAS: &str = r#"
+enable = true
+
+[[components]]
+[components.FragmentCanvas]
+texture_path = "/tmp/img.png"
+[components.FragmentCanvas.audio_conf]
+amount_bars = 60
+sensitivity = 4.5
+freq_range = "Bass"
+[components.FragmentCanvas.fragment_code]
+language = "Wgsl"
+path = "/tmp/fragment_code.wgsl"
+"#;
+
+    #[test]
+    fn upgrade_texture_path() {
+        let mut table: Table = toml::from_str(OLD_FRAGMENT_CANVAS).unwrap();
+
+        assert_eq!(
+            migrate(&mut table).unwrap(),
+            Some(Upgrade {
+                old_version: 0,
+                changed: true
+            })
+        );
+        assert_eq!(table[VERSION_KEY], Value::Integer(CURRENT_VERSION.into()));
+
+        let canvas = &table["components"][0]["FragmentCanvas"];
+        assert!(canvas.get("texture_path").is_none());
+        assert_eq!(canvas["texture"]["path"].as_str(), Some("/tmp/img.png"));
+
+        let config: crate::output::config::OutputConfig = parse(OLD_FRAGMENT_CANVAS).unwrap();
+        assert_eq!(config.version, Version(CURRENT_VERSION));
+    }
+
+    #[test]
+    fn current_version_is_untouched() {
+        let content = format!(
+            "version = {}\nenable = true\ncomponents = []",
+            CURRENT_VERSION
+        );
+        let mut table: Table = toml::from_str(&content).unwrap();
+
+        assert_eq!(migrate(&mut table).unwrap(), None);
+        assert_eq!(table, toml::from_str::<Table>(&content).unwrap());
+    }
+
+    #[test]
+    fn version_bump_keeps_comments() {
+        let content = "# my bars\nenable = true # on all outputs\ncomponents = []\n";
+        let mut table: Table = toml::from_str(content).unwrap();
+
+        assert_eq!(
+            migrate(&mut table).unwrap(),
+            Some(Upgrade {
+                old_version: 0,
+                changed: false
+            })
+        );
+
+        let upgraded = with_version(content, 0);
+        assert_eq!(
+            upgraded,
+            format!("{} = {}\n{}", VERSION_KEY, CURRENT_VERSION, content)
+        );
+        assert_eq!(toml::from_str::<Table>(&upgraded).unwrap(), table);
+
+        let explicit = "version = 0 # old\n[section]\nversion = 0\n";
+        assert_eq!(
+            with_version(explicit, 0),
+            format!(
+                "{} = {} # old\n[section]\nversion = 0\n",
+                VERSION_KEY, CURRENT_VERSION
+            )
+        );
+    }
+
+    #[test]
+    fn reject_newer_version() {
+        let mut table: Table =
+            toml::from_str(&format!("version = {}", CURRENT_VERSION + 1)).unwrap();
+
+        assert!(matches!(
+            migrate(&mut table),
+            Err(ConfigError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn backup_path_contains_version() {
+        assert_eq!(
+            backup_path(Path::new("/config/DP-1.toml"), 0),
+            PathBuf::from("/config/DP-1.toml.v0.back")
+        );
+    }
+}
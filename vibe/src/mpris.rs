@@ -0,0 +1,266 @@
+//! Follows the track of the media player through [MPRIS](https://specifications.freedesktop.org/mpris-spec/latest/).
+//!
+//! The metadata is read with [`playerctl`](https://github.com/altdesktop/playerctl) which has to be installed.
+use std::{
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, BufRead, BufReader},
+    path::PathBuf,
+    process::{Child, Command, Stdio},
+};
+
+use serde::{Deserialize, Serialize};
+use smithay_client_toolkit::reexports::calloop::channel::Sender;
+use tracing::{debug, warn};
+
+use crate::colors::Palette;
+
+// `playerctl` replaces the placeholders of the format with the metadata of the track
+const FORMAT: &str = "{{title}}\t{{artist}}\t{{album}}\t{{mpris:artUrl}}";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MprisConfig {
+    /// The name of the player which should be followed (see `playerctl --list-all`).
+    /// Follows the player which got active most recently if not set.
+    pub player: Option<String>,
+    /// Use the colors of the album art as `iColors` while the track is playing.
+    pub colors_from_art: bool,
+}
+
+/// The metadata of a track.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub art_url: Option<String>,
+    /// The colors of the album art if `colors_from_art` is enabled.
+    pub palette: Option<Palette>,
+}
+
+impl Track {
+    /// Parses a line of `playerctl` with [FORMAT]. Returns `None` if no track is playing.
+    fn parse(line: &str) -> Option<Self> {
+        let mut fields = line.trim_end_matches(['\r', '\n']).split('\t');
+        let mut next_field = || fields.next().unwrap_or_default().to_string();
+
+        let track = Self {
+            title: next_field(),
+            artist: next_field(),
+            album: next_field(),
+            art_url: Some(next_field()).filter(|url| !url.is_empty()),
+            palette: None,
+        };
+
+        let is_empty = track.title.is_empty() && track.artist.is_empty() && track.album.is_empty();
+        (!is_empty).then_some(track)
+    }
+
+    /// Returns a value within `[0, 1)` which identifies the track (`iTrack.y`).
+    pub fn id(&self) -> f32 {
+        let mut hasher = DefaultHasher::new();
+        (&self.title, &self.artist, &self.album).hash(&mut hasher);
+
+        // use the 24 bits which fit into the mantissa of a f32
+        (hasher.finish() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Returns the path of the album art if it's a local file.
+    pub fn art_path(&self) -> Option<PathBuf> {
+        let path = self.art_url.as_ref()?.strip_prefix("file://")?;
+        Some(PathBuf::from(percent_decode(path)))
+    }
+}
+
+/// Runs `playerctl` in the background and sends the playing track to the event loop
+/// each time it changes. `None` is sent if the player stopped.
+///
+/// `playerctl` is stopped once the listener is dropped.
+pub struct Listener {
+    child: Child,
+}
+
+impl Listener {
+    pub fn spawn(config: &MprisConfig, sender: Sender<Option<Track>>) -> io::Result<Self> {
+        let mut command = Command::new("playerctl");
+        if let Some(player) = &config.player {
+            command.arg("--player").arg(player);
+        }
+
+        let mut child = command
+            .args(["--follow", "metadata", "--format", FORMAT])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+
+        let stdout = child.stdout.take().unwrap();
+        let colors_from_art = config.colors_from_art;
+        std::thread::Builder::new()
+            .name("MPRIS listener".to_string())
+            .spawn(move || {
+                let mut last_track = None;
+
+                // the loop ends once `playerctl` got killed
+                for line in BufReader::new(stdout).lines() {
+                    let Ok(line) = line else {
+                        break;
+                    };
+
+                    let mut track = Track::parse(&line);
+                    // `playerctl` repeats the metadata if only other properties (like the art url) changed
+                    if track == last_track {
+                        continue;
+                    }
+                    last_track = track.clone();
+
+                    if let Some(track) = track.as_mut().filter(|_| colors_from_art) {
+                        track.palette = track.art_path().and_then(|path| {
+                            image::open(&path)
+                                .inspect_err(|err| {
+                                    warn!(
+                                        "Couldn't load album art '{}': {}",
+                                        path.to_string_lossy(),
+                                        err
+                                    )
+                                })
+                                .ok()
+                                .map(|img| crate::colors::palette_from_image(&img))
+                        });
+                    }
+
+                    debug!("Track changed: {:?}", track);
+                    if sender.send(track).is_err() {
+                        break;
+                    }
+                }
+            })?;
+
+        Ok(Self { child })
+    }
+}
+
+impl Drop for Listener {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// The track which is playing right now.
+pub struct Player {
+    track: Option<Track>,
+    // the amount of track changes since the start
+    changes: u64,
+    // the time (in seconds since the start) of the last track change, `-1` if it didn't change yet
+    change_time: f32,
+}
+
+impl Player {
+    pub fn new() -> Self {
+        Self {
+            track: None,
+            changes: 0,
+            change_time: -1.,
+        }
+    }
+
+    pub fn set_track(&mut self, track: Option<Track>, time: f32) {
+        if track.is_some() {
+            self.changes += 1;
+            self.change_time = time;
+        }
+
+        self.track = track;
+    }
+
+    pub fn track(&self) -> Option<&Track> {
+        self.track.as_ref()
+    }
+
+    /// Returns how often a new track started playing.
+    pub fn changes(&self) -> u64 {
+        self.changes
+    }
+
+    pub fn change_time(&self) -> f32 {
+        self.change_time
+    }
+}
+
+/// Decodes the `%XX` escapes of an url.
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| input.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_playerctl_line() {
+        let track = Track::parse("Song\tBand\tAlbum\tfile:///tmp/My%20Cover.png\n").unwrap();
+
+        assert_eq!(track.title, "Song");
+        assert_eq!(track.artist, "Band");
+        assert_eq!(track.album, "Album");
+        assert_eq!(track.art_path(), Some(PathBuf::from("/tmp/My Cover.png")));
+
+        let track = Track::parse("Stream\t\t\t").unwrap();
+        assert_eq!(track.art_url, None);
+
+        // the player stopped
+        assert_eq!(Track::parse("\t\t\t"), None);
+        assert_eq!(Track::parse(""), None);
+    }
+
+    #[test]
+    fn remote_art_is_ignored() {
+        let track = Track::parse("Song\tBand\tAlbum\thttps://example.com/cover.png").unwrap();
+        assert_eq!(track.art_path(), None);
+    }
+
+    #[test]
+    fn track_id_is_stable() {
+        let song = Track::parse("Song\tBand\tAlbum\t").unwrap();
+        let other = Track::parse("Other song\tBand\tAlbum\t").unwrap();
+
+        assert_eq!(song.id(), song.clone().id());
+        assert_ne!(song.id(), other.id());
+        assert!((0. ..1.).contains(&song.id()));
+    }
+
+    #[test]
+    fn count_track_changes() {
+        let mut player = Player::new();
+        assert_eq!(player.change_time(), -1.);
+
+        player.set_track(Track::parse("Song\tBand\tAlbum\t"), 3.);
+        player.set_track(None, 5.);
+        assert_eq!(player.changes(), 1);
+        assert_eq!(player.change_time(), 3.);
+        assert!(player.track().is_none());
+    }
+}
@@ -0,0 +1,163 @@
+//! Creates commented starter configs for outputs.
+use std::path::PathBuf;
+
+use clap::ValueEnum;
+use smithay_client_toolkit::{
+    delegate_output, delegate_registry,
+    output::{OutputHandler, OutputState},
+    registry::{ProvidesRegistryState, RegistryState},
+    registry_handlers,
+};
+use tracing::warn;
+use wayland_client::{
+    globals::registry_queue_init, protocol::wl_output::WlOutput, Connection, QueueHandle,
+};
+
+/// The component which a new config starts with.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Template {
+    Bars,
+    Graph,
+    FragmentCanvas,
+}
+
+impl Template {
+    fn content(&self) -> &'static str {
+        match self {
+            Self::Bars => include_str!("./output/config/templates/bars.toml"),
+            Self::Graph => include_str!("./output/config/templates/graph.toml"),
+            Self::FragmentCanvas => include_str!("./output/config/templates/fragment_canvas.toml"),
+        }
+    }
+}
+
+/// Writes the template as the config of the given output.
+///
+/// Lists the detected outputs instead if no output is given.
+pub fn run(output: Option<String>, template: Template, force: bool) -> anyhow::Result<()> {
+    let detected_outputs = match detect_outputs() {
+        Ok(outputs) => Some(outputs),
+        Err(err) => {
+            warn!("Couldn't detect the outputs: {}", err);
+            None
+        }
+    };
+
+    let Some(output) = output else {
+        let Some(outputs) = detected_outputs else {
+            anyhow::bail!("Please pass the name of the output to `vibe config new`.");
+        };
+
+        println!("Detected outputs:");
+        for output in outputs {
+            let status = if config_path(&output).exists() {
+                "has a config"
+            } else if crate::get_all_outputs_config_path().exists() {
+                "uses all-outputs.toml"
+            } else {
+                "no config yet"
+            };
+            println!("  {} ({})", output, status);
+        }
+        println!("\nRun `vibe config new <output>` to create a config for one of them.");
+
+        return Ok(());
+    };
+
+    if detected_outputs.is_some_and(|outputs| !outputs.contains(&output)) {
+        warn!(
+            "The output '{}' isn't connected. The config will be used once it is.",
+            output
+        );
+    }
+
+    let path = config_path(&output);
+    if path.exists() && !force {
+        anyhow::bail!(
+            "'{}' exists already. Use `--force` to overwrite it.",
+            path.to_string_lossy()
+        );
+    }
+
+    std::fs::write(&path, template.content())?;
+    println!("Created '{}'.", path.to_string_lossy());
+
+    Ok(())
+}
+
+fn config_path(output: &str) -> PathBuf {
+    let mut path = crate::get_output_config_dir();
+    path.push(format!("{}.toml", output));
+    path
+}
+
+/// Collects the outputs of the compositor.
+struct OutputLister {
+    registry_state: RegistryState,
+    output_state: OutputState,
+}
+
+/// Returns the names of the outputs of the compositor.
+fn detect_outputs() -> anyhow::Result<Vec<String>> {
+    let conn = Connection::connect_to_env()?;
+    let (globals, mut event_queue) = registry_queue_init(&conn)?;
+    let qh = event_queue.handle();
+
+    let mut lister = OutputLister {
+        registry_state: RegistryState::new(&globals),
+        output_state: OutputState::new(&globals, &qh),
+    };
+
+    // the first roundtrip binds the outputs, the second one receives their information
+    event_queue.roundtrip(&mut lister)?;
+    event_queue.roundtrip(&mut lister)?;
+
+    let mut names: Vec<String> = lister
+        .output_state
+        .outputs()
+        .filter_map(|output| lister.output_state.info(&output))
+        .filter_map(|info| info.name)
+        .collect();
+    names.sort();
+
+    Ok(names)
+}
+
+delegate_output!(OutputLister);
+impl OutputHandler for OutputLister {
+    fn output_state(&mut self) -> &mut OutputState {
+        &mut self.output_state
+    }
+
+    fn new_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {}
+
+    fn update_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {}
+
+    fn output_destroyed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {
+    }
+}
+
+delegate_registry!(OutputLister);
+impl ProvidesRegistryState for OutputLister {
+    fn registry(&mut self) -> &mut RegistryState {
+        &mut self.registry_state
+    }
+
+    registry_handlers![OutputState];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::config::OutputConfig;
+
+    #[test]
+    fn templates_are_valid_configs() {
+        for template in Template::value_variants() {
+            match toml::from_str::<OutputConfig>(template.content()) {
+                Ok(config) => assert_eq!(config.version.0, crate::migration::CURRENT_VERSION),
+                Err(err) => panic!("{:?}: {}", template, err),
+            }
+        }
+    }
+}
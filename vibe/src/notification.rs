@@ -0,0 +1,75 @@
+//! Shows errors as desktop notifications since the log of the daemon is usually hidden.
+//!
+//! The notifications are sent with `notify-send` (usually part of `libnotify`).
+//! Nothing happens if it isn't installed.
+use std::process::{Command, Stdio};
+
+use tracing::debug;
+
+const NOTIFY_SEND: &str = "notify-send";
+
+/// The maximal amount of lines of the body. Notification daemons don't show much more anyways.
+const MAX_BODY_LINES: usize = 10;
+
+/// Sends a desktop notification about an error without blocking.
+pub fn error(summary: &str, body: &str) {
+    let mut command = Command::new(NOTIFY_SEND);
+    command
+        .args([
+            "--app-name",
+            crate::APP_NAME,
+            "--urgency",
+            "critical",
+            summary,
+            &shorten(body),
+        ])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+
+    std::thread::spawn(move || {
+        if let Err(err) = command.status() {
+            debug!(
+                "Couldn't send the notification with `{}`: {}",
+                NOTIFY_SEND, err
+            );
+        }
+    });
+}
+
+fn shorten(body: &str) -> String {
+    let mut lines = body.lines();
+    let mut shortened = lines
+        .by_ref()
+        .take(MAX_BODY_LINES)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    if lines.next().is_some() {
+        shortened.push_str("\n…");
+    }
+
+    shortened
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_body_is_kept() {
+        assert_eq!(shorten("a\nb"), "a\nb");
+    }
+
+    #[test]
+    fn long_body_is_cut() {
+        let body = (0..20)
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let shortened = shorten(&body);
+        assert_eq!(shortened.lines().count(), MAX_BODY_LINES + 1);
+        assert!(shortened.ends_with("9\n…"));
+    }
+}
@@ -1,5 +1,6 @@
-use super::{ComponentConfig, FreqRange, Rgb};
+use super::{ComponentConfig, FreqRange, Rgb, Viewport};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use vibe_audio::{fetcher::Fetcher, SampleProcessor};
 use vibe_renderer::{
     components::{Aurodio, AurodioDescriptor, AurodioLayerDescriptor},
@@ -12,6 +13,18 @@ pub struct AurodioConfig {
     pub movement_speed: f32,
     pub audio_conf: AurodioAudioConfig,
     pub layers: Vec<AurodioLayerConfig>,
+    /// Only render the component within this rectangle of the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
+    /// Replaces the colors of `colors.toml` (`iColors` and `iPalette`) for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Vec<[f32; 3]>>,
+    /// Named values which the component can read. Changing them only updates the running component.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, f32>,
+    /// Renders the component with this fraction of the resolution of the output and upscales it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_scale: Option<f32>,
 }
 
 impl ComponentConfig for AurodioConfig {
@@ -20,7 +33,7 @@ impl ComponentConfig for AurodioConfig {
         renderer: &Renderer,
         processor: &SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn vibe_renderer::ComponentAudio<F>>, super::ConfigError> {
+    ) -> Result<Box<dyn vibe_renderer::Component>, super::ConfigError> {
         let layers: Vec<AurodioLayerDescriptor> = self
             .layers
             .iter()
@@ -1,8 +1,8 @@
 use crate::output::config::component::ComponentConfig;
 
-use super::{FreqRange, Rgba};
+use super::{FreqRange, Rgba, Viewport};
 use serde::{Deserialize, Serialize};
-use std::num::NonZero;
+use std::{collections::HashMap, num::NonZero};
 use vibe_audio::fetcher::Fetcher;
 use vibe_renderer::components::{
     BarVariant, Bars, BarsDescriptor, BarsFormat, BarsPlacement, Pixels,
@@ -15,6 +15,18 @@ pub struct BarsConfig {
     pub variant: BarsVariantConfig,
     pub placement: BarsPlacementConfig,
     pub format: BarsFormatConfig,
+    /// Only render the component within this rectangle of the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
+    /// Replaces the colors of `colors.toml` (`iColors` and `iPalette`) for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Vec<[f32; 3]>>,
+    /// Named values which the component can read. Changing them only updates the running component.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, f32>,
+    /// Renders the component with this fraction of the resolution of the output and upscales it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_scale: Option<f32>,
 }
 
 impl ComponentConfig for BarsConfig {
@@ -23,7 +35,7 @@ impl ComponentConfig for BarsConfig {
         renderer: &vibe_renderer::Renderer,
         processor: &vibe_audio::SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn vibe_renderer::ComponentAudio<F>>, super::ConfigError> {
+    ) -> Result<Box<dyn vibe_renderer::Component>, super::ConfigError> {
         let variant = match &self.variant {
             BarsVariantConfig::Color(rgba) => BarVariant::Color(rgba.as_f32()),
             BarsVariantConfig::PresenceGradient {
@@ -78,6 +90,10 @@ impl Default for BarsConfig {
             variant: BarsVariantConfig::Color(Rgba::TURQUOISE),
             placement: BarsPlacementConfig::Bottom,
             format: BarsFormatConfig::BassTreble,
+            viewport: None,
+            palette: None,
+            params: HashMap::new(),
+            render_scale: None,
         }
     }
 }
@@ -1,8 +1,8 @@
 use crate::output::config::component::ComponentConfig;
 
-use super::FreqRange;
+use super::{FreqRange, Viewport};
 use serde::{Deserialize, Serialize};
-use std::num::NonZero;
+use std::{collections::HashMap, num::NonZero};
 use vibe_audio::{fetcher::Fetcher, BarProcessorConfig};
 use vibe_renderer::{
     components::{Chessy, ChessyDescriptor},
@@ -15,6 +15,18 @@ pub struct ChessyConfig {
     pub pattern: SdfPattern,
     pub zoom_factor: f32,
     pub audio_conf: ChessyAudioConfig,
+    /// Only render the component within this rectangle of the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
+    /// Replaces the colors of `colors.toml` (`iColors` and `iPalette`) for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Vec<[f32; 3]>>,
+    /// Named values which the component can read. Changing them only updates the running component.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, f32>,
+    /// Renders the component with this fraction of the resolution of the output and upscales it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_scale: Option<f32>,
 }
 
 impl ComponentConfig for ChessyConfig {
@@ -23,16 +35,16 @@ impl ComponentConfig for ChessyConfig {
         renderer: &vibe_renderer::Renderer,
         processor: &vibe_audio::SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn vibe_renderer::ComponentAudio<F>>, super::ConfigError> {
+    ) -> Result<Box<dyn vibe_renderer::Component>, super::ConfigError> {
         Ok(Box::new(Chessy::new(&ChessyDescriptor {
             renderer,
             sample_processor: processor,
             audio_config: vibe_audio::BarProcessorConfig::from(&self.audio_conf),
             texture_format,
             movement_speed: self.movement_speed,
-            pattern: self.pattern,
+            pattern: self.pattern.clone(),
             zoom_factor: self.zoom_factor,
-        })))
+        })?))
     }
 
     fn external_paths(&self) -> Vec<std::path::PathBuf> {
@@ -1,8 +1,8 @@
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 use crate::output::config::component::ComponentConfig;
 
-use super::FreqRange;
+use super::{FreqRange, Viewport};
 use image::ImageReader;
 use serde::{Deserialize, Serialize};
 use vibe_audio::fetcher::Fetcher;
@@ -10,6 +10,7 @@ use vibe_renderer::components::live_wallpaper;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WallpaperPulseEdgesConfig {
+    /// The path to the image or `auto` to use the current wallpaper.
     pub wallpaper_path: PathBuf,
     pub audio_conf: WallpaperPulseEdgesAudioConfig,
 
@@ -19,6 +20,18 @@ pub struct WallpaperPulseEdgesConfig {
     pub pulse_brightness: f32,
 
     pub gaussian_blur: WallpaperPulseEdgesGaussianBlur,
+    /// Only render the component within this rectangle of the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
+    /// Replaces the colors of `colors.toml` (`iColors` and `iPalette`) for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Vec<[f32; 3]>>,
+    /// Named values which the component can read. Changing them only updates the running component.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, f32>,
+    /// Renders the component with this fraction of the resolution of the output and upscales it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_scale: Option<f32>,
 }
 
 impl ComponentConfig for WallpaperPulseEdgesConfig {
@@ -27,10 +40,12 @@ impl ComponentConfig for WallpaperPulseEdgesConfig {
         renderer: &vibe_renderer::Renderer,
         processor: &vibe_audio::SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn vibe_renderer::ComponentAudio<F>>, super::ConfigError> {
-        let img = ImageReader::open(&self.wallpaper_path)
+    ) -> Result<Box<dyn vibe_renderer::Component>, super::ConfigError> {
+        let wallpaper_path = crate::current_wallpaper::resolve(&self.wallpaper_path)
+            .ok_or(super::ConfigError::NoCurrentWallpaper)?;
+        let img = ImageReader::open(&wallpaper_path)
             .map_err(|err| super::ConfigError::OpenFile {
-                path: self.wallpaper_path.to_string_lossy().to_string(),
+                path: wallpaper_path.to_string_lossy().to_string(),
                 reason: err,
             })?
             .decode()?;
@@ -62,7 +77,10 @@ impl ComponentConfig for WallpaperPulseEdgesConfig {
     }
 
     fn external_paths(&self) -> Vec<PathBuf> {
-        vec![self.wallpaper_path.clone()]
+        // the current wallpaper if it's `auto`
+        crate::current_wallpaper::resolve(&self.wallpaper_path)
+            .into_iter()
+            .collect()
     }
 }
 
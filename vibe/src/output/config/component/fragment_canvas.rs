@@ -1,12 +1,13 @@
 use crate::output::config::component::{ComponentConfig, ConfigError};
 
-use super::FreqRange;
+use super::{FreqRange, Viewport};
 use image::{DynamicImage, ImageReader};
 use serde::{Deserialize, Serialize};
-use std::{num::NonZero, path::PathBuf};
+use std::{collections::HashMap, num::NonZero, path::PathBuf};
 use vibe_audio::{fetcher::Fetcher, BarProcessorConfig};
-use vibe_renderer::components::{
-    FragmentCanvas, FragmentCanvasDescriptor, ShaderCode, ShaderSource,
+use vibe_renderer::{
+    components::{FragmentCanvas, FragmentCanvasDescriptor, ShaderCode, ShaderSource},
+    readback::ReadbackSlot,
 };
 
 #[derive(thiserror::Error, Debug)]
@@ -25,6 +26,23 @@ pub struct FragmentCanvasConfig {
     pub fragment_code: ShaderCode,
 
     pub texture: Option<FragmentCanvasTexture>,
+    /// Only render the component within this rectangle of the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
+    /// Replaces the colors of `colors.toml` (`iColors` and `iPalette`) for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Vec<[f32; 3]>>,
+    /// The members of the `iParams` uniform struct of the shader (for example `{ speed = 2.0 }`).
+    /// Changing their values only updates the running component.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, f32>,
+    /// Renders the component with this fraction of the resolution of the output and upscales it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_scale: Option<f32>,
+    /// Named outputs of the shader which are read back after each frame and sent to `events`
+    /// (for example `{ name = "score", index = 0 }` for `iOutputs[0]`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub readback: Vec<ReadbackSlot>,
 }
 
 impl ComponentConfig for FragmentCanvasConfig {
@@ -33,7 +51,7 @@ impl ComponentConfig for FragmentCanvasConfig {
         renderer: &vibe_renderer::Renderer,
         processor: &vibe_audio::SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn vibe_renderer::ComponentAudio<F>>, ConfigError> {
+    ) -> Result<Box<dyn vibe_renderer::Component>, ConfigError> {
         let img = match &self.texture {
             None => None,
             Some(texture) => match texture.load() {
@@ -63,6 +81,8 @@ impl ComponentConfig for FragmentCanvasConfig {
             format: texture_format,
             fragment_code: self.fragment_code.clone(),
             img,
+            readback_slots: self.readback.clone(),
+            params: self.params.clone(),
         })?;
 
         Ok(Box::new(fragment_canvas))
@@ -165,6 +185,11 @@ mod tests {
                 source: ShaderSource::Code("@fragment\nfn main(@builtin(position) pos: vec4f) -> @location(0) { return textureSample(iTexture, iSampler, pos.xy/iResolution.xy); }".to_string()),
             },
             texture: None,
+            viewport: None,
+            palette: None,
+            params: HashMap::new(),
+            render_scale: None,
+            readback: Vec::new(),
         });
 
             let err = config
@@ -195,6 +220,11 @@ mod tests {
                 source: ShaderSource::Code("void main() { fragColor = texture(sampler2D(iTexture, iSampler), gl_FragCoord.xy/iResolution.xy); }".to_string()),
             },
             texture: None,
+            viewport: None,
+            palette: None,
+            params: HashMap::new(),
+            render_scale: None,
+            readback: Vec::new(),
         });
 
             let err = config
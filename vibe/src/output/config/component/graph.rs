@@ -1,9 +1,9 @@
 use crate::output::config::component::ComponentConfig;
 
-use super::{FreqRange, Rgba};
+use super::{FreqRange, Rgba, Viewport};
 use cgmath::Deg;
 use serde::{Deserialize, Serialize};
-use std::num::NonZero;
+use std::{collections::HashMap, num::NonZero};
 use vibe_audio::fetcher::Fetcher;
 use vibe_renderer::components::{
     Graph, GraphDescriptor, GraphFormat, GraphPlacement, GraphVariant,
@@ -11,11 +11,23 @@ use vibe_renderer::components::{
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GraphConfig {
-    audio_conf: GraphAudioConfig,
+    pub audio_conf: GraphAudioConfig,
     max_height: f32,
     variant: GraphVariantConfig,
     placement: GraphPlacementConfig,
     format: GraphFormatConfig,
+    /// Only render the component within this rectangle of the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
+    /// Replaces the colors of `colors.toml` (`iColors` and `iPalette`) for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Vec<[f32; 3]>>,
+    /// Named values which the component can read. Changing them only updates the running component.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, f32>,
+    /// Renders the component with this fraction of the resolution of the output and upscales it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_scale: Option<f32>,
 }
 
 impl ComponentConfig for GraphConfig {
@@ -24,7 +36,7 @@ impl ComponentConfig for GraphConfig {
         renderer: &vibe_renderer::Renderer,
         processor: &vibe_audio::SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn vibe_renderer::ComponentAudio<F>>, super::ConfigError> {
+    ) -> Result<Box<dyn vibe_renderer::Component>, super::ConfigError> {
         let variant = GraphVariant::from(&self.variant);
         let placement = GraphPlacement::from(&self.placement);
 
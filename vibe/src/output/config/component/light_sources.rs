@@ -1,8 +1,8 @@
-use super::ConfigError;
+use super::{ConfigError, Viewport};
 use crate::output::config::component::ComponentConfig;
 use image::ImageReader;
 use serde::{Deserialize, Serialize};
-use std::{num::NonZero, ops::Range, path::PathBuf};
+use std::{collections::HashMap, num::NonZero, ops::Range, path::PathBuf};
 use vibe_audio::fetcher::Fetcher;
 use vibe_renderer::components::live_wallpaper::light_sources::{
     LightSourceData, LightSources, LightSourcesDescriptor,
@@ -17,6 +17,7 @@ pub enum LightSourcesError {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LightSourcesConfig {
+    /// The path to the image or `auto` to use the current wallpaper.
     pub wallpaper_path: PathBuf,
 
     pub audio_conf: LightSourcesAudioConfig,
@@ -24,6 +25,18 @@ pub struct LightSourcesConfig {
     pub sources: Vec<LightSourcesSource>,
     pub uniform_pulse: bool,
     pub debug_sources: bool,
+    /// Only render the component within this rectangle of the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
+    /// Replaces the colors of `colors.toml` (`iColors` and `iPalette`) for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Vec<[f32; 3]>>,
+    /// Named values which the component can read. Changing them only updates the running component.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, f32>,
+    /// Renders the component with this fraction of the resolution of the output and upscales it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_scale: Option<f32>,
 }
 
 impl ComponentConfig for LightSourcesConfig {
@@ -32,10 +45,12 @@ impl ComponentConfig for LightSourcesConfig {
         renderer: &vibe_renderer::Renderer,
         processor: &vibe_audio::SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn vibe_renderer::ComponentAudio<F>>, ConfigError> {
-        let img = ImageReader::open(&self.wallpaper_path)
+    ) -> Result<Box<dyn vibe_renderer::Component>, ConfigError> {
+        let wallpaper_path = crate::current_wallpaper::resolve(&self.wallpaper_path)
+            .ok_or(ConfigError::NoCurrentWallpaper)?;
+        let img = ImageReader::open(&wallpaper_path)
             .map_err(|err| ConfigError::OpenFile {
-                path: self.wallpaper_path.to_string_lossy().to_string(),
+                path: wallpaper_path.to_string_lossy().to_string(),
                 reason: err,
             })?
             .decode()?;
@@ -64,7 +79,10 @@ impl ComponentConfig for LightSourcesConfig {
     }
 
     fn external_paths(&self) -> Vec<PathBuf> {
-        vec![self.wallpaper_path.clone()]
+        // the current wallpaper if it's `auto`
+        crate::current_wallpaper::resolve(&self.wallpaper_path)
+            .into_iter()
+            .collect()
     }
 }
 
@@ -9,9 +9,12 @@ mod light_sources;
 mod radial;
 
 use serde::{Deserialize, Serialize};
-use std::{num::NonZero, ops::Range, path::PathBuf};
+use std::{collections::HashMap, num::NonZero, ops::Range, path::PathBuf};
 use vibe_audio::{fetcher::Fetcher, SampleProcessor};
-use vibe_renderer::{components::ComponentAudio, Renderer};
+use vibe_renderer::{
+    components::{Viewport, WithPalette, WithRenderScale, WithViewport, MIN_RENDER_SCALE},
+    Component, Renderer, MAX_COLORS,
+};
 
 pub use aurodio::*;
 pub use bars::*;
@@ -68,6 +71,20 @@ pub enum ConfigError {
     #[error(transparent)]
     IO(#[from] std::io::Error),
 
+    #[error("Invalid viewport: {0}")]
+    Viewport(String),
+
+    #[error(
+        "`palette` contains {0} colors but it needs at least 1 and at most {MAX_COLORS} colors."
+    )]
+    Palette(usize),
+
+    #[error("`render_scale` is {0} but it has to be between {MIN_RENDER_SCALE} and 1.")]
+    RenderScale(f32),
+
+    #[error("Couldn't find the current wallpaper for `wallpaper_path = \"auto\"`. Set `wallpaper_command` in the config file of vibe if your wallpaper daemon isn't swww, hyprpaper or swaybg.")]
+    NoCurrentWallpaper,
+
     #[error("It looks like as if you've tried to access `iSampler` or `iTexture` in your shader code but you didn't set `texture_path` in the 'FragmentCanvas' config.")]
     MissingTexture,
 }
@@ -79,7 +96,7 @@ pub trait ComponentConfig {
         renderer: &Renderer,
         processor: &SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn ComponentAudio<F>>, ConfigError>;
+    ) -> Result<Box<dyn Component>, ConfigError>;
 
     /// Returns a `vec` of paths which are stored in this component config.
     ///
@@ -109,14 +126,181 @@ impl Default for Config {
     }
 }
 
+impl Config {
+    /// Returns the sensitivity of the audio config of the component.
+    pub fn sensitivity(&self) -> f32 {
+        match self {
+            Self::Bars(config) => config.audio_conf.sensitivity,
+            Self::FragmentCanvas(config) => config.audio_conf.sensitivity,
+            Self::Aurodio(config) => config.audio_conf.sensitivity,
+            Self::Graph(config) => config.audio_conf.sensitivity,
+            Self::Circle(config) => config.audio_conf.sensitivity,
+            Self::Radial(config) => config.audio_conf.sensitivity,
+            Self::Chessy(config) => config.audio_conf.sensitivity,
+            Self::WallpaperPulseEdges(config) => config.audio_conf.sensitivity,
+            Self::WallpaperLightSources(config) => config.audio_conf.sensitivity,
+        }
+    }
+
+    /// Sets the sensitivity of the audio config of the component.
+    pub fn set_sensitivity(&mut self, sensitivity: f32) {
+        match self {
+            Self::Bars(config) => config.audio_conf.sensitivity = sensitivity,
+            Self::FragmentCanvas(config) => config.audio_conf.sensitivity = sensitivity,
+            Self::Aurodio(config) => config.audio_conf.sensitivity = sensitivity,
+            Self::Graph(config) => config.audio_conf.sensitivity = sensitivity,
+            Self::Circle(config) => config.audio_conf.sensitivity = sensitivity,
+            Self::Radial(config) => config.audio_conf.sensitivity = sensitivity,
+            Self::Chessy(config) => config.audio_conf.sensitivity = sensitivity,
+            Self::WallpaperPulseEdges(config) => config.audio_conf.sensitivity = sensitivity,
+            Self::WallpaperLightSources(config) => config.audio_conf.sensitivity = sensitivity,
+        }
+    }
+
+    /// Returns the name of the component like in the config files (for example `Bars`).
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bars(_) => "Bars",
+            Self::FragmentCanvas(_) => "FragmentCanvas",
+            Self::Aurodio(_) => "Aurodio",
+            Self::Graph(_) => "Graph",
+            Self::Circle(_) => "Circle",
+            Self::Radial(_) => "Radial",
+            Self::Chessy(_) => "Chessy",
+            Self::WallpaperPulseEdges(_) => "WallpaperPulseEdges",
+            Self::WallpaperLightSources(_) => "WallpaperLightSources",
+        }
+    }
+
+    /// Returns the rectangle of the output which the component is restricted to.
+    pub fn viewport(&self) -> Option<Viewport> {
+        match self {
+            Self::Bars(config) => config.viewport,
+            Self::FragmentCanvas(config) => config.viewport,
+            Self::Aurodio(config) => config.viewport,
+            Self::Graph(config) => config.viewport,
+            Self::Circle(config) => config.viewport,
+            Self::Radial(config) => config.viewport,
+            Self::Chessy(config) => config.viewport,
+            Self::WallpaperPulseEdges(config) => config.viewport,
+            Self::WallpaperLightSources(config) => config.viewport,
+        }
+    }
+
+    /// Returns the named values which are passed to [Component::update_params].
+    pub fn params(&self) -> &HashMap<String, f32> {
+        match self {
+            Self::Bars(config) => &config.params,
+            Self::FragmentCanvas(config) => &config.params,
+            Self::Aurodio(config) => &config.params,
+            Self::Graph(config) => &config.params,
+            Self::Circle(config) => &config.params,
+            Self::Radial(config) => &config.params,
+            Self::Chessy(config) => &config.params,
+            Self::WallpaperPulseEdges(config) => &config.params,
+            Self::WallpaperLightSources(config) => &config.params,
+        }
+    }
+
+    fn params_mut(&mut self) -> &mut HashMap<String, f32> {
+        match self {
+            Self::Bars(config) => &mut config.params,
+            Self::FragmentCanvas(config) => &mut config.params,
+            Self::Aurodio(config) => &mut config.params,
+            Self::Graph(config) => &mut config.params,
+            Self::Circle(config) => &mut config.params,
+            Self::Radial(config) => &mut config.params,
+            Self::Chessy(config) => &mut config.params,
+            Self::WallpaperPulseEdges(config) => &mut config.params,
+            Self::WallpaperLightSources(config) => &mut config.params,
+        }
+    }
+
+    /// Returns `true` if the configs are equal apart from the values of their `params`.
+    ///
+    /// Adding or removing a param counts as a difference since it might change
+    /// the component (like the members of `iParams` of the `FragmentCanvas`).
+    pub fn eq_without_params(&self, other: &Self) -> bool {
+        let without_params = |config: &Self| {
+            let mut config = config.clone();
+            config
+                .params_mut()
+                .values_mut()
+                .for_each(|value| *value = 0.);
+            toml::Value::try_from(config).ok()
+        };
+
+        without_params(self).is_some_and(|config| Some(config) == without_params(other))
+    }
+
+    /// Returns the fraction of the resolution of the output which the component is rendered with.
+    pub fn render_scale(&self) -> Option<f32> {
+        match self {
+            Self::Bars(config) => config.render_scale,
+            Self::FragmentCanvas(config) => config.render_scale,
+            Self::Aurodio(config) => config.render_scale,
+            Self::Graph(config) => config.render_scale,
+            Self::Circle(config) => config.render_scale,
+            Self::Radial(config) => config.render_scale,
+            Self::Chessy(config) => config.render_scale,
+            Self::WallpaperPulseEdges(config) => config.render_scale,
+            Self::WallpaperLightSources(config) => config.render_scale,
+        }
+    }
+
+    /// Returns the colors which replace the ones of `colors.toml` for this component.
+    pub fn palette(&self) -> Option<&[[f32; 3]]> {
+        match self {
+            Self::Bars(config) => config.palette.as_deref(),
+            Self::FragmentCanvas(config) => config.palette.as_deref(),
+            Self::Aurodio(config) => config.palette.as_deref(),
+            Self::Graph(config) => config.palette.as_deref(),
+            Self::Circle(config) => config.palette.as_deref(),
+            Self::Radial(config) => config.palette.as_deref(),
+            Self::Chessy(config) => config.palette.as_deref(),
+            Self::WallpaperPulseEdges(config) => config.palette.as_deref(),
+            Self::WallpaperLightSources(config) => config.palette.as_deref(),
+        }
+    }
+
+    /// Returns `true` if the component shows the current wallpaper (`wallpaper_path = "auto"`).
+    pub fn uses_current_wallpaper(&self) -> bool {
+        match self {
+            Self::WallpaperPulseEdges(config) => {
+                crate::current_wallpaper::is_auto(&config.wallpaper_path)
+            }
+            Self::WallpaperLightSources(config) => {
+                crate::current_wallpaper::is_auto(&config.wallpaper_path)
+            }
+            _ => false,
+        }
+    }
+}
+
 impl ComponentConfig for Config {
     fn create_component<F: Fetcher>(
         &self,
         renderer: &Renderer,
         processor: &SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn ComponentAudio<F>>, ConfigError> {
-        match self {
+    ) -> Result<Box<dyn Component>, ConfigError> {
+        if let Some(viewport) = self.viewport() {
+            viewport.validate().map_err(ConfigError::Viewport)?;
+        }
+
+        if let Some(scale) = self.render_scale() {
+            if !(MIN_RENDER_SCALE..=1.).contains(&scale) {
+                return Err(ConfigError::RenderScale(scale));
+            }
+        }
+
+        if let Some(palette) = self.palette() {
+            if palette.is_empty() || palette.len() > MAX_COLORS {
+                return Err(ConfigError::Palette(palette.len()));
+            }
+        }
+
+        let component = match self {
             Self::Bars(config) => config.create_component(renderer, processor, texture_format),
             Self::FragmentCanvas(config) => {
                 config.create_component(renderer, processor, texture_format)
@@ -136,7 +320,37 @@ impl ComponentConfig for Config {
             Self::WallpaperLightSources(config) => {
                 config.create_component(renderer, processor, texture_format)
             }
-        }
+        }?;
+
+        let component: Box<dyn Component> = match self.palette() {
+            Some(palette) => Box::new(
+                WithPalette::new(renderer, component, palette.to_vec())
+                    .expect("The palette isn't empty"),
+            ),
+            None => component,
+        };
+
+        // a viewport renders into its own texture, so it takes care of the render scale as well
+        let render_scale = self.render_scale().unwrap_or(1.);
+        let mut component: Box<dyn Component> = match self.viewport() {
+            Some(viewport) => Box::new(WithViewport::with_render_scale(
+                renderer,
+                component,
+                viewport,
+                render_scale,
+                texture_format,
+            )),
+            None if render_scale < 1. => Box::new(WithRenderScale::new(
+                renderer,
+                component,
+                render_scale,
+                texture_format,
+            )),
+            None => component,
+        };
+
+        component.update_params(renderer.queue(), self.params());
+        Ok(component)
     }
 
     fn external_paths(&self) -> Vec<PathBuf> {
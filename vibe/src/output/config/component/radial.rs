@@ -1,8 +1,8 @@
 use crate::output::config::component::ComponentConfig;
 
-use super::{FreqRange, Rgba};
+use super::{FreqRange, Rgba, Viewport};
 use serde::{Deserialize, Serialize};
-use std::num::NonZero;
+use std::{collections::HashMap, num::NonZero};
 use vibe_audio::fetcher::Fetcher;
 use vibe_renderer::components::{Radial, RadialDescriptor, RadialFormat, RadialVariant};
 
@@ -17,6 +17,18 @@ pub struct RadialConfig {
     pub bar_height_sensitivity: f32,
     pub bar_width: f32,
     pub position: (f32, f32),
+    /// Only render the component within this rectangle of the output.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub viewport: Option<Viewport>,
+    /// Replaces the colors of `colors.toml` (`iColors` and `iPalette`) for this component.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub palette: Option<Vec<[f32; 3]>>,
+    /// Named values which the component can read. Changing them only updates the running component.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub params: HashMap<String, f32>,
+    /// Renders the component with this fraction of the resolution of the output and upscales it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_scale: Option<f32>,
 }
 
 impl ComponentConfig for RadialConfig {
@@ -25,7 +37,7 @@ impl ComponentConfig for RadialConfig {
         renderer: &vibe_renderer::Renderer,
         processor: &vibe_audio::SampleProcessor<F>,
         texture_format: wgpu::TextureFormat,
-    ) -> Result<Box<dyn vibe_renderer::ComponentAudio<F>>, super::ConfigError> {
+    ) -> Result<Box<dyn vibe_renderer::Component>, super::ConfigError> {
         let variant = match &self.variant {
             RadialVariantConfig::Color(rgba) => RadialVariant::Color(rgba.as_f32()),
             RadialVariantConfig::HeightGradient { inner, outer } => RadialVariant::HeightGradient {
@@ -1,26 +1,296 @@
 pub mod component;
 
-use std::{ffi::OsStr, io, path::PathBuf};
+use std::{
+    ffi::OsStr,
+    io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
-use smithay_client_toolkit::output::OutputInfo;
+use smithay_client_toolkit::{output::OutputInfo, shell::wlr_layer::Anchor};
 
-use crate::output::config::component::ComponentConfig;
+use crate::{migration::Version, output::config::component::ComponentConfig};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OutputConfig {
+    #[serde(default)]
+    pub version: Version,
     pub enable: bool,
+    /// Can be left out if the output has a `playlist`.
+    #[serde(default)]
     pub components: Vec<component::Config>,
+    /// Files with reusable components (in the form of a [Preset]) which are placed in front of `components`.
+    /// Relative paths are relative to the directory of the config file.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include: Vec<PathBuf>,
+    pub silence_config: Option<SilenceConfig>,
+    /// Stop rendering while the user is idle (needs the `ext-idle-notify` protocol).
+    pub idle_config: Option<IdleConfig>,
+    /// The maximum frame rate of the output. Unlimited if not set.
+    pub fps_limit: Option<f32>,
+    /// The placement of the wallpaper on the output. Covers the whole output if not set.
+    pub layer_surface_config: Option<LayerSurfaceConfig>,
+    /// The id of the audio device which should be visualized on this output.
+    /// Uses the device of the main config if not set.
+    pub audio_target: Option<String>,
+    /// Cycle through multiple presets instead of showing `components`.
+    pub playlist: Option<PlaylistConfig>,
+    /// How the frames are presented. Uses vsync with up to 3 queued frames if not set.
+    pub graphics_config: Option<GraphicsConfig>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum Edge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Margin {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+/// Describes where the wallpaper should be placed on the output,
+/// for example as a strip at the bottom of the output.
+///
+/// See the [layer shell protocol](https://wayland.app/protocols/wlr-layer-shell-unstable-v1)
+/// for more information.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LayerSurfaceConfig {
+    /// The edges of the output which the wallpaper should be attached to.
+    pub anchor: Vec<Edge>,
+    pub margin: Margin,
+    /// The width in pixels. Stretches between the left and right edge if not set.
+    pub width: Option<u32>,
+    /// The height in pixels. Stretches between the top and bottom edge if not set.
+    pub height: Option<u32>,
+    /// The amount of pixels which other surfaces (like windows) shouldn't overlap
+    /// at the anchored edge, like a panel.
+    /// The wallpaper doesn't reserve any space if not set.
+    pub exclusive_zone: Option<u32>,
+    /// Receive keyboard input after clicking on the wallpaper.
+    /// The key events are available in shaders with `iKeyboard`.
+    pub keyboard_interactivity: bool,
+}
+
+impl Default for LayerSurfaceConfig {
+    fn default() -> Self {
+        Self {
+            anchor: vec![Edge::Top, Edge::Bottom, Edge::Left, Edge::Right],
+            margin: Margin::default(),
+            width: None,
+            height: None,
+            exclusive_zone: None,
+            keyboard_interactivity: false,
+        }
+    }
+}
+
+impl LayerSurfaceConfig {
+    pub fn exclusive_zone(&self) -> i32 {
+        // "-1" otherwise: https://github.com/TornaxO7/vibe/issues/167 happens
+        self.exclusive_zone.map(|zone| zone as i32).unwrap_or(-1)
+    }
+
+    pub fn anchor(&self) -> Anchor {
+        self.anchor.iter().fold(Anchor::empty(), |anchor, edge| {
+            anchor
+                | match edge {
+                    Edge::Top => Anchor::TOP,
+                    Edge::Bottom => Anchor::BOTTOM,
+                    Edge::Left => Anchor::LEFT,
+                    Edge::Right => Anchor::RIGHT,
+                }
+        })
+    }
+
+    /// Checks if the compositor is able to compute the size of the surface.
+    pub fn validate(&self) -> Result<(), String> {
+        let anchor = self.anchor();
+
+        if self.width.is_none() && !anchor.contains(Anchor::LEFT | Anchor::RIGHT) {
+            return Err(
+                "`width` has to be set if the wallpaper isn't anchored to the left and right edge."
+                    .to_string(),
+            );
+        }
+
+        if self.height.is_none() && !anchor.contains(Anchor::TOP | Anchor::BOTTOM) {
+            return Err(
+                "`height` has to be set if the wallpaper isn't anchored to the top and bottom edge."
+                    .to_string(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// How the rendered frames are handed over to the compositor.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GraphicsConfig {
+    /// One of `AutoVsync`, `AutoNoVsync`, `Fifo`, `FifoRelaxed`, `Mailbox` or `Immediate`.
+    /// Falls back to `AutoVsync` if the GPU doesn't support it.
+    pub present_mode: wgpu::PresentMode,
+    /// The amount of frames which can be queued before they are presented.
+    /// Lower values reduce the latency, higher values smooth out slow frames.
+    pub desired_maximum_frame_latency: u32,
+}
+
+impl Default for GraphicsConfig {
+    fn default() -> Self {
+        Self {
+            present_mode: wgpu::PresentMode::AutoVsync,
+            desired_maximum_frame_latency: 3,
+        }
+    }
+}
+
+impl GraphicsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.desired_maximum_frame_latency == 0 {
+            return Err("`desired_maximum_frame_latency` has to be at least 1.".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SilenceConfig {
+    /// Stop rendering the output after this amount of seconds of silence.
+    /// Rendering continues as soon as the audio returns.
+    pub pause_after_secs: f32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IdleConfig {
+    /// Stop rendering the output once the user didn't use the keyboard or the mouse for this
+    /// amount of seconds. The compositor also reports the user as idle while the screen is locked.
+    /// Rendering continues as soon as the user is active again.
+    pub pause_after_secs: f32,
+}
+
+impl IdleConfig {
+    /// Returns `None` if `pause_after_secs` is invalid.
+    pub fn timeout(&self) -> Option<Duration> {
+        Duration::try_from_secs_f32(self.pause_after_secs).ok()
+    }
+}
+
+/// A list of presets (or components) which are shown one after another on the output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistConfig {
+    pub entries: Vec<PlaylistEntry>,
+    /// Switch to the next entry after this amount of seconds.
+    pub interval_secs: Option<f32>,
+    /// Switch to the next entry once the audio returns after a short silence, like between two tracks.
+    #[serde(default)]
+    pub on_track_change: bool,
+    /// Show the entries in a random order which changes after each round.
+    #[serde(default)]
+    pub shuffle: bool,
+    #[serde(default)]
+    pub transition: Transition,
+}
+
+impl PlaylistConfig {
+    /// Checks if the playlist is able to switch between its entries.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.entries.is_empty() {
+            return Err("The playlist doesn't have any `entries`.".to_string());
+        }
+
+        match self.interval_secs {
+            Some(secs) if secs.is_nan() || secs <= 0. => {
+                return Err("`interval_secs` of the playlist has to be positive.".to_string());
+            }
+            None if !self.on_track_change => {
+                return Err(
+                    "The playlist needs `interval_secs` or `on_track_change = true` to switch its entries."
+                        .to_string(),
+                );
+            }
+            _ => {}
+        }
+
+        if let Transition::Fade { duration_secs } = self.transition {
+            if duration_secs.is_nan() || duration_secs < 0. {
+                return Err(
+                    "`duration_secs` of the playlist transition can't be negative.".to_string(),
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// An entry of a [PlaylistConfig].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PlaylistEntry {
+    /// The name of a preset (see [load_preset]).
+    Preset(String),
+    Components {
+        components: Vec<component::Config>,
+    },
+}
+
+impl PlaylistEntry {
+    /// Returns the components which should be shown for this entry.
+    pub fn load(&self) -> anyhow::Result<Vec<component::Config>> {
+        match self {
+            Self::Preset(name) => load_preset(name).map(|preset| preset.components),
+            Self::Components { components } => Ok(components.clone()),
+        }
+    }
+
+    /// Returns the name of the preset if the entry refers to one.
+    pub fn preset(&self) -> Option<&str> {
+        match self {
+            Self::Preset(name) => Some(name),
+            Self::Components { .. } => None,
+        }
+    }
+}
+
+/// How the playlist switches from one entry to the next one.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub enum Transition {
+    /// Switch immediately.
+    #[default]
+    Cut,
+    /// Fade the old entry out and the new one in, within the given amount of seconds.
+    Fade { duration_secs: f32 },
 }
 
 impl OutputConfig {
-    pub fn new(info: &OutputInfo, default_component: component::Config) -> anyhow::Result<Self> {
+    pub fn new(info: &OutputInfo, components: Vec<component::Config>) -> anyhow::Result<Self> {
         let name = info.name.as_ref().unwrap();
 
         let new = Self {
+            version: Version::default(),
             enable: true,
-            components: vec![default_component],
+            components,
+            include: Vec::new(),
+            silence_config: None,
+            idle_config: None,
+            fps_limit: None,
+            layer_surface_config: None,
+            audio_target: None,
+            playlist: None,
+            graphics_config: None,
         };
 
         new.save(name)?;
@@ -40,12 +310,33 @@ impl OutputConfig {
         Ok(())
     }
 
+    /// Loads the components of the included files and puts them in front of the own components.
+    ///
+    /// The paths of `include` are replaced by their absolute form afterwards.
+    pub fn resolve_includes(&mut self, config_dir: &Path) -> anyhow::Result<()> {
+        let mut components = Vec::new();
+
+        for path in self.include.iter_mut() {
+            *path = resolve_include_path(path, config_dir);
+
+            let preset: Preset = crate::migration::load(path).with_context(|| {
+                format!("Couldn't load included file '{}'", path.to_string_lossy())
+            })?;
+            components.extend(preset.components);
+        }
+
+        components.append(&mut self.components);
+        self.components = components;
+
+        Ok(())
+    }
+
     /// Returns all relevant paths which are occurring inside the config file.
     ///
     /// Only relevant for hot reloading to know which other files have to be watched as
     /// well.
     pub fn external_paths(&self) -> Vec<PathBuf> {
-        let mut paths = Vec::new();
+        let mut paths = self.include.clone();
 
         for component in self.components.iter() {
             paths.extend(component.external_paths());
@@ -55,22 +346,116 @@ impl OutputConfig {
     }
 }
 
+/// Loads the config of the given output. See [find] for the lookup of the config file.
 pub fn load<S: AsRef<str>>(output_name: S) -> Option<(PathBuf, anyhow::Result<OutputConfig>)> {
-    let iterator = std::fs::read_dir(crate::get_output_config_dir()).unwrap();
+    let path = find(output_name)?;
+    let config = load_from_path(&path);
+
+    Some((path, config))
+}
+
+/// Returns the path to the config file of the given output if it exists.
+///
+/// Falls back to the config of all outputs (see [crate::get_all_outputs_config_path])
+/// if the output doesn't have its own config file.
+pub fn find<S: AsRef<str>>(output_name: S) -> Option<PathBuf> {
+    find_in(
+        &crate::get_output_config_dir(),
+        &crate::get_all_outputs_config_path(),
+        output_name,
+    )
+}
+
+/// Like [find] but with the given output config directory and config of all outputs.
+fn find_in<S: AsRef<str>>(
+    output_config_dir: &Path,
+    all_outputs_path: &Path,
+    output_name: S,
+) -> Option<PathBuf> {
+    find_own(output_config_dir, output_name).or_else(|| {
+        all_outputs_path
+            .exists()
+            .then(|| all_outputs_path.to_path_buf())
+    })
+}
+
+/// Returns the path to the config file which belongs only to the given output.
+fn find_own<S: AsRef<str>>(output_config_dir: &Path, output_name: S) -> Option<PathBuf> {
+    let iterator = std::fs::read_dir(output_config_dir).unwrap();
 
     for entry in iterator {
         let entry = entry.unwrap();
         let path = entry.path();
 
         if path.file_stem().unwrap() == OsStr::new(output_name.as_ref()) {
-            let content = std::fs::read_to_string(&path).unwrap();
-            return Some((path, toml::from_str(&content).context("")));
+            return Some(path);
         }
     }
 
     None
 }
 
+pub fn load_from_path(path: &Path) -> anyhow::Result<OutputConfig> {
+    let mut config: OutputConfig = crate::migration::load(path)
+        .with_context(|| format!("Couldn't load '{}'", path.to_string_lossy()))?;
+
+    config.resolve_includes(path.parent().unwrap_or(Path::new("/")))?;
+    Ok(config)
+}
+
+/// Expands a leading `~` to the home directory and makes relative paths relative to `config_dir`.
+fn resolve_include_path(path: &Path, config_dir: &Path) -> PathBuf {
+    // `join` keeps absolute paths as they are
+    config_dir.join(crate::expand_home(path))
+}
+
+/// A set of components which can be shown on an output instead of the components of its config.
+///
+/// Presets lie inside the preset directory and have the form `<preset-name>.toml`.
+/// Since unknown fields are ignored, an output config file is a valid preset as well.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Preset {
+    pub components: Vec<component::Config>,
+}
+
+pub fn load_preset<S: AsRef<str>>(name: S) -> anyhow::Result<Preset> {
+    let path = {
+        let mut path = crate::get_preset_dir();
+        path.push(format!("{}.toml", name.as_ref()));
+        path
+    };
+
+    crate::migration::load(&path)
+        .with_context(|| format!("Couldn't load preset '{}'", path.to_string_lossy()))
+}
+
+/// Returns the names of all presets sorted alphabetically.
+pub fn preset_names() -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(crate::get_preset_dir())? {
+        let path = entry?.path();
+
+        if path.extension() == Some(OsStr::new("toml")) {
+            if let Some(name) = path.file_stem() {
+                names.push(name.to_string_lossy().to_string());
+            }
+        }
+    }
+
+    names.sort();
+    Ok(names)
+}
+
+/// Returns the preset after `current` (and the first one after the last one).
+pub fn next_preset<'a>(names: &'a [String], current: Option<&str>) -> Option<&'a str> {
+    let next_idx = current
+        .and_then(|current| names.iter().position(|name| name == current))
+        .map(|idx| (idx + 1) % names.len())
+        .unwrap_or(0);
+
+    names.get(next_idx).map(String::as_str)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,12 +464,27 @@ mod tests {
         WallpaperPulseEdgesAudioConfig, WallpaperPulseEdgesConfig, WallpaperPulseEdgesGaussianBlur,
         WallpaperPulseEdgesThresholds,
     };
-    use std::{collections::HashSet, num::NonZero};
+    use std::{
+        collections::{HashMap, HashSet},
+        num::NonZero,
+    };
     use vibe_renderer::components::{ShaderCode, ShaderLanguage, ShaderSource};
 
+    #[test]
+    fn cycle_presets() {
+        let names = ["calm", "party"].map(str::to_string);
+
+        assert_eq!(next_preset(&names, None), Some("calm"));
+        assert_eq!(next_preset(&names, Some("calm")), Some("party"));
+        assert_eq!(next_preset(&names, Some("party")), Some("calm"));
+        assert_eq!(next_preset(&names, Some("deleted")), Some("calm"));
+        assert_eq!(next_preset(&[], None), None);
+    }
+
     #[test]
     fn external_paths() {
         let output_config = OutputConfig {
+            version: Version::default(),
             enable: true,
             components: vec![
                 component::Config::FragmentCanvas(FragmentCanvasConfig {
@@ -96,6 +496,11 @@ mod tests {
                         language: ShaderLanguage::Wgsl,
                         source: ShaderSource::Path("/dir/fragment_canvas_code.wgsl".into()),
                     },
+                    viewport: None,
+                    palette: None,
+                    params: HashMap::new(),
+                    render_scale: None,
+                    readback: Vec::new(),
                 }),
                 component::Config::WallpaperPulseEdges(WallpaperPulseEdgesConfig {
                     wallpaper_path: "/tmp/wallpaper_palse_edges.png".into(),
@@ -116,6 +521,10 @@ mod tests {
                         sigma: 0.5,
                         kernel_size: 3,
                     },
+                    viewport: None,
+                    palette: None,
+                    params: HashMap::new(),
+                    render_scale: None,
                 }),
                 component::Config::WallpaperLightSources(LightSourcesConfig {
                     wallpaper_path: "/tmp/wallpaper_light_sources.png".into(),
@@ -126,11 +535,24 @@ mod tests {
                     sources: vec![],
                     uniform_pulse: true,
                     debug_sources: false,
+                    viewport: None,
+                    palette: None,
+                    params: HashMap::new(),
+                    render_scale: None,
                 }),
             ],
+            include: vec!["/components/bars.toml".into()],
+            silence_config: None,
+            idle_config: None,
+            fps_limit: None,
+            layer_surface_config: None,
+            audio_target: None,
+            playlist: None,
+            graphics_config: None,
         };
 
         let expected = HashSet::from([
+            "/components/bars.toml".into(),
             "/dir/fragment_canvas_img.png".into(),
             "/dir/fragment_canvas_code.wgsl".into(),
             "/tmp/wallpaper_palse_edges.png".into(),
@@ -142,6 +564,175 @@ mod tests {
         assert_eq!(expected, current);
     }
 
+    #[test]
+    fn eq_without_params() {
+        let with_speed = |speed: f32| {
+            component::Config::Bars(component::BarsConfig {
+                params: HashMap::from([("speed".into(), speed)]),
+                ..Default::default()
+            })
+        };
+
+        let config = with_speed(1.);
+        let tweaked = with_speed(2.);
+        assert!(config.eq_without_params(&tweaked));
+        assert_ne!(config.params(), tweaked.params());
+
+        let without_speed = component::Config::Bars(component::BarsConfig::default());
+        assert!(!config.eq_without_params(&without_speed));
+
+        let mut changed = tweaked.clone();
+        changed.set_sensitivity(42.);
+        assert!(!tweaked.eq_without_params(&changed));
+    }
+
+    #[test]
+    fn include_paths() {
+        let config_dir = Path::new("/config/output_configs");
+
+        assert_eq!(
+            resolve_include_path(Path::new("/components/bars.toml"), config_dir),
+            PathBuf::from("/components/bars.toml")
+        );
+        assert_eq!(
+            resolve_include_path(Path::new("../components/bars.toml"), config_dir),
+            PathBuf::from("/config/output_configs/../components/bars.toml")
+        );
+
+        if let Some(home) = std::env::var_os("HOME") {
+            assert_eq!(
+                resolve_include_path(Path::new("~/components/bars.toml"), config_dir),
+                PathBuf::from(home).join("components/bars.toml")
+            );
+        }
+    }
+
+    fn config_dir(name: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "vibe-output-configs-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let output_config_dir = dir.join("output_configs");
+        std::fs::create_dir_all(&output_config_dir).unwrap();
+        for file in files {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+
+        dir
+    }
+
+    #[test]
+    fn find_falls_back_to_all_outputs() {
+        let dir = config_dir(
+            "fallback",
+            &["all-outputs.toml", "output_configs/DP-1.toml"],
+        );
+        let all_outputs_path = dir.join("all-outputs.toml");
+
+        assert_eq!(
+            find_in(&dir.join("output_configs"), &all_outputs_path, "HDMI-A-1"),
+            Some(all_outputs_path)
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn find_prefers_own_config() {
+        let dir = config_dir("own", &["all-outputs.toml", "output_configs/DP-1.toml"]);
+        let output_config_dir = dir.join("output_configs");
+        let all_outputs_path = dir.join("all-outputs.toml");
+
+        assert_eq!(
+            find_in(&output_config_dir, &all_outputs_path, "DP-1"),
+            Some(output_config_dir.join("DP-1.toml"))
+        );
+
+        std::fs::remove_file(&all_outputs_path).unwrap();
+        assert_eq!(
+            find_in(&output_config_dir, &all_outputs_path, "HDMI-A-1"),
+            None
+        );
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn playlist_entries() {
+        let playlist: PlaylistConfig = toml::from_str(
+            r#"
+interval_secs = 60.0
+entries = ["neon", { components = [] }]
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(playlist.entries[0].preset(), Some("neon"));
+        assert!(matches!(
+            &playlist.entries[1],
+            PlaylistEntry::Components { components } if components.is_empty()
+        ));
+        assert_eq!(playlist.transition, Transition::Cut);
+        assert!(playlist.validate().is_ok());
+
+        let never_switches = PlaylistConfig {
+            interval_secs: None,
+            ..playlist
+        };
+        assert!(never_switches.validate().is_err());
+    }
+
+    #[test]
+    fn layer_surface_anchor() {
+        let config = LayerSurfaceConfig {
+            anchor: vec![Edge::Bottom, Edge::Left, Edge::Right],
+            height: Some(120),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config.anchor(),
+            Anchor::BOTTOM | Anchor::LEFT | Anchor::RIGHT
+        );
+        assert_eq!(LayerSurfaceConfig::default().anchor(), Anchor::all());
+    }
+
+    #[test]
+    fn layer_surface_exclusive_zone() {
+        assert_eq!(LayerSurfaceConfig::default().exclusive_zone(), -1);
+
+        let panel = LayerSurfaceConfig {
+            exclusive_zone: Some(120),
+            ..Default::default()
+        };
+        assert_eq!(panel.exclusive_zone(), 120);
+    }
+
+    #[test]
+    fn layer_surface_size_requires_anchors() {
+        let strip = LayerSurfaceConfig {
+            anchor: vec![Edge::Bottom, Edge::Left, Edge::Right],
+            height: Some(120),
+            ..Default::default()
+        };
+        assert!(strip.validate().is_ok());
+
+        let missing_height = LayerSurfaceConfig {
+            height: None,
+            ..strip.clone()
+        };
+        assert!(missing_height.validate().is_err());
+
+        let missing_width = LayerSurfaceConfig {
+            anchor: vec![Edge::Bottom, Edge::Left],
+            ..strip
+        };
+        assert!(missing_width.validate().is_err());
+
+        assert!(LayerSurfaceConfig::default().validate().is_ok());
+    }
+
     #[test]
     fn accept_reference_config() -> Result<(), toml::de::Error> {
         let reference_config = include_str!("./reference-config.toml");
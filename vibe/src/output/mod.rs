@@ -1,23 +1,61 @@
 pub mod config;
 
-use crate::{output::config::component::ComponentConfig, state::State, types::size::Size};
-use config::OutputConfig;
+use crate::{
+    output::config::component::{self, ComponentConfig, ConfigError},
+    playlist::Playlist,
+    state::State,
+    stats::FrameStats,
+    types::size::Size,
+    video_export::VideoExport,
+};
+use config::{GraphicsConfig, LayerSurfaceConfig, OutputConfig, SilenceConfig};
 use smithay_client_toolkit::{
     output::OutputInfo,
     shell::{
-        wlr_layer::{Anchor, LayerSurface},
+        wlr_layer::{KeyboardInteractivity, LayerSurface},
         WaylandSurface,
     },
 };
-use tracing::error;
-use vibe_audio::{fetcher::SystemAudioFetcher, SampleProcessor};
-use vibe_renderer::{ComponentAudio, Renderer};
+use std::time::{Duration, Instant};
+use tracing::{error, warn};
+use vibe_audio::{
+    fetcher::{Fetcher, SystemAudioFetcher},
+    SampleProcessor,
+};
+use vibe_renderer::{Component, FadeOverlay, FrameUniforms, Renderable, Renderer};
 use wayland_client::QueueHandle;
 use wgpu::{PresentMode, Surface, SurfaceConfiguration};
 
 /// Contains every relevant information for an output.
 pub struct OutputCtx {
-    pub components: Vec<Box<dyn ComponentAudio<SystemAudioFetcher>>>,
+    pub components: Vec<Box<dyn Component>>,
+    pub frame_uniforms: FrameUniforms,
+
+    name: String,
+    // the configs of `components`
+    components_config: Vec<component::Config>,
+    // the name of the preset if the components don't come from the output config
+    preset: Option<String>,
+    // `true` if we are waiting for the compositor to send the next frame callback
+    // or a frame is scheduled by the frame pacer
+    frame_pending: bool,
+    last_render: Instant,
+    pub silence_config: Option<SilenceConfig>,
+    pub fps_limit: Option<f32>,
+    // the key of the audio source which is visualized
+    pub audio_target: Option<String>,
+    // `None` if the output doesn't have a playlist or it got stopped by `switch-preset`
+    pub playlist: Option<Playlist>,
+    // used for the transitions of the playlist
+    fade_overlay: FadeOverlay,
+    pub stats: FrameStats,
+    // `None` if `video_export` isn't set
+    pub video_export: Option<VideoExport>,
+    layer_surface_config: LayerSurfaceConfig,
+    // the size of the surface in surface-local (logical) coordinates
+    logical_size: Size,
+    // the integer scale of the buffers, the surface config uses the physical size
+    scale: u32,
 
     // don't know if this is required, but better drop `surface` first before
     // `layer_surface`
@@ -34,47 +72,156 @@ impl OutputCtx {
         renderer: &Renderer,
         sample_processor: &SampleProcessor<SystemAudioFetcher>,
         config: OutputConfig,
+        playlist: Option<Playlist>,
     ) -> Self {
-        let size = Size::from(&info);
+        let layer_surface_config = config.layer_surface_config.unwrap_or_default();
 
-        // Should be "-1" otherwise: https://github.com/TornaxO7/vibe/issues/167 happens
-        layer_surface.set_exclusive_zone(-1);
-        layer_surface.set_anchor(Anchor::all());
-        layer_surface.set_size(size.width, size.height);
-        layer_surface.commit();
+        // the compositor tells us the real size with the first configure event
+        let size = {
+            let output_size = Size::from(&info);
 
-        let surface_config = get_surface_config(renderer.adapter(), &surface, size);
-        surface.configure(renderer.device(), &surface_config);
+            Size {
+                width: layer_surface_config.width.unwrap_or(output_size.width),
+                height: layer_surface_config.height.unwrap_or(output_size.height),
+            }
+        };
 
-        let components = {
-            let mut components = Vec::with_capacity(config.components.len());
+        let scale = info.scale_factor.max(1) as u32;
 
-            for comp_conf in config.components {
-                let component = comp_conf
-                    .create_component(renderer, sample_processor, surface_config.format)
-                    .unwrap_or_else(|msg| {
-                        error!("{}", msg);
-                        panic!("Invalid fragment shader code");
-                    });
+        layer_surface.wl_surface().set_buffer_scale(scale as i32);
+        apply_layer_surface_config(&layer_surface, &layer_surface_config);
 
-                components.push(component);
-            }
+        let surface_config = get_surface_config(
+            renderer.adapter(),
+            &surface,
+            physical_size(size, scale),
+            &config.graphics_config.clone().unwrap_or_default(),
+        );
+        surface.configure(renderer.device(), &surface_config);
 
-            components
-        };
+        let components = create_components(
+            renderer,
+            sample_processor,
+            &config.components,
+            surface_config.format,
+        )
+        .unwrap_or_else(|msg| {
+            error!("{}", msg);
+            panic!("Invalid fragment shader code");
+        });
+
+        let fade_overlay = FadeOverlay::new(renderer, surface_config.format);
 
         Self {
             surface_config,
             surface,
             layer_surface,
             components,
+            frame_uniforms: FrameUniforms::new(renderer),
+
+            name: info.name.unwrap_or_default(),
+            components_config: config.components,
+            preset: playlist
+                .as_ref()
+                .and_then(|playlist| playlist.current().preset())
+                .map(|preset| preset.to_string()),
+            frame_pending: false,
+            last_render: Instant::now(),
+            silence_config: config.silence_config,
+            fps_limit: config.fps_limit,
+            audio_target: config.audio_target,
+            playlist,
+            fade_overlay,
+            stats: FrameStats::default(),
+            video_export: None,
+            layer_surface_config,
+            logical_size: size,
+            scale,
+        }
+    }
+
+    /// Replaces the components of the output with the ones of the given configs.
+    ///
+    /// The current components are kept if one of the configs is invalid. If the configs only
+    /// differ in their `params`, the current components just get the new params.
+    pub fn set_components(
+        &mut self,
+        renderer: &Renderer,
+        sample_processor: &SampleProcessor<SystemAudioFetcher>,
+        configs: Vec<component::Config>,
+        preset: Option<String>,
+    ) -> Result<(), ConfigError> {
+        let only_params_changed = self.components_config.len() == configs.len()
+            && self
+                .components_config
+                .iter()
+                .zip(&configs)
+                .all(|(old, new)| old.eq_without_params(new))
+            && self
+                .components_config
+                .iter()
+                .zip(&configs)
+                .any(|(old, new)| old.params() != new.params());
+
+        if only_params_changed {
+            for (component, config) in self.components.iter_mut().zip(&configs) {
+                component.update_params(renderer.queue(), config.params());
+            }
+
+            self.components_config = configs;
+            self.preset = preset;
+            return Ok(());
+        }
+
+        let mut components = create_components(
+            renderer,
+            sample_processor,
+            &configs,
+            self.surface_config.format,
+        )?;
+
+        for component in components.iter_mut() {
+            component.update_resolution(
+                renderer,
+                [self.surface_config.width, self.surface_config.height],
+            );
+        }
+
+        self.components = components;
+        self.components_config = configs;
+        self.preset = preset;
+        Ok(())
+    }
+
+    /// Fades the rendered frame out by the given amount (`0` to `1`).
+    pub fn set_fade(&mut self, queue: &wgpu::Queue, opacity: f32) {
+        self.fade_overlay.set_opacity(queue, opacity);
+    }
+
+    /// Dims the rendered frame, `1` keeps it as it is.
+    pub fn set_brightness(&mut self, queue: &wgpu::Queue, brightness: f32) {
+        self.fade_overlay.set_brightness(queue, brightness);
+    }
+
+    /// Returns everything which has to be rendered in this order.
+    pub fn renderables(&self) -> Vec<&dyn Renderable> {
+        let mut renderables: Vec<&dyn Renderable> = self
+            .components
+            .iter()
+            .map(|component| component as &dyn Renderable)
+            .collect();
+
+        if !self.fade_overlay.is_invisible() {
+            renderables.push(&self.fade_overlay);
         }
+
+        renderables
     }
 
-    pub fn request_redraw(&self, qh: &QueueHandle<State>) {
+    pub fn request_redraw(&mut self, qh: &QueueHandle<State>) {
         let surface = self.layer_surface.wl_surface();
 
-        let size = Size::from(&self.surface_config);
+        let size = self.logical_size;
         surface.damage(
             0,
             0,
@@ -83,20 +230,109 @@ impl OutputCtx {
         );
         surface.frame(qh, surface.clone());
         self.layer_surface.commit();
+        self.frame_pending = true;
+    }
+
+    /// Requests a redraw unless the compositor is going to send a frame callback anyway.
+    ///
+    /// Used to restart rendering after it got stopped by not requesting the next frame.
+    pub fn wake(&mut self, qh: &QueueHandle<State>) {
+        if !self.frame_pending {
+            self.request_redraw(qh);
+        }
+    }
+
+    /// Moves/resizes the wallpaper on the output.
+    ///
+    /// The compositor sends a configure event with the new size afterwards.
+    pub fn set_layer_surface_config(&mut self, config: LayerSurfaceConfig) {
+        apply_layer_surface_config(&self.layer_surface, &config);
+        self.layer_surface_config = config;
     }
 
-    /// Update the internal data to the new output size.
+    /// Applies the layer surface config again so that the compositor sends a configure event
+    /// with the size which fits to the current mode and transform of the output.
+    pub fn refresh_layer_surface(&self) {
+        apply_layer_surface_config(&self.layer_surface, &self.layer_surface_config);
+    }
+
+    /// Returns the minimal duration between two frames according to `fps_limit`.
+    pub fn min_frame_interval(&self) -> Duration {
+        self.fps_limit
+            .map(|fps| Duration::from_secs_f64(1. / fps.max(1.) as f64))
+            .unwrap_or_default()
+    }
+
+    /// Has to be called once the compositor sent the frame callback.
+    pub fn frame_received(&mut self) {
+        self.frame_pending = false;
+    }
+
+    /// Has to be called if the next frame is going to be rendered later (without a frame callback).
+    pub fn frame_scheduled(&mut self) {
+        self.frame_pending = true;
+    }
+
+    /// Has to be called after each rendered frame.
+    pub fn frame_rendered(&mut self) {
+        self.last_render = Instant::now();
+    }
+
+    /// Update the internal data to the new (logical) output size.
     pub fn resize(&mut self, renderer: &Renderer, new_size: Size) {
         if new_size.width > 0 && new_size.height > 0 {
-            self.surface_config.width = new_size.width;
-            self.surface_config.height = new_size.height;
+            self.logical_size = new_size;
+            self.configure_surface(renderer);
+        }
+    }
 
-            self.surface
-                .configure(renderer.device(), &self.surface_config);
+    /// Renders the buffers with the given scale factor of the output.
+    ///
+    /// The next rendered frame has to be committed with the new buffer size.
+    pub fn set_scale(&mut self, renderer: &Renderer, scale: i32) {
+        let scale = scale.max(1) as u32;
+        if scale != self.scale {
+            self.scale = scale;
+            self.layer_surface
+                .wl_surface()
+                .set_buffer_scale(scale as i32);
+            self.configure_surface(renderer);
+        }
+    }
 
-            for component in self.components.iter_mut() {
-                component.update_resolution(renderer, [new_size.width, new_size.height]);
-            }
+    /// Presents the next frames with the given present mode and frame latency.
+    pub fn set_graphics_config(&mut self, renderer: &Renderer, config: &GraphicsConfig) {
+        let present_mode = choose_present_mode(
+            &self
+                .surface
+                .get_capabilities(renderer.adapter())
+                .present_modes,
+            config.present_mode,
+        );
+
+        if present_mode != self.surface_config.present_mode
+            || config.desired_maximum_frame_latency
+                != self.surface_config.desired_maximum_frame_latency
+        {
+            self.surface_config.present_mode = present_mode;
+            self.surface_config.desired_maximum_frame_latency =
+                config.desired_maximum_frame_latency;
+            self.configure_surface(renderer);
+        }
+    }
+
+    /// Configures the surface again with the current size and updates the resolution of the components,
+    /// for example after the surface got outdated or lost.
+    pub fn configure_surface(&mut self, renderer: &Renderer) {
+        let size = physical_size(self.logical_size, self.scale);
+        self.surface_config.width = size.width;
+        self.surface_config.height = size.height;
+
+        self.surface
+            .configure(renderer.device(), &self.surface_config);
+
+        for component in self.components.iter_mut() {
+            component.update_resolution(renderer, [size.width, size.height]);
         }
     }
 
@@ -104,10 +340,11 @@ impl OutputCtx {
     /// See `Component::update_mouse_position` for the coordinate system contract.
     pub fn update_mouse_position(&mut self, queue: &wgpu::Queue, new_pos: (f64, f64)) {
         let normalized_pos = (
-            new_pos.0 as f32 / self.surface_config.width as f32,
-            new_pos.1 as f32 / self.surface_config.height as f32,
+            new_pos.0 as f32 / self.logical_size.width as f32,
+            new_pos.1 as f32 / self.logical_size.height as f32,
         );
 
+        self.frame_uniforms.set_mouse_position(normalized_pos);
         for component in self.components.iter_mut() {
             component.update_mouse_position(queue, normalized_pos);
         }
@@ -122,15 +359,25 @@ impl OutputCtx {
             (pos.0 as f32, pos.1 as f32)
         } else {
             (
-                pos.0 as f32 / self.surface_config.width as f32,
-                pos.1 as f32 / self.surface_config.height as f32,
+                pos.0 as f32 / self.logical_size.width as f32,
+                pos.1 as f32 / self.logical_size.height as f32,
             )
         };
 
+        self.frame_uniforms.set_mouse_click(normalized_pos, time);
         for component in self.components.iter_mut() {
             component.update_mouse_click(queue, normalized_pos, time);
         }
     }
+
+    /// Forward a key event to all components.
+    /// See `Component::update_key`.
+    pub fn update_key(&mut self, queue: &wgpu::Queue, keysym: u32, pressed: bool, time: f32) {
+        self.frame_uniforms.set_key(keysym, pressed, time);
+        for component in self.components.iter_mut() {
+            component.update_key(queue, keysym, pressed, time);
+        }
+    }
 }
 
 // getters
@@ -142,12 +389,68 @@ impl OutputCtx {
     pub fn surface(&self) -> &wgpu::Surface<'static> {
         &self.surface
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn components_config(&self) -> &[component::Config] {
+        &self.components_config
+    }
+
+    pub fn preset(&self) -> Option<&str> {
+        self.preset.as_deref()
+    }
+
+    pub fn frame_pending(&self) -> bool {
+        self.frame_pending
+    }
+
+    pub fn last_render(&self) -> Instant {
+        self.last_render
+    }
+}
+
+fn physical_size(logical_size: Size, scale: u32) -> Size {
+    Size {
+        width: logical_size.width * scale,
+        height: logical_size.height * scale,
+    }
+}
+
+fn apply_layer_surface_config(layer_surface: &LayerSurface, config: &LayerSurfaceConfig) {
+    let margin = &config.margin;
+
+    layer_surface.set_exclusive_zone(config.exclusive_zone());
+    layer_surface.set_keyboard_interactivity(if config.keyboard_interactivity {
+        KeyboardInteractivity::OnDemand
+    } else {
+        KeyboardInteractivity::None
+    });
+    layer_surface.set_anchor(config.anchor());
+    layer_surface.set_margin(margin.top, margin.right, margin.bottom, margin.left);
+    // `0` lets the compositor stretch the surface between the anchored edges
+    layer_surface.set_size(config.width.unwrap_or(0), config.height.unwrap_or(0));
+    layer_surface.commit();
+}
+
+pub(crate) fn create_components<F: Fetcher>(
+    renderer: &Renderer,
+    sample_processor: &SampleProcessor<F>,
+    configs: &[component::Config],
+    texture_format: wgpu::TextureFormat,
+) -> Result<Vec<Box<dyn Component>>, ConfigError> {
+    configs
+        .iter()
+        .map(|config| config.create_component(renderer, sample_processor, texture_format))
+        .collect()
 }
 
 pub fn get_surface_config(
     adapter: &wgpu::Adapter,
     surface: &wgpu::Surface<'_>,
     size: Size,
+    graphics_config: &GraphicsConfig,
 ) -> wgpu::SurfaceConfiguration {
     let surface_caps = surface.get_capabilities(adapter);
     // Prefer common renderable non-sRGB formats; some GPUs (AMD) expose
@@ -166,18 +469,12 @@ pub fn get_surface_config(
         .or_else(|| surface_caps.formats.iter().find(|f| !f.is_srgb()).copied())
         .unwrap_or(surface_caps.formats[0]);
 
-    if !surface_caps
-        .alpha_modes
-        .contains(&wgpu::CompositeAlphaMode::PreMultiplied)
-    {
-        error!(concat![
-                    "Ok, now this is getting tricky (great to hear that from a software, right?).\n",
-                    "\tSimply speaking: For the time being I'm expecting that the selected gpu supports the 'PreMultiplied'-'feature'\n",
-                    "\tbut the selected gpu only supports: {:?}\n",
-                    "\tPlease create an issue (or give the existing issue an upvote) that you've encountered this so I can prioritize this problem."
-                ], &surface_caps.alpha_modes);
-
-        todo!("Sorry :(");
+    let alpha_mode = choose_alpha_mode(&surface_caps.alpha_modes);
+    if alpha_mode != wgpu::CompositeAlphaMode::PreMultiplied {
+        warn!(
+            "The GPU doesn't support the 'PreMultiplied' alpha mode (only {:?}). Using {:?} instead, so transparent parts become black.",
+            &surface_caps.alpha_modes, alpha_mode
+        );
     }
 
     wgpu::SurfaceConfiguration {
@@ -185,9 +482,92 @@ pub fn get_surface_config(
         format,
         width: size.width,
         height: size.height,
-        present_mode: PresentMode::AutoVsync,
-        alpha_mode: wgpu::CompositeAlphaMode::PreMultiplied,
+        present_mode: choose_present_mode(
+            &surface_caps.present_modes,
+            graphics_config.present_mode,
+        ),
+        alpha_mode,
         view_formats: vec![],
-        desired_maximum_frame_latency: 3,
+        desired_maximum_frame_latency: graphics_config.desired_maximum_frame_latency,
+    }
+}
+
+/// The components render premultiplied colors. If the compositor can't blend them,
+/// `Opaque` drops the alpha channel which looks like blending them over black.
+/// `PostMultiplied` is the last resort since it darkens the transparent parts twice.
+fn choose_alpha_mode(supported: &[wgpu::CompositeAlphaMode]) -> wgpu::CompositeAlphaMode {
+    [
+        wgpu::CompositeAlphaMode::PreMultiplied,
+        wgpu::CompositeAlphaMode::Opaque,
+        wgpu::CompositeAlphaMode::Inherit,
+        wgpu::CompositeAlphaMode::PostMultiplied,
+    ]
+    .into_iter()
+    .find(|mode| supported.contains(mode))
+    .unwrap_or(wgpu::CompositeAlphaMode::Auto)
+}
+
+/// Returns `AutoVsync` if the surface doesn't support the wanted present mode.
+fn choose_present_mode(supported: &[PresentMode], wanted: PresentMode) -> PresentMode {
+    let is_auto = matches!(wanted, PresentMode::AutoVsync | PresentMode::AutoNoVsync);
+    if is_auto || supported.contains(&wanted) {
+        return wanted;
+    }
+
+    warn!(
+        "The GPU doesn't support the present mode {:?} (only {:?}). Using AutoVsync instead.",
+        wanted, supported
+    );
+    PresentMode::AutoVsync
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wgpu::CompositeAlphaMode;
+
+    #[test]
+    fn prefers_premultiplied() {
+        assert_eq!(
+            choose_alpha_mode(&[
+                CompositeAlphaMode::Opaque,
+                CompositeAlphaMode::PreMultiplied
+            ]),
+            CompositeAlphaMode::PreMultiplied
+        );
+    }
+
+    #[test]
+    fn falls_back_to_opaque() {
+        assert_eq!(
+            choose_alpha_mode(&[
+                CompositeAlphaMode::PostMultiplied,
+                CompositeAlphaMode::Opaque
+            ]),
+            CompositeAlphaMode::Opaque
+        );
+        assert_eq!(
+            choose_alpha_mode(&[CompositeAlphaMode::PostMultiplied]),
+            CompositeAlphaMode::PostMultiplied
+        );
+        assert_eq!(choose_alpha_mode(&[]), CompositeAlphaMode::Auto);
+    }
+
+    #[test]
+    fn unsupported_present_mode() {
+        let supported = [PresentMode::Fifo, PresentMode::Mailbox];
+
+        assert_eq!(
+            choose_present_mode(&supported, PresentMode::Mailbox),
+            PresentMode::Mailbox
+        );
+        assert_eq!(
+            choose_present_mode(&supported, PresentMode::Immediate),
+            PresentMode::AutoVsync
+        );
+        assert_eq!(
+            choose_present_mode(&supported, PresentMode::AutoNoVsync),
+            PresentMode::AutoNoVsync
+        );
     }
 }
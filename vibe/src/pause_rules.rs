@@ -0,0 +1,125 @@
+//! Pauses rendering while some programs (like games or video players) are running.
+//!
+//! The programs are found by the app id of their windows (see [crate::toplevel])
+//! and by the names of the processes in `/proc`.
+use std::{path::Path, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+const PROC_DIR: &str = "/proc";
+
+/// The kernel cuts the names of processes (`/proc/<pid>/comm`) to this length.
+const MAX_PROCESS_NAME_LEN: usize = 15;
+
+/// How often the processes are checked.
+pub const POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PauseRulesConfig {
+    /// The app ids of windows (like `mpv` or `steam_app_1091500`) or the names of processes
+    /// (like `gamescope`) which pause rendering as long as they are running.
+    pub apps: Vec<String>,
+}
+
+impl PauseRulesConfig {
+    /// Returns the first app of the config which has a window or is running as a process.
+    pub fn running_app<'a>(
+        &'a self,
+        app_ids: impl Iterator<Item = &'a str> + Clone,
+        processes: &[String],
+    ) -> Option<&'a str> {
+        self.apps
+            .iter()
+            .find(|app| {
+                app_ids
+                    .clone()
+                    .any(|app_id| app_id.eq_ignore_ascii_case(app))
+                    || processes.iter().any(|process| is_process_of(process, app))
+            })
+            .map(String::as_str)
+    }
+}
+
+/// Returns `true` if the (possibly cut) process name belongs to the app.
+fn is_process_of(process: &str, app: &str) -> bool {
+    let app: String = app.chars().take(MAX_PROCESS_NAME_LEN).collect();
+    process.eq_ignore_ascii_case(&app)
+}
+
+/// Returns the names of all running processes.
+pub fn process_names() -> Vec<String> {
+    process_names_in(Path::new(PROC_DIR))
+}
+
+fn process_names_in(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter(|entry| {
+            entry
+                .file_name()
+                .to_str()
+                .is_some_and(|name| name.bytes().all(|byte| byte.is_ascii_digit()))
+        })
+        // processes might exit in the meantime
+        .filter_map(|entry| std::fs::read_to_string(entry.path().join("comm")).ok())
+        .map(|name| name.trim_end().to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(apps: &[&str]) -> PauseRulesConfig {
+        PauseRulesConfig {
+            apps: apps.iter().map(|app| app.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn windows_and_processes() {
+        let config = config(&["mpv", "gamescope"]);
+        let processes = ["bash".to_string(), "gamescope".to_string()];
+
+        assert_eq!(
+            config.running_app(["firefox", "MPV"].into_iter(), &[]),
+            Some("mpv")
+        );
+        assert_eq!(
+            config.running_app(["firefox"].into_iter(), &processes),
+            Some("gamescope")
+        );
+        assert_eq!(
+            config.running_app(["firefox"].into_iter(), &processes[..1]),
+            None
+        );
+    }
+
+    #[test]
+    fn long_process_names_are_cut() {
+        assert!(is_process_of("CyberpunkEngine", "CyberpunkEngine.exe"));
+        assert!(!is_process_of("Cyberpunk", "CyberpunkEngine.exe"));
+    }
+
+    #[test]
+    fn reads_proc() {
+        let dir = std::env::temp_dir().join(format!("vibe-proc-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        for (pid, name) in [("1", "systemd"), ("42", "mpv"), ("self", "bash")] {
+            std::fs::create_dir_all(dir.join(pid)).unwrap();
+            std::fs::write(dir.join(pid).join("comm"), format!("{}\n", name)).unwrap();
+        }
+
+        let mut names = process_names_in(&dir);
+        names.sort();
+        assert_eq!(names, ["mpv", "systemd"]);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
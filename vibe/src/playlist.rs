@@ -0,0 +1,287 @@
+//! Switches the entries of the playlist of an output.
+use std::time::{Duration, Instant};
+
+use crate::output::config::{PlaylistConfig, PlaylistEntry, Transition};
+
+/// The state of the playlist of an output.
+pub struct Playlist {
+    config: PlaylistConfig,
+    // the indices of the entries in the order they are shown
+    order: Vec<usize>,
+    // the index inside `order` of the current entry
+    position: usize,
+    // the point of time since the current entry is shown
+    shown_since: Instant,
+    // the amount of track changes of the audio source while the current entry got shown
+    track_changes: u64,
+    fade: Option<Fade>,
+}
+
+/// A running [Transition::Fade].
+struct Fade {
+    start: Instant,
+    duration: Duration,
+    // `true` once the new entry got shown (in the middle of the fade)
+    switched: bool,
+}
+
+/// The result of [Playlist::update].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Update {
+    /// `true` if [Playlist::current] changed and its components have to be shown.
+    pub switched: bool,
+    /// The opacity of the fade overlay, `0` if there's no running transition.
+    pub fade: f32,
+}
+
+impl Playlist {
+    /// `track_changes` is the current amount of track changes of the audio source of the output.
+    pub fn new(config: PlaylistConfig, now: Instant, track_changes: u64) -> Self {
+        let mut order: Vec<usize> = (0..config.entries.len()).collect();
+        if config.shuffle {
+            fastrand::shuffle(&mut order);
+        }
+
+        Self {
+            config,
+            order,
+            position: 0,
+            shown_since: now,
+            track_changes,
+            fade: None,
+        }
+    }
+
+    /// Returns the entry which is shown.
+    pub fn current(&self) -> &PlaylistEntry {
+        &self.config.entries[self.order[self.position]]
+    }
+
+    /// Returns the position of the current entry in the playlist (starting with `1`)
+    /// and the amount of entries.
+    pub fn progress(&self) -> (usize, usize) {
+        (self.position + 1, self.order.len())
+    }
+
+    /// Has to be called before each frame.
+    /// Switches to the next entry if it's time to.
+    pub fn update(&mut self, now: Instant, track_changes: u64) -> Update {
+        if self.fade.is_none() && self.is_due(now, track_changes) {
+            match self.config.transition {
+                Transition::Fade { duration_secs } if duration_secs > 0. => {
+                    self.fade = Some(Fade {
+                        start: now,
+                        duration: Duration::from_secs_f32(duration_secs),
+                        switched: false,
+                    });
+                }
+                _ => {
+                    self.next(now, track_changes);
+                    return Update {
+                        switched: true,
+                        fade: 0.,
+                    };
+                }
+            }
+        }
+
+        let Some(fade) = self.fade.as_mut() else {
+            return Update {
+                switched: false,
+                fade: 0.,
+            };
+        };
+
+        // fade out within the first half and fade in within the second half
+        let progress = (now.duration_since(fade.start).as_secs_f32() / fade.duration.as_secs_f32())
+            .clamp(0., 1.);
+
+        let switched = progress >= 0.5 && !fade.switched;
+        if switched {
+            fade.switched = true;
+        }
+
+        if progress >= 1. {
+            self.fade = None;
+        }
+
+        if switched {
+            self.next(now, track_changes);
+        }
+
+        Update {
+            switched,
+            fade: 1. - (2. * progress - 1.).abs(),
+        }
+    }
+
+    fn is_due(&self, now: Instant, track_changes: u64) -> bool {
+        if self.order.len() < 2 {
+            return false;
+        }
+
+        let interval_elapsed = self
+            .config
+            .interval_secs
+            .is_some_and(|secs| now.duration_since(self.shown_since).as_secs_f32() >= secs);
+        let track_changed = self.config.on_track_change && track_changes != self.track_changes;
+
+        interval_elapsed || track_changed
+    }
+
+    fn next(&mut self, now: Instant, track_changes: u64) {
+        self.position += 1;
+
+        if self.position == self.order.len() {
+            self.position = 0;
+
+            if self.config.shuffle {
+                let last = self.order[self.order.len() - 1];
+                fastrand::shuffle(&mut self.order);
+
+                // don't show the same entry twice in a row
+                if self.order[0] == last {
+                    let other = fastrand::usize(1..self.order.len());
+                    self.order.swap(0, other);
+                }
+            }
+        }
+
+        self.shown_since = now;
+        self.track_changes = track_changes;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(amount_entries: usize) -> PlaylistConfig {
+        PlaylistConfig {
+            entries: (0..amount_entries)
+                .map(|idx| PlaylistEntry::Preset(idx.to_string()))
+                .collect(),
+            interval_secs: Some(10.),
+            on_track_change: false,
+            shuffle: false,
+            transition: Transition::Cut,
+        }
+    }
+
+    fn current(playlist: &Playlist) -> &str {
+        playlist.current().preset().unwrap()
+    }
+
+    #[test]
+    fn switch_after_interval() {
+        let start = Instant::now();
+        let mut playlist = Playlist::new(config(2), start, 0);
+        assert_eq!(current(&playlist), "0");
+
+        let update = playlist.update(start + Duration::from_secs(5), 0);
+        assert!(!update.switched);
+
+        let update = playlist.update(start + Duration::from_secs(10), 0);
+        assert!(update.switched);
+        assert_eq!(current(&playlist), "1");
+
+        let update = playlist.update(start + Duration::from_secs(20), 0);
+        assert!(update.switched);
+        assert_eq!(current(&playlist), "0");
+        assert_eq!(playlist.progress(), (1, 2));
+    }
+
+    #[test]
+    fn switch_on_track_change() {
+        let start = Instant::now();
+        let mut playlist = Playlist::new(
+            PlaylistConfig {
+                interval_secs: None,
+                on_track_change: true,
+                ..config(3)
+            },
+            start,
+            4,
+        );
+
+        assert!(!playlist.update(start + Duration::from_secs(60), 4).switched);
+        assert!(playlist.update(start + Duration::from_secs(61), 5).switched);
+        assert!(!playlist.update(start + Duration::from_secs(62), 5).switched);
+        assert_eq!(current(&playlist), "1");
+    }
+
+    #[test]
+    fn fade_switches_in_the_middle() {
+        let start = Instant::now();
+        let mut playlist = Playlist::new(
+            PlaylistConfig {
+                transition: Transition::Fade { duration_secs: 2. },
+                ..config(2)
+            },
+            start,
+            0,
+        );
+
+        let fade_start = start + Duration::from_secs(10);
+        let update = playlist.update(fade_start, 0);
+        assert_eq!(
+            update,
+            Update {
+                switched: false,
+                fade: 0.
+            }
+        );
+
+        let update = playlist.update(fade_start + Duration::from_millis(500), 0);
+        assert!(!update.switched);
+        assert_eq!(update.fade, 0.5);
+
+        let update = playlist.update(fade_start + Duration::from_secs(1), 0);
+        assert!(update.switched);
+        assert_eq!(update.fade, 1.);
+        assert_eq!(current(&playlist), "1");
+
+        let update = playlist.update(fade_start + Duration::from_secs(2), 0);
+        assert!(!update.switched);
+        assert_eq!(update.fade, 0.);
+        assert_eq!(current(&playlist), "1");
+    }
+
+    #[test]
+    fn shuffle_shows_every_entry_once_per_round() {
+        let start = Instant::now();
+        let mut playlist = Playlist::new(
+            PlaylistConfig {
+                shuffle: true,
+                ..config(5)
+            },
+            start,
+            0,
+        );
+
+        for round in 0..3 {
+            let mut shown = Vec::new();
+            for idx in 0..5 {
+                shown.push(current(&playlist).to_string());
+
+                let time = start + Duration::from_secs(10 * (round * 5 + idx + 1));
+                playlist.update(time, 0);
+            }
+
+            shown.sort();
+            assert_eq!(shown, ["0", "1", "2", "3", "4"]);
+        }
+    }
+
+    #[test]
+    fn single_entry_never_switches() {
+        let start = Instant::now();
+        let mut playlist = Playlist::new(config(1), start, 0);
+
+        assert!(
+            !playlist
+                .update(start + Duration::from_secs(100), 0)
+                .switched
+        );
+    }
+}
@@ -0,0 +1,191 @@
+//! Renders an output config together with an audio file into a video without a compositor.
+//!
+//! `ffmpeg` decodes the audio file and encodes the frames, so it has to be installed.
+use std::{
+    io::{Read, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{bail, Context};
+use tracing::info;
+use vibe_audio::{fetcher::SampleFetcher, SampleProcessor, DEFAULT_SAMPLE_RATE};
+use vibe_renderer::{FrameUniforms, Renderer, RendererDescriptor};
+
+use crate::{colors::ColorManager, snapshot, types::size::Size};
+
+const FFMPEG: &str = "ffmpeg";
+pub(crate) const AMOUNT_CHANNELS: u16 = 2;
+
+/// How the clip is recorded.
+#[derive(Debug, Clone)]
+pub struct RecordOptions {
+    pub duration: Duration,
+    pub audio: PathBuf,
+    pub size: Size,
+    pub fps: u32,
+}
+
+/// Replays the audio file through the config of `target` (an output name or the path to an
+/// output config file) and writes the frames together with the audio to `file`.
+pub fn run(target: String, options: RecordOptions, file: PathBuf) -> anyhow::Result<()> {
+    if options.fps == 0 {
+        bail!("`--fps` has to be greater than 0.");
+    }
+
+    let config = crate::config::load()?;
+    config.apply_global_config();
+    let output_config = snapshot::load_output_config(&target)?;
+
+    let samples = decode_audio(&options.audio, options.duration)?;
+
+    let renderer = Renderer::new(&RendererDescriptor::from(&config.graphics_config));
+    let fetcher = SampleFetcher::new(DEFAULT_SAMPLE_RATE, AMOUNT_CHANNELS);
+    let pusher = fetcher.pusher();
+    let mut processor = SampleProcessor::new(fetcher);
+
+    let size = options.size;
+    let mut components = crate::output::create_components(
+        &renderer,
+        &processor,
+        &output_config.components,
+        snapshot::TEXTURE_FORMAT,
+    )
+    .context("Couldn't create the components")?;
+    for component in components.iter_mut() {
+        component.update_resolution(&renderer, [size.width, size.height]);
+    }
+
+    let texture = snapshot::create_texture(&renderer, size);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let colors = ColorManager::new().colors();
+    let mut frame_uniforms = FrameUniforms::new(&renderer);
+    frame_uniforms.set_colors(&colors);
+
+    let mut encoder = spawn_encoder(&options, &file)?;
+    let mut stdin = encoder.stdin.take().unwrap();
+
+    let amount_frames = (options.duration.as_secs_f32() * options.fps as f32).ceil() as usize;
+
+    info!("Recording {} frames of '{}'...", amount_frames, target);
+    for frame in 0..amount_frames {
+        let range = frame_samples(frame, options.fps);
+        let start = range.start.min(samples.len());
+        let end = range.end.min(samples.len());
+        pusher.push(&samples[start..end]);
+        processor.process_next_samples();
+
+        let time = frame as f32 / options.fps as f32;
+        frame_uniforms.set_time(time);
+        for component in components.iter_mut() {
+            component.update_time(renderer.queue(), time);
+            component.update_audio(renderer.queue(), &processor);
+            component.update_colors(renderer.queue(), &colors);
+        }
+
+        renderer.render(&view, &frame_uniforms, &components);
+
+        // the premultiplied colors are the ones on top of a black background
+        let img = snapshot::read_texture(&renderer, &texture);
+        if stdin.write_all(img.as_raw()).is_err() {
+            // `ffmpeg` exited, its error is reported below
+            break;
+        }
+    }
+    drop(stdin);
+
+    let status = encoder.wait().context("`ffmpeg` didn't finish")?;
+    if !status.success() {
+        bail!("`ffmpeg` couldn't encode the video ({}).", status);
+    }
+
+    info!("Saved the clip to '{}'.", file.to_string_lossy());
+    Ok(())
+}
+
+/// Returns the range of the interleaved samples which belong to the given frame.
+///
+/// The frames get a different amount of samples if the frame rate doesn't divide the sample rate,
+/// so the audio doesn't drift away from the video.
+pub(crate) fn frame_samples(frame: usize, fps: u32) -> Range<usize> {
+    let sample_frame = |frame: usize| frame * DEFAULT_SAMPLE_RATE as usize / fps as usize;
+    let channels = AMOUNT_CHANNELS as usize;
+
+    sample_frame(frame) * channels..sample_frame(frame + 1) * channels
+}
+
+/// Decodes the first `duration` of the audio file to interleaved stereo samples.
+pub(crate) fn decode_audio(path: &Path, duration: Duration) -> anyhow::Result<Vec<f32>> {
+    let mut decoder = Command::new(FFMPEG)
+        .args(["-loglevel", "error", "-i"])
+        .arg(path)
+        .args(["-t", &duration.as_secs_f32().to_string()])
+        .args(["-f", "f32le", "-ac", &AMOUNT_CHANNELS.to_string()])
+        .args(["-ar", &DEFAULT_SAMPLE_RATE.to_string(), "-"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .context("Couldn't start `ffmpeg`. Is it installed?")?;
+
+    let mut bytes = Vec::new();
+    decoder
+        .stdout
+        .take()
+        .unwrap()
+        .read_to_end(&mut bytes)
+        .context("Couldn't read the decoded audio")?;
+
+    let status = decoder.wait()?;
+    if !status.success() {
+        bail!(
+            "`ffmpeg` couldn't decode '{}' ({}).",
+            path.to_string_lossy(),
+            status
+        );
+    }
+
+    Ok(bytes
+        .as_chunks::<4>()
+        .0
+        .iter()
+        .map(|&sample| f32::from_le_bytes(sample))
+        .collect())
+}
+
+/// Starts `ffmpeg` which reads the raw frames from its stdin.
+fn spawn_encoder(options: &RecordOptions, file: &Path) -> anyhow::Result<std::process::Child> {
+    Command::new(FFMPEG)
+        .args(["-loglevel", "error", "-y"])
+        .args(["-f", "rawvideo", "-pix_fmt", "rgba"])
+        .args([
+            "-s",
+            &format!("{}x{}", options.size.width, options.size.height),
+        ])
+        .args(["-r", &options.fps.to_string(), "-i", "-"])
+        .args(["-t", &options.duration.as_secs_f32().to_string(), "-i"])
+        .arg(&options.audio)
+        .args(["-map", "0:v", "-map", "1:a", "-shortest"])
+        .args(["-pix_fmt", "yuv420p"])
+        .arg(file)
+        .stdin(Stdio::piped())
+        .spawn()
+        .context("Couldn't start `ffmpeg`. Is it installed?")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_samples_dont_drift() {
+        assert_eq!(frame_samples(0, 60), 0..1470);
+        assert_eq!(frame_samples(1, 60), 1470..2940);
+
+        // 44100 / 144 = 306.25 samples per frame
+        assert_eq!(frame_samples(0, 144), 0..612);
+        assert_eq!(frame_samples(3, 144), 1836..2450);
+        assert_eq!(frame_samples(143, 144).end, 88_200);
+    }
+}
@@ -0,0 +1,386 @@
+//! Changes the brightness and the colors depending on the time of the day.
+use std::{f64::consts::PI, fmt, str::FromStr};
+
+use chrono::{Datelike, Local, NaiveDate, Offset, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::colors::{Palette, MAX_COLORS};
+
+const HOURS_PER_DAY: f32 = 24.;
+const DEFAULT_TRANSITION_MINS: f32 = 30.;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScheduleConfig {
+    /// Your latitude in degrees, required for `sunrise` and `sunset`.
+    pub latitude: Option<f64>,
+    /// Your longitude in degrees (east is positive), required for `sunrise` and `sunset`.
+    pub longitude: Option<f64>,
+    /// How long (in minutes) the brightness of the previous entry blends into the one of the next entry.
+    pub transition_mins: f32,
+    pub entries: Vec<ScheduleEntry>,
+}
+
+impl Default for ScheduleConfig {
+    fn default() -> Self {
+        Self {
+            latitude: None,
+            longitude: None,
+            transition_mins: DEFAULT_TRANSITION_MINS,
+            entries: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    /// When the entry starts, for example `"07:30"`, `"sunrise"` or `"sunset-00:30"`.
+    pub start: Start,
+    /// `1` shows the frame as it is, `0` makes it black.
+    #[serde(default = "default_brightness")]
+    pub brightness: f32,
+    /// Replaces the colors of `colors.toml` while the entry is active.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub colors: Option<Palette>,
+}
+
+fn default_brightness() -> f32 {
+    1.
+}
+
+/// The start of a [ScheduleEntry]. All values are in hours.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Start {
+    Time(f32),
+    Sunrise { offset: f32 },
+    Sunset { offset: f32 },
+}
+
+impl FromStr for Start {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid start '{}'. Expected a time like \"07:30\", \"sunrise\" or \"sunset-00:30\".",
+                s
+            )
+        };
+
+        for (name, is_sunrise) in [("sunrise", true), ("sunset", false)] {
+            let Some(offset) = s.strip_prefix(name) else {
+                continue;
+            };
+
+            let offset = match offset.chars().next() {
+                None => 0.,
+                Some('+') => parse_time(&offset[1..]).ok_or_else(invalid)?,
+                Some('-') => -parse_time(&offset[1..]).ok_or_else(invalid)?,
+                Some(_) => return Err(invalid()),
+            };
+
+            return Ok(if is_sunrise {
+                Self::Sunrise { offset }
+            } else {
+                Self::Sunset { offset }
+            });
+        }
+
+        parse_time(s)
+            .filter(|&hours| hours < HOURS_PER_DAY)
+            .map(Self::Time)
+            .ok_or_else(invalid)
+    }
+}
+
+impl TryFrom<String> for Start {
+    type Error = String;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl fmt::Display for Start {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, offset) = match *self {
+            Self::Time(hours) => return write!(f, "{}", format_time(hours)),
+            Self::Sunrise { offset } => ("sunrise", offset),
+            Self::Sunset { offset } => ("sunset", offset),
+        };
+
+        write!(f, "{}", name)?;
+        if offset != 0. {
+            let sign = if offset < 0. { '-' } else { '+' };
+            write!(f, "{}{}", sign, format_time(offset.abs()))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<Start> for String {
+    fn from(start: Start) -> Self {
+        start.to_string()
+    }
+}
+
+/// Parses `HH:MM` into hours.
+fn parse_time(s: &str) -> Option<f32> {
+    let (hours, minutes) = s.split_once(':')?;
+    let hours: u32 = hours.parse().ok()?;
+    let minutes: u32 = minutes.parse().ok()?;
+
+    (minutes < 60).then(|| hours as f32 + minutes as f32 / 60.)
+}
+
+fn format_time(hours: f32) -> String {
+    let minutes = (hours * 60.).round() as u32;
+    format!("{:02}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// The values of the schedule at a point of time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scheduled {
+    pub brightness: f32,
+    pub colors: Option<Palette>,
+}
+
+pub struct Schedule {
+    config: ScheduleConfig,
+}
+
+impl Schedule {
+    pub fn new(config: ScheduleConfig) -> Result<Self, String> {
+        config.validate()?;
+        Ok(Self { config })
+    }
+
+    /// Returns the brightness and the colors for the current local time.
+    pub fn now(&self) -> Scheduled {
+        let now = Local::now();
+        let hour = now.hour() as f32 + now.minute() as f32 / 60. + now.second() as f32 / 3600.;
+        let utc_offset = now.offset().fix().local_minus_utc() as f32 / 3600.;
+
+        self.at(now.date_naive(), hour, utc_offset)
+    }
+
+    /// `hour` is the local time and `utc_offset` the offset of the local time zone (both in hours).
+    fn at(&self, date: NaiveDate, hour: f32, utc_offset: f32) -> Scheduled {
+        let mut entries: Vec<(f32, &ScheduleEntry)> = self
+            .config
+            .entries
+            .iter()
+            .map(|entry| (self.start_hour(entry.start, date, utc_offset), entry))
+            .collect();
+        entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        // the entry which started last, it's the last one of yesterday if no entry started today
+        let idx = entries
+            .iter()
+            .rposition(|(start, _)| *start <= hour)
+            .unwrap_or(entries.len() - 1);
+        let (start, current) = entries[idx];
+        let (_, previous) = entries[(idx + entries.len() - 1) % entries.len()];
+
+        let since_start = (hour - start).rem_euclid(HOURS_PER_DAY);
+        let progress = if self.config.transition_mins > 0. {
+            (since_start * 60. / self.config.transition_mins).min(1.)
+        } else {
+            1.
+        };
+
+        Scheduled {
+            brightness: previous.brightness + (current.brightness - previous.brightness) * progress,
+            colors: current.colors.clone(),
+        }
+    }
+
+    // returns the local start time of the entry within `[0, 24)`
+    fn start_hour(&self, start: Start, date: NaiveDate, utc_offset: f32) -> f32 {
+        let hour = match start {
+            Start::Time(hour) => hour,
+            Start::Sunrise { offset } | Start::Sunset { offset } => {
+                // `validate` makes sure that the location is set
+                let (sunrise, sunset) = sun_times(
+                    date,
+                    self.config.latitude.unwrap_or_default(),
+                    self.config.longitude.unwrap_or_default(),
+                );
+
+                let utc_hour = if matches!(start, Start::Sunrise { .. }) {
+                    sunrise
+                } else {
+                    sunset
+                };
+                utc_hour + utc_offset + offset
+            }
+        };
+
+        hour.rem_euclid(HOURS_PER_DAY)
+    }
+}
+
+impl ScheduleConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.entries.is_empty() {
+            return Err("The schedule needs at least one entry.".to_string());
+        }
+
+        let uses_sun = self
+            .entries
+            .iter()
+            .any(|entry| !matches!(entry.start, Start::Time(_)));
+        if uses_sun && (self.latitude.is_none() || self.longitude.is_none()) {
+            return Err(
+                "`latitude` and `longitude` are required for `sunrise` and `sunset`.".to_string(),
+            );
+        }
+
+        for entry in self.entries.iter() {
+            if !(0. ..=1.).contains(&entry.brightness) {
+                return Err(format!(
+                    "The brightness of the entry at '{}' has to be within [0, 1] but is {}.",
+                    entry.start, entry.brightness
+                ));
+            }
+
+            if let Some(colors) = &entry.colors {
+                if colors.is_empty() || colors.len() > MAX_COLORS {
+                    return Err(format!(
+                        "The entry at '{}' has to contain between 1 and {} colors.",
+                        entry.start, MAX_COLORS
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns the time of the sunrise and the sunset of the given day in UTC (in hours).
+///
+/// Uses the approximation of the [NOAA](https://gml.noaa.gov/grad/solcalc/solareqns.PDF).
+/// The sun rises and sets at noon during the polar night and at midnight during the polar day.
+fn sun_times(date: NaiveDate, latitude: f64, longitude: f64) -> (f32, f32) {
+    // the fractional year at noon
+    let gamma = 2. * PI / 365. * (date.ordinal0() as f64);
+
+    // in minutes
+    let equation_of_time = 229.18
+        * (0.000075 + 0.001868 * gamma.cos()
+            - 0.032077 * gamma.sin()
+            - 0.014615 * (2. * gamma).cos()
+            - 0.040849 * (2. * gamma).sin());
+
+    let declination = 0.006918 - 0.399912 * gamma.cos() + 0.070257 * gamma.sin()
+        - 0.006758 * (2. * gamma).cos()
+        + 0.000907 * (2. * gamma).sin()
+        - 0.002697 * (3. * gamma).cos()
+        + 0.00148 * (3. * gamma).sin();
+
+    let latitude = latitude.to_radians();
+    // the zenith of the sunrise/sunset includes the refraction of the atmosphere
+    let zenith = 90.833f64.to_radians();
+    let hour_angle = (zenith.cos() / (latitude.cos() * declination.cos())
+        - latitude.tan() * declination.tan())
+    .clamp(-1., 1.)
+    .acos()
+    .to_degrees();
+
+    let to_hours = |minutes: f64| (minutes / 60.) as f32;
+    (
+        to_hours(720. - 4. * (longitude + hour_angle) - equation_of_time),
+        to_hours(720. - 4. * (longitude - hour_angle) - equation_of_time),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(start: &str, brightness: f32) -> ScheduleEntry {
+        ScheduleEntry {
+            start: start.parse().unwrap(),
+            brightness,
+            colors: None,
+        }
+    }
+
+    fn schedule(entries: Vec<ScheduleEntry>) -> Schedule {
+        Schedule::new(ScheduleConfig {
+            latitude: Some(52.52),
+            longitude: Some(13.405),
+            transition_mins: 60.,
+            entries,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn parse_start() {
+        assert_eq!("07:30".parse(), Ok(Start::Time(7.5)));
+        assert_eq!("sunrise".parse(), Ok(Start::Sunrise { offset: 0. }));
+        assert_eq!("sunset-00:30".parse(), Ok(Start::Sunset { offset: -0.5 }));
+        assert_eq!("sunrise+01:15".parse(), Ok(Start::Sunrise { offset: 1.25 }));
+
+        for invalid in ["24:00", "7", "07:60", "sunset30", "noon"] {
+            assert!(invalid.parse::<Start>().is_err(), "{}", invalid);
+        }
+
+        for start in ["07:30", "sunrise", "sunset-00:30"] {
+            assert_eq!(start.parse::<Start>().unwrap().to_string(), start);
+        }
+    }
+
+    #[test]
+    fn brightness_blends_into_the_next_entry() {
+        let schedule = schedule(vec![entry("22:00", 0.5), entry("07:00", 1.)]);
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        assert_eq!(schedule.at(date, 12., 0.).brightness, 1.);
+        assert_eq!(schedule.at(date, 22.5, 0.).brightness, 0.75);
+        assert_eq!(schedule.at(date, 23.5, 0.).brightness, 0.5);
+        // the entry of yesterday is still active
+        assert_eq!(schedule.at(date, 3., 0.).brightness, 0.5);
+        assert_eq!(schedule.at(date, 7.5, 0.).brightness, 0.75);
+    }
+
+    #[test]
+    fn colors_of_the_active_entry() {
+        let night = vec![[0.; 3]; 2];
+        let schedule = schedule(vec![
+            entry("08:00", 1.),
+            ScheduleEntry {
+                colors: Some(night.clone()),
+                ..entry("20:00", 0.8)
+            },
+        ]);
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        assert_eq!(schedule.at(date, 12., 0.).colors, None);
+        assert_eq!(schedule.at(date, 21., 0.).colors, Some(night));
+    }
+
+    #[test]
+    fn sun_times_of_berlin() {
+        // sunrise at 04:48 and sunset at 21:20 (CEST) on the 2024-06-01
+        let date = NaiveDate::from_ymd_opt(2024, 6, 1).unwrap();
+        let (sunrise, sunset) = sun_times(date, 52.52, 13.405);
+
+        assert!((sunrise + 2. - 4.8).abs() < 0.1, "{}", sunrise);
+        assert!((sunset + 2. - 21.33).abs() < 0.1, "{}", sunset);
+    }
+
+    #[test]
+    fn sun_requires_location() {
+        let config = ScheduleConfig {
+            entries: vec![entry("sunset", 0.5)],
+            ..Default::default()
+        };
+        assert!(Schedule::new(config).is_err());
+        assert!(Schedule::new(ScheduleConfig::default()).is_err());
+    }
+}
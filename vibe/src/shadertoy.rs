@@ -0,0 +1,529 @@
+//! Imports shaders of [Shadertoy](https://www.shadertoy.com) as presets with a `FragmentCanvas`.
+//!
+//! The shaders are fetched with `curl` through the [Shadertoy API](https://www.shadertoy.com/howto#q2)
+//! which needs an API key. Only single-pass shaders (an `Image` pass with an optional `Common` pass) are supported.
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context};
+use tracing::info;
+use vibe_renderer::components::{ShaderCode, ShaderLanguage, ShaderSource};
+
+use crate::{
+    colors::json::{self, Value},
+    output::config::{
+        component::{self, FragmentCanvasAudioConfig, FragmentCanvasConfig, FragmentCanvasTexture},
+        Preset,
+    },
+};
+
+const CURL: &str = "curl";
+const SHADERTOY_URL: &str = "https://www.shadertoy.com";
+const VIEW_URL_PREFIX: &str = "https://www.shadertoy.com/view/";
+
+/// The environment variable which is used if `--key` isn't set.
+const API_KEY_VAR: &str = "SHADERTOY_API_KEY";
+
+/// The directory inside the config directory which contains the imported shaders and their textures.
+const SHADERTOY_DIR_NAME: &str = "shadertoy";
+
+/// The shadertoy uniforms which differ from the ones of `vibe`. They are renamed in the imported code
+/// and defined in [COMPAT_PREAMBLE].
+const RENAMED_UNIFORMS: [&str; 7] = [
+    "iResolution",
+    "iMouse",
+    "iFrame",
+    "iTimeDelta",
+    "iFrameRate",
+    "iDate",
+    "iSampleRate",
+];
+
+/// Maps the uniforms of shadertoy to the ones of `vibe` (see `fragment_preamble.glsl`).
+const COMPAT_PREAMBLE: &str = r#"// Shadertoy compatibility (added by `vibe import shadertoy`)
+#define stResolution vec3(iResolution, 1.0)
+// shadertoy measures the mouse in pixels from the bottom-left corner
+#define stMouse vec4(iMouse.x * iResolution.x, (1.0 - iMouse.y) * iResolution.y, 0.0, 0.0)
+#define stFrame int(iTime * 60.0)
+#define stTimeDelta (1.0 / 60.0)
+#define stFrameRate 60.0
+#define stDate vec4(0.0, 0.0, 0.0, iLocalTime * 3600.0)
+#define stSampleRate 44100.0
+
+// the spectrum of shadertoy's audio inputs
+vec4 stAudio(vec2 uv) {
+    int amount = freqs.length();
+    return vec4(freqs[clamp(int(uv.x * float(amount)), 0, amount - 1)]);
+}
+
+vec4 stAudio(vec2 uv, float lod) {
+    return stAudio(uv);
+}
+
+vec4 stAudioFetch(ivec2 coord, int lod) {
+    // the audio textures of shadertoy are 512 pixels wide
+    return stAudio(vec2((float(coord.x) + 0.5) / 512.0, 0.0));
+}
+"#;
+
+const COMPAT_MAIN: &str = r#"
+void main() {
+    vec4 color = vec4(0.0);
+    // shadertoy has its origin at the bottom-left corner
+    mainImage(color, vec2(gl_FragCoord.x, iResolution.y - gl_FragCoord.y));
+    // and ignores the alpha channel
+    fragColor = vec4(color.rgb, 1.0);
+}
+"#;
+
+/// An input channel (`iChannel0` to `iChannel3`) of a shader.
+#[derive(Debug, Clone, PartialEq)]
+enum Input {
+    /// The path of the image on the server and if it has to be flipped vertically.
+    Texture {
+        src: String,
+        vflip: bool,
+    },
+    Audio,
+}
+
+/// The parts of a shader from the API which are needed to import it.
+#[derive(Debug, Clone, PartialEq)]
+struct Shader {
+    id: String,
+    name: String,
+    username: String,
+    common_code: Option<String>,
+    image_code: String,
+    inputs: Vec<(u8, Input)>,
+}
+
+impl Shader {
+    /// Reads the response of `/api/v1/shaders/<id>`.
+    fn from_response(response: &str) -> anyhow::Result<Self> {
+        let value = json::parse(response)
+            .map_err(|err| anyhow::anyhow!("Invalid response of shadertoy: {}", err))?;
+
+        if let Some(err) = value.get("Error").and_then(Value::as_str) {
+            bail!("Shadertoy: {}", err);
+        }
+
+        let shader = value
+            .get("Shader")
+            .context("The response of shadertoy doesn't contain a shader")?;
+        let info = shader.get("info");
+        let info_str = |key: &str| {
+            info.and_then(|info| info.get(key))
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let mut common_code = None;
+        let mut image_pass = None;
+        for pass in shader
+            .get("renderpass")
+            .and_then(Value::as_array)
+            .unwrap_or_default()
+        {
+            match pass.get("type").and_then(Value::as_str) {
+                Some("common") => common_code = pass_code(pass),
+                Some("image") => image_pass = Some(pass),
+                Some(other) => bail!(
+                    "The shader has a '{}' pass but only single-pass shaders can be imported.",
+                    other
+                ),
+                None => bail!("A pass of the shader doesn't have a type."),
+            }
+        }
+
+        let image_pass = image_pass.context("The shader doesn't have an image pass.")?;
+        let image_code = pass_code(image_pass).context("The image pass doesn't have any code.")?;
+
+        let mut inputs = Vec::new();
+        for input in image_pass
+            .get("inputs")
+            .and_then(Value::as_array)
+            .unwrap_or_default()
+        {
+            let channel = input
+                .get("channel")
+                .and_then(Value::as_f64)
+                .context("An input of the shader doesn't have a channel.")?
+                as u8;
+
+            let input = match input.get("ctype").and_then(Value::as_str) {
+                Some("texture") => Input::Texture {
+                    src: input
+                        .get("src")
+                        .and_then(Value::as_str)
+                        .context("A texture of the shader doesn't have a source.")?
+                        .to_string(),
+                    // shadertoy flips the textures by default
+                    vflip: input
+                        .get("sampler")
+                        .and_then(|sampler| sampler.get("vflip"))
+                        .and_then(Value::as_str)
+                        != Some("false"),
+                },
+                Some("music" | "musicstream" | "mic") => Input::Audio,
+                Some(other) => bail!(
+                    "iChannel{} is a '{}' which isn't supported (only textures and audio are).",
+                    channel,
+                    other
+                ),
+                None => bail!("iChannel{} doesn't have a type.", channel),
+            };
+
+            inputs.push((channel, input));
+        }
+
+        let amount_textures = inputs
+            .iter()
+            .filter(|(_channel, input)| matches!(input, Input::Texture { .. }))
+            .count();
+        if amount_textures > 1 {
+            bail!(
+                "The shader uses {} textures but a `FragmentCanvas` only supports one.",
+                amount_textures
+            );
+        }
+
+        Ok(Self {
+            id: info_str("id"),
+            name: info_str("name"),
+            username: info_str("username"),
+            common_code,
+            image_code,
+            inputs,
+        })
+    }
+
+    fn texture(&self) -> Option<(u8, &str, bool)> {
+        self.inputs.iter().find_map(|(channel, input)| match input {
+            Input::Texture { src, vflip } => Some((*channel, src.as_str(), *vflip)),
+            Input::Audio => None,
+        })
+    }
+
+    /// Converts the passes into the code of a `FragmentCanvas`.
+    fn glsl_code(&self) -> String {
+        let mut code = String::new();
+        if let Some(common_code) = &self.common_code {
+            code.push_str(common_code);
+            code.push('\n');
+        }
+        code.push_str(&self.image_code);
+
+        for uniform in RENAMED_UNIFORMS {
+            code = replace_identifier(&code, uniform, &uniform.replacen('i', "st", 1));
+        }
+
+        let mut channels = String::new();
+        for (channel, input) in self.inputs.iter() {
+            let name = format!("iChannel{}", channel);
+
+            match input {
+                Input::Texture { .. } => {
+                    channels.push_str(&format!("#define {} sampler2D(iTexture, iSampler)\n", name))
+                }
+                Input::Audio => {
+                    for (function, replacement) in [
+                        ("texture", "stAudio"),
+                        ("textureLod", "stAudio"),
+                        ("texelFetch", "stAudioFetch"),
+                    ] {
+                        code = replace_channel_call(&code, function, &name, replacement);
+                    }
+                }
+            }
+        }
+
+        format!(
+            "// \"{}\" by {}: {}{}\n{}{}\n{}\n{}",
+            self.name,
+            self.username,
+            VIEW_URL_PREFIX,
+            self.id,
+            COMPAT_PREAMBLE,
+            channels,
+            code,
+            COMPAT_MAIN
+        )
+    }
+}
+
+fn pass_code(pass: &Value) -> Option<String> {
+    pass.get("code")
+        .and_then(Value::as_str)
+        .map(|code| code.to_string())
+}
+
+fn is_identifier_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Replaces all occurrences of the identifier `from` (but not of identifiers which contain it).
+fn replace_identifier(code: &str, from: &str, to: &str) -> String {
+    let mut result = String::with_capacity(code.len());
+    let mut rest = code;
+
+    while let Some(idx) = rest.find(from) {
+        let before = result
+            .chars()
+            .last()
+            .filter(|_| idx == 0)
+            .or_else(|| rest[..idx].chars().last());
+        let after = rest[idx + from.len()..].chars().next();
+
+        result.push_str(&rest[..idx]);
+        if before.is_some_and(is_identifier_char) || after.is_some_and(is_identifier_char) {
+            result.push_str(from);
+        } else {
+            result.push_str(to);
+        }
+        rest = &rest[idx + from.len()..];
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Replaces calls like `texture(iChannel0, uv)` with `replacement(uv)`.
+fn replace_channel_call(code: &str, function: &str, channel: &str, replacement: &str) -> String {
+    let mut result = String::with_capacity(code.len());
+    let mut rest = code;
+
+    while let Some(idx) = rest.find(function) {
+        let is_whole_identifier = !rest[..idx].chars().last().is_some_and(is_identifier_char)
+            && !result
+                .chars()
+                .last()
+                .filter(|_| idx == 0)
+                .is_some_and(is_identifier_char);
+
+        let arguments = rest[idx + function.len()..]
+            .trim_start()
+            .strip_prefix('(')
+            .map(str::trim_start)
+            .and_then(|args| args.strip_prefix(channel))
+            .map(str::trim_start)
+            .and_then(|args| args.strip_prefix(','))
+            .map(str::trim_start);
+
+        result.push_str(&rest[..idx]);
+        match arguments {
+            Some(arguments) if is_whole_identifier => {
+                result.push_str(replacement);
+                result.push('(');
+                rest = arguments;
+            }
+            _ => {
+                result.push_str(function);
+                rest = &rest[idx + function.len()..];
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Accepts the id of a shader or the URL of its page.
+fn shader_id(id_or_url: &str) -> &str {
+    id_or_url
+        .strip_prefix(VIEW_URL_PREFIX)
+        .unwrap_or(id_or_url)
+        .trim_end_matches('/')
+}
+
+/// Downloads `url` into `file` or returns its content if `file` is `None`.
+fn fetch(url: &str, file: Option<&Path>) -> anyhow::Result<Vec<u8>> {
+    let mut command = Command::new(CURL);
+    command.args(["--silent", "--show-error", "--fail", "--location"]);
+    if let Some(file) = file {
+        command.arg("--output").arg(file);
+    }
+
+    let output = command
+        .arg(url)
+        .output()
+        .context("Couldn't start `curl`. Is it installed?")?;
+
+    if !output.status.success() {
+        bail!(
+            "Couldn't download '{}': {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    Ok(output.stdout)
+}
+
+/// Downloads the shader (and its texture) and saves it as the preset `shadertoy-<id>`.
+pub fn run(id_or_url: String, key: Option<String>, force: bool) -> anyhow::Result<()> {
+    let Some(key) = key.or_else(|| std::env::var(API_KEY_VAR).ok()) else {
+        bail!(
+            "Shadertoy needs an API key (see {}/myapps). Pass it with `--key` or set `{}`.",
+            SHADERTOY_URL,
+            API_KEY_VAR
+        );
+    };
+
+    let id = shader_id(&id_or_url);
+    let preset_name = format!("shadertoy-{}", id);
+    let preset_path = crate::get_preset_dir().join(format!("{}.toml", preset_name));
+    if preset_path.exists() && !force {
+        bail!(
+            "'{}' exists already. Use `--force` to overwrite it.",
+            preset_path.to_string_lossy()
+        );
+    }
+
+    let response = fetch(
+        &format!("{}/api/v1/shaders/{}?key={}", SHADERTOY_URL, id, key),
+        None,
+    )?;
+    let shader = Shader::from_response(&String::from_utf8_lossy(&response))?;
+
+    let dir: PathBuf = crate::get_xdg()
+        .create_config_directory(Path::new(SHADERTOY_DIR_NAME).join(id))
+        .context("Couldn't create the directory of the shader")?;
+
+    let texture = match shader.texture() {
+        Some((channel, src, vflip)) => {
+            let file_name = Path::new(src)
+                .file_name()
+                .context("The texture of the shader doesn't have a file name.")?;
+            let path = dir.join(file_name);
+
+            info!("Downloading the texture of iChannel{}...", channel);
+            fetch(&format!("{}{}", SHADERTOY_URL, src), Some(&path))?;
+
+            if vflip {
+                let image = image::open(&path)
+                    .with_context(|| format!("Couldn't open '{}'", path.to_string_lossy()))?;
+                image
+                    .flipv()
+                    .save(&path)
+                    .with_context(|| format!("Couldn't save '{}'", path.to_string_lossy()))?;
+            }
+
+            Some(FragmentCanvasTexture { path })
+        }
+        None => None,
+    };
+
+    let shader_path = dir.join("image.glsl");
+    std::fs::write(&shader_path, shader.glsl_code())
+        .with_context(|| format!("Couldn't write '{}'", shader_path.to_string_lossy()))?;
+
+    let preset = Preset {
+        components: vec![component::Config::FragmentCanvas(FragmentCanvasConfig {
+            audio_conf: FragmentCanvasAudioConfig::default(),
+            fragment_code: ShaderCode {
+                language: ShaderLanguage::Glsl,
+                source: ShaderSource::Path(shader_path),
+            },
+            texture,
+            viewport: None,
+            palette: None,
+            params: HashMap::new(),
+            render_scale: None,
+            readback: Vec::new(),
+        })],
+    };
+
+    let content = format!(
+        "# \"{}\" by {}: {}{}\n# Check the license of the shader before sharing it.\n{}",
+        shader.name,
+        shader.username,
+        VIEW_URL_PREFIX,
+        shader.id,
+        toml::to_string(&preset)?
+    );
+    std::fs::write(&preset_path, content)
+        .with_context(|| format!("Couldn't write '{}'", preset_path.to_string_lossy()))?;
+
+    println!(
+        "Saved \"{}\" as the preset '{}'. Show it with `vibe ctl switch-preset {}`.",
+        shader.name, preset_name, preset_name
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RESPONSE: &str = r#"{"Shader":{"ver":"0.1","info":{"id":"abcd12","name":"Waves","username":"someone"},
+        "renderpass":[
+            {"inputs":[],"code":"float wave(float x) { return sin(x); }","name":"Common","type":"common"},
+            {"inputs":[
+                {"id":1,"src":"/media/a/noise.png","ctype":"texture","channel":1,"sampler":{"vflip":"true"}},
+                {"id":2,"src":"/media/a/track.mp3","ctype":"music","channel":0,"sampler":{"vflip":"true"}}
+            ],"code":"void mainImage(out vec4 fragColor, in vec2 fragCoord) {\n    fragColor = texture(iChannel0, vec2(fragCoord.x / iResolution.x, 0.25));\n}","name":"Image","type":"image"}
+        ]}}"#;
+
+    #[test]
+    fn read_response() {
+        let shader = Shader::from_response(RESPONSE).unwrap();
+
+        assert_eq!(shader.id, "abcd12");
+        assert_eq!(shader.name, "Waves");
+        assert_eq!(
+            shader.common_code.as_deref(),
+            Some("float wave(float x) { return sin(x); }")
+        );
+        assert_eq!(shader.texture(), Some((1, "/media/a/noise.png", true)));
+        assert_eq!(shader.inputs[1], (0, Input::Audio));
+    }
+
+    #[test]
+    fn reject_multipass_shaders() {
+        let response = r#"{"Shader":{"renderpass":[
+            {"inputs":[],"code":"","type":"buffer"},
+            {"inputs":[],"code":"","type":"image"}
+        ]}}"#;
+        assert!(Shader::from_response(response).is_err());
+
+        assert!(Shader::from_response(r#"{"Error":"Shader not found"}"#).is_err());
+    }
+
+    #[test]
+    fn convert_code() {
+        let code = Shader::from_response(RESPONSE).unwrap().glsl_code();
+
+        assert!(code.contains("float wave(float x)"));
+        assert!(code.contains("#define iChannel1 sampler2D(iTexture, iSampler)"));
+        assert!(code.contains("fragColor = stAudio(vec2(fragCoord.x / stResolution.x, 0.25));"));
+        assert!(code.contains("void main()"));
+    }
+
+    #[test]
+    fn replace_identifiers() {
+        assert_eq!(
+            replace_identifier("iMouse.xy + iMouseX + myiMouse", "iMouse", "stMouse"),
+            "stMouse.xy + iMouseX + myiMouse"
+        );
+        assert_eq!(
+            replace_channel_call(
+                "mytexture(iChannel0, a) + texture( iChannel0 ,b)",
+                "texture",
+                "iChannel0",
+                "stAudio"
+            ),
+            "mytexture(iChannel0, a) + stAudio(b)"
+        );
+    }
+
+    #[test]
+    fn id_from_url() {
+        assert_eq!(shader_id("https://www.shadertoy.com/view/abcd12"), "abcd12");
+        assert_eq!(shader_id("abcd12"), "abcd12");
+    }
+}
@@ -0,0 +1,152 @@
+//! Lets scripts control the daemon with signals (for example `pkill -HUP vibe`).
+//!
+//! The signal handlers only write the number of the signal into a socket which the
+//! event loop reads, so the signals are handled like any other event.
+use std::{
+    io::{self, Read},
+    os::{
+        fd::{AsFd, BorrowedFd, IntoRawFd},
+        unix::net::UnixStream,
+    },
+    sync::atomic::{AtomicI32, Ordering},
+};
+
+// the socket which the signal handlers write to
+static SIGNAL_WRITER: AtomicI32 = AtomicI32::new(-1);
+
+/// A signal which the daemon reacts to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Signal {
+    /// `SIGHUP`: Reload `config.toml` and the config files of all outputs.
+    Reload,
+    /// `SIGUSR1`: Pause the rendering or resume it if it's paused.
+    TogglePause,
+    /// `SIGTERM`: Destroy the layer surfaces and exit.
+    Terminate,
+}
+
+impl Signal {
+    const ALL: [Self; 3] = [Self::Reload, Self::TogglePause, Self::Terminate];
+
+    fn number(self) -> libc::c_int {
+        match self {
+            Self::Reload => libc::SIGHUP,
+            Self::TogglePause => libc::SIGUSR1,
+            Self::Terminate => libc::SIGTERM,
+        }
+    }
+
+    fn from_number(number: libc::c_int) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|signal| signal.number() == number)
+    }
+}
+
+/// Receives the signals of [Signal] after [Signals::install] replaced their default handlers.
+#[derive(Debug)]
+pub struct Signals {
+    reader: UnixStream,
+}
+
+impl Signals {
+    /// Installs the signal handlers. Should only be called once.
+    pub fn install() -> io::Result<Self> {
+        let (reader, writer) = UnixStream::pair()?;
+        reader.set_nonblocking(true)?;
+        // a full socket drops the signal instead of blocking the handler
+        writer.set_nonblocking(true)?;
+        SIGNAL_WRITER.store(writer.into_raw_fd(), Ordering::Relaxed);
+
+        for signal in Signal::ALL {
+            // SAFETY: `on_signal` only calls async-signal-safe functions
+            let result = unsafe {
+                let mut action: libc::sigaction = std::mem::zeroed();
+                action.sa_sigaction = on_signal as *const () as libc::sighandler_t;
+                action.sa_flags = libc::SA_RESTART;
+                libc::sigemptyset(&mut action.sa_mask);
+                libc::sigaction(signal.number(), &action, std::ptr::null_mut())
+            };
+
+            if result != 0 {
+                return Err(io::Error::last_os_error());
+            }
+        }
+
+        Ok(Self { reader })
+    }
+
+    /// Returns another handle to the same signals (for the event loop of a new session).
+    pub fn try_clone(&self) -> io::Result<Self> {
+        Ok(Self {
+            reader: self.reader.try_clone()?,
+        })
+    }
+
+    /// Returns the signals which arrived since the last call.
+    pub fn read(&self) -> io::Result<Vec<Signal>> {
+        let mut signals = Vec::new();
+        let mut buf = [0u8; 16];
+
+        loop {
+            match (&self.reader).read(&mut buf) {
+                Ok(0) => break,
+                Ok(len) => signals.extend(
+                    buf[..len]
+                        .iter()
+                        .filter_map(|&number| Signal::from_number(number as libc::c_int)),
+                ),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(signals)
+    }
+}
+
+impl AsFd for Signals {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.reader.as_fd()
+    }
+}
+
+extern "C" fn on_signal(number: libc::c_int) {
+    // `write` might overwrite the `errno` of the interrupted code
+    // SAFETY: `__errno_location` always returns a valid pointer for the current thread
+    let errno = unsafe { *libc::__errno_location() };
+
+    let byte = number as u8;
+    // SAFETY: `byte` lives until `write` returns and a closed fd only makes `write` fail
+    unsafe {
+        libc::write(
+            SIGNAL_WRITER.load(Ordering::Relaxed),
+            (&byte as *const u8).cast(),
+            1,
+        );
+        *libc::__errno_location() = errno;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receives_signals() {
+        let signals = Signals::install().unwrap();
+        assert_eq!(signals.read().unwrap(), []);
+
+        // SAFETY: the handlers of these signals are installed above
+        unsafe {
+            libc::raise(libc::SIGUSR1);
+            libc::raise(libc::SIGHUP);
+        }
+
+        assert_eq!(
+            signals.read().unwrap(),
+            [Signal::TogglePause, Signal::Reload]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! Renders a single frame of an output config into an image without a compositor.
+use std::{
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context};
+use image::RgbaImage;
+use tracing::info;
+use vibe_renderer::{FrameUniforms, Renderer, RendererDescriptor};
+
+use crate::{colors::ColorManager, output::config::OutputConfig, types::size::Size};
+
+pub(crate) const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+const PIXEL_SIZE: u32 = 4;
+
+/// The time between two frames while waiting for the snapshot.
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+/// Renders the config of `target` (an output name or the path to an output config file)
+/// for `after` and writes the last frame to `file`.
+pub fn run(target: String, after: Duration, size: Size, file: PathBuf) -> anyhow::Result<()> {
+    let config = crate::config::load()?;
+    config.apply_global_config();
+    let output_config = load_output_config(&target)?;
+
+    let renderer = Renderer::new(&RendererDescriptor::from(&config.graphics_config));
+    let mut processor = config
+        .audio_config
+        .clone()
+        .unwrap_or_default()
+        .sample_processor(output_config.audio_target.as_deref())?;
+
+    let mut components = crate::output::create_components(
+        &renderer,
+        &processor,
+        &output_config.components,
+        TEXTURE_FORMAT,
+    )
+    .context("Couldn't create the components")?;
+    for component in components.iter_mut() {
+        component.update_resolution(&renderer, [size.width, size.height]);
+    }
+
+    let texture = create_texture(&renderer, size);
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let colors = ColorManager::new().colors();
+    let mut frame_uniforms = FrameUniforms::new(&renderer);
+    frame_uniforms.set_colors(&colors);
+
+    // render the frames in between as well, so that the audio and the (stateful) components
+    // look like they would after `after` in the daemon
+    info!("Rendering '{}' for {:?}...", target, after);
+    let start = Instant::now();
+    loop {
+        processor.process_next_samples();
+        let time = start.elapsed().as_secs_f32();
+        frame_uniforms.set_time(time);
+
+        for component in components.iter_mut() {
+            component.update_time(renderer.queue(), time);
+            component.update_audio(renderer.queue(), &processor);
+            component.update_colors(renderer.queue(), &colors);
+        }
+
+        renderer.render(&view, &frame_uniforms, &components);
+
+        if start.elapsed() >= after {
+            break;
+        }
+        std::thread::sleep(FRAME_INTERVAL);
+    }
+
+    let mut img = read_texture(&renderer, &texture);
+    unpremultiply(&mut img);
+    img.save(&file)
+        .with_context(|| format!("Couldn't save the snapshot to '{}'", file.to_string_lossy()))?;
+
+    info!("Saved the snapshot to '{}'.", file.to_string_lossy());
+    Ok(())
+}
+
+/// Loads the config of an output name or of the path to an output config file.
+pub(crate) fn load_output_config(target: &str) -> anyhow::Result<OutputConfig> {
+    let path = PathBuf::from(target);
+
+    let path = if path.is_file() {
+        path
+    } else if let Some(path) = crate::output::config::find(target) {
+        path
+    } else {
+        bail!(
+            "'{}' is neither a config file nor an output with a config file.",
+            target
+        );
+    };
+
+    crate::output::config::load_from_path(&path)
+}
+
+/// Creates the texture which the components render into.
+pub(crate) fn create_texture(renderer: &Renderer, size: Size) -> wgpu::Texture {
+    renderer.device().create_texture(&wgpu::TextureDescriptor {
+        label: Some("Offscreen texture"),
+        size: wgpu::Extent3d {
+            width: size.width,
+            height: size.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: TEXTURE_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Copies the texture of [create_texture] back from the GPU.
+///
+/// The colors are premultiplied with their alpha value (like they are on top of black).
+pub(crate) fn read_texture(renderer: &Renderer, texture: &wgpu::Texture) -> RgbaImage {
+    let width = texture.width();
+    let height = texture.height();
+
+    let unpadded_line_size = width * PIXEL_SIZE;
+    let padded_line_size = unpadded_line_size.div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+        * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = renderer.device().create_buffer(&wgpu::BufferDescriptor {
+        label: Some("Snapshot buffer"),
+        size: (padded_line_size * height) as u64,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+
+    let mut encoder = renderer
+        .device()
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        texture.as_image_copy(),
+        wgpu::TexelCopyBufferInfo {
+            buffer: &buffer,
+            layout: wgpu::TexelCopyBufferLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_line_size),
+                rows_per_image: Some(height),
+            },
+        },
+        texture.size(),
+    );
+    renderer.queue().submit([encoder.finish()]);
+
+    let buffer_slice = buffer.slice(..);
+    let (tx, rx) = std::sync::mpsc::channel();
+    buffer_slice.map_async(wgpu::MapMode::Read, move |result| tx.send(result).unwrap());
+    renderer
+        .device()
+        .poll(wgpu::PollType::Wait {
+            submission_index: None,
+            timeout: None,
+        })
+        .unwrap();
+    rx.recv().unwrap().unwrap();
+
+    let mut bytes = Vec::with_capacity((unpadded_line_size * height) as usize);
+    for row in buffer_slice
+        .get_mapped_range()
+        .chunks(padded_line_size as usize)
+    {
+        bytes.extend_from_slice(&row[..unpadded_line_size as usize]);
+    }
+    buffer.unmap();
+
+    RgbaImage::from_raw(width, height, bytes).unwrap()
+}
+
+/// The components render with premultiplied alpha but images store straight alpha.
+fn unpremultiply(img: &mut RgbaImage) {
+    for pixel in img.pixels_mut() {
+        let alpha = pixel[3];
+        if alpha > 0 && alpha < u8::MAX {
+            for channel in 0..3 {
+                pixel[channel] =
+                    (pixel[channel] as u32 * u8::MAX as u32 / alpha as u32).min(255) as u8;
+            }
+        }
+    }
+}
+
+/// Parses durations like `5s`, `500ms` or `1.5m`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "Invalid duration '{}'. Expected something like `5s`, `500ms` or `1m`.",
+            s
+        )
+    };
+
+    let unit_start = s
+        .find(|c: char| c.is_ascii_alphabetic())
+        .ok_or_else(invalid)?;
+    let (value, unit) = s.split_at(unit_start);
+    let value: f32 = value.trim().parse().map_err(|_| invalid())?;
+
+    let secs = match unit {
+        "ms" => value / 1000.,
+        "s" => value,
+        "m" => value * 60.,
+        _ => return Err(invalid()),
+    };
+
+    Duration::try_from_secs_f32(secs).map_err(|_| invalid())
+}
+
+/// Returns `true` if the file has an extension which the snapshot can be saved as.
+pub fn is_supported_file(path: &Path) -> bool {
+    image::ImageFormat::from_path(path).is_ok_and(|format| format.can_write())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("5s"), Ok(Duration::from_secs(5)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5m"), Ok(Duration::from_secs(90)));
+        assert!(parse_duration("5").is_err());
+        assert!(parse_duration("5h").is_err());
+        assert!(parse_duration("-1s").is_err());
+    }
+}
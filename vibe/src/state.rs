@@ -1,24 +1,51 @@
 use crate::{
+    audio_source::{AudioSource, AudioSources},
+    battery::{self, BatteryConfig, PowerStatus},
     colors::ColorManager,
-    config::ConfigError,
+    config::{ConfigError, DefaultOutputConfig, OutputFilter},
+    current_wallpaper,
+    frame_pacer::FramePacer,
+    idle::{IdleHandler, IdleState},
+    ipc::{Command, Reply},
+    mpris::{self, Player, Track},
     output::{
-        config::{component::Config, OutputConfig},
+        config::{component::Config, IdleConfig, OutputConfig, PlaylistEntry},
         OutputCtx,
     },
+    pause_rules::{self, PauseRulesConfig},
+    playlist::Playlist,
+    schedule::Schedule,
+    signals::Signal,
+    stats,
+    toplevel::{ToplevelHandler, ToplevelState},
     types::size::Size,
+    video_export::{VideoExport, VideoExportConfig},
 };
 use anyhow::Context;
 use raw_window_handle::{
     RawDisplayHandle, RawWindowHandle, WaylandDisplayHandle, WaylandWindowHandle,
 };
+use smithay_client_toolkit::reexports::calloop::{
+    self,
+    timer::{TimeoutAction, Timer},
+    LoopHandle,
+};
+use smithay_client_toolkit::reexports::protocols::ext::idle_notify::v1::client::{
+    ext_idle_notification_v1::ExtIdleNotificationV1, ext_idle_notifier_v1::ExtIdleNotifierV1,
+};
+use smithay_client_toolkit::reexports::protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::ZwlrForeignToplevelHandleV1,
+    zwlr_foreign_toplevel_manager_v1::ZwlrForeignToplevelManagerV1,
+};
 use smithay_client_toolkit::{
     compositor::{CompositorHandler, CompositorState},
-    delegate_compositor, delegate_layer, delegate_output, delegate_pointer, delegate_registry,
-    delegate_seat,
-    output::{OutputHandler, OutputState},
+    delegate_compositor, delegate_keyboard, delegate_layer, delegate_output, delegate_pointer,
+    delegate_registry, delegate_seat,
+    output::{OutputHandler, OutputInfo, OutputState},
     registry::{ProvidesRegistryState, RegistryState},
     registry_handlers,
     seat::{
+        keyboard::{KeyEvent, KeyboardHandler, Keysym, Modifiers, RawModifiers},
         pointer::{PointerEventKind, PointerHandler},
         Capability, SeatHandler, SeatState,
     },
@@ -27,40 +54,156 @@ use smithay_client_toolkit::{
         WaylandSurface,
     },
 };
-use std::{collections::HashMap, ptr::NonNull, time::Instant};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    ptr::NonNull,
+    time::{Duration, Instant},
+};
 use tracing::{debug, error, info, warn};
-use vibe_audio::{fetcher::SystemAudioFetcher, SampleProcessor};
 use vibe_renderer::Renderer;
 use wayland_client::{
     globals::GlobalList,
-    protocol::{wl_output::WlOutput, wl_pointer::WlPointer, wl_surface::WlSurface},
+    protocol::{
+        wl_keyboard::WlKeyboard, wl_output::WlOutput, wl_pointer::WlPointer, wl_seat::WlSeat,
+        wl_surface::WlSurface,
+    },
     Connection, Proxy, QueueHandle,
 };
 
+/// The factor of `sensitivity-up` and `sensitivity-down`.
+const SENSITIVITY_STEP: f32 = 1.25;
+
+/// The changes of `set-sensitivity`, `sensitivity-up` and `sensitivity-down` until the next `reload`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct SensitivityOverride {
+    // set by `set-sensitivity`, replaces the sensitivity of all components
+    value: Option<f32>,
+    // changed by `sensitivity-up` and `sensitivity-down`, scales the sensitivity of each component
+    factor: f32,
+}
+
+impl Default for SensitivityOverride {
+    fn default() -> Self {
+        Self {
+            value: None,
+            factor: 1.,
+        }
+    }
+}
+
+impl SensitivityOverride {
+    /// Applies the changes to the sensitivity which is configured for each component.
+    fn apply(&self, mut configs: Vec<Config>) -> Vec<Config> {
+        for config in configs.iter_mut() {
+            let sensitivity = self.value.unwrap_or(config.sensitivity());
+            config.set_sensitivity(sensitivity * self.factor);
+        }
+
+        configs
+    }
+
+    /// Replaces the `previous` changes of the configs of running components with these ones.
+    fn replace(&self, previous: &Self, mut configs: Vec<Config>) -> Vec<Config> {
+        for config in configs.iter_mut() {
+            // `value` is only reset by a `reload`, so only the previous factor has to be undone
+            let configured = config.sensitivity() / previous.factor;
+            config.set_sensitivity(self.value.unwrap_or(configured) * self.factor);
+        }
+
+        configs
+    }
+}
+
+/// The input devices of a seat.
+#[derive(Default)]
+struct SeatInput {
+    pointer: Option<WlPointer>,
+    // the surface which the pointer is on
+    pointer_focus: Option<WlSurface>,
+    keyboard: Option<WlKeyboard>,
+    // the surface which has the keyboard focus
+    keyboard_focus: Option<WlSurface>,
+}
+
+impl SeatInput {
+    fn release(self) {
+        if let Some(pointer) = self.pointer {
+            pointer.release();
+        }
+
+        if let Some(keyboard) = self.keyboard {
+            keyboard.release();
+        }
+    }
+}
+
 pub struct State {
     pub run: bool,
+    // set if the session has to be recreated, for example to switch the GPU
+    pub restart: bool,
+    // set by the `pause` command
+    paused: bool,
+    // applied to the components of all outputs
+    sensitivity: SensitivityOverride,
+    // `None` if frame pacing is disabled
+    frame_pacer: Option<FramePacer>,
+    loop_handle: LoopHandle<'static, Self>,
 
     default_component: Config,
+    default_output_configs: Vec<DefaultOutputConfig>,
+    output_filter: OutputFilter,
+    notify_errors: bool,
+    pause_when_covered: bool,
 
     output_state: OutputState,
     registry_state: RegistryState,
     seat_state: SeatState,
     layer_shell: LayerShell,
     compositor_state: CompositorState,
+    toplevel_state: ToplevelState,
+    idle_state: IdleState,
 
     renderer: Renderer,
-    sample_processor: SampleProcessor<SystemAudioFetcher>,
+    audio_sources: AudioSources,
 
     time: Instant,
-    pointer: Option<WlPointer>,
+    // each seat has its own pointer and keyboard (and focus)
+    seats: HashMap<WlSeat, SeatInput>,
 
     outputs: HashMap<WlOutput, OutputCtx>,
 
     color_manager: ColorManager,
+    // `None` if `mpris_config` isn't set
+    player: Option<Player>,
+    // keeps `playerctl` running
+    _mpris_listener: Option<mpris::Listener>,
+    // `None` if `schedule_config` isn't set
+    schedule: Option<Schedule>,
+    // `None` if `battery_config` isn't set
+    battery_config: Option<BatteryConfig>,
+    saving_power: bool,
+    // the power preference of `graphics_config`
+    power_preference: wgpu::PowerPreference,
+    // `None` if `pause_rules` isn't set
+    pause_rules: Option<PauseRulesConfig>,
+    // the names of the running processes, updated every `pause_rules::POLL_INTERVAL`
+    processes: Vec<String>,
+    // the app of `pause_rules` which is running
+    paused_by: Option<String>,
+    // `None` if `video_export` isn't set
+    video_export: Option<VideoExportConfig>,
+    // the wallpaper of the last check, only looked up if a component uses `wallpaper_path = "auto"`
+    current_wallpaper: Option<PathBuf>,
 }
 
 impl State {
-    pub fn new(globals: &GlobalList, qh: &QueueHandle<Self>) -> anyhow::Result<Self> {
+    pub fn new(
+        globals: &GlobalList,
+        qh: &QueueHandle<Self>,
+        loop_handle: LoopHandle<'static, Self>,
+        time: Instant,
+    ) -> anyhow::Result<Self> {
         let Ok(layer_shell) = LayerShell::bind(globals, qh) else {
             error!(concat![
                 "Your compositor doesn't seem to implement the wlr_layer_shell protocol but this is required for this program to run. ",
@@ -114,44 +257,914 @@ impl State {
                         warn!("Couldn't create default config file: {:?}", err);
                     };
                 }
+                // don't overwrite the config file, it might belong to a newer version of `vibe`
+                err @ (ConfigError::InvalidVersion(_) | ConfigError::UnsupportedVersion(_)) => {
+                    warn!("{} Fallback to default config file.", err);
+                }
             };
 
             default_config
         });
 
-        let sample_processor = vibe_config.sample_processor()?;
+        vibe_config.apply_global_config();
+        let audio_sources =
+            AudioSources::new(vibe_config.audio_config.clone().unwrap_or_default())?;
+
+        let saving_power = vibe_config
+            .battery_config
+            .as_ref()
+            .is_some_and(|config| config.should_save_power(&PowerStatus::read()));
+
+        let mut renderer = {
+            let mut descriptor =
+                vibe_renderer::RendererDescriptor::from(&vibe_config.graphics_config);
+            if saving_power
+                && vibe_config
+                    .battery_config
+                    .as_ref()
+                    .is_some_and(|config| config.low_power_adapter)
+            {
+                descriptor.power_preference = wgpu::PowerPreference::LowPower;
+            }
+
+            Renderer::new(&descriptor)
+        };
+        renderer.set_event_sink(
+            vibe_config
+                .events
+                .clone()
+                .unwrap_or_default()
+                .event_sink()?,
+        );
+
+        if vibe_config.battery_config.is_some() {
+            let qh = qh.clone();
+            loop_handle
+                .insert_source(
+                    Timer::from_duration(battery::POLL_INTERVAL),
+                    move |_deadline, _, state: &mut Self| {
+                        state.update_power_status(&qh);
+                        TimeoutAction::ToDuration(battery::POLL_INTERVAL)
+                    },
+                )
+                .map_err(|err| err.error)?;
+        }
+
+        if vibe_config.pause_rules.is_some() {
+            let qh = qh.clone();
+            loop_handle
+                .insert_source(Timer::immediate(), move |_deadline, _, state: &mut Self| {
+                    state.processes = pause_rules::process_names();
+                    state.update_pause_rules(&qh);
+                    TimeoutAction::ToDuration(pause_rules::POLL_INTERVAL)
+                })
+                .map_err(|err| err.error)?;
+        }
+
+        loop_handle
+            .insert_source(
+                Timer::from_duration(current_wallpaper::POLL_INTERVAL),
+                |_deadline, _, state: &mut Self| {
+                    state.update_current_wallpaper();
+                    TimeoutAction::ToDuration(current_wallpaper::POLL_INTERVAL)
+                },
+            )
+            .map_err(|err| err.error)?;
+
+        let mpris_listener = match &vibe_config.mpris_config {
+            Some(mpris_config) => {
+                let (sender, channel) = calloop::channel::channel();
+                loop_handle
+                    .insert_source(channel, |event, _, state: &mut Self| {
+                        if let calloop::channel::Event::Msg(track) = event {
+                            state.set_track(track);
+                        }
+                    })
+                    .map_err(|err| err.error)?;
+
+                match mpris::Listener::spawn(mpris_config, sender) {
+                    Ok(listener) => Some(listener),
+                    Err(err) => {
+                        warn!(
+                            "Couldn't start `playerctl` to follow the media player: {}. Ignoring `mpris_config`.",
+                            err
+                        );
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
 
-        let renderer = Renderer::new(&vibe_renderer::RendererDescriptor::from(
-            &vibe_config.graphics_config,
-        ));
+        let schedule = vibe_config
+            .schedule_config
+            .clone()
+            .and_then(|schedule_config| {
+                Schedule::new(schedule_config)
+                    .inspect_err(|err| warn!("Invalid `schedule_config`: {} Ignoring it.", err))
+                    .ok()
+            });
 
         Ok(Self {
             run: true,
+            restart: false,
+            paused: false,
+            sensitivity: SensitivityOverride::default(),
+            frame_pacer: vibe_config
+                .frame_pacing_config
+                .as_ref()
+                .map(FramePacer::new),
+            loop_handle,
             compositor_state: CompositorState::bind(globals, qh).unwrap(),
             seat_state: SeatState::new(globals, qh),
             output_state: OutputState::new(globals, qh),
             registry_state: RegistryState::new(globals),
+            toplevel_state: ToplevelState::new(globals, qh),
+            idle_state: IdleState::new(globals, qh),
             layer_shell,
             renderer,
 
-            time: Instant::now(),
-            pointer: None,
+            time,
+            seats: HashMap::new(),
 
-            sample_processor,
+            audio_sources,
 
             outputs: HashMap::new(),
 
             default_component: vibe_config.default_component.unwrap_or_default(),
+            default_output_configs: vibe_config.default_output_configs,
+            output_filter: vibe_config.output_filter.unwrap_or_default(),
+            notify_errors: vibe_config.notify_errors.unwrap_or(true),
+            pause_when_covered: vibe_config.pause_when_covered.unwrap_or(true),
 
             color_manager: ColorManager::new(),
+            player: mpris_listener.as_ref().map(|_| Player::new()),
+            _mpris_listener: mpris_listener,
+            schedule,
+            battery_config: vibe_config.battery_config,
+            saving_power,
+            power_preference: vibe_config.graphics_config.power_preference,
+            pause_rules: vibe_config.pause_rules,
+            processes: Vec::new(),
+            paused_by: None,
+            video_export: vibe_config.video_export,
+            current_wallpaper: None,
         })
     }
 
+    /// Returns the components of the config file of a new output.
+    fn default_components(&self, info: &OutputInfo) -> Vec<Config> {
+        let name = info.name.as_deref().unwrap_or_default();
+
+        match DefaultOutputConfig::find(
+            &self.default_output_configs,
+            name,
+            info.description.as_deref(),
+        ) {
+            Some(config) => {
+                info!(
+                    "Using the default config '{}' for '{}'",
+                    config.pattern, name
+                );
+                config.components.clone()
+            }
+            None => vec![self.default_component.clone()],
+        }
+    }
+
+    /// Called by the MPRIS listener once another track is playing.
+    fn set_track(&mut self, track: Option<Track>) {
+        let Some(player) = self.player.as_mut() else {
+            return;
+        };
+
+        match &track {
+            Some(track) => info!("Now playing: {} - {}", track.artist, track.title),
+            None => debug!("The media player stopped."),
+        }
+
+        self.color_manager
+            .set_override(track.as_ref().and_then(|track| track.palette.clone()));
+        player.set_track(track, self.time.elapsed().as_secs_f32());
+    }
+
+    /// Pauses rendering while one of the apps of `pause_rules` is running.
+    fn update_pause_rules(&mut self, qh: &QueueHandle<Self>) {
+        let Some(config) = &self.pause_rules else {
+            return;
+        };
+
+        let paused_by = config
+            .running_app(self.toplevel_state.app_ids(), &self.processes)
+            .map(|app| app.to_string());
+        if paused_by == self.paused_by {
+            return;
+        }
+
+        match &paused_by {
+            Some(app) => info!("Pausing while '{}' is running.", app),
+            None => info!("Resuming since the paused apps exited."),
+        }
+        self.paused_by = paused_by;
+        self.update_audio_capture();
+
+        // the outputs stop rendering on their own with their next frame
+        self.wake_outputs(qh);
+    }
+
+    /// Recreates the components with `wallpaper_path = "auto"` once the wallpaper changed.
+    fn update_current_wallpaper(&mut self) {
+        let uses_current_wallpaper = |output: &OutputCtx| {
+            output
+                .components_config()
+                .iter()
+                .any(Config::uses_current_wallpaper)
+        };
+        if !self.outputs.values().any(uses_current_wallpaper) {
+            return;
+        }
+
+        let wallpaper = current_wallpaper::find();
+        if wallpaper.is_none() || wallpaper == self.current_wallpaper {
+            return;
+        }
+
+        // the components got created with the current wallpaper if it's the first check
+        let previous = std::mem::replace(&mut self.current_wallpaper, wallpaper);
+        if previous.is_none() {
+            return;
+        }
+
+        info!(
+            "The wallpaper changed to '{}'.",
+            self.current_wallpaper.as_ref().unwrap().to_string_lossy()
+        );
+        for output in self.outputs.values_mut() {
+            if !uses_current_wallpaper(output) {
+                continue;
+            }
+
+            let configs = output.components_config().to_vec();
+            let preset = output.preset().map(str::to_string);
+            if let Err(err) = output.set_components(
+                &self.renderer,
+                &self.audio_sources.get(&output.audio_target).processor,
+                configs,
+                preset,
+            ) {
+                warn!(
+                    "Couldn't show the new wallpaper on output '{}': {}",
+                    output.name(),
+                    err
+                );
+            }
+        }
+    }
+
+    /// Called periodically if `battery_config` is set.
+    /// Lowers the quality once the laptop runs on battery and restores it once it's plugged in.
+    fn update_power_status(&mut self, qh: &QueueHandle<Self>) {
+        let Some(config) = self.battery_config.clone() else {
+            return;
+        };
+
+        let saving_power = config.should_save_power(&PowerStatus::read());
+        if saving_power == self.saving_power {
+            return;
+        }
+        self.saving_power = saving_power;
+
+        if saving_power {
+            info!("Running on battery. Saving power.");
+        } else {
+            info!("Stopped saving power.");
+        }
+
+        if config.low_power_adapter && self.power_preference != wgpu::PowerPreference::LowPower {
+            info!("Restarting to switch the GPU.");
+            self.restart = true;
+            return;
+        }
+
+        let Some(fallback_component) = config.fallback_component else {
+            return;
+        };
+
+        if saving_power {
+            for output in self.outputs.values_mut() {
+                output.playlist = None;
+                output.set_fade(self.renderer.queue(), 0.);
+
+                if let Err(err) = output.set_components(
+                    &self.renderer,
+                    &self.audio_sources.get(&output.audio_target).processor,
+                    vec![fallback_component.clone()],
+                    None,
+                ) {
+                    warn!(
+                        "Couldn't show the fallback component on output '{}': {}",
+                        output.name(),
+                        err
+                    );
+                }
+            }
+        } else if let Err(err) = self.handle_command(Command::Reload, qh) {
+            warn!("Couldn't restore the components of the outputs:\n{}", err);
+        }
+    }
+
+    /// Reacts to a signal which got sent to the daemon.
+    pub fn handle_signal(&mut self, signal: Signal, qh: &QueueHandle<Self>) {
+        debug!("Received signal: {:?}", signal);
+
+        let command = match signal {
+            Signal::Reload => {
+                if let Err(err) = self.reload_config() {
+                    warn!("{}", err);
+                }
+                return;
+            }
+            Signal::TogglePause if self.paused => Command::Resume,
+            Signal::TogglePause => Command::Pause,
+            Signal::Terminate => {
+                // dropping the outputs destroys their layer surfaces
+                self.outputs.clear();
+                self.run = false;
+                return;
+            }
+        };
+
+        if let Err(err) = self.handle_command(command, qh) {
+            warn!("{}", err);
+        }
+    }
+
+    /// Reloads `config.toml` and the configs of all outputs by recreating the session.
+    ///
+    /// Keeps the current session if `config.toml` can't be loaded.
+    fn reload_config(&mut self) -> Reply {
+        if let Err(err) = crate::config::load() {
+            let err = format!(
+                "Couldn't load '{}': {}",
+                crate::get_config_path().to_string_lossy(),
+                err
+            );
+            if self.notify_errors {
+                crate::notification::error("vibe: Couldn't reload the config", &err);
+            }
+
+            return Err(err);
+        }
+
+        info!("Reloading the configs.");
+        self.restart = true;
+        Ok(String::new())
+    }
+
+    /// Executes a command which got sent through the IPC socket.
+    pub fn handle_command(&mut self, command: Command, qh: &QueueHandle<Self>) -> Reply {
+        debug!("Received command: {}", command);
+
+        match command {
+            Command::Pause => {
+                self.paused = true;
+                self.update_audio_capture();
+                Ok(String::new())
+            }
+            Command::Resume => {
+                if self.paused {
+                    self.paused = false;
+                    self.update_audio_capture();
+                    self.wake_outputs(qh);
+                }
+
+                Ok(String::new())
+            }
+            Command::Reload => {
+                self.sensitivity = SensitivityOverride::default();
+
+                // keep showing the fallback component until the laptop is plugged in again
+                let fallback_component = match &self.battery_config {
+                    Some(BatteryConfig {
+                        fallback_component: Some(fallback_component),
+                        ..
+                    }) if self.saving_power => Some(fallback_component.clone()),
+                    _ => None,
+                };
+
+                // outputs which got disabled are removed, `config.toml` is only reloaded by `SIGHUP`
+                let disabled: Vec<WlOutput> = self
+                    .outputs
+                    .iter()
+                    .filter(|(_key, output)| {
+                        matches!(
+                            crate::output::config::load(output.name()),
+                            Some((_path, Ok(config))) if !config.enable
+                        )
+                    })
+                    .map(|(key, _output)| key.clone())
+                    .collect();
+                for key in disabled {
+                    info!(
+                        "Output is disabled. Removing output '{}'",
+                        self.outputs[&key].name()
+                    );
+                    self.idle_state.unwatch(&key);
+                    self.outputs.remove(&key);
+                }
+
+                let mut errors = Vec::new();
+                for (key, output) in self.outputs.iter_mut() {
+                    let result = match crate::output::config::load(output.name()) {
+                        Some((_path, Ok(config))) => {
+                            let layer_surface_config =
+                                config.layer_surface_config.unwrap_or_default();
+                            let graphics_config = config.graphics_config.unwrap_or_default();
+
+                            let result = layer_surface_config
+                                .validate()
+                                .and_then(|()| graphics_config.validate())
+                                .and_then(|()| match &config.playlist {
+                                    Some(playlist) => playlist.validate(),
+                                    None => Ok(()),
+                                })
+                                .and_then(|()| {
+                                    self.audio_sources
+                                        .add(&config.audio_target)
+                                        .map_err(|err| format!("{:?}", err))
+                                });
+
+                            let playlist = config.playlist.map(|playlist| {
+                                Playlist::new(
+                                    playlist,
+                                    Instant::now(),
+                                    track_changes(
+                                        &self.player,
+                                        &self.audio_sources,
+                                        &config.audio_target,
+                                    ),
+                                )
+                            });
+
+                            let result = result.and_then(|()| match &playlist {
+                                Some(playlist) => playlist
+                                    .current()
+                                    .load()
+                                    .map_err(|err| format!("{:?}", err)),
+                                None => Ok(config.components),
+                            });
+
+                            match result {
+                                Ok(components) => {
+                                    let preset = playlist
+                                        .as_ref()
+                                        .and_then(|playlist| playlist.current().preset())
+                                        .map(|preset| preset.to_string());
+                                    let (components, playlist, preset) = match &fallback_component {
+                                        Some(component) => (vec![component.clone()], None, None),
+                                        None => (components, playlist, preset),
+                                    };
+
+                                    output.set_layer_surface_config(layer_surface_config);
+                                    output.set_graphics_config(&self.renderer, &graphics_config);
+                                    output.silence_config = config.silence_config;
+                                    self.idle_state.watch(
+                                        key,
+                                        self.seat_state.seats().next().as_ref(),
+                                        config.idle_config.as_ref().and_then(IdleConfig::timeout),
+                                        qh,
+                                    );
+                                    output.fps_limit = config.fps_limit;
+                                    output.audio_target = config.audio_target;
+                                    output.playlist = playlist;
+                                    output.set_fade(self.renderer.queue(), 0.);
+                                    output
+                                        .set_components(
+                                            &self.renderer,
+                                            &self.audio_sources.get(&output.audio_target).processor,
+                                            components,
+                                            preset,
+                                        )
+                                        .map_err(|err| err.to_string())
+                                }
+                                Err(err) => Err(err),
+                            }
+                        }
+                        Some((_path, Err(err))) => Err(format!("{:?}", err)),
+                        None => Err("Config file doesn't exist anymore".to_string()),
+                    };
+
+                    if let Err(err) = result {
+                        errors.push(format!("{}: {}", output.name(), err));
+                    }
+                }
+
+                self.audio_sources
+                    .retain(self.outputs.values().map(|output| &output.audio_target));
+
+                if errors.is_empty() {
+                    Ok(String::new())
+                } else {
+                    let errors = errors.join("\n");
+                    if self.notify_errors {
+                        // the previous components keep running
+                        crate::notification::error("vibe: Couldn't reload the config", &errors);
+                    }
+
+                    Err(errors)
+                }
+            }
+            Command::SetSensitivity { value } => {
+                if !value.is_finite() || value < 0. {
+                    return Err(format!("Invalid sensitivity: {}", value));
+                }
+
+                self.update_sensitivity(SensitivityOverride {
+                    value: Some(value),
+                    factor: 1.,
+                })
+            }
+            Command::SensitivityUp => self.update_sensitivity(SensitivityOverride {
+                factor: self.sensitivity.factor * SENSITIVITY_STEP,
+                ..self.sensitivity
+            }),
+            Command::SensitivityDown => self.update_sensitivity(SensitivityOverride {
+                factor: self.sensitivity.factor / SENSITIVITY_STEP,
+                ..self.sensitivity
+            }),
+            Command::SwitchPreset { name, output } => self.switch_preset(name, output),
+            Command::NextPreset { output } => {
+                let names = crate::output::config::preset_names().map_err(|err| {
+                    format!(
+                        "Couldn't read '{}': {}",
+                        crate::get_preset_dir().to_string_lossy(),
+                        err
+                    )
+                })?;
+
+                let mut outputs: Vec<&OutputCtx> = self
+                    .outputs
+                    .values()
+                    .filter(|ctx| output.as_deref().is_none_or(|output| output == ctx.name()))
+                    .collect();
+                outputs.sort_by_key(|ctx| ctx.name());
+                let current = outputs.first().and_then(|ctx| ctx.preset());
+
+                let name = crate::output::config::next_preset(&names, current)
+                    .ok_or("There are no presets")?
+                    .to_string();
+                self.switch_preset(name, output)
+            }
+            Command::Status => {
+                let track = match self.player.as_ref().map(|player| player.track()) {
+                    Some(Some(track)) => format!("{} - {}", track.artist, track.title),
+                    Some(None) => "none".to_string(),
+                    None => "mpris disabled".to_string(),
+                };
+
+                let brightness = match &self.schedule {
+                    Some(schedule) => format!("{:.2}", schedule.now().brightness),
+                    None => "no schedule".to_string(),
+                };
+
+                let power = match &self.battery_config {
+                    Some(_config) if self.saving_power => "saving",
+                    Some(_config) => "normal",
+                    None => "battery_config disabled",
+                };
+
+                let mut status = format!(
+                    "paused: {}\nsensitivity: {}\ntrack: {}\nbrightness: {}\npower: {}\noutputs:",
+                    self.paused,
+                    match self.sensitivity.value {
+                        Some(value) => format!("{} x {}", value, self.sensitivity.factor),
+                        None => format!("from config x {}", self.sensitivity.factor),
+                    },
+                    track,
+                    brightness,
+                    power
+                );
+
+                let mut outputs: Vec<(&WlOutput, &OutputCtx)> = self.outputs.iter().collect();
+                outputs.sort_by_key(|(_key, output)| output.name());
+
+                for (key, output) in outputs {
+                    let playlist = match &output.playlist {
+                        Some(playlist) => {
+                            let (position, amount_entries) = playlist.progress();
+                            format!("{}/{}", position, amount_entries)
+                        }
+                        None => "none".to_string(),
+                    };
+
+                    status.push_str(&format!(
+                        "\n  {}: {} component(s), preset: {}, playlist: {}, audio: {}, covered: {}, idle: {}, pointers: {}",
+                        output.name(),
+                        output.components.len(),
+                        output.preset().unwrap_or("none"),
+                        playlist,
+                        output.audio_target.as_deref().unwrap_or("default"),
+                        self.toplevel_state.is_covered(key),
+                        self.idle_state.is_idle(key),
+                        self.amount_pointers(output.layer_surface().wl_surface())
+                    ));
+                }
+
+                Ok(status)
+            }
+            Command::Stats => Ok(self.stats()),
+        }
+    }
+
+    /// Replaces the changes to the sensitivity of all components until the next `reload`.
+    fn update_sensitivity(&mut self, sensitivity: SensitivityOverride) -> Reply {
+        let previous = self.sensitivity;
+        self.sensitivity = sensitivity;
+
+        let mut errors = Vec::new();
+        for output in self.outputs.values_mut() {
+            let configs = sensitivity.replace(&previous, output.components_config().to_vec());
+            let preset = output.preset().map(|preset| preset.to_string());
+
+            if let Err(err) = output.set_components(
+                &self.renderer,
+                &self.audio_sources.get(&output.audio_target).processor,
+                configs,
+                preset,
+            ) {
+                errors.push(format!("{}: {}", output.name(), err));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(String::new())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    /// Shows the components of the given preset on the given output (or all outputs).
+    fn switch_preset(&mut self, name: String, output: Option<String>) -> Reply {
+        let preset =
+            crate::output::config::load_preset(&name).map_err(|err| format!("{:?}", err))?;
+
+        let configs = self.sensitivity.apply(preset.components);
+
+        let mut found_output = false;
+        for ctx in self.outputs.values_mut() {
+            if output.as_deref().is_some_and(|output| output != ctx.name()) {
+                continue;
+            }
+
+            found_output = true;
+            ctx.playlist = None;
+            ctx.set_fade(self.renderer.queue(), 0.);
+            ctx.set_components(
+                &self.renderer,
+                &self.audio_sources.get(&ctx.audio_target).processor,
+                configs.clone(),
+                Some(name.clone()),
+            )
+            .map_err(|err| format!("{}: {}", ctx.name(), err))?;
+        }
+
+        match output {
+            Some(output) if !found_output => Err(format!("Unknown output: '{}'", output)),
+            _ => Ok(String::new()),
+        }
+    }
+
+    /// Returns a table with the frame times of the outputs and the load of the audio sources.
+    pub fn stats(&self) -> String {
+        let mut outputs: Vec<&OutputCtx> = self.outputs.values().collect();
+        outputs.sort_by_key(|output| output.name());
+
+        let mut table = format!(
+            "{:<12} {:>6} {:>10} {:>10} {:>10} {:>8}",
+            "output", "fps", "frame avg", "frame max", "render", "dropped"
+        );
+        for output in outputs {
+            let summary = output.stats.summary();
+            table.push_str(&format!(
+                "\n{:<12} {:>6.1} {:>10} {:>10} {:>10} {:>8}",
+                output.name(),
+                summary.fps,
+                stats::millis(summary.avg_frame_time),
+                stats::millis(summary.max_frame_time),
+                stats::millis(summary.avg_render_time),
+                summary.dropped
+            ));
+        }
+
+        let mut sources: Vec<(&Option<String>, &AudioSource)> = self.audio_sources.iter().collect();
+        sources.sort_by_key(|(target, _source)| *target);
+
+        table.push_str(&format!("\n\n{:<30} {:>6} {:>6}", "audio", "cpu", "bpm"));
+        for (target, source) in sources {
+            table.push_str(&format!(
+                "\n{:<30} {:>5.1}% {:>6.0}",
+                target.as_deref().unwrap_or("default"),
+                source.cpu_usage() * 100.,
+                source.bpm()
+            ));
+        }
+
+        table
+    }
+
+    /// Returns `false` if we are paused, the output is hidden by a window,
+    /// it has been silent for too long or the user is idle.
+    fn should_render(&self, key: &WlOutput) -> bool {
+        let output = &self.outputs[key];
+
+        let silent_since = self.audio_sources.get(&output.audio_target).silent_since();
+        let is_silenced = match (&output.silence_config, silent_since) {
+            (Some(config), Some(silent_since)) => {
+                silent_since.elapsed().as_secs_f32() >= config.pause_after_secs
+            }
+            _ => false,
+        };
+
+        !self.paused
+            && self.paused_by.is_none()
+            && !(self.pause_when_covered && self.toplevel_state.is_covered(key))
+            && !is_silenced
+            && !self.idle_state.is_idle(key)
+    }
+
+    /// Stops capturing audio while rendering is paused (by `vibe ctl pause` or the pause rules).
+    fn update_audio_capture(&mut self) {
+        self.audio_sources
+            .set_paused(self.paused || self.paused_by.is_some());
+    }
+
+    /// Restarts rendering of all outputs which should be rendered.
+    fn wake_outputs(&mut self, qh: &QueueHandle<Self>) {
+        let keys: Vec<WlOutput> = self
+            .outputs
+            .keys()
+            .filter(|key| self.should_render(key))
+            .cloned()
+            .collect();
+
+        for key in keys {
+            self.outputs.get_mut(&key).unwrap().wake(qh);
+        }
+    }
+
+    /// Processes the next samples of all audio sources and wakes the outputs up if a silence ended.
+    fn process_audio(&mut self, qh: &QueueHandle<Self>) {
+        let silence_ended = self.audio_sources.process_next_samples();
+
+        if let Some(frame_pacer) = &mut self.frame_pacer {
+            frame_pacer.update(self.audio_sources.spectral_flux());
+        }
+
+        if silence_ended {
+            self.wake_outputs(qh);
+        }
+    }
+
+    /// Called periodically by the event loop.
+    ///
+    /// The audio is only processed while rendering, so this keeps processing it
+    /// if no output is rendering to notice when the silence ends.
+    pub fn poll_audio(&mut self, qh: &QueueHandle<Self>) {
+        let is_rendering = self.outputs.values().any(|output| output.frame_pending());
+
+        if !self.audio_sources.is_paused() && !is_rendering {
+            self.process_audio(qh);
+        }
+    }
+
+    /// Returns how long we should wait until the output is rendered the next time.
+    fn frame_delay(&self, key: &WlOutput) -> Option<Duration> {
+        let output = &self.outputs[key];
+
+        let frame_interval = {
+            let pacer_interval = self
+                .frame_pacer
+                .as_ref()
+                .map(|frame_pacer| frame_pacer.frame_interval())
+                .unwrap_or_default();
+
+            let battery_interval = match &self.battery_config {
+                Some(config) if self.saving_power => config.min_frame_interval(),
+                _ => Duration::ZERO,
+            };
+
+            pacer_interval
+                .max(output.min_frame_interval())
+                .max(battery_interval)
+        };
+        let delay = frame_interval.saturating_sub(output.last_render().elapsed());
+
+        (!delay.is_zero()).then_some(delay)
+    }
+
+    /// Returns the output whose wallpaper is shown on the given surface.
+    fn output_key(&self, surface: &WlSurface) -> Option<WlOutput> {
+        self.outputs
+            .iter()
+            .find(|(_out, ctx)| ctx.layer_surface().wl_surface() == surface)
+            .map(|(out, _ctx)| out.clone())
+    }
+
+    /// Renders the output after the given delay instead of waiting for the next frame callback.
+    fn schedule_frame(&mut self, key: WlOutput, delay: Duration, qh: &QueueHandle<Self>) {
+        self.outputs.get_mut(&key).unwrap().frame_scheduled();
+
+        let timer_key = key.clone();
+        let timer_qh = qh.clone();
+        let result = self.loop_handle.insert_source(
+            Timer::from_duration(delay),
+            move |_deadline, _, state: &mut Self| {
+                // the output might have been removed in the meantime
+                if let Some(output) = state.outputs.get_mut(&timer_key) {
+                    output.frame_received();
+
+                    if state.should_render(&timer_key) {
+                        state.process_audio(&timer_qh);
+                        state.render(timer_key.clone(), &timer_qh);
+                    }
+                }
+
+                TimeoutAction::Drop
+            },
+        );
+
+        if let Err(err) = result {
+            warn!("Couldn't schedule frame: {}. Rendering immediately.", err);
+            self.outputs.get_mut(&key).unwrap().frame_received();
+            self.process_audio(qh);
+            self.render(key, qh);
+        }
+    }
+
+    /// Shows the next entry of the playlist of the output if it's time to.
+    fn update_playlist(&mut self, key: &WlOutput) {
+        let output = self.outputs.get_mut(key).unwrap();
+        let audio_source = self.audio_sources.get(&output.audio_target);
+
+        let Some(playlist) = output.playlist.as_mut() else {
+            return;
+        };
+
+        let track_changes = track_changes(&self.player, &self.audio_sources, &output.audio_target);
+        let update = playlist.update(Instant::now(), track_changes);
+        let next_entry = update.switched.then(|| playlist.current().clone());
+
+        output.set_fade(self.renderer.queue(), update.fade);
+
+        if let Some(entry) = next_entry {
+            let result = match load_playlist_entry(&entry, &self.sensitivity) {
+                Ok(configs) => output
+                    .set_components(
+                        &self.renderer,
+                        &audio_source.processor,
+                        configs,
+                        entry.preset().map(|preset| preset.to_string()),
+                    )
+                    .map_err(|err| err.to_string()),
+                Err(err) => Err(format!("{:?}", err)),
+            };
+
+            if let Err(err) = result {
+                warn!(
+                    "Couldn't show the next playlist entry of output '{}': {}",
+                    output.name(),
+                    err
+                );
+            }
+        }
+    }
+
+    /// Returns `true` if the GPU got lost and the state has to be recreated.
+    pub fn is_device_lost(&self) -> bool {
+        self.renderer.is_device_lost()
+    }
+
     pub fn render(&mut self, output_key: WlOutput, qh: &QueueHandle<Self>) {
+        if self.renderer.is_device_lost() {
+            return;
+        }
+        let render_start = Instant::now();
+
         // Check for color config changes (cheap mtime check)
         self.color_manager.check_and_reload();
 
+        self.update_playlist(&output_key);
+
+        let scheduled = self.schedule.as_ref().map(Schedule::now);
+        if let Some(scheduled) = &scheduled {
+            self.color_manager.set_scheduled(scheduled.colors.clone());
+        }
+
         let output = self.outputs.get_mut(&output_key).unwrap();
+        if let Some(scheduled) = &scheduled {
+            output.set_brightness(self.renderer.queue(), scheduled.brightness);
+        }
+        let sample_processor = &self.audio_sources.get(&output.audio_target).processor;
 
         // update the buffers for the next frame
         {
@@ -159,8 +1172,16 @@ impl State {
             let curr_time = self.time.elapsed().as_secs_f32();
             let colors = self.color_manager.colors();
 
+            output.frame_uniforms.set_time(curr_time);
+            output.frame_uniforms.set_colors(&colors);
+            if let Some(player) = &self.player {
+                output
+                    .frame_uniforms
+                    .set_track(player.change_time(), player.track().map(|track| track.id()));
+            }
+
             for component in output.components.iter_mut() {
-                component.update_audio(queue, &self.sample_processor);
+                component.update_audio(queue, sample_processor);
                 component.update_time(queue, curr_time);
                 component.update_colors(queue, &colors);
             }
@@ -172,7 +1193,8 @@ impl State {
                     &surface_texture
                         .texture
                         .create_view(&wgpu::TextureViewDescriptor::default()),
-                    &output.components,
+                    &output.frame_uniforms,
+                    &output.renderables(),
                 );
 
                 // GPU readback: let components read pixels from the rendered surface
@@ -184,18 +1206,59 @@ impl State {
                     );
                 }
 
+                if let Some(video_export) = output.video_export.as_mut() {
+                    video_export.export(&self.renderer, &surface_texture.texture);
+                }
+
                 surface_texture.present();
+                output.frame_rendered();
+                output.stats.frame_rendered(render_start.elapsed());
                 output.request_redraw(qh);
             }
             Err(wgpu::SurfaceError::OutOfMemory) => unreachable!("Out of memory"),
+            Err(err @ (wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost)) => {
+                // for example after the output got resized or rotated
+                output.stats.frame_dropped();
+                info!("Reconfiguring the surface of '{}': {}", output.name(), err);
+                output.configure_surface(&self.renderer);
+                output.request_redraw(qh);
+            }
             Err(wgpu::SurfaceError::Timeout) => {
-                error!("A frame took too long to be present")
+                output.stats.frame_dropped();
+                error!("A frame took too long to be present");
+                // try again instead of freezing the wallpaper
+                output.request_redraw(qh);
+            }
+            Err(err) => {
+                output.stats.frame_dropped();
+                warn!("{}", err);
+                output.request_redraw(qh);
             }
-            Err(err) => warn!("{}", err),
         };
     }
 }
 
+/// Returns how often the track changed, used for `on_track_change` of playlists.
+fn track_changes(
+    player: &Option<Player>,
+    audio_sources: &AudioSources,
+    audio_target: &Option<String>,
+) -> u64 {
+    // the media player knows better than the silence between two tracks
+    match player {
+        Some(player) => player.changes(),
+        None => audio_sources.get(audio_target).track_changes(),
+    }
+}
+
+/// Loads the components of the playlist entry with the changes to the sensitivity.
+fn load_playlist_entry(
+    entry: &PlaylistEntry,
+    sensitivity: &SensitivityOverride,
+) -> anyhow::Result<Vec<Config>> {
+    Ok(sensitivity.apply(entry.load()?))
+}
+
 delegate_output!(State);
 impl OutputHandler for State {
     fn output_state(&mut self) -> &mut OutputState {
@@ -214,6 +1277,17 @@ impl OutputHandler for State {
 
         info!("Detected output: '{}'", &name);
 
+        if !self
+            .output_filter
+            .allows(&name, info.description.as_deref())
+        {
+            info!(
+                "Output is excluded by `output_filter`. Skipping output '{}'",
+                name
+            );
+            return;
+        }
+
         let config = match crate::output::config::load(&name) {
             Some((path, res)) => match res {
                 Ok(config) => {
@@ -229,7 +1303,7 @@ impl OutputHandler for State {
                     return;
                 }
             },
-            None => match OutputConfig::new(&info, self.default_component.clone()) {
+            None => match OutputConfig::new(&info, self.default_components(&info)) {
                 Ok(config) => {
                     info!("Created new default config file for output: '{}'", name);
                     config
@@ -249,6 +1323,72 @@ impl OutputHandler for State {
             return;
         }
 
+        if let Some(Err(err)) = config.layer_surface_config.as_ref().map(|c| c.validate()) {
+            error!(
+                "Invalid layer surface config of output '{}': {} Skipping output...",
+                name, err
+            );
+            return;
+        }
+
+        if let Some(Err(err)) = config.graphics_config.as_ref().map(|c| c.validate()) {
+            error!(
+                "Invalid graphics config of output '{}': {} Skipping output...",
+                name, err
+            );
+            return;
+        }
+
+        if let Some(Err(err)) = config.playlist.as_ref().map(|c| c.validate()) {
+            error!(
+                "Invalid playlist of output '{}': {} Skipping output...",
+                name, err
+            );
+            return;
+        }
+
+        if let Err(err) = self.audio_sources.add(&config.audio_target) {
+            error!(
+                "Couldn't listen to the audio target of output '{}': {:?} Skipping output...",
+                name, err
+            );
+            return;
+        }
+        let audio_target = config.audio_target.clone();
+
+        let playlist = config.playlist.clone().map(|playlist| {
+            Playlist::new(
+                playlist,
+                Instant::now(),
+                track_changes(&self.player, &self.audio_sources, &audio_target),
+            )
+        });
+
+        let components = match &playlist {
+            Some(playlist) => match load_playlist_entry(playlist.current(), &self.sensitivity) {
+                Ok(components) => components,
+                Err(err) => {
+                    error!(
+                        "Couldn't load the first playlist entry of output '{}': {:?} Skipping output...",
+                        name, err
+                    );
+                    return;
+                }
+            },
+            None => self.sensitivity.apply(config.components),
+        };
+        let (components, playlist) = match &self.battery_config {
+            Some(BatteryConfig {
+                fallback_component: Some(fallback_component),
+                ..
+            }) if self.saving_power => (vec![fallback_component.clone()], None),
+            _ => (components, playlist),
+        };
+        let config = OutputConfig {
+            components,
+            ..config
+        };
+
         let layer_surface = {
             let wl_surface = self.compositor_state.create_surface(qh);
             let layer_surface = self.layer_shell.create_layer_surface(
@@ -281,23 +1421,44 @@ impl OutputHandler for State {
             }
         };
 
-        let ctx = OutputCtx::new(
+        let idle_timeout = config.idle_config.as_ref().and_then(IdleConfig::timeout);
+        self.idle_state.watch(
+            &output,
+            self.seat_state.seats().next().as_ref(),
+            idle_timeout,
+            qh,
+        );
+
+        let mut ctx = OutputCtx::new(
             info,
             surface,
             layer_surface,
             &self.renderer,
-            &self.sample_processor,
+            &self.audio_sources.get(&audio_target).processor,
             config,
+            playlist,
         );
+        ctx.video_export = self
+            .video_export
+            .as_ref()
+            .map(|config| VideoExport::new(ctx.name(), config));
 
         self.outputs.insert(output, ctx);
     }
 
-    fn update_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, _output: WlOutput) {}
+    fn update_output(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, output: WlOutput) {
+        // the mode or transform of the output might have changed (for example after rotating a monitor)
+        if let Some(ctx) = self.outputs.get(&output) {
+            ctx.refresh_layer_surface();
+        }
+    }
 
     fn output_destroyed(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, output: WlOutput) {
         info!("An output was removed.");
+        self.idle_state.unwatch(&output);
         self.outputs.remove(&output);
+        self.audio_sources
+            .retain(self.outputs.values().map(|output| &output.audio_target));
     }
 }
 
@@ -306,19 +1467,41 @@ impl CompositorHandler for State {
     fn scale_factor_changed(
         &mut self,
         _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _surface: &wayland_client::protocol::wl_surface::WlSurface,
-        _new_factor: i32,
+        qh: &QueueHandle<Self>,
+        surface: &WlSurface,
+        new_factor: i32,
     ) {
+        let Some(key) = self.output_key(surface) else {
+            return;
+        };
+        debug!("New scale factor: {}", new_factor);
+
+        self.outputs
+            .get_mut(&key)
+            .unwrap()
+            .set_scale(&self.renderer, new_factor);
+
+        // the buffer scale gets applied with the next commit which must contain a buffer with the new size
+        self.process_audio(qh);
+        self.render(key, qh);
     }
 
     fn transform_changed(
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _surface: &wayland_client::protocol::wl_surface::WlSurface,
-        _new_transform: wayland_client::protocol::wl_output::Transform,
+        surface: &WlSurface,
+        new_transform: wayland_client::protocol::wl_output::Transform,
     ) {
+        let Some(key) = self.output_key(surface) else {
+            return;
+        };
+        debug!("New transform: {:?}", new_transform);
+
+        // We keep rendering upright buffers and let the compositor rotate them.
+        // The width and height of a rotated output are swapped though, so let the compositor
+        // send us the new size.
+        self.outputs[&key].refresh_layer_surface();
     }
 
     fn frame(
@@ -328,15 +1511,22 @@ impl CompositorHandler for State {
         surface: &WlSurface,
         _time: u32,
     ) {
-        self.sample_processor.process_next_samples();
+        let key = self.output_key(surface).unwrap();
 
-        let key = self
-            .outputs
-            .iter()
-            .find(|(_out, ctx)| ctx.layer_surface().wl_surface() == surface)
-            .map(|(out, _ctx)| out.clone())
-            .unwrap();
+        self.outputs.get_mut(&key).unwrap().frame_received();
 
+        // Don't request the next frame so the compositor stops calling us until
+        // we get resumed, the output becomes visible again or the audio returns.
+        if !self.should_render(&key) {
+            return;
+        }
+
+        if let Some(delay) = self.frame_delay(&key) {
+            self.schedule_frame(key, delay, qh);
+            return;
+        }
+
+        self.process_audio(qh);
         self.render(key, qh);
     }
 
@@ -367,7 +1557,7 @@ impl LayerShellHandler for State {
 
     fn configure(
         &mut self,
-        conn: &Connection,
+        _conn: &Connection,
         qh: &QueueHandle<Self>,
         layer: &LayerSurface,
         configure: LayerSurfaceConfigure,
@@ -376,11 +1566,11 @@ impl LayerShellHandler for State {
         let new_size = Size::from(configure.new_size);
         debug!("Configure new size: {:?}", new_size);
 
-        let (key, surface) = self
+        let key = self
             .outputs
             .iter()
             .find(|(_out, ctx)| ctx.layer_surface() == layer)
-            .map(|(out, ctx)| (out.clone(), ctx.layer_surface().wl_surface().clone()))
+            .map(|(out, _ctx)| out.clone())
             .unwrap();
 
         {
@@ -388,7 +1578,37 @@ impl LayerShellHandler for State {
             output_mut.resize(&self.renderer, new_size);
         }
 
-        self.frame(conn, qh, &surface, 0);
+        // Always render (even if we're paused or the output is covered)
+        // so that the surface has a buffer with the new size.
+        self.process_audio(qh);
+        self.render(key, qh);
+    }
+}
+
+wayland_client::delegate_dispatch!(State: [ZwlrForeignToplevelManagerV1: ()] => ToplevelState);
+wayland_client::delegate_dispatch!(State: [ZwlrForeignToplevelHandleV1: ()] => ToplevelState);
+wayland_client::delegate_dispatch!(State: [ExtIdleNotifierV1: ()] => IdleState);
+wayland_client::delegate_dispatch!(State: [ExtIdleNotificationV1: WlOutput] => IdleState);
+impl IdleHandler for State {
+    fn idle_state(&mut self) -> &mut IdleState {
+        &mut self.idle_state
+    }
+
+    fn idle_changed(&mut self, output: &WlOutput, qh: &QueueHandle<Self>) {
+        if self.outputs.contains_key(output) && self.should_render(output) {
+            self.outputs.get_mut(output).unwrap().wake(qh);
+        }
+    }
+}
+
+impl ToplevelHandler for State {
+    fn toplevel_state(&mut self) -> &mut ToplevelState {
+        &mut self.toplevel_state
+    }
+
+    fn toplevels_changed(&mut self, qh: &QueueHandle<Self>) {
+        self.update_pause_rules(qh);
+        self.wake_outputs(qh);
     }
 }
 
@@ -407,28 +1627,34 @@ impl SeatHandler for State {
         &mut self.seat_state
     }
 
-    fn new_seat(
-        &mut self,
-        _conn: &Connection,
-        _qh: &QueueHandle<Self>,
-        _seat: wayland_client::protocol::wl_seat::WlSeat,
-    ) {
+    fn new_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, seat: WlSeat) {
+        self.seats.entry(seat).or_default();
     }
 
     fn new_capability(
         &mut self,
         _conn: &Connection,
         qh: &QueueHandle<Self>,
-        seat: wayland_client::protocol::wl_seat::WlSeat,
+        seat: WlSeat,
         capability: smithay_client_toolkit::seat::Capability,
     ) {
-        if capability == Capability::Pointer && self.pointer.is_none() {
+        let input = self.seats.entry(seat.clone()).or_default();
+
+        if capability == Capability::Pointer && input.pointer.is_none() {
             debug!("Mouse found");
             let pointer = self
                 .seat_state
                 .get_pointer(qh, &seat)
                 .expect("Create pointer");
-            self.pointer = Some(pointer);
+            input.pointer = Some(pointer);
+        }
+
+        if capability == Capability::Keyboard && input.keyboard.is_none() {
+            debug!("Keyboard found");
+            match self.seat_state.get_keyboard(qh, &seat, None) {
+                Ok(keyboard) => input.keyboard = Some(keyboard),
+                Err(err) => warn!("Couldn't create keyboard: {}", err),
+            }
         }
     }
 
@@ -436,20 +1662,150 @@ impl SeatHandler for State {
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _seat: wayland_client::protocol::wl_seat::WlSeat,
+        seat: WlSeat,
         capability: smithay_client_toolkit::seat::Capability,
     ) {
-        if capability == Capability::Pointer && self.pointer.is_some() {
-            debug!("Mouse removed");
-            self.pointer.take().unwrap().release();
+        let Some(input) = self.seats.get_mut(&seat) else {
+            return;
+        };
+
+        if capability == Capability::Pointer {
+            if let Some(pointer) = input.pointer.take() {
+                debug!("Mouse removed");
+                pointer.release();
+                input.pointer_focus = None;
+            }
+        }
+
+        if capability == Capability::Keyboard {
+            if let Some(keyboard) = input.keyboard.take() {
+                debug!("Keyboard removed");
+                keyboard.release();
+                input.keyboard_focus = None;
+            }
+        }
+    }
+
+    fn remove_seat(&mut self, _conn: &Connection, _qh: &QueueHandle<Self>, seat: WlSeat) {
+        if let Some(input) = self.seats.remove(&seat) {
+            input.release();
+        }
+    }
+}
+
+impl State {
+    /// Returns the input devices of the seat which the keyboard belongs to.
+    fn keyboard_seat(&mut self, keyboard: &WlKeyboard) -> Option<&mut SeatInput> {
+        self.seats
+            .values_mut()
+            .find(|input| input.keyboard.as_ref() == Some(keyboard))
+    }
+
+    /// Returns the input devices of the seat which the pointer belongs to.
+    fn pointer_seat(&mut self, pointer: &WlPointer) -> Option<&mut SeatInput> {
+        self.seats
+            .values_mut()
+            .find(|input| input.pointer.as_ref() == Some(pointer))
+    }
+
+    /// Returns how many pointers are on the given surface.
+    fn amount_pointers(&self, surface: &WlSurface) -> usize {
+        self.seats
+            .values()
+            .filter(|input| input.pointer_focus.as_ref() == Some(surface))
+            .count()
+    }
+
+    fn key_event(&mut self, keyboard: &WlKeyboard, keysym: Keysym, pressed: bool) {
+        let Some(focus) = self
+            .keyboard_seat(keyboard)
+            .and_then(|input| input.keyboard_focus.clone())
+        else {
+            return;
+        };
+
+        if let Some(output) = self
+            .outputs
+            .values_mut()
+            .find(|output| output.layer_surface().wl_surface() == &focus)
+        {
+            let current_time = self.time.elapsed().as_secs_f32();
+            output.update_key(self.renderer.queue(), keysym.raw(), pressed, current_time);
+        }
+    }
+}
+
+delegate_keyboard!(State);
+impl KeyboardHandler for State {
+    fn enter(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        keyboard: &WlKeyboard,
+        surface: &WlSurface,
+        _serial: u32,
+        _raw: &[u32],
+        _keysyms: &[Keysym],
+    ) {
+        if let Some(input) = self.keyboard_seat(keyboard) {
+            input.keyboard_focus = Some(surface.clone());
+        }
+    }
+
+    fn leave(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        keyboard: &WlKeyboard,
+        _surface: &WlSurface,
+        _serial: u32,
+    ) {
+        if let Some(input) = self.keyboard_seat(keyboard) {
+            input.keyboard_focus = None;
         }
     }
 
-    fn remove_seat(
+    fn press_key(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        keyboard: &WlKeyboard,
+        _serial: u32,
+        event: KeyEvent,
+    ) {
+        self.key_event(keyboard, event.keysym, true);
+    }
+
+    fn repeat_key(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &WlKeyboard,
+        _serial: u32,
+        _event: KeyEvent,
+    ) {
+    }
+
+    fn release_key(
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _seat: wayland_client::protocol::wl_seat::WlSeat,
+        keyboard: &WlKeyboard,
+        _serial: u32,
+        event: KeyEvent,
+    ) {
+        self.key_event(keyboard, event.keysym, false);
+    }
+
+    fn update_modifiers(
+        &mut self,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+        _keyboard: &WlKeyboard,
+        _serial: u32,
+        _modifiers: Modifiers,
+        _raw_modifiers: RawModifiers,
+        _layout: u32,
     ) {
     }
 }
@@ -460,10 +1816,24 @@ impl PointerHandler for State {
         &mut self,
         _conn: &Connection,
         _qh: &QueueHandle<Self>,
-        _pointer: &wayland_client::protocol::wl_pointer::WlPointer,
+        pointer: &WlPointer,
         events: &[smithay_client_toolkit::seat::pointer::PointerEvent],
     ) {
         for event in events {
+            match event.kind {
+                PointerEventKind::Enter { .. } => {
+                    if let Some(input) = self.pointer_seat(pointer) {
+                        input.pointer_focus = Some(event.surface.clone());
+                    }
+                }
+                PointerEventKind::Leave { .. } => {
+                    if let Some(input) = self.pointer_seat(pointer) {
+                        input.pointer_focus = None;
+                    }
+                }
+                _ => {}
+            }
+
             if let Some(output) = self
                 .outputs
                 .values_mut()
@@ -471,7 +1841,8 @@ impl PointerHandler for State {
             {
                 let queue = self.renderer.queue();
                 match event.kind {
-                    PointerEventKind::Motion { .. } => {
+                    // the pointer might have moved to another output in the meantime
+                    PointerEventKind::Enter { .. } | PointerEventKind::Motion { .. } => {
                         output.update_mouse_position(queue, event.position);
                     }
                     PointerEventKind::Press { button, .. } => {
@@ -494,3 +1865,51 @@ impl PointerHandler for State {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::output::config::component::BarsConfig;
+
+    fn bars(sensitivity: f32) -> Config {
+        let mut config = Config::Bars(BarsConfig::default());
+        config.set_sensitivity(sensitivity);
+        config
+    }
+
+    fn sensitivities(configs: &[Config]) -> Vec<f32> {
+        configs.iter().map(Config::sensitivity).collect()
+    }
+
+    #[test]
+    fn sensitivity_factor_scales_each_component() {
+        let configured = vec![bars(5.), bars(0.5)];
+        let up = SensitivityOverride {
+            value: None,
+            factor: 2.,
+        };
+
+        let running = up.replace(&SensitivityOverride::default(), configured.clone());
+        assert_eq!(sensitivities(&running), [10., 1.]);
+        assert_eq!(sensitivities(&up.apply(configured)), [10., 1.]);
+
+        let down = SensitivityOverride {
+            value: None,
+            factor: 0.5,
+        };
+        assert_eq!(sensitivities(&down.replace(&up, running)), [2.5, 0.25]);
+    }
+
+    #[test]
+    fn sensitivity_value_replaces_the_configured_one() {
+        let set = SensitivityOverride {
+            value: Some(3.),
+            factor: 1.,
+        };
+        let running = set.replace(&SensitivityOverride::default(), vec![bars(5.), bars(0.5)]);
+        assert_eq!(sensitivities(&running), [3., 3.]);
+
+        let up = SensitivityOverride { factor: 2., ..set };
+        assert_eq!(sensitivities(&up.replace(&set, running)), [6., 6.]);
+    }
+}
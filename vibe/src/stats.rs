@@ -0,0 +1,202 @@
+//! Collects runtime statistics to diagnose performance issues.
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+/// The amount of frames which the statistics are computed from.
+const WINDOW: usize = 120;
+
+/// A longer gap between two frames means that the output didn't render on purpose
+/// (it was paused, covered, silent, ...) instead of dropping frames.
+const MAX_FRAME_GAP: Duration = Duration::from_secs(1);
+
+/// A frame which took this much longer than the average counts as dropped.
+const DROPPED_FACTOR: u32 = 2;
+
+/// How long the processing time of the audio is summed up before the usage is updated.
+const USAGE_WINDOW: Duration = Duration::from_secs(2);
+
+/// The frame times of an output.
+#[derive(Debug, Default)]
+pub struct FrameStats {
+    // the durations between the last frames
+    intervals: VecDeque<Duration>,
+    // how long the CPU needed to prepare and submit the last frames
+    render_times: VecDeque<Duration>,
+    last_frame: Option<Instant>,
+    dropped: u64,
+}
+
+/// A summary of [FrameStats].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameSummary {
+    pub fps: f32,
+    pub avg_frame_time: Duration,
+    pub max_frame_time: Duration,
+    pub avg_render_time: Duration,
+    pub dropped: u64,
+}
+
+impl FrameStats {
+    /// Has to be called after each presented frame.
+    pub fn frame_rendered(&mut self, render_time: Duration) {
+        self.record(Instant::now(), render_time);
+    }
+
+    /// Has to be called if a frame couldn't be presented.
+    pub fn frame_dropped(&mut self) {
+        self.dropped += 1;
+    }
+
+    fn record(&mut self, now: Instant, render_time: Duration) {
+        if let Some(interval) = self.last_frame.map(|last_frame| now - last_frame) {
+            if interval <= MAX_FRAME_GAP {
+                if let Some(avg) = average(&self.intervals) {
+                    if interval > avg * DROPPED_FACTOR {
+                        self.dropped += 1;
+                    }
+                }
+
+                push(&mut self.intervals, interval);
+            }
+        }
+
+        push(&mut self.render_times, render_time);
+        self.last_frame = Some(now);
+    }
+
+    pub fn summary(&self) -> FrameSummary {
+        let avg_frame_time = average(&self.intervals).unwrap_or_default();
+
+        FrameSummary {
+            fps: if avg_frame_time.is_zero() {
+                0.
+            } else {
+                1. / avg_frame_time.as_secs_f32()
+            },
+            avg_frame_time,
+            max_frame_time: self.intervals.iter().copied().max().unwrap_or_default(),
+            avg_render_time: average(&self.render_times).unwrap_or_default(),
+            dropped: self.dropped,
+        }
+    }
+}
+
+/// Measures which part of the time is spent on a task.
+#[derive(Debug)]
+pub struct Usage {
+    busy: Duration,
+    window_start: Instant,
+    // the usage of the last complete window
+    usage: f32,
+}
+
+impl Default for Usage {
+    fn default() -> Self {
+        Self {
+            busy: Duration::ZERO,
+            window_start: Instant::now(),
+            usage: 0.,
+        }
+    }
+}
+
+impl Usage {
+    /// Adds the time which the task took once.
+    pub fn add(&mut self, busy: Duration) {
+        self.add_at(Instant::now(), busy);
+    }
+
+    fn add_at(&mut self, now: Instant, busy: Duration) {
+        self.busy += busy;
+
+        let elapsed = now - self.window_start;
+        if elapsed >= USAGE_WINDOW {
+            self.usage = self.busy.as_secs_f32() / elapsed.as_secs_f32();
+            self.busy = Duration::ZERO;
+            self.window_start = now;
+        }
+    }
+
+    /// Returns the fraction (`0..=1`) of the time which is spent on the task.
+    pub fn usage(&self) -> f32 {
+        self.usage
+    }
+}
+
+fn push(values: &mut VecDeque<Duration>, value: Duration) {
+    if values.len() == WINDOW {
+        values.pop_front();
+    }
+    values.push_back(value);
+}
+
+fn average(values: &VecDeque<Duration>) -> Option<Duration> {
+    (!values.is_empty()).then(|| values.iter().sum::<Duration>() / values.len() as u32)
+}
+
+/// Formats a duration as milliseconds for the tables.
+pub fn millis(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f32() * 1000.)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_millis(16);
+
+    #[test]
+    fn frame_times() {
+        let mut stats = FrameStats::default();
+        let start = Instant::now();
+
+        for frame in 0..10 {
+            stats.record(start + FRAME * frame, Duration::from_millis(2));
+        }
+
+        let summary = stats.summary();
+        assert_eq!(summary.avg_frame_time, FRAME);
+        assert_eq!(summary.max_frame_time, FRAME);
+        assert_eq!(summary.avg_render_time, Duration::from_millis(2));
+        assert_eq!(summary.dropped, 0);
+        assert!((summary.fps - 62.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn slow_frames_are_dropped_but_pauses_not() {
+        let mut stats = FrameStats::default();
+        let mut now = Instant::now();
+
+        for _ in 0..10 {
+            now += FRAME;
+            stats.record(now, Duration::ZERO);
+        }
+
+        // a hiccup
+        now += FRAME * 3;
+        stats.record(now, Duration::ZERO);
+        assert_eq!(stats.summary().dropped, 1);
+
+        // the output didn't render on purpose
+        now += Duration::from_secs(10);
+        stats.record(now, Duration::ZERO);
+        assert_eq!(stats.summary().dropped, 1);
+
+        stats.frame_dropped();
+        assert_eq!(stats.summary().dropped, 2);
+    }
+
+    #[test]
+    fn usage() {
+        let mut usage = Usage::default();
+        let start = usage.window_start;
+
+        usage.add_at(start + Duration::from_secs(1), Duration::from_millis(100));
+        assert_eq!(usage.usage(), 0.);
+
+        usage.add_at(start + USAGE_WINDOW, Duration::from_millis(100));
+        assert!((usage.usage() - 0.1).abs() < 1e-4);
+    }
+}
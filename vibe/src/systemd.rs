@@ -0,0 +1,112 @@
+//! Integration into systemd (user) services with the `sd_notify` protocol.
+//!
+//! See <https://www.freedesktop.org/software/systemd/man/latest/sd_notify.html>.
+use std::{
+    io,
+    os::{
+        linux::net::SocketAddrExt,
+        unix::net::{SocketAddr, UnixDatagram},
+    },
+    time::Duration,
+};
+
+use tracing::debug;
+
+const NOTIFY_SOCKET: &str = "NOTIFY_SOCKET";
+const WATCHDOG_USEC: &str = "WATCHDOG_USEC";
+const WATCHDOG_PID: &str = "WATCHDOG_PID";
+
+/// Sends state changes of the daemon to the service manager.
+pub struct Notifier {
+    socket: UnixDatagram,
+    addr: SocketAddr,
+}
+
+impl Notifier {
+    /// Returns `None` if the daemon isn't started by systemd (or the unit isn't of `Type=notify`).
+    pub fn from_env() -> io::Result<Option<Self>> {
+        let Some(path) = std::env::var_os(NOTIFY_SOCKET) else {
+            return Ok(None);
+        };
+        let path = path.to_string_lossy();
+
+        // a leading `@` stands for an abstract socket
+        let addr = match path.strip_prefix('@') {
+            Some(name) => SocketAddr::from_abstract_name(name)?,
+            None => SocketAddr::from_pathname(path.as_ref())?,
+        };
+
+        Ok(Some(Self {
+            socket: UnixDatagram::unbound()?,
+            addr,
+        }))
+    }
+
+    /// Tells systemd that the startup finished.
+    pub fn ready(&self) -> io::Result<()> {
+        self.notify("READY=1")
+    }
+
+    /// Tells systemd that we are still alive.
+    pub fn watchdog(&self) -> io::Result<()> {
+        self.notify("WATCHDOG=1")
+    }
+
+    /// Tells systemd that the daemon is shutting down.
+    pub fn stopping(&self) -> io::Result<()> {
+        self.notify("STOPPING=1")
+    }
+
+    fn notify(&self, state: &str) -> io::Result<()> {
+        debug!("Notify systemd: {}", state);
+        self.socket.send_to_addr(state.as_bytes(), &self.addr)?;
+        Ok(())
+    }
+}
+
+/// Returns how often systemd expects [Notifier::watchdog] to be called.
+///
+/// `None` if the watchdog isn't enabled for the unit (`WatchdogSec=`).
+pub fn watchdog_interval() -> Option<Duration> {
+    parse_watchdog_interval(
+        &std::env::var(WATCHDOG_USEC).ok()?,
+        std::env::var(WATCHDOG_PID).ok().as_deref(),
+        std::process::id(),
+    )
+}
+
+fn parse_watchdog_interval(usec: &str, pid: Option<&str>, own_pid: u32) -> Option<Duration> {
+    // the watchdog might be meant for another process
+    if pid.is_some_and(|pid| pid.parse() != Ok(own_pid)) {
+        return None;
+    }
+
+    let usec: u64 = usec.parse().ok().filter(|&usec| usec > 0)?;
+
+    // notify twice per timeout as recommended by `sd_watchdog_enabled(3)`
+    Some(Duration::from_micros(usec) / 2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchdog_interval_is_half_of_timeout() {
+        assert_eq!(
+            parse_watchdog_interval("10000000", None, 42),
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            parse_watchdog_interval("10000000", Some("42"), 42),
+            Some(Duration::from_secs(5))
+        );
+    }
+
+    #[test]
+    fn watchdog_of_other_process_is_ignored() {
+        assert_eq!(parse_watchdog_interval("10000000", Some("7"), 42), None);
+        assert_eq!(parse_watchdog_interval("0", None, 42), None);
+        assert_eq!(parse_watchdog_interval("soon", None, 42), None);
+    }
+}
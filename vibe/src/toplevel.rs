@@ -0,0 +1,229 @@
+//! Detects outputs which are fully covered by a window with the `wlr-foreign-toplevel-management` protocol.
+//!
+//! There's no need to render the wallpaper of an output if a focused fullscreen or maximized window hides it.
+use std::collections::{HashMap, HashSet};
+
+use smithay_client_toolkit::reexports::protocols_wlr::foreign_toplevel::v1::client::{
+    zwlr_foreign_toplevel_handle_v1::{self, ZwlrForeignToplevelHandleV1},
+    zwlr_foreign_toplevel_manager_v1::{self, ZwlrForeignToplevelManagerV1},
+};
+use tracing::{debug, info};
+use wayland_client::{
+    event_created_child, globals::GlobalList, protocol::wl_output::WlOutput, Connection, Dispatch,
+    QueueHandle,
+};
+
+pub trait ToplevelHandler {
+    fn toplevel_state(&mut self) -> &mut ToplevelState;
+
+    /// Called each time a window changed its state, its outputs or got closed.
+    fn toplevels_changed(&mut self, qh: &QueueHandle<Self>)
+    where
+        Self: Sized;
+}
+
+#[derive(Debug, Default)]
+struct Toplevel {
+    outputs: HashSet<WlOutput>,
+    covers_outputs: bool,
+    app_id: Option<String>,
+}
+
+/// Keeps track of the windows (toplevels) of the compositor.
+#[derive(Debug)]
+pub struct ToplevelState {
+    // `None` if the compositor doesn't support the protocol
+    manager: Option<ZwlrForeignToplevelManagerV1>,
+    toplevels: HashMap<ZwlrForeignToplevelHandleV1, Toplevel>,
+}
+
+impl ToplevelState {
+    pub fn new<D>(globals: &GlobalList, qh: &QueueHandle<D>) -> Self
+    where
+        D: Dispatch<ZwlrForeignToplevelManagerV1, ()> + 'static,
+    {
+        let manager = globals.bind(qh, 1..=3, ()).ok();
+        if manager.is_none() {
+            info!(concat![
+                "Your compositor doesn't support the wlr_foreign_toplevel_management protocol. ",
+                "Outputs will be rendered even if they are covered by a fullscreen window."
+            ]);
+        }
+
+        Self {
+            manager,
+            toplevels: HashMap::new(),
+        }
+    }
+
+    /// Returns `true` if a focused fullscreen or maximized window is shown on the given output.
+    pub fn is_covered(&self, output: &WlOutput) -> bool {
+        self.toplevels
+            .values()
+            .any(|toplevel| toplevel.covers_outputs && toplevel.outputs.contains(output))
+    }
+
+    /// Returns the app ids of all windows.
+    pub fn app_ids(&self) -> impl Iterator<Item = &str> + Clone {
+        self.toplevels
+            .values()
+            .filter_map(|toplevel| toplevel.app_id.as_deref())
+    }
+}
+
+impl Drop for ToplevelState {
+    fn drop(&mut self) {
+        if let Some(manager) = self.manager.take() {
+            manager.stop();
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrForeignToplevelManagerV1, (), D> for ToplevelState
+where
+    D: Dispatch<ZwlrForeignToplevelManagerV1, ()>
+        + Dispatch<ZwlrForeignToplevelHandleV1, ()>
+        + ToplevelHandler
+        + 'static,
+{
+    fn event(
+        state: &mut D,
+        _manager: &ZwlrForeignToplevelManagerV1,
+        event: zwlr_foreign_toplevel_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_manager_v1::Event::Toplevel { toplevel } => {
+                state
+                    .toplevel_state()
+                    .toplevels
+                    .insert(toplevel, Toplevel::default());
+            }
+            zwlr_foreign_toplevel_manager_v1::Event::Finished => {
+                debug!("Foreign toplevel manager finished");
+                let toplevel_state = state.toplevel_state();
+                toplevel_state.manager = None;
+                for (handle, _toplevel) in toplevel_state.toplevels.drain() {
+                    handle.destroy();
+                }
+
+                state.toplevels_changed(qh);
+            }
+            _ => {}
+        }
+    }
+
+    event_created_child!(D, ZwlrForeignToplevelManagerV1, [
+        zwlr_foreign_toplevel_manager_v1::EVT_TOPLEVEL_OPCODE => (ZwlrForeignToplevelHandleV1, ()),
+    ]);
+}
+
+impl<D> Dispatch<ZwlrForeignToplevelHandleV1, (), D> for ToplevelState
+where
+    D: Dispatch<ZwlrForeignToplevelHandleV1, ()> + ToplevelHandler + 'static,
+{
+    fn event(
+        state: &mut D,
+        handle: &ZwlrForeignToplevelHandleV1,
+        event: zwlr_foreign_toplevel_handle_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        qh: &QueueHandle<D>,
+    ) {
+        match event {
+            zwlr_foreign_toplevel_handle_v1::Event::OutputEnter { output } => {
+                if let Some(toplevel) = state.toplevel_state().toplevels.get_mut(handle) {
+                    toplevel.outputs.insert(output);
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::OutputLeave { output } => {
+                if let Some(toplevel) = state.toplevel_state().toplevels.get_mut(handle) {
+                    toplevel.outputs.remove(&output);
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::State { state: states } => {
+                if let Some(toplevel) = state.toplevel_state().toplevels.get_mut(handle) {
+                    toplevel.covers_outputs = covers_outputs(&states);
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::AppId { app_id } => {
+                if let Some(toplevel) = state.toplevel_state().toplevels.get_mut(handle) {
+                    toplevel.app_id = Some(app_id);
+                }
+            }
+            zwlr_foreign_toplevel_handle_v1::Event::Done => state.toplevels_changed(qh),
+            zwlr_foreign_toplevel_handle_v1::Event::Closed => {
+                state.toplevel_state().toplevels.remove(handle);
+                handle.destroy();
+
+                state.toplevels_changed(qh);
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Returns `true` if the given (raw) states of a toplevel hide everything behind it.
+///
+/// Only the focused toplevel counts since the compositors also report the outputs of
+/// windows on hidden workspaces.
+fn covers_outputs(states: &[u8]) -> bool {
+    use zwlr_foreign_toplevel_handle_v1::State;
+
+    let mut activated = false;
+    let mut minimized = false;
+    let mut covers = false;
+
+    for state in states.as_chunks::<4>().0 {
+        let state = u32::from_ne_bytes(*state);
+
+        if state == State::Activated as u32 {
+            activated = true;
+        } else if state == State::Minimized as u32 {
+            minimized = true;
+        } else if state == State::Fullscreen as u32 || state == State::Maximized as u32 {
+            covers = true;
+        }
+    }
+
+    covers && activated && !minimized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zwlr_foreign_toplevel_handle_v1::State;
+
+    fn raw(states: &[State]) -> Vec<u8> {
+        states
+            .iter()
+            .flat_map(|state| (*state as u32).to_ne_bytes())
+            .collect()
+    }
+
+    #[test]
+    fn fullscreen_and_maximized_cover() {
+        assert!(covers_outputs(&raw(&[State::Fullscreen, State::Activated])));
+        assert!(covers_outputs(&raw(&[State::Activated, State::Maximized])));
+    }
+
+    #[test]
+    fn normal_and_minimized_dont_cover() {
+        assert!(!covers_outputs(&raw(&[])));
+        assert!(!covers_outputs(&raw(&[State::Activated])));
+        assert!(!covers_outputs(&raw(&[
+            State::Activated,
+            State::Maximized,
+            State::Minimized
+        ])));
+    }
+
+    #[test]
+    fn unfocused_windows_dont_cover() {
+        // for example a maximized window on another workspace
+        assert!(!covers_outputs(&raw(&[State::Maximized])));
+        assert!(!covers_outputs(&raw(&[State::Fullscreen])));
+    }
+}
@@ -1,3 +1,5 @@
+use std::str::FromStr;
+
 use smithay_client_toolkit::output::OutputInfo;
 use wgpu::SurfaceConfiguration;
 use winit::dpi::PhysicalSize;
@@ -9,6 +11,29 @@ pub struct Size {
     pub height: u32,
 }
 
+impl FromStr for Size {
+    type Err = String;
+
+    /// Parses sizes like `800x200`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "Invalid size '{}'. Expected `<width>x<height>`, like `800x200`.",
+                s
+            )
+        };
+
+        let (width, height) = s.split_once('x').ok_or_else(invalid)?;
+        let width: u32 = width.trim().parse().map_err(|_| invalid())?;
+        let height: u32 = height.trim().parse().map_err(|_| invalid())?;
+        if width == 0 || height == 0 {
+            return Err(invalid());
+        }
+
+        Ok(Self { width, height })
+    }
+}
+
 impl From<&OutputInfo> for Size {
     fn from(value: &OutputInfo) -> Self {
         let (width, height) = value
@@ -46,3 +71,18 @@ impl From<PhysicalSize<u32>> for Size {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let size: Size = "800x200".parse().unwrap();
+        assert_eq!((size.width, size.height), (800, 200));
+
+        assert!("800".parse::<Size>().is_err());
+        assert!("0x200".parse::<Size>().is_err());
+        assert!("800x-1".parse::<Size>().is_err());
+    }
+}
@@ -0,0 +1,245 @@
+//! Checks all config files without connecting to the compositor.
+use std::path::{Path, PathBuf};
+
+use vibe_audio::{fetcher::DummyFetcher, SampleProcessor};
+use vibe_renderer::{Renderer, RendererDescriptor};
+
+use crate::{
+    config::ConfigError,
+    output::config::{
+        component::{self, ComponentConfig},
+        OutputConfig, Preset,
+    },
+};
+
+/// The texture format which the components are compiled for.
+const TEXTURE_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Bgra8Unorm;
+
+/// The result of a single config file.
+struct FileReport {
+    path: PathBuf,
+    errors: Vec<String>,
+}
+
+/// Validates `config.toml`, `colors.toml`, `all-outputs.toml`, all output configs and all presets
+/// and prints the errors of each file.
+///
+/// Returns an error if at least one file is invalid.
+pub fn run() -> anyhow::Result<()> {
+    let mut reports = Vec::new();
+
+    let config = {
+        let path = crate::get_config_path();
+
+        match crate::config::load() {
+            Ok(config) => {
+                reports.push(FileReport {
+                    path,
+                    errors: Vec::new(),
+                });
+                config
+            }
+            Err(ConfigError::IO(err)) if err.kind() == std::io::ErrorKind::NotFound => {
+                crate::config::Config::default()
+            }
+            Err(err) => {
+                reports.push(FileReport {
+                    path,
+                    errors: vec![err.to_string()],
+                });
+                crate::config::Config::default()
+            }
+        }
+    };
+
+    if let Some((path, result)) = crate::colors::load() {
+        reports.push(FileReport {
+            path,
+            errors: result
+                .err()
+                .into_iter()
+                .map(|err| err.to_string())
+                .collect(),
+        });
+    }
+
+    let renderer = Renderer::new(&RendererDescriptor::from(&config.graphics_config));
+    let processor = SampleProcessor::new(DummyFetcher::new(2));
+
+    // the report of `config.toml` is the first one if the file exists
+    if let Some(report) = reports.first_mut() {
+        if let Some(default_component) = &config.default_component {
+            report.errors.extend(validate_components(
+                &renderer,
+                &processor,
+                std::slice::from_ref(default_component),
+            ));
+        }
+
+        if let Some(fallback_component) = config
+            .battery_config
+            .as_ref()
+            .and_then(|c| c.fallback_component.as_ref())
+        {
+            report.errors.extend(validate_components(
+                &renderer,
+                &processor,
+                std::slice::from_ref(fallback_component),
+            ));
+        }
+
+        for (idx, default_config) in config.default_output_configs.iter().enumerate() {
+            if let Err(err) = default_config.matches("", None) {
+                report
+                    .errors
+                    .push(format!("default_output_configs[{}]: {}", idx, err));
+            }
+
+            report.errors.extend(
+                validate_components(&renderer, &processor, &default_config.components)
+                    .into_iter()
+                    .map(|err| format!("default_output_configs[{}].{}", idx, err)),
+            );
+        }
+
+        if let Some(Err(err)) = config.schedule_config.as_ref().map(|c| c.validate()) {
+            report.errors.push(format!("schedule_config: {}", err));
+        }
+    }
+
+    let mut output_config_paths = toml_files(&crate::get_output_config_dir())?;
+    let all_outputs_config_path = crate::get_all_outputs_config_path();
+    if all_outputs_config_path.exists() {
+        output_config_paths.insert(0, all_outputs_config_path);
+    }
+
+    for path in output_config_paths {
+        let config = parse::<OutputConfig>(&path).and_then(|mut config| {
+            let config_dir = path.parent().unwrap_or(Path::new("/"));
+            config
+                .resolve_includes(config_dir)
+                .map_err(|err| format!("{:#}", err))?;
+            Ok(config)
+        });
+
+        let errors = match config {
+            Ok(config) => {
+                let mut errors = Vec::new();
+                if let Some(Err(err)) = config.layer_surface_config.as_ref().map(|c| c.validate()) {
+                    errors.push(format!("layer_surface_config: {}", err));
+                }
+
+                if let Some(Err(err)) = config.graphics_config.as_ref().map(|c| c.validate()) {
+                    errors.push(format!("graphics_config: {}", err));
+                }
+
+                if let Some(idle_config) = &config.idle_config {
+                    if idle_config.timeout().is_none() {
+                        errors.push(format!(
+                            "idle_config: Invalid `pause_after_secs`: {}",
+                            idle_config.pause_after_secs
+                        ));
+                    }
+                }
+
+                errors.extend(validate_components(
+                    &renderer,
+                    &processor,
+                    &config.components,
+                ));
+
+                if let Some(playlist) = &config.playlist {
+                    if let Err(err) = playlist.validate() {
+                        errors.push(format!("playlist: {}", err));
+                    }
+
+                    for (idx, entry) in playlist.entries.iter().enumerate() {
+                        match entry.load() {
+                            Ok(components) => errors.extend(
+                                validate_components(&renderer, &processor, &components)
+                                    .into_iter()
+                                    .map(|err| format!("playlist.entries[{}].{}", idx, err)),
+                            ),
+                            Err(err) => {
+                                errors.push(format!("playlist.entries[{}]: {:#}", idx, err))
+                            }
+                        }
+                    }
+                }
+                errors
+            }
+            Err(err) => vec![err],
+        };
+
+        reports.push(FileReport { path, errors });
+    }
+
+    for path in toml_files(&crate::get_preset_dir())? {
+        let errors = match parse::<Preset>(&path) {
+            Ok(preset) => validate_components(&renderer, &processor, &preset.components),
+            Err(err) => vec![err],
+        };
+
+        reports.push(FileReport { path, errors });
+    }
+
+    let mut amount_invalid = 0;
+    for report in reports.iter() {
+        if report.errors.is_empty() {
+            println!("ok      {}", report.path.to_string_lossy());
+        } else {
+            amount_invalid += 1;
+            println!("error   {}", report.path.to_string_lossy());
+
+            for error in report.errors.iter() {
+                for line in error.lines() {
+                    println!("          {}", line);
+                }
+            }
+        }
+    }
+
+    if amount_invalid > 0 {
+        anyhow::bail!("{} config file(s) are invalid.", amount_invalid);
+    }
+
+    Ok(())
+}
+
+/// Creates each component to check its config and compile its shaders.
+fn validate_components(
+    renderer: &Renderer,
+    processor: &SampleProcessor<DummyFetcher>,
+    configs: &[component::Config],
+) -> Vec<String> {
+    configs
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, config)| {
+            config
+                .create_component(renderer, processor, TEXTURE_FORMAT)
+                .err()
+                .map(|err| format!("components[{}]: {}", idx, err))
+        })
+        .collect()
+}
+
+fn parse<T: serde::de::DeserializeOwned>(path: &Path) -> Result<T, String> {
+    let content = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+    crate::migration::parse(&content).map_err(|err| err.to_string())
+}
+
+/// Returns the `.toml` files inside the given directory sorted by their name.
+fn toml_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+
+        if path.extension().is_some_and(|ext| ext == "toml") {
+            paths.push(path);
+        }
+    }
+
+    paths.sort();
+    Ok(paths)
+}
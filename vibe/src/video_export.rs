@@ -0,0 +1,414 @@
+//! Publishes the rendered frames of the outputs as PipeWire video streams,
+//! so that apps like OBS can capture them without recording the screen.
+//!
+//! The frames are piped into `gst-launch-1.0` whose `pipewiresink` creates the stream,
+//! so GStreamer and its PipeWire plugin have to be installed.
+use std::{
+    io::Write,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        mpsc::{self, SyncSender, TrySendError},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+use vibe_renderer::Renderer;
+
+const GST_LAUNCH: &str = "gst-launch-1.0";
+
+const DEFAULT_MAX_FPS: u32 = 30;
+
+/// The amount of frames which can be on their way back from the GPU at the same time.
+const AMOUNT_STAGING_BUFFERS: usize = 3;
+
+const PIXEL_SIZE: u32 = 4;
+
+// states of a staging buffer
+const IDLE: u8 = 0;
+const PENDING: u8 = 1;
+const MAPPED: u8 = 2;
+const FAILED: u8 = 3;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoExportConfig {
+    /// The maximal frame rate of the streams.
+    /// Each exported frame has to be copied back from the GPU, so keep it low.
+    pub max_fps: u32,
+}
+
+impl Default for VideoExportConfig {
+    fn default() -> Self {
+        Self {
+            max_fps: DEFAULT_MAX_FPS,
+        }
+    }
+}
+
+/// The video stream of a single output.
+///
+/// The stream is started with the first frame and restarted once the size of the output changes.
+pub struct VideoExport {
+    node_name: String,
+    max_fps: u32,
+    min_frame_interval: Duration,
+    last_frame: Option<Instant>,
+    stream: Option<Stream>,
+    // don't restart `gst-launch-1.0` with every frame if it can't run
+    failed: bool,
+}
+
+struct Stream {
+    width: u32,
+    height: u32,
+    // sends the frames to the thread which writes them to `gst-launch-1.0`
+    frames: SyncSender<Vec<u8>>,
+    child: Child,
+    reader: FrameReader,
+}
+
+/// Copies the frames back from the GPU without waiting for it.
+///
+/// The copies go into a ring of staging buffers, so a frame arrives one or more frames later.
+/// Frames are dropped while all buffers are still busy.
+struct FrameReader {
+    width: u32,
+    height: u32,
+    padded_line_size: u32,
+    buffers: Vec<StagingBuffer>,
+    // the buffer which gets the next frame, it's also the one with the oldest frame
+    next: usize,
+}
+
+struct StagingBuffer {
+    buffer: wgpu::Buffer,
+    state: Arc<AtomicU8>,
+}
+
+impl VideoExport {
+    pub fn new(output_name: &str, config: &VideoExportConfig) -> Self {
+        let max_fps = config.max_fps.max(1);
+
+        Self {
+            node_name: node_name(output_name),
+            max_fps,
+            min_frame_interval: Duration::from_secs_f32(1. / max_fps as f32),
+            last_frame: None,
+            stream: None,
+            failed: false,
+        }
+    }
+
+    /// Sends the rendered frame to the stream unless the last one was sent less than
+    /// `1 / max_fps` seconds ago.
+    ///
+    /// The frames are copied back from the GPU without waiting for it, so each call sends the
+    /// frames of earlier calls which arrived in the meantime.
+    pub fn export(&mut self, renderer: &Renderer, texture: &wgpu::Texture) {
+        self.send_finished_frames(renderer);
+
+        if self.failed
+            || self
+                .last_frame
+                .is_some_and(|last_frame| last_frame.elapsed() < self.min_frame_interval)
+        {
+            return;
+        }
+        self.last_frame = Some(Instant::now());
+
+        let (width, height) = (texture.width(), texture.height());
+        if self
+            .stream
+            .as_ref()
+            .is_none_or(|stream| (stream.width, stream.height) != (width, height))
+        {
+            self.stream = None;
+            match self.start_stream(renderer, width, height, texture.format()) {
+                Ok(stream) => self.stream = Some(stream),
+                Err(err) => {
+                    warn!(
+                        "Couldn't export the frames of '{}': {}",
+                        self.node_name, err
+                    );
+                    self.failed = true;
+                    return;
+                }
+            }
+        }
+
+        self.stream.as_mut().unwrap().reader.copy(renderer, texture);
+    }
+
+    fn send_finished_frames(&mut self, renderer: &Renderer) {
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
+
+        for frame in stream.reader.finished_frames(renderer.device()) {
+            match stream.frames.try_send(frame) {
+                // `gst-launch-1.0` is still busy with the last frame
+                Ok(()) | Err(TrySendError::Full(_)) => {}
+                Err(TrySendError::Disconnected(_)) => {
+                    warn!(
+                        "The video stream '{}' stopped. Won't export its frames anymore.",
+                        self.node_name
+                    );
+                    self.stream = None;
+                    self.failed = true;
+                    return;
+                }
+            }
+        }
+    }
+
+    fn start_stream(
+        &self,
+        renderer: &Renderer,
+        width: u32,
+        height: u32,
+        format: wgpu::TextureFormat,
+    ) -> Result<Stream, String> {
+        let format = gst_format(format)
+            .ok_or_else(|| format!("The surface format {:?} isn't supported.", format))?;
+
+        let mut child = Command::new(GST_LAUNCH)
+            .args(pipeline(
+                &self.node_name,
+                width,
+                height,
+                format,
+                self.max_fps,
+            ))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()
+            .map_err(|err| format!("Couldn't start `{}`: {}", GST_LAUNCH, err))?;
+
+        let mut stdin = child.stdin.take().unwrap();
+        let (frames, receiver) = mpsc::sync_channel::<Vec<u8>>(1);
+        std::thread::spawn(move || {
+            for frame in receiver {
+                if stdin.write_all(&frame).is_err() {
+                    break;
+                }
+            }
+        });
+
+        info!(
+            "Exporting the frames as the PipeWire stream '{}' ({}x{}).",
+            self.node_name, width, height
+        );
+        Ok(Stream {
+            width,
+            height,
+            frames,
+            child,
+            reader: FrameReader::new(renderer.device(), width, height),
+        })
+    }
+}
+
+impl FrameReader {
+    fn new(device: &wgpu::Device, width: u32, height: u32) -> Self {
+        let padded_line_size = (width * PIXEL_SIZE).div_ceil(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT)
+            * wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+        let buffers = (0..AMOUNT_STAGING_BUFFERS)
+            .map(|_| StagingBuffer {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Video export: staging buffer"),
+                    size: (padded_line_size * height) as u64,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                state: Arc::new(AtomicU8::new(IDLE)),
+            })
+            .collect();
+
+        Self {
+            width,
+            height,
+            padded_line_size,
+            buffers,
+            next: 0,
+        }
+    }
+
+    /// Returns the frames which arrived since the last call (the oldest one first).
+    fn finished_frames(&mut self, device: &wgpu::Device) -> Vec<Vec<u8>> {
+        // invokes the callbacks of `map_async` if the copies are done
+        let _ = device.poll(wgpu::PollType::Poll);
+
+        let mut frames = Vec::new();
+        for offset in 0..self.buffers.len() {
+            let staging = &self.buffers[(self.next + offset) % self.buffers.len()];
+
+            match staging.state.load(Ordering::Acquire) {
+                MAPPED => {
+                    frames.push(unpad(
+                        &staging.buffer.slice(..).get_mapped_range(),
+                        self.width,
+                        self.padded_line_size,
+                    ));
+                    staging.buffer.unmap();
+                }
+                FAILED => {}
+                _ => continue,
+            }
+
+            staging.state.store(IDLE, Ordering::Release);
+        }
+
+        frames
+    }
+
+    /// Starts to copy the frame in `texture` into the next staging buffer.
+    fn copy(&mut self, renderer: &Renderer, texture: &wgpu::Texture) {
+        let staging = &self.buffers[self.next];
+        if staging.state.load(Ordering::Acquire) != IDLE {
+            debug!("The GPU is still busy with the previous frames. Dropping the frame.");
+            return;
+        }
+
+        let mut encoder =
+            renderer
+                .device()
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                    label: Some("Video export: copy encoder"),
+                });
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::TexelCopyBufferInfo {
+                buffer: &staging.buffer,
+                layout: wgpu::TexelCopyBufferLayout {
+                    offset: 0,
+                    bytes_per_row: Some(self.padded_line_size),
+                    rows_per_image: Some(self.height),
+                },
+            },
+            texture.size(),
+        );
+        renderer.queue().submit([encoder.finish()]);
+
+        staging.state.store(PENDING, Ordering::Release);
+        let state = staging.state.clone();
+        staging
+            .buffer
+            .slice(..)
+            .map_async(wgpu::MapMode::Read, move |result| {
+                state.store(
+                    if result.is_ok() { MAPPED } else { FAILED },
+                    Ordering::Release,
+                );
+            });
+
+        self.next = (self.next + 1) % self.buffers.len();
+    }
+}
+
+/// Removes the padding at the end of each line of a frame which got copied out of a texture.
+fn unpad(data: &[u8], width: u32, padded_line_size: u32) -> Vec<u8> {
+    let line_size = (width * PIXEL_SIZE) as usize;
+
+    let mut bytes = Vec::with_capacity(data.len());
+    for line in data.chunks(padded_line_size as usize) {
+        bytes.extend_from_slice(&line[..line_size]);
+    }
+
+    bytes
+}
+
+impl Drop for Stream {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// The name of the PipeWire node of the output. `gst-launch-1.0` doesn't like spaces in it.
+fn node_name(output_name: &str) -> String {
+    let output_name: String = output_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+
+    format!("vibe-{}", output_name)
+}
+
+/// The name of the raw video format of GStreamer with the same memory layout.
+fn gst_format(format: wgpu::TextureFormat) -> Option<&'static str> {
+    match format {
+        wgpu::TextureFormat::Bgra8Unorm => Some("bgra"),
+        wgpu::TextureFormat::Rgba8Unorm => Some("rgba"),
+        _ => None,
+    }
+}
+
+/// The arguments of `gst-launch-1.0` which read raw frames from stdin into a PipeWire video source.
+fn pipeline(node_name: &str, width: u32, height: u32, format: &str, fps: u32) -> Vec<String> {
+    [
+        "-q",
+        "fdsrc",
+        "fd=0",
+        "!",
+        "rawvideoparse",
+        &format!("width={}", width),
+        &format!("height={}", height),
+        &format!("format={}", format),
+        &format!("framerate={}/1", fps),
+        "!",
+        "videoconvert",
+        "!",
+        "pipewiresink",
+        "mode=provide",
+        "client-name=vibe",
+        &format!(
+            "stream-properties=props,media.class=Video/Source,node.name={}",
+            node_name
+        ),
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn node_names() {
+        assert_eq!(node_name("DP-1"), "vibe-DP-1");
+        assert_eq!(node_name("HDMI A 1"), "vibe-HDMI-A-1");
+    }
+
+    #[test]
+    fn formats() {
+        assert_eq!(gst_format(wgpu::TextureFormat::Bgra8Unorm), Some("bgra"));
+        assert_eq!(gst_format(wgpu::TextureFormat::Rgba16Float), None);
+    }
+
+    #[test]
+    fn unpadded_lines() {
+        let data = [[1, 2, 3, 4, 0, 0, 0, 0], [5, 6, 7, 8, 0, 0, 0, 0]].concat();
+
+        assert_eq!(unpad(&data, 1, 8), [1, 2, 3, 4, 5, 6, 7, 8]);
+    }
+
+    #[test]
+    fn pipeline_args() {
+        let args = pipeline("vibe-DP-1", 1920, 1080, "bgra", 30);
+
+        assert!(args.contains(&"width=1920".to_string()));
+        assert!(args.contains(&"format=bgra".to_string()));
+        assert!(args.contains(&"framerate=30/1".to_string()));
+        assert!(args
+            .last()
+            .unwrap()
+            .ends_with("media.class=Video/Source,node.name=vibe-DP-1"));
+    }
+}
@@ -6,41 +6,127 @@ use std::{
 
 use anyhow::{bail, Context};
 use notify::{INotifyWatcher, Watcher};
-use tracing::{error, warn};
+use tracing::{error, info, warn};
 use vibe_audio::{fetcher::SystemAudioFetcher, SampleProcessor};
-use vibe_renderer::{components::ComponentAudio, Renderer, RendererDescriptor};
+use vibe_renderer::{Component, FrameUniforms, Renderer, RendererDescriptor};
 use winit::{
-    application::ApplicationHandler, dpi::PhysicalPosition, event::WindowEvent,
-    event_loop::EventLoop, keyboard::Key, window::Window,
+    application::ApplicationHandler,
+    dpi::{PhysicalPosition, PhysicalSize},
+    event::WindowEvent,
+    event_loop::{ActiveEventLoop, EventLoop},
+    keyboard::Key,
+    window::{Window, WindowAttributes, WindowLevel},
 };
 
 use crate::{
     colors::ColorManager,
+    migration::Version,
     output::config::{
         component::{ComponentConfig, Config, ConfigError},
-        OutputConfig,
+        GraphicsConfig, OutputConfig,
     },
     types::size::Size,
 };
 
+/// Where the window is placed on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum WindowPosition {
+    Top,
+    Bottom,
+    Left,
+    Right,
+    Center,
+}
+
+/// How the window is shown.
+#[derive(Debug, Clone)]
+pub struct WindowOptions {
+    /// The size of the window. The window manager decides if not set.
+    pub size: Option<Size>,
+    /// Ignored on wayland since clients can't place their windows there.
+    pub position: Option<WindowPosition>,
+    pub decorations: bool,
+    /// Keep the window below all other windows.
+    pub on_bottom: bool,
+    /// Reload the config once it (or one of its files) changes.
+    pub hot_reload: bool,
+}
+
+impl WindowOptions {
+    /// The options to work on a config.
+    pub fn preview() -> Self {
+        Self {
+            size: None,
+            position: None,
+            decorations: true,
+            on_bottom: false,
+            hot_reload: true,
+        }
+    }
+
+    fn attributes(&self, event_loop: &ActiveEventLoop) -> WindowAttributes {
+        let mut attributes = WindowAttributes::default()
+            .with_decorations(self.decorations)
+            .with_window_level(if self.on_bottom {
+                WindowLevel::AlwaysOnBottom
+            } else {
+                WindowLevel::Normal
+            });
+
+        if let Some(size) = self.size {
+            attributes = attributes.with_inner_size(PhysicalSize::new(size.width, size.height));
+        }
+
+        let monitor = event_loop
+            .primary_monitor()
+            .or_else(|| event_loop.available_monitors().next());
+        if let (Some(position), Some(size), Some(monitor)) = (self.position, self.size, monitor) {
+            let origin = monitor.position();
+            let screen = monitor.size();
+
+            let center_x = (screen.width.saturating_sub(size.width) / 2) as i32;
+            let center_y = (screen.height.saturating_sub(size.height) / 2) as i32;
+            let (x, y) = match position {
+                WindowPosition::Top => (center_x, 0),
+                WindowPosition::Bottom => {
+                    (center_x, screen.height.saturating_sub(size.height) as i32)
+                }
+                WindowPosition::Left => (0, center_y),
+                WindowPosition::Right => (screen.width.saturating_sub(size.width) as i32, center_y),
+                WindowPosition::Center => (center_x, center_y),
+            };
+
+            attributes =
+                attributes.with_position(PhysicalPosition::new(origin.x + x, origin.y + y));
+        }
+
+        attributes
+    }
+}
+
 struct State<'a> {
     surface: wgpu::Surface<'a>,
     surface_config: wgpu::SurfaceConfiguration,
     window: Arc<Window>,
     last_cursor_pos: PhysicalPosition<f64>,
 
-    components: Vec<Box<dyn ComponentAudio<SystemAudioFetcher>>>,
+    components: Vec<Box<dyn Component>>,
+    frame_uniforms: FrameUniforms,
 }
 
 impl State<'_> {
-    pub fn new(window: Window, renderer: &Renderer) -> Self {
+    pub fn new(window: Window, renderer: &Renderer, graphics_config: &GraphicsConfig) -> Self {
         let window = Arc::new(window);
         let size = window.inner_size();
 
         let surface = renderer.instance().create_surface(window.clone()).unwrap();
 
-        let surface_config =
-            crate::output::get_surface_config(renderer.adapter(), &surface, Size::from(size));
+        let surface_config = crate::output::get_surface_config(
+            renderer.adapter(),
+            &surface,
+            Size::from(size),
+            graphics_config,
+        );
         surface.configure(renderer.device(), &surface_config);
 
         Self {
@@ -49,6 +135,7 @@ impl State<'_> {
             window,
             last_cursor_pos: PhysicalPosition::new(0.0, 0.0),
             components: Vec::new(),
+            frame_uniforms: FrameUniforms::new(renderer),
         }
     }
 
@@ -95,7 +182,7 @@ impl State<'_> {
             .texture
             .create_view(&wgpu::TextureViewDescriptor::default());
 
-        renderer.render(&view, &self.components);
+        renderer.render(&view, &self.frame_uniforms, &self.components);
 
         // GPU readback: let components read pixels from the rendered surface
         for component in self.components.iter_mut() {
@@ -115,6 +202,7 @@ impl State<'_> {
         let rel_x = new_pos.x as f32 / self.surface_config.width as f32;
         let rel_y = new_pos.y as f32 / self.surface_config.height as f32;
 
+        self.frame_uniforms.set_mouse_position((rel_x, rel_y));
         for component in self.components.iter_mut() {
             component.update_mouse_position(queue, (rel_x, rel_y));
         }
@@ -126,6 +214,7 @@ impl State<'_> {
         let rel_x = self.last_cursor_pos.x as f32 / self.surface_config.width as f32;
         let rel_y = self.last_cursor_pos.y as f32 / self.surface_config.height as f32;
 
+        self.frame_uniforms.set_mouse_click((rel_x, rel_y), time);
         for component in self.components.iter_mut() {
             component.update_mouse_click(queue, (rel_x, rel_y), time);
         }
@@ -138,44 +227,73 @@ struct OutputRenderer<'a> {
     state: Option<State<'a>>,
 
     output_config: OutputConfig,
-    output_name: String,
+    output_config_path: PathBuf,
+    // the name which is shown in the title of the window
+    name: String,
     lookup_paths: Vec<PathBuf>,
     watcher: INotifyWatcher,
     rx: Receiver<notify::Result<notify::Event>>,
     time: Instant,
     color_manager: ColorManager,
+    options: WindowOptions,
+    notify_errors: bool,
 }
 
 impl OutputRenderer<'_> {
-    pub fn new(output_name: String) -> anyhow::Result<Self> {
+    /// `target` is either the name of an output or the path to an output config file.
+    pub fn new(target: String, options: WindowOptions) -> anyhow::Result<Self> {
         let config = crate::config::load()?;
+        config.apply_global_config();
+
+        let mut renderer = Renderer::new(&RendererDescriptor::from(&config.graphics_config));
+        renderer.set_event_sink(config.events.clone().unwrap_or_default().event_sink()?);
 
-        let renderer = Renderer::new(&RendererDescriptor::from(&config.graphics_config));
-        let processor = config.sample_processor()?;
+        let output_config_path = {
+            let path = PathBuf::from(&target);
 
-        let (output_config_path, output_config) = {
-            let Some((path, config)) = crate::output::config::load(&output_name) else {
+            if path.is_file() {
+                path
+            } else if let Some(path) = crate::output::config::find(&target) {
+                path
+            } else {
                 bail!(
-                    "The config file for '{}' does not exist. Can't start hot reloading.`",
-                    output_name
+                    "'{}' is neither a config file nor an output with a config file.",
+                    target
                 );
-            };
+            }
+        };
 
-            match config {
-                Ok(config) => (path, config),
-                Err(err) => {
-                    error!("{:?}", err);
-                    (
-                        path,
-                        OutputConfig {
-                            enable: true,
-                            components: Vec::new(),
-                        },
-                    )
+        let name = output_config_path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or(target);
+
+        let output_config = match crate::output::config::load_from_path(&output_config_path) {
+            Ok(config) => config,
+            Err(err) => {
+                error!("{:?}", err);
+                OutputConfig {
+                    version: Version::default(),
+                    enable: true,
+                    components: Vec::new(),
+                    include: Vec::new(),
+                    silence_config: None,
+                    idle_config: None,
+                    fps_limit: None,
+                    layer_surface_config: None,
+                    audio_target: None,
+                    playlist: None,
+                    graphics_config: None,
                 }
             }
         };
 
+        let processor = config
+            .audio_config
+            .clone()
+            .unwrap_or_default()
+            .sample_processor(output_config.audio_target.as_deref())?;
+
         let lookup_paths = output_config.external_paths();
 
         let (tx, rx) = std::sync::mpsc::channel::<notify::Result<notify::Event>>();
@@ -196,9 +314,12 @@ impl OutputRenderer<'_> {
             lookup_paths,
             rx,
             output_config,
-            output_name,
+            output_config_path,
+            name,
             time: Instant::now(),
             color_manager: ColorManager::new(),
+            notify_errors: config.notify_errors() && options.hot_reload,
+            options,
         })
     }
 
@@ -222,22 +343,24 @@ impl OutputRenderer<'_> {
     // Returns `Err` if something un-saveable happened. => Signal for exiting
     pub fn refresh_config(&mut self) -> anyhow::Result<()> {
         self.output_config = {
-            let Some((path, output_config)) = crate::output::config::load(&self.output_name) else {
+            let path = &self.output_config_path;
+            if !path.exists() {
                 bail!(
-                    "The config file of your output '{}' got removed. `vibe` will stop rendering...",
-                    self.output_name
+                    "The config file '{}' got removed. `vibe` will stop rendering...",
+                    path.to_string_lossy()
                 );
-            };
+            }
 
-            let _ = self.watcher.unwatch(&path);
+            let _ = self.watcher.unwatch(path);
             self.watcher
-                .watch(&path, notify::RecursiveMode::NonRecursive)
+                .watch(path, notify::RecursiveMode::NonRecursive)
                 .context("Start watching the output config file.")?;
 
-            match output_config {
+            match crate::output::config::load_from_path(path) {
                 Ok(conf) => conf,
                 Err(err) => {
                     error!("{:?}", err);
+                    self.notify_error(&format!("{:?}", err));
                     return Ok(());
                 }
             }
@@ -272,23 +395,40 @@ impl OutputRenderer<'_> {
                 &self.output_config.components,
             ) {
                 error!("{}", err);
+                self.notify_error(&err.to_string());
             }
         }
 
         Ok(())
     }
+
+    /// The previous components keep running after the error.
+    fn notify_error(&self, err: &str) {
+        if self.notify_errors {
+            crate::notification::error(&format!("vibe: Couldn't reload '{}'", self.name), err);
+        }
+    }
 }
 
 impl ApplicationHandler for OutputRenderer<'_> {
-    fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
+    fn resumed(&mut self, event_loop: &ActiveEventLoop) {
         let window = event_loop
             .create_window(
-                winit::window::WindowAttributes::default()
-                    .with_title(format!("vibe - {}", &self.output_name)),
+                self.options
+                    .attributes(event_loop)
+                    .with_title(format!("vibe - {}", self.name)),
             )
             .expect("Create window");
 
-        self.state = Some(State::new(window, &self.renderer));
+        self.state = Some(State::new(
+            window,
+            &self.renderer,
+            &self
+                .output_config
+                .graphics_config
+                .clone()
+                .unwrap_or_default(),
+        ));
 
         if let Err(err) = self.refresh_config() {
             error!("{:?}", err);
@@ -298,11 +438,11 @@ impl ApplicationHandler for OutputRenderer<'_> {
 
     fn window_event(
         &mut self,
-        event_loop: &winit::event_loop::ActiveEventLoop,
+        event_loop: &ActiveEventLoop,
         _window_id: winit::window::WindowId,
         event: WindowEvent,
     ) {
-        if self.config_is_modified() {
+        if self.options.hot_reload && self.config_is_modified() {
             if let Err(err) = self.refresh_config() {
                 error!("{:?}", err);
                 event_loop.exit();
@@ -321,8 +461,12 @@ impl ApplicationHandler for OutputRenderer<'_> {
                 let colors = self.color_manager.colors();
 
                 self.processor.process_next_samples();
+                let curr_time = self.time.elapsed().as_secs_f32();
+                state.frame_uniforms.set_time(curr_time);
+                state.frame_uniforms.set_colors(&colors);
+
                 for component in state.components.iter_mut() {
-                    component.update_time(self.renderer.queue(), self.time.elapsed().as_secs_f32());
+                    component.update_time(self.renderer.queue(), curr_time);
                     component.update_audio(self.renderer.queue(), &self.processor);
                     component.update_colors(self.renderer.queue(), &colors);
                 }
@@ -335,6 +479,12 @@ impl ApplicationHandler for OutputRenderer<'_> {
                     Err(wgpu::SurfaceError::OutOfMemory) => {
                         unreachable!("Dayum, you don't have any memory left for rendering....");
                     }
+                    Err(err @ (wgpu::SurfaceError::Outdated | wgpu::SurfaceError::Lost)) => {
+                        info!("Reconfiguring the surface: {}", err);
+                        // use the current size of the window, it might have changed in the meantime
+                        let size = state.window.inner_size();
+                        state.resize(Size::from(size), &self.renderer);
+                    }
                     Err(err) => {
                         warn!("{}", err);
                     }
@@ -363,21 +513,20 @@ impl ApplicationHandler for OutputRenderer<'_> {
                 state: button_state,
                 button,
                 ..
-            } => {
-                if button == winit::event::MouseButton::Left
-                    && button_state == winit::event::ElementState::Pressed
-                {
-                    let current_time = self.time.elapsed().as_secs_f32();
-                    state.update_mouse_click(self.renderer.queue(), current_time);
-                }
+            } if button == winit::event::MouseButton::Left
+                && button_state == winit::event::ElementState::Pressed =>
+            {
+                let current_time = self.time.elapsed().as_secs_f32();
+                state.update_mouse_click(self.renderer.queue(), current_time);
             }
             _ => {}
         }
     }
 }
 
-pub fn run(output_name: String) -> anyhow::Result<()> {
-    let mut app = OutputRenderer::new(output_name)?;
+/// Renders the config of the given output (or config file) in a window.
+pub fn run(target: String, options: WindowOptions) -> anyhow::Result<()> {
+    let mut app = OutputRenderer::new(target, options)?;
     let event_loop = EventLoop::new().unwrap();
     event_loop.run_app(&mut app)?;
     Ok(())